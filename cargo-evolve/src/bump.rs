@@ -0,0 +1,568 @@
+//! Adding a version to a chain, as edits to the source text.
+//!
+//! The file is parsed with `syn` only to locate the chain attribute and the latest DTO; the
+//! edits themselves are made to the original text, so formatting and comments elsewhere in
+//! the file are untouched.
+
+use proc_macro2::{Delimiter, LineColumn, TokenStream, TokenTree};
+use syn::spanned::Spanned;
+use syn::{Ident, Item, Meta, UseTree};
+
+/// Options of `cargo evolve bump`.
+#[derive(Debug, Default)]
+pub struct Options {
+    /// The domain type whose chain to extend, if the file holds more than one.
+    pub domain: Option<String>,
+    /// The name of the new version DTO, instead of the latest one numbered up.
+    pub name: Option<String>,
+}
+
+/// The outcome of [`bump`].
+#[derive(Debug)]
+pub struct Bumped {
+    /// The edited source file holding the chain.
+    pub source: String,
+    /// The edited source file defining the latest DTO, if it is not the file holding the
+    /// chain.
+    pub dto_source: Option<String>,
+    /// The domain type whose chain was extended.
+    pub domain: String,
+    /// The previously latest version DTO.
+    pub latest: String,
+    /// The new version DTO.
+    pub next: String,
+}
+
+/// How the stubbed migration into the new version converts.
+#[derive(Debug, PartialEq, Eq)]
+enum Migration {
+    From,
+    TryFrom { error: String },
+}
+
+/// A `#[versioned(...)]` attribute.
+#[derive(Debug)]
+struct Chain {
+    domain: String,
+    /// The closing parenthesis of `chain(...)`.
+    close: LineColumn,
+    /// The module path the latest entry is named through, as in `dto::`.
+    prefix: String,
+    latest: String,
+    migration: Migration,
+}
+
+/// The latest version DTO of the chain of a `#[versioned(...)]` type in `source`.
+///
+/// # Errors
+///
+/// Returns an error if the file does not parse or does not hold exactly one matching chain.
+pub fn latest(source: &str, options: &Options) -> Result<String, String> {
+    let file = parse(source)?;
+    Ok(find_chain(source, &file, options)?.latest)
+}
+
+/// Whether `source` defines a struct or enum named `name`.
+#[must_use]
+pub fn defines(source: &str, name: &str) -> bool {
+    syn::parse_file(source).is_ok_and(|file| find_item(&file, name).is_some())
+}
+
+/// Add a version to the chain of a `#[versioned(...)]` type in `source`.
+///
+/// `dto_source` is the file defining the latest DTO, if it is not `source`. The new DTO and
+/// its migration are added after the latest DTO, and the chain names the new DTO the way it
+/// names the latest one, importing it alongside the latest one where that is imported.
+///
+/// # Errors
+///
+/// Returns an error if a file does not parse, `source` does not hold exactly one matching
+/// chain, or its latest version is not a DTO defined in `dto_source` (or in `source`).
+pub fn bump(source: &str, dto_source: Option<&str>, options: &Options) -> Result<Bumped, String> {
+    let file = parse(source)?;
+    let chain = find_chain(source, &file, options)?;
+    let next = match &options.name {
+        Some(name) => name.clone(),
+        None => numbered_up(&chain.latest)?,
+    };
+
+    let dto_file = dto_source.map(parse).transpose()?;
+    let (dto_text, dto_items) = match (dto_source, &dto_file) {
+        (Some(text), Some(dto_file)) => (text, dto_file),
+        _ => (source, &file),
+    };
+    if find_item(&file, &next).is_some() || find_item(dto_items, &next).is_some() {
+        return Err(format!("{next} is already defined"));
+    }
+    let latest_item = find_item(dto_items, &chain.latest).ok_or_else(|| {
+        if dto_source.is_some() {
+            format!("{} is not defined in the DTO file", chain.latest)
+        } else {
+            format!("{} is not defined in this file", chain.latest)
+        }
+    })?;
+    let dto_edit = (
+        item_end(dto_text, latest_item),
+        format!(
+            "\n\n{}\n\n{}",
+            renamed_copy(dto_text, latest_item, &chain.latest, &next),
+            stub(&chain.latest, &next, &chain.migration)
+        ),
+    );
+
+    let lines = line_starts(source);
+    let close = offset(source, &lines, chain.close);
+    let before_close = source[..close].trim_end();
+    let entry = format!("{}{next}", chain.prefix);
+    let mut chain_edits = vec![(
+        before_close.len(),
+        if before_close.ends_with(',') {
+            format!(" {entry},")
+        } else {
+            format!(", {entry}")
+        },
+    )];
+    if dto_source.is_some() && chain.prefix.is_empty() {
+        if let Some((path, end)) = import_of(source, &file, &chain.latest) {
+            chain_edits.push((end, format!("\nuse {path}{next};")));
+        }
+    }
+
+    let (source, dto_source) = if dto_source.is_some() {
+        (
+            apply(source, chain_edits),
+            Some(apply(dto_text, vec![dto_edit])),
+        )
+    } else {
+        chain_edits.push(dto_edit);
+        (apply(source, chain_edits), None)
+    };
+    Ok(Bumped {
+        source,
+        dto_source,
+        domain: chain.domain,
+        latest: chain.latest,
+        next,
+    })
+}
+
+fn parse(source: &str) -> Result<syn::File, String> {
+    syn::parse_file(source).map_err(|err| format!("cannot parse the file: {err}"))
+}
+
+/// The single `#[versioned(...)]` chain of `file`, or of the domain type `options` names.
+fn find_chain(source: &str, file: &syn::File, options: &Options) -> Result<Chain, String> {
+    let mut chains = Vec::new();
+    for item in &file.items {
+        if let Some((ident, attrs)) = type_item(item) {
+            for attr in attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("versioned"))
+            {
+                if let Meta::List(list) = &attr.meta {
+                    chains.push(parse_chain(source, ident, &list.tokens)?);
+                }
+            }
+        }
+    }
+    if let Some(domain) = &options.domain {
+        chains.retain(|chain| &chain.domain == domain);
+    }
+    match chains.len() {
+        1 => Ok(chains.remove(0)),
+        0 => Err("no #[versioned(...)] chain found".to_string()),
+        _ => Err("several chains found; choose one with --type".to_string()),
+    }
+}
+
+/// The byte offset of the end of `item` in `source`.
+fn item_end(source: &str, item: &Item) -> usize {
+    offset(source, &line_starts(source), item.span().end())
+}
+
+/// The text of `item`, with its name changed from `latest` to `next`.
+fn renamed_copy(source: &str, item: &Item, latest: &str, next: &str) -> String {
+    let lines = line_starts(source);
+    let start = offset(source, &lines, item.span().start());
+    let end = offset(source, &lines, item.span().end());
+    let (ident, _) = type_item(item).unwrap_or_else(|| unreachable!());
+    let ident_start = offset(source, &lines, ident.span().start()) - start;
+
+    let mut copy = source[start..end].to_string();
+    copy.replace_range(ident_start..ident_start + latest.len(), next);
+    copy
+}
+
+/// The path `name` is imported through by a `use` item of `file` (as in `crate::dto::`),
+/// and the byte offset of the end of that item.
+fn import_of(source: &str, file: &syn::File, name: &str) -> Option<(String, usize)> {
+    fn find(tree: &UseTree, name: &str, path: &mut Vec<String>) -> bool {
+        match tree {
+            UseTree::Path(segment) => {
+                path.push(segment.ident.to_string());
+                if find(&segment.tree, name, path) {
+                    return true;
+                }
+                path.pop();
+                false
+            }
+            UseTree::Name(leaf) => leaf.ident == name,
+            UseTree::Group(group) => group.items.iter().any(|tree| find(tree, name, path)),
+            UseTree::Rename(_) | UseTree::Glob(_) => false,
+        }
+    }
+
+    file.items.iter().find_map(|item| {
+        let Item::Use(import) = item else {
+            return None;
+        };
+        let mut path = Vec::new();
+        if !find(&import.tree, name, &mut path) {
+            return None;
+        }
+        let leading = if import.leading_colon.is_some() {
+            "::"
+        } else {
+            ""
+        };
+        let path: String = path.iter().flat_map(|segment| [segment, "::"]).collect();
+        Some((format!("{leading}{path}"), item_end(source, item)))
+    })
+}
+
+/// Apply insertions at byte offsets of `source`, from the back so that earlier offsets stay
+/// valid.
+fn apply(source: &str, mut edits: Vec<(usize, String)>) -> String {
+    edits.sort_by_key(|(offset, _)| std::cmp::Reverse(*offset));
+    let mut edited = source.to_string();
+    for (offset, text) in edits {
+        edited.insert_str(offset, &text);
+    }
+    edited
+}
+
+/// The name and attributes of a struct or enum.
+fn type_item(item: &Item) -> Option<(&Ident, &[syn::Attribute])> {
+    match item {
+        Item::Struct(item) => Some((&item.ident, &item.attrs)),
+        Item::Enum(item) => Some((&item.ident, &item.attrs)),
+        _ => None,
+    }
+}
+
+fn find_item<'a>(file: &'a syn::File, name: &str) -> Option<&'a Item> {
+    file.items
+        .iter()
+        .find(|item| type_item(item).is_some_and(|(ident, _)| ident == name))
+}
+
+/// The options of a `#[versioned(...)]` attribute that `bump` needs.
+fn parse_chain(source: &str, domain: &Ident, tokens: &TokenStream) -> Result<Chain, String> {
+    let mut chain = None;
+    let mut infallible = false;
+    let mut error = None;
+    for option in split_commas(tokens.clone()) {
+        let name = match option.first() {
+            Some(TokenTree::Ident(name)) => name.to_string(),
+            _ => continue,
+        };
+        match (name.as_str(), &option[1..]) {
+            ("chain", [TokenTree::Group(group)]) if group.delimiter() == Delimiter::Parenthesis => {
+                chain = Some(group.clone());
+            }
+            ("mode", [_, TokenTree::Literal(mode)]) => {
+                infallible = mode.to_string() == "\"infallible\"";
+            }
+            ("error", [_, value @ ..]) if !value.is_empty() => {
+                let start = value[0].span().start();
+                let end = value[value.len() - 1].span().end();
+                let lines = line_starts(source);
+                let text = &source[offset(source, &lines, start)..offset(source, &lines, end)];
+                error = Some(text.trim_matches('"').to_string());
+            }
+            ("self_latest" | "generate_latest", _) => {
+                return Err(format!(
+                    "the latest version of {domain} is the domain type, so there is no DTO to copy"
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    let chain = chain.ok_or_else(|| format!("{domain} has no chain(...)"))?;
+    let latest = split_commas(chain.stream())
+        .pop()
+        .ok_or_else(|| format!("the chain of {domain} is empty"))?;
+    let (prefix, latest) = entry_name(&latest)?;
+    let migration = if infallible {
+        Migration::From
+    } else {
+        Migration::TryFrom {
+            error: error.ok_or_else(|| format!("{domain} is fallible but names no error type"))?,
+        }
+    };
+    Ok(Chain {
+        domain: domain.to_string(),
+        close: chain.span_close().start(),
+        prefix,
+        latest,
+        migration,
+    })
+}
+
+/// The type named by a chain entry, without its modifiers, and the module path before it
+/// (as in `dto::`).
+fn entry_name(entry: &[TokenTree]) -> Result<(String, String), String> {
+    let mut prefix = String::new();
+    let mut name = None;
+    let mut tokens = entry.iter().peekable();
+    while let Some(TokenTree::Ident(ident)) = tokens.next() {
+        name = Some(ident.to_string());
+        match tokens.peek() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == ':' => {
+                tokens.next();
+                tokens.next();
+                prefix.push_str(&ident.to_string());
+                prefix.push_str("::");
+            }
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => {
+                return Err(format!(
+                    "generic versions such as {ident}<..> are not supported"
+                ));
+            }
+            _ => break,
+        }
+    }
+    name.map(|name| (prefix, name))
+        .ok_or_else(|| "the latest chain entry is not a type".to_string())
+}
+
+/// Split a token stream at its top-level commas, dropping empty segments.
+fn split_commas(tokens: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut segments = vec![Vec::new()];
+    for token in tokens {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => segments.push(Vec::new()),
+            _ => segments
+                .last_mut()
+                .unwrap_or_else(|| unreachable!())
+                .push(token),
+        }
+    }
+    segments.retain(|segment| !segment.is_empty());
+    segments
+}
+
+/// The name after `latest`, counting up its trailing number (`ProductV2` to `ProductV3`).
+fn numbered_up(latest: &str) -> Result<String, String> {
+    let prefix = latest.trim_end_matches(|c: char| c.is_ascii_digit());
+    let number: u32 = latest[prefix.len()..].parse().map_err(|_| {
+        format!("{latest} does not end in a version number; name the new version with --name")
+    })?;
+    Ok(format!("{prefix}{}", number + 1))
+}
+
+/// The stubbed migration from `latest` into `next`.
+fn stub(latest: &str, next: &str, migration: &Migration) -> String {
+    match migration {
+        Migration::From => format!(
+            "impl From<{latest}> for {next} {{\n    \
+                 fn from(prev: {latest}) -> Self {{\n        \
+                     todo!(\"migrate {latest} to {next}\")\n    \
+                 }}\n\
+             }}"
+        ),
+        Migration::TryFrom { error } => format!(
+            "impl TryFrom<{latest}> for {next} {{\n    \
+                 type Error = {error};\n\n    \
+                 fn try_from(prev: {latest}) -> Result<Self, Self::Error> {{\n        \
+                     todo!(\"migrate {latest} to {next}\")\n    \
+                 }}\n\
+             }}"
+        ),
+    }
+}
+
+/// The byte offset at which each line of `source` starts.
+fn line_starts(source: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect()
+}
+
+/// The byte offset of a span location, whose column counts characters.
+fn offset(source: &str, lines: &[usize], location: LineColumn) -> usize {
+    let line_start = lines[location.line - 1];
+    source[line_start..]
+        .char_indices()
+        .nth(location.column)
+        .map_or(source.len(), |(idx, _)| line_start + idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFALLIBLE: &str = r#"use serde_evolve::Versioned;
+
+/// The first version.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProductV1 {
+    pub name: String,
+}
+
+/// The second version.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProductV2 {
+    pub name: String,
+    pub price: u32,
+}
+
+#[derive(Versioned)]
+#[versioned(mode = "infallible", chain(ProductV1, ProductV2))]
+pub struct Product {
+    pub name: String,
+}
+"#;
+
+    #[test]
+    fn copies_the_latest_dto_and_extends_the_chain() {
+        let bumped = bump(INFALLIBLE, None, &Options::default()).unwrap();
+        assert_eq!(
+            (&*bumped.domain, &*bumped.latest, &*bumped.next),
+            ("Product", "ProductV2", "ProductV3")
+        );
+        assert!(
+            bumped
+                .source
+                .contains("chain(ProductV1, ProductV2, ProductV3)")
+        );
+        assert!(bumped.source.contains(
+            "/// The second version.\n#[derive(Clone, Serialize, Deserialize)]\npub struct ProductV3 {\n    pub name: String,\n    pub price: u32,\n}"
+        ));
+        assert!(bumped.source.contains(
+            "impl From<ProductV2> for ProductV3 {\n    fn from(prev: ProductV2) -> Self {\n        todo!(\"migrate ProductV2 to ProductV3\")\n    }\n}"
+        ));
+        assert!(bumped.source.contains("pub struct ProductV2 {"));
+        syn::parse_file(&bumped.source).unwrap();
+    }
+
+    #[test]
+    fn stubs_fallible_migrations_with_the_chain_error() {
+        let source = r"
+#[derive(Versioned)]
+#[versioned(
+    error = crate::Invalid,
+    chain(
+        OrderV1,
+        OrderV2 infallible,
+    ),
+)]
+pub enum Order {}
+
+pub enum OrderV2 { Open }
+";
+        let bumped = bump(source, None, &Options::default()).unwrap();
+        assert!(bumped.source.contains("OrderV2 infallible, OrderV3,\n"));
+        assert!(bumped.source.contains("pub enum OrderV3 { Open }"));
+        assert!(
+            bumped
+                .source
+                .contains("impl TryFrom<OrderV2> for OrderV3 {\n    type Error = crate::Invalid;")
+        );
+        syn::parse_file(&bumped.source).unwrap();
+    }
+
+    #[test]
+    fn defines_the_new_version_beside_a_dto_in_another_file() {
+        let chain = r#"use crate::dto::{ProductV1, ProductV2};
+
+#[derive(Versioned)]
+#[versioned(mode = "infallible", chain(ProductV1, ProductV2))]
+pub struct Product {
+    pub name: String,
+}
+"#;
+        let dto = "pub struct ProductV1 {}\n\npub struct ProductV2 {\n    pub name: String,\n}\n";
+        assert_eq!(latest(chain, &Options::default()).unwrap(), "ProductV2");
+        assert!(!defines(chain, "ProductV2"));
+        assert!(defines(dto, "ProductV2"));
+
+        let bumped = bump(chain, Some(dto), &Options::default()).unwrap();
+        assert!(
+            bumped
+                .source
+                .contains("chain(ProductV1, ProductV2, ProductV3)")
+        );
+        assert!(
+            bumped.source.starts_with(
+                "use crate::dto::{ProductV1, ProductV2};\nuse crate::dto::ProductV3;\n"
+            )
+        );
+        let dto = bumped.dto_source.unwrap();
+        assert!(dto.contains("pub struct ProductV3 {\n    pub name: String,\n}"));
+        assert!(dto.contains("impl From<ProductV2> for ProductV3 {"));
+        syn::parse_file(&bumped.source).unwrap();
+        syn::parse_file(&dto).unwrap();
+    }
+
+    #[test]
+    fn names_the_new_version_through_the_path_of_the_latest() {
+        let chain = INFALLIBLE.replace(
+            "chain(ProductV1, ProductV2)",
+            "chain(v1::ProductV1, dto::ProductV2)",
+        );
+        let dto = "pub struct ProductV2 {}\n";
+        let bumped = bump(&chain, Some(dto), &Options::default()).unwrap();
+        assert!(
+            bumped
+                .source
+                .contains("chain(v1::ProductV1, dto::ProductV2, dto::ProductV3)")
+        );
+        assert!(!bumped.source.contains("use dto"));
+
+        assert_eq!(
+            bump(&chain, Some("pub struct Other {}"), &Options::default()).unwrap_err(),
+            "ProductV2 is not defined in the DTO file"
+        );
+    }
+
+    #[test]
+    fn names_the_new_version_on_request() {
+        let options = Options {
+            domain: Some("Product".to_string()),
+            name: Some("ProductNext".to_string()),
+        };
+        let bumped = bump(INFALLIBLE, None, &options).unwrap();
+        assert!(
+            bumped
+                .source
+                .contains("chain(ProductV1, ProductV2, ProductNext)")
+        );
+    }
+
+    #[test]
+    fn errors_on_chains_it_cannot_extend() {
+        let missing = INFALLIBLE.replace("pub struct ProductV2", "pub struct Other");
+        assert_eq!(
+            bump(&missing, None, &Options::default()).unwrap_err(),
+            "ProductV2 is not defined in this file"
+        );
+
+        let unnumbered = INFALLIBLE.replace("ProductV2", "ProductLatest");
+        assert!(
+            bump(&unnumbered, None, &Options::default())
+                .unwrap_err()
+                .starts_with("ProductLatest does not end in a version number")
+        );
+
+        let options = Options {
+            domain: Some("Order".to_string()),
+            name: None,
+        };
+        assert_eq!(
+            bump(INFALLIBLE, None, &options).unwrap_err(),
+            "no #[versioned(...)] chain found"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! `cargo evolve`: scaffolding for `serde-evolve` migration chains.
+//!
+//! `cargo evolve bump <FILE>` adds a version to the chain of the `#[versioned(...)]` type in
+//! `FILE`: it copies the latest version DTO to the next version number, appends the copy to
+//! the chain, and stubs the migration into it with a `todo!()`. A DTO defined in another
+//! file is found among the source files of the crate holding `FILE`.
+
+mod bump;
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: cargo evolve bump <FILE> [--type <NAME>] [--name <NAME>] [--dto <DTO_FILE>]
+
+Add a version to the chain of the #[versioned(...)] type in FILE.
+
+options:
+    --type <NAME>      the domain type, if FILE holds more than one chain
+    --name <NAME>      the name of the new version DTO (default: the latest, numbered up)
+    --dto <DTO_FILE>   the file defining the latest DTO (default: FILE, or the one file of
+                       the crate's sources defining it)";
+
+fn main() -> ExitCode {
+    // Run as `cargo evolve`, cargo passes the subcommand name as the first argument.
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .skip_while(|arg| arg == "evolve")
+        .collect();
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(USAGE.to_string());
+    };
+    if command != "bump" {
+        return Err(format!("unknown command '{command}'\n\n{USAGE}"));
+    }
+
+    let mut file = None;
+    let mut dto = None;
+    let mut options = bump::Options::default();
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = || {
+            rest.next()
+                .cloned()
+                .ok_or_else(|| format!("'{arg}' needs a value"))
+        };
+        match arg.as_str() {
+            "--type" => options.domain = Some(value()?),
+            "--name" => options.name = Some(value()?),
+            "--dto" => dto = Some(PathBuf::from(value()?)),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ if file.is_none() && !arg.starts_with('-') => file = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument '{arg}'\n\n{USAGE}")),
+        }
+    }
+    let file = file.ok_or_else(|| USAGE.to_string())?;
+
+    let source = read(&file)?;
+    let latest = bump::latest(&source, &options)?;
+    let dto = match dto {
+        Some(dto) => Some(dto),
+        None if bump::defines(&source, &latest) => None,
+        None => Some(find_dto(&file, &latest)?),
+    };
+    let dto_source = dto.as_deref().map(read).transpose()?;
+    let bumped = bump::bump(&source, dto_source.as_deref(), &options)?;
+    write(&file, &bumped.source)?;
+    if let (Some(dto), Some(dto_source)) = (&dto, &bumped.dto_source) {
+        write(dto, dto_source)?;
+    }
+
+    println!(
+        "added {} to the chain of {} in {}",
+        bumped.next,
+        bumped.domain,
+        file.display()
+    );
+    if let Some(dto) = &dto {
+        println!("defined {} in {}", bumped.next, dto.display());
+    }
+    println!(
+        "next: fill in the migration from {} to {}, and convert {} from {} instead of {}",
+        bumped.latest, bumped.next, bumped.domain, bumped.next, bumped.latest
+    );
+    Ok(())
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|err| format!("cannot read {}: {err}", path.display()))
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    std::fs::write(path, contents).map_err(|err| format!("cannot write {}: {err}", path.display()))
+}
+
+/// The one source file of the crate holding `file` that defines the DTO `name`.
+fn find_dto(file: &Path, name: &str) -> Result<PathBuf, String> {
+    let root = file
+        .canonicalize()
+        .map_err(|err| format!("cannot read {}: {err}", file.display()))?
+        .ancestors()
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(|dir| dir.join("src"))
+        .ok_or_else(|| format!("{} is not part of a crate; pass --dto", file.display()))?;
+
+    let mut found = Vec::new();
+    let mut dirs = vec![root];
+    while let Some(dir) = dirs.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|err| format!("cannot read {}: {err}", dir.display()))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "rs")
+                && read(&path).is_ok_and(|source| bump::defines(&source, name))
+            {
+                found.push(path);
+            }
+        }
+    }
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        0 => Err(format!(
+            "{name} is not defined in the crate's sources; pass its file with --dto"
+        )),
+        _ => Err(format!(
+            "{name} is defined in several files; choose one with --dto"
+        )),
+    }
+}
@@ -0,0 +1,110 @@
+//! `actix-web` request and response bodies in any version.
+//!
+//! [`VersionedJson`] extracts a JSON request body of any historical version, migrating it to
+//! the domain type, and responds with the latest version:
+//!
+//! ```rust,ignore
+//! use serde_evolve::actix::VersionedJson;
+//!
+//! #[post("/products")]
+//! async fn update(VersionedJson(product): VersionedJson<Product>) -> VersionedJson<Product> {
+//!     VersionedJson(product.with_discount(10))
+//! }
+//! ```
+//!
+//! Limits and error handlers configured with `web::JsonConfig` apply to the body as usual.
+
+use crate::Versioned;
+use crate::migrate::BoxError;
+use ::actix_web::body::EitherBody;
+use ::actix_web::dev::Payload;
+use ::actix_web::http::StatusCode;
+use ::actix_web::web::Json;
+use ::actix_web::{FromRequest, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+/// A JSON body holding a versioned domain type.
+///
+/// As an extractor, it accepts any version of the representation and migrates it to `T`.
+/// As a response, it serializes `T` at the latest version.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VersionedJson<T>(pub T);
+
+impl<T> FromRequest for VersionedJson<T>
+where
+    T: Versioned + TryFrom<T::Representation> + 'static,
+    T::Representation: DeserializeOwned + 'static,
+    T::Error: Into<BoxError>,
+{
+    type Error = Rejection;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = Json::<T::Representation>::from_request(req, payload);
+        Box::pin(async move {
+            let Json(rep) = json.await.map_err(Rejection::Json)?;
+            T::try_from(rep)
+                .map(Self)
+                .map_err(|err| Rejection::Migration(err.into()))
+        })
+    }
+}
+
+impl<T> Responder for VersionedJson<T>
+where
+    T: Versioned,
+    T::Representation: Serialize + for<'a> From<&'a T>,
+{
+    type Body = EitherBody<String>;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        Json(T::Representation::from(&self.0)).respond_to(req)
+    }
+}
+
+/// Rejection returned when a [`VersionedJson`] body cannot be extracted.
+#[derive(Debug)]
+pub enum Rejection {
+    /// The body is not valid JSON for any version.
+    Json(::actix_web::Error),
+    /// The body is a valid document, but a migration step rejected it.
+    Migration(BoxError),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "{err}"),
+            Self::Migration(err) => write!(f, "migration failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Rejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(err) => Some(err),
+            Self::Migration(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl ResponseError for Rejection {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Json(err) => err.as_response_error().status_code(),
+            Self::Migration(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            Self::Json(err) => err.error_response(),
+            Self::Migration(_) => HttpResponse::UnprocessableEntity().body(self.to_string()),
+        }
+    }
+}
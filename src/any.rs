@@ -0,0 +1,113 @@
+//! Type-erased representations, for stores holding documents of many types.
+//!
+//! With `#[versioned(any = true)]`, the representation enum implements [`AnyVersioned`], an
+//! object-safe view of a value of any version. A document store can then hold a mixed bag of
+//! versioned types as trait objects, and migrate each one without knowing its type:
+//!
+//! ```rust,ignore
+//! use serde_evolve::any::AnyVersioned;
+//!
+//! let documents: Vec<Box<dyn AnyVersioned>> = vec![
+//!     Box::new(UserVersions::V1(old_user)),
+//!     Box::new(ProductVersions::V3(product)),
+//! ];
+//!
+//! for document in documents.iter().filter(|document| !document.is_current()) {
+//!     let latest = document.migrate_to_latest_json()?;
+//!     store.put(document.type_name(), latest)?;
+//! }
+//! ```
+//!
+//! For tables holding many types, [`AnyVersioned::to_typed_json`] also writes the type name as
+//! a [`TYPE_FIELD`] next to the version tag, and
+//! [`Registry::deserialize_any_versioned`](crate::registry::Registry::deserialize_any_versioned)
+//! reads such documents back without a hand-written match on the type.
+
+use crate::registry::Error;
+use serde::ser::Error as _;
+use serde_json::Value;
+use std::any::Any;
+use std::fmt;
+
+/// The field holding the type name in documents written by [`AnyVersioned::to_typed_json`].
+pub const TYPE_FIELD: &str = "_type";
+
+/// An object-safe view of a representation enum.
+///
+/// Implemented by `#[derive(Versioned)]` with `any = true` or `register = "..."`.
+pub trait AnyVersioned: Any {
+    /// The name the type is registered as with `register`, or the name of the domain type.
+    fn type_name(&self) -> &'static str;
+
+    /// The version number of this value.
+    fn version(&self) -> u32;
+
+    /// The current (latest) version number of the type.
+    fn current_version(&self) -> u32;
+
+    /// Whether this value is at the current version.
+    fn is_current(&self) -> bool {
+        self.version() == self.current_version()
+    }
+
+    /// The value as a tagged JSON document, at its own version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be serialized.
+    fn to_json(&self) -> Result<Value, Error>;
+
+    /// The value as a JSON document tagged with both its type name and its version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be serialized, or does not serialize as an object
+    /// (as with untagged representations of tuple versions).
+    fn to_typed_json(&self) -> Result<Value, Error> {
+        let Value::Object(mut document) = self.to_json()? else {
+            return Err(Error::Json(serde_json::Error::custom(
+                "type-tagged documents must serialize as an object",
+            )));
+        };
+        document.insert(TYPE_FIELD.to_owned(), Value::from(self.type_name()));
+        Ok(Value::Object(document))
+    }
+
+    /// The value migrated to the current version, as a tagged JSON document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be serialized, or a migration step rejects it.
+    fn migrate_to_latest_json(&self) -> Result<Value, Error>;
+}
+
+impl dyn AnyVersioned {
+    /// The concrete representation, if it is a `T`.
+    #[must_use]
+    pub fn downcast_ref<T: AnyVersioned>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref()
+    }
+
+    /// The concrete representation, if it is a `T`, and the trait object otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns the trait object unchanged if it is not a `T`.
+    pub fn downcast<T: AnyVersioned>(self: Box<Self>) -> Result<Box<T>, Box<Self>> {
+        if (&*self as &dyn Any).is::<T>() {
+            let any: Box<dyn Any> = self;
+            Ok(any.downcast().unwrap_or_else(|_| unreachable!()))
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Debug for dyn AnyVersioned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyVersioned")
+            .field("type_name", &self.type_name())
+            .field("version", &self.version())
+            .finish_non_exhaustive()
+    }
+}
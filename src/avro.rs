@@ -0,0 +1,382 @@
+//! Avro schemas for version DTOs, derived from their JSON Schemas.
+//!
+//! With `schema = true`, the representation enum gains `avro_schema_for(version)`, which
+//! translates the JSON Schema of a version DTO into an Avro record schema named after the DTO
+//! (`ProductV2`). The record also carries the version number as a `serde_evolve.version`
+//! attribute, so schemas registered for each version can be told apart by Avro tooling:
+//!
+//! ```rust,ignore
+//! let v2 = ProductVersions::avro_schema_for(2).unwrap()?;
+//! // {"type": "record", "name": "ProductV2", "serde_evolve.version": 2, "fields": [...]}
+//! ```
+//!
+//! With the `avro` feature, [`write_container`] writes values of one version to an Avro object
+//! container file, storing the version in its header under [`VERSION_ATTRIBUTE`], and
+//! [`read_container`] reads such a file back into the representation enum, decoding each record
+//! as the DTO of the version in the header:
+//!
+//! ```rust,ignore
+//! let schema = ProductVersions::avro_schema_for(2).unwrap()?;
+//! let bytes = avro::write_container(&schema, &reps)?;
+//!
+//! let products = avro::read_container::<ProductVersions>(&bytes)?
+//!     .into_iter()
+//!     .map(Product::from);
+//! ```
+//!
+//! The translation covers the shapes serde data types usually take: records, optional
+//! fields (as unions with `null`), sequences, string-keyed maps, unit-variant enums, and
+//! the scalar types. Anything else is reported as an [`Error`].
+
+#[cfg(feature = "avro")]
+use crate::{Payload, Representation, UnsupportedVersion, payload, representation::PayloadRef};
+use schemars::Schema;
+use serde_json::{Map, Value, json};
+use std::collections::HashSet;
+use std::fmt;
+
+/// An Avro schema, in its JSON form.
+pub type AvroSchema = Value;
+
+/// The attribute holding the version number on the record schema of each version, and the
+/// key to store it under in an Avro container header.
+pub const VERSION_ATTRIBUTE: &str = "serde_evolve.version";
+
+/// A JSON Schema construct with no Avro translation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    /// Where the construct is, as a path of field names from the record.
+    pub path: String,
+    /// Why it has no translation.
+    pub reason: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no Avro schema for '{}': {}", self.path, self.reason)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Translate the JSON Schema of a version DTO into an Avro record schema named `name`.
+///
+/// # Errors
+///
+/// Returns an error if the schema uses a construct with no Avro translation, or a field or
+/// type name is not a valid Avro name.
+pub fn from_json_schema(schema: &Schema, name: &str, version: u32) -> Result<AvroSchema, Error> {
+    let root = schema.as_value();
+    let mut translator = Translator {
+        defs: root.get("$defs").and_then(Value::as_object),
+        named: HashSet::new(),
+    };
+    let mut record = translator.record(root, name, name)?;
+    record[VERSION_ATTRIBUTE] = json!(version);
+    Ok(record)
+}
+
+/// The Avro name for a version DTO: the last segment of its path, without type arguments.
+#[must_use]
+pub fn record_name(type_name: &str) -> &str {
+    let path = type_name.split('<').next().unwrap_or(type_name);
+    path.rsplit("::").next().unwrap_or(path).trim()
+}
+
+/// Error returned by [`write_container`] and [`read_container`].
+#[cfg(feature = "avro")]
+#[derive(Debug)]
+pub enum ContainerError {
+    /// The container could not be encoded or decoded.
+    Avro(Box<apache_avro::Error>),
+    /// The schema, or the container header, does not carry a version.
+    MissingVersion,
+    /// A value is not at the version of the container's schema.
+    VersionMismatch {
+        /// The version of the schema.
+        schema: u32,
+        /// The version of the value.
+        found: u32,
+    },
+    /// The container holds a version this build cannot read.
+    UnsupportedVersion(UnsupportedVersion),
+    /// A record does not match the DTO of the container's version.
+    Payload(payload::Error),
+}
+
+#[cfg(feature = "avro")]
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Avro(err) => write!(f, "Avro error: {err}"),
+            Self::MissingVersion => {
+                write!(f, "no '{VERSION_ATTRIBUTE}' in the Avro schema or header")
+            }
+            Self::VersionMismatch { schema, found } => {
+                write!(
+                    f,
+                    "cannot write version {found} with the schema of version {schema}"
+                )
+            }
+            Self::UnsupportedVersion(err) => err.fmt(f),
+            Self::Payload(err) => write!(f, "invalid record: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "avro")]
+impl std::error::Error for ContainerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Avro(err) => Some(err),
+            Self::UnsupportedVersion(err) => Some(err),
+            Self::Payload(err) => Some(err),
+            Self::MissingVersion | Self::VersionMismatch { .. } => None,
+        }
+    }
+}
+
+#[cfg(feature = "avro")]
+impl From<apache_avro::Error> for ContainerError {
+    fn from(err: apache_avro::Error) -> Self {
+        Self::Avro(Box::new(err))
+    }
+}
+
+/// Write `reps` to an Avro object container file with `schema`, the Avro schema of their
+/// version (as returned by `avro_schema_for`), storing the version in the file header.
+///
+/// # Errors
+///
+/// Returns an error if `schema` carries no version or is not a valid Avro schema, a value is
+/// at another version, or a value does not match the schema.
+#[cfg(feature = "avro")]
+pub fn write_container<'a, R: Representation + 'a>(
+    schema: &AvroSchema,
+    reps: impl IntoIterator<Item = &'a R>,
+) -> Result<Vec<u8>, ContainerError> {
+    let version = schema
+        .get(VERSION_ATTRIBUTE)
+        .and_then(Value::as_u64)
+        .and_then(|version| u32::try_from(version).ok())
+        .ok_or(ContainerError::MissingVersion)?;
+    let schema = apache_avro::Schema::parse(schema)?;
+    let mut writer = apache_avro::Writer::new(&schema, Vec::new());
+    writer.add_user_metadata(VERSION_ATTRIBUTE.to_owned(), version.to_string())?;
+    for rep in reps {
+        if rep.version() != version {
+            return Err(ContainerError::VersionMismatch {
+                schema: version,
+                found: rep.version(),
+            });
+        }
+        // `append_ser` writes record fields in the order they are serialized, rather than in
+        // schema order; going through a `Value` matches them by name.
+        writer.append(apache_avro::to_value(PayloadRef(rep))?)?;
+    }
+    Ok(writer.into_inner()?)
+}
+
+/// Read an Avro object container file written by [`write_container`], decoding every record
+/// as the DTO of the version in the file header.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be decoded, its header has no version or one this
+/// build cannot read, or a record does not match the DTO of that version.
+#[cfg(feature = "avro")]
+pub fn read_container<R: Representation>(bytes: &[u8]) -> Result<Vec<R>, ContainerError> {
+    let reader = apache_avro::Reader::new(bytes)?;
+    let version: u32 = reader
+        .user_metadata()
+        .get(VERSION_ATTRIBUTE)
+        .and_then(|version| std::str::from_utf8(version).ok()?.parse().ok())
+        .ok_or(ContainerError::MissingVersion)?;
+    let unsupported = || {
+        ContainerError::UnsupportedVersion(UnsupportedVersion {
+            found: version,
+            min: R::MIN_SUPPORTED,
+            max: R::CURRENT,
+        })
+    };
+    // Check the version up front, so that it is rejected even without records.
+    if R::tag_of_version(version).is_none() {
+        return Err(unsupported());
+    }
+    reader
+        .map(|record| {
+            let payload: Payload = apache_avro::from_value(&record?)?;
+            R::from_payload(version, payload)
+                .ok_or_else(unsupported)?
+                .map_err(ContainerError::Payload)
+        })
+        .collect()
+}
+
+struct Translator<'a> {
+    defs: Option<&'a Map<String, Value>>,
+    /// Named types already defined, which later uses refer to by name.
+    named: HashSet<String>,
+}
+
+impl Translator<'_> {
+    fn translate(&mut self, schema: &Value, name: &str, path: &str) -> Result<Value, Error> {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return self.reference(reference, path);
+        }
+        if let Some(variants) = schema
+            .get("anyOf")
+            .or_else(|| schema.get("oneOf"))
+            .and_then(Value::as_array)
+        {
+            return self.nullable(variants, name, path);
+        }
+        if let Some(symbols) = schema.get("enum").and_then(Value::as_array) {
+            return self.enumeration(symbols, name, path);
+        }
+
+        match schema.get("type") {
+            Some(Value::String(ty)) => self.typed(schema, ty, name, path),
+            Some(Value::Array(types)) => {
+                let types: Vec<_> = types.iter().filter(|ty| *ty != "null").collect();
+                let [Value::String(ty)] = types.as_slice() else {
+                    return Err(unsupported(path, "a union of several types"));
+                };
+                Ok(json!(["null", self.typed(schema, ty, name, path)?]))
+            }
+            _ => Err(unsupported(path, "a schema without a type")),
+        }
+    }
+
+    fn typed(&mut self, schema: &Value, ty: &str, name: &str, path: &str) -> Result<Value, Error> {
+        let format = schema.get("format").and_then(Value::as_str);
+        Ok(match ty {
+            "null" | "boolean" | "string" => json!(ty),
+            "integer" => match format {
+                Some("int8" | "int16" | "int32" | "uint8" | "uint16") => json!("int"),
+                _ => json!("long"),
+            },
+            "number" => match format {
+                Some("float") => json!("float"),
+                _ => json!("double"),
+            },
+            "array" => {
+                let items = schema
+                    .get("items")
+                    .ok_or_else(|| unsupported(path, "a sequence without an item schema"))?;
+                json!({ "type": "array", "items": self.translate(items, name, path)? })
+            }
+            "object" if schema.get("properties").is_some() => self.record(schema, name, path)?,
+            "object" => {
+                let values = schema
+                    .get("additionalProperties")
+                    .filter(|values| values.is_object())
+                    .ok_or_else(|| unsupported(path, "an object without properties"))?;
+                json!({ "type": "map", "values": self.translate(values, name, path)? })
+            }
+            other => return Err(unsupported(path, &format!("the type '{other}'"))),
+        })
+    }
+
+    fn record(&mut self, schema: &Value, name: &str, path: &str) -> Result<Value, Error> {
+        check_name(name, path)?;
+        self.named.insert(name.to_owned());
+        let required: HashSet<_> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+
+        let mut fields = Vec::new();
+        for (field, field_schema) in schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            let field_path = format!("{path}.{field}");
+            check_name(field, &field_path)?;
+            let ty = self.translate(field_schema, &format!("{name}_{field}"), &field_path)?;
+            let nullable = ty.as_array().is_some_and(|types| types[0] == "null");
+            let mut avro_field = json!({ "name": field, "type": ty });
+            if !required.contains(field.as_str()) && nullable {
+                avro_field["default"] = Value::Null;
+            }
+            fields.push(avro_field);
+        }
+        // Field order is part of an Avro schema. Sort by name, so that it does not depend on
+        // whether `serde_json/preserve_order` is enabled somewhere in the build.
+        fields.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+        Ok(json!({ "type": "record", "name": name, "fields": fields }))
+    }
+
+    fn reference(&mut self, reference: &str, path: &str) -> Result<Value, Error> {
+        let name = reference
+            .strip_prefix("#/$defs/")
+            .ok_or_else(|| unsupported(path, &format!("the reference '{reference}'")))?;
+        if self.named.contains(name) {
+            return Ok(json!(name));
+        }
+        let def = self
+            .defs
+            .and_then(|defs| defs.get(name))
+            .ok_or_else(|| unsupported(path, &format!("the missing definition '{name}'")))?;
+        self.translate(def, name, path)
+    }
+
+    fn nullable(&mut self, variants: &[Value], name: &str, path: &str) -> Result<Value, Error> {
+        let is_null = |variant: &Value| variant.get("type").is_some_and(|ty| ty == "null");
+        let non_null: Vec<_> = variants
+            .iter()
+            .filter(|variant| !is_null(variant))
+            .collect();
+        match non_null.as_slice() {
+            [inner] if non_null.len() < variants.len() => {
+                Ok(json!(["null", self.translate(inner, name, path)?]))
+            }
+            _ => Err(unsupported(path, "a union other than an optional value")),
+        }
+    }
+
+    fn enumeration(&mut self, symbols: &[Value], name: &str, path: &str) -> Result<Value, Error> {
+        check_name(name, path)?;
+        let symbols = symbols
+            .iter()
+            .map(|symbol| {
+                let symbol = symbol
+                    .as_str()
+                    .ok_or_else(|| unsupported(path, "an enum with non-string values"))?;
+                check_name(symbol, path)?;
+                Ok(symbol)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.named.insert(name.to_owned());
+        Ok(json!({ "type": "enum", "name": name, "symbols": symbols }))
+    }
+}
+
+/// Avro names start with a letter or underscore, followed by letters, digits or underscores.
+fn check_name(name: &str, path: &str) -> Result<(), Error> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(unsupported(
+            path,
+            &format!("'{name}' is not a valid Avro name"),
+        ))
+    }
+}
+
+fn unsupported(path: &str, reason: &str) -> Error {
+    Error {
+        path: path.to_owned(),
+        reason: reason.to_owned(),
+    }
+}
@@ -0,0 +1,97 @@
+//! `axum` request and response bodies in any version.
+//!
+//! [`VersionedJson`] extracts a JSON request body of any historical version, migrating it to
+//! the domain type, and responds with the latest version:
+//!
+//! ```rust,ignore
+//! use serde_evolve::axum::VersionedJson;
+//!
+//! async fn update(VersionedJson(product): VersionedJson<Product>) -> VersionedJson<Product> {
+//!     VersionedJson(product.with_discount(10))
+//! }
+//! ```
+
+use crate::Versioned;
+use crate::migrate::BoxError;
+use ::axum::Json;
+use ::axum::extract::rejection::JsonRejection;
+use ::axum::extract::{FromRequest, Request};
+use ::axum::http::StatusCode;
+use ::axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+/// A JSON body holding a versioned domain type.
+///
+/// As an extractor, it accepts any version of the representation and migrates it to `T`.
+/// As a response, it serializes `T` at the latest version.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VersionedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for VersionedJson<T>
+where
+    T: Versioned + TryFrom<T::Representation> + Send,
+    T::Representation: DeserializeOwned + Send,
+    T::Error: Into<BoxError>,
+    S: Send + Sync,
+{
+    type Rejection = Rejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(rep) = Json::<T::Representation>::from_request(req, state)
+            .await
+            .map_err(Rejection::Json)?;
+        T::try_from(rep)
+            .map(Self)
+            .map_err(|err| Rejection::Migration(err.into()))
+    }
+}
+
+impl<T> IntoResponse for VersionedJson<T>
+where
+    T: Versioned,
+    T::Representation: Serialize + for<'a> From<&'a T>,
+{
+    fn into_response(self) -> Response {
+        Json(T::Representation::from(&self.0)).into_response()
+    }
+}
+
+/// Rejection returned when a [`VersionedJson`] body cannot be extracted.
+#[derive(Debug)]
+pub enum Rejection {
+    /// The body is not valid JSON for any version.
+    Json(JsonRejection),
+    /// The body is a valid document, but a migration step rejected it.
+    Migration(BoxError),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "{err}"),
+            Self::Migration(err) => write!(f, "migration failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Rejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(err) => Some(err),
+            Self::Migration(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Json(err) => err.into_response(),
+            Self::Migration(_) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, self.to_string()).into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! Migrating collections of documents written at different versions.
+//!
+//! Bulk exports hold records written across many releases, and a single bad record should
+//! not cost the rest. [`VersionedVec`] deserializes a sequence of mixed-version documents,
+//! migrating each element on its own and recording failures by index:
+//!
+//! ```rust,ignore
+//! use serde_evolve::batch::VersionedVec;
+//!
+//! let export: VersionedVec<Order> = serde_json::from_str(&json)?;
+//! for (index, err) in &export.errors {
+//!     eprintln!("record {index}: {err}");
+//! }
+//! let orders: Vec<Order> = export.items;
+//! ```
+//!
+//! For representations already in hand, [`migrate_all_lossy`] keeps the values that migrate
+//! and reports the rest as [`MigrationFailure`]s.
+//!
+//! Columnar exports hold each document as an encoded blob in a binary column. [`migrate_column`]
+//! re-encodes such a column at the current version, row by row. Its input and output are the
+//! shapes an Arrow `BinaryArray` iterates over and is built from. With the `arrow` feature,
+//! [`migrate_record_batch`] does the same to a named column of a `RecordBatch`, so a Parquet
+//! backfill is:
+//!
+//! ```rust,ignore
+//! let migrated = batch::migrate_record_batch::<Order, _>(&Json::default(), &batch, "payload")?;
+//! writer.write(&migrated.batch)?;
+//! ```
+//!
+//! Row groups written at a single version are columns like any other.
+
+use crate::migrate::BoxError;
+use crate::payload::{self, Payload};
+use crate::{Format, Representation, Versioned};
+#[cfg(feature = "arrow")]
+use arrow_array::{ArrayRef, BinaryArray, LargeBinaryArray, RecordBatch, cast::AsArray};
+#[cfg(feature = "arrow")]
+use arrow_schema::{ArrowError, DataType, Schema};
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+#[cfg(feature = "arrow")]
+use std::sync::Arc;
+
+/// Why a single element could not be migrated.
+#[derive(Debug)]
+pub enum ElementError {
+    /// The element is not a valid document of any version.
+    Payload(payload::Error),
+    /// A migration step rejected the element.
+    Migration(BoxError),
+}
+
+impl fmt::Display for ElementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Payload(err) => write!(f, "invalid document: {err}"),
+            Self::Migration(err) => write!(f, "migration failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ElementError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Payload(err) => Some(err),
+            Self::Migration(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// A representation that failed to migrate in [`migrate_all_lossy`].
+#[derive(Debug)]
+pub struct MigrationFailure {
+    /// The position of the representation in the input.
+    pub index: usize,
+    /// The version the representation was at.
+    pub version: u32,
+    /// Why the migration failed.
+    pub error: BoxError,
+}
+
+impl fmt::Display for MigrationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "record {} at version {} failed to migrate: {}",
+            self.index, self.version, self.error
+        )
+    }
+}
+
+impl std::error::Error for MigrationFailure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
+/// Why a single row of a column could not be migrated.
+#[derive(Debug)]
+pub enum ColumnError<E> {
+    /// The row could not be decoded, or its migrated value encoded.
+    Format(E),
+    /// A migration step rejected the row.
+    Migration(BoxError),
+}
+
+impl<E: fmt::Display> fmt::Display for ColumnError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Format(err) => write!(f, "format error: {err}"),
+            Self::Migration(err) => write!(f, "migration failed: {err}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for ColumnError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Format(err) => Some(err),
+            Self::Migration(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// A column of encoded documents, re-encoded at the current version by [`migrate_column`].
+#[derive(Debug)]
+pub struct MigratedColumn<E> {
+    /// The re-encoded rows, in their original order. Null rows and rows that failed are
+    /// `None`, so the column keeps its length.
+    pub values: Vec<Option<Vec<u8>>>,
+    /// The rows that failed, with their index.
+    pub errors: Vec<(usize, ColumnError<E>)>,
+}
+
+impl<E> MigratedColumn<E> {
+    /// Whether every non-null row migrated.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Decode each row of a binary column with `format`, migrate it to the domain type, and
+/// encode it again at the current version.
+///
+/// Null rows stay null. Rows that fail are recorded with their index and become null.
+pub fn migrate_column<D, F, I, B>(format: &F, rows: I) -> MigratedColumn<F::Error>
+where
+    D: Versioned + TryFrom<D::Representation>,
+    D::Error: Into<BoxError>,
+    D::Representation: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    F: Format,
+    I: IntoIterator<Item = Option<B>>,
+    B: AsRef<[u8]>,
+{
+    let mut column = MigratedColumn {
+        values: Vec::new(),
+        errors: Vec::new(),
+    };
+    for (index, row) in rows.into_iter().enumerate() {
+        let value = row.map(|bytes| migrate_row::<D, F>(format, bytes.as_ref()));
+        column.values.push(match value {
+            Some(Ok(bytes)) => Some(bytes),
+            Some(Err(err)) => {
+                column.errors.push((index, err));
+                None
+            }
+            None => None,
+        });
+    }
+    column
+}
+
+fn migrate_row<D, F>(format: &F, bytes: &[u8]) -> Result<Vec<u8>, ColumnError<F::Error>>
+where
+    D: Versioned + TryFrom<D::Representation>,
+    D::Error: Into<BoxError>,
+    D::Representation: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    F: Format,
+{
+    let rep: D::Representation = format.decode(bytes).map_err(ColumnError::Format)?;
+    let domain = D::try_from(rep).map_err(|err| ColumnError::Migration(err.into()))?;
+    format
+        .encode(&D::Representation::from(&domain))
+        .map_err(ColumnError::Format)
+}
+
+/// A record batch with one column re-encoded at the current version by
+/// [`migrate_record_batch`].
+#[cfg(feature = "arrow")]
+#[derive(Debug)]
+pub struct MigratedBatch<E> {
+    /// The batch, with the migrated column in place of the original. Rows that failed are null.
+    pub batch: RecordBatch,
+    /// The rows that failed, with their index.
+    pub errors: Vec<(usize, ColumnError<E>)>,
+}
+
+#[cfg(feature = "arrow")]
+impl<E> MigratedBatch<E> {
+    /// Whether every non-null row migrated.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Migrate the `Binary` or `LargeBinary` column named `column` of a record batch with
+/// [`migrate_column`], leaving the other columns as they are.
+///
+/// Rows that fail become null, and the column is made nullable if it was not.
+///
+/// # Errors
+///
+/// Returns an error if the batch has no such column, or it is not a binary column.
+#[cfg(feature = "arrow")]
+pub fn migrate_record_batch<D, F>(
+    format: &F,
+    batch: &RecordBatch,
+    column: &str,
+) -> Result<MigratedBatch<F::Error>, ArrowError>
+where
+    D: Versioned + TryFrom<D::Representation>,
+    D::Error: Into<BoxError>,
+    D::Representation: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    F: Format,
+{
+    let schema = batch.schema();
+    let index = schema.index_of(column)?;
+    let rows = batch.column(index);
+    let (migrated, errors): (ArrayRef, _) = match rows.data_type() {
+        DataType::Binary => {
+            let column = migrate_column::<D, F, _, _>(format, rows.as_binary::<i32>());
+            (
+                Arc::new(BinaryArray::from_iter(column.values)),
+                column.errors,
+            )
+        }
+        DataType::LargeBinary => {
+            let column = migrate_column::<D, F, _, _>(format, rows.as_binary::<i64>());
+            (
+                Arc::new(LargeBinaryArray::from_iter(column.values)),
+                column.errors,
+            )
+        }
+        other => {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "column '{column}' is {other}, not binary"
+            )));
+        }
+    };
+
+    let mut fields = schema.fields().to_vec();
+    if migrated.null_count() > 0 && !fields[index].is_nullable() {
+        fields[index] = Arc::new(fields[index].as_ref().clone().with_nullable(true));
+    }
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    let mut columns = batch.columns().to_vec();
+    columns[index] = migrated;
+    Ok(MigratedBatch {
+        batch: RecordBatch::try_new(Arc::new(schema), columns)?,
+        errors,
+    })
+}
+
+/// Migrate every representation to the domain type, keeping those that succeed.
+///
+/// Returns the migrated values in input order, and a failure for each representation that a
+/// migration step rejected.
+pub fn migrate_all_lossy<D, I>(reps: I) -> (Vec<D>, Vec<MigrationFailure>)
+where
+    D: Versioned + TryFrom<D::Representation>,
+    D::Error: Into<BoxError>,
+    I: IntoIterator<Item = D::Representation>,
+{
+    let mut migrated = Vec::new();
+    let mut failures = Vec::new();
+    for (index, rep) in reps.into_iter().enumerate() {
+        let version = rep.version();
+        match D::try_from(rep) {
+            Ok(domain) => migrated.push(domain),
+            Err(err) => failures.push(MigrationFailure {
+                index,
+                version,
+                error: err.into(),
+            }),
+        }
+    }
+    (migrated, failures)
+}
+
+/// A sequence of documents of any version, migrated element by element.
+///
+/// Deserialization only fails if the input is not a sequence: elements that do not decode or
+/// migrate are recorded in [`errors`](Self::errors) with their index, and skipped.
+/// Serializing writes the migrated items at the current version.
+#[derive(Debug)]
+pub struct VersionedVec<D> {
+    /// The elements that migrated, in their original order.
+    pub items: Vec<D>,
+    /// The elements that failed, with their index in the input.
+    pub errors: Vec<(usize, ElementError)>,
+}
+
+impl<D> VersionedVec<D> {
+    /// Whether every element migrated.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The migrated items if every element migrated, and the failures otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns the indexed failures if any element failed.
+    pub fn into_result(self) -> Result<Vec<D>, Vec<(usize, ElementError)>> {
+        if self.errors.is_empty() {
+            Ok(self.items)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+impl<D> Default for VersionedVec<D> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl<'de, D> Deserialize<'de> for VersionedVec<D>
+where
+    D: Versioned + TryFrom<D::Representation>,
+    D::Error: Into<BoxError>,
+    D::Representation: DeserializeOwned,
+{
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let elements = Vec::<Payload>::deserialize(deserializer)?;
+        let mut vec = Self::default();
+        for (index, element) in elements.into_iter().enumerate() {
+            match migrate_element::<D>(element) {
+                Ok(item) => vec.items.push(item),
+                Err(err) => vec.errors.push((index, err)),
+            }
+        }
+        Ok(vec)
+    }
+}
+
+impl<D> Serialize for VersionedVec<D>
+where
+    D: Versioned,
+    D::Representation: Serialize + for<'a> From<&'a D>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.items.len()))?;
+        for item in &self.items {
+            seq.serialize_element(&D::Representation::from(item))?;
+        }
+        seq.end()
+    }
+}
+
+fn migrate_element<D>(element: Payload) -> Result<D, ElementError>
+where
+    D: Versioned + TryFrom<D::Representation>,
+    D::Error: Into<BoxError>,
+    D::Representation: DeserializeOwned,
+{
+    let rep: D::Representation = element.deserialize().map_err(ElementError::Payload)?;
+    D::try_from(rep).map_err(|err| ElementError::Migration(err.into()))
+}
@@ -0,0 +1,253 @@
+//! Chain entries whose types do not implement serde.
+//!
+//! Historical versions are sometimes types from another serialization framework, such as
+//! prost-generated protobuf messages. Marking such an entry with `codec(...)` lets it take
+//! part in the chain: a [`Codec`] converts it to and from a serde-compatible wire type, and
+//! the representation enum holds it in a [`Coded`] wrapper.
+//!
+//! ```rust,ignore
+//! struct Protobuf;
+//!
+//! impl Codec<OrderProto> for Protobuf {
+//!     type Wire = ProtoBytes; // a serde struct holding the encoded message
+//!     type Error = prost::DecodeError;
+//!
+//!     fn to_wire(value: &OrderProto) -> ProtoBytes {
+//!         ProtoBytes { proto: value.encode_to_vec() }
+//!     }
+//!
+//!     fn from_wire(wire: ProtoBytes) -> Result<OrderProto, Self::Error> {
+//!         OrderProto::decode(wire.proto.as_slice())
+//!     }
+//! }
+//!
+//! #[derive(Versioned)]
+//! #[versioned(mode = "infallible", chain(OrderProto codec(Protobuf), OrderV2))]
+//! pub struct Order { /* ... */ }
+//! ```
+//!
+//! The migration out of a coded entry converts the unwrapped type, as with any other entry
+//! (`impl From<OrderProto> for OrderV2`).
+//!
+//! Types from another crate that implement neither serde nor a codec, but whose layout is
+//! known, are marked with `remote(...)` instead, naming a serde
+//! [remote definition](https://serde.rs/remote-derive.html) of the type. The representation
+//! enum holds it in a [`Remote`] wrapper, which serializes through the definition:
+//!
+//! ```rust,ignore
+//! #[derive(Serialize, Deserialize)]
+//! #[serde(remote = "vendor::Config")]
+//! struct ConfigDef {
+//!     host: String,
+//!     port: u16,
+//! }
+//!
+//! #[derive(Versioned)]
+//! #[versioned(mode = "infallible", chain(vendor::Config remote(ConfigDef), ConfigV2))]
+//! pub struct Config { /* ... */ }
+//! ```
+
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+/// Converts a chain entry type `T` to and from a serde-compatible wire type.
+pub trait Codec<T> {
+    /// The type `T` is serialized as.
+    type Wire: Serialize + DeserializeOwned;
+
+    /// The error returned when a wire value is not a valid `T`.
+    type Error: fmt::Display;
+
+    /// Convert a value to its wire type.
+    fn to_wire(value: &T) -> Self::Wire;
+
+    /// Convert a wire value back.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the wire value is not a valid `T`.
+    fn from_wire(wire: Self::Wire) -> Result<T, Self::Error>;
+}
+
+/// A value of `T`, serialized through the codec `C`.
+pub struct Coded<T, C> {
+    value: T,
+    codec: PhantomData<fn() -> C>,
+}
+
+impl<T, C> Coded<T, C> {
+    /// Wrap a value.
+    pub const fn new(value: T) -> Self {
+        Self {
+            value,
+            codec: PhantomData,
+        }
+    }
+
+    /// The wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, C> From<T> for Coded<T, C> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T, C> Deref for Coded<T, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, C> DerefMut for Coded<T, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: Clone, C> Clone for Coded<T, C> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for Coded<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: PartialEq, C> PartialEq for Coded<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, C> Eq for Coded<T, C> {}
+
+impl<T: Hash, C> Hash for Coded<T, C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<T, C: Codec<T>> Serialize for Coded<T, C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        C::to_wire(&self.value).serialize(serializer)
+    }
+}
+
+impl<'de, T, C: Codec<T>> Deserialize<'de> for Coded<T, C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wire = C::Wire::deserialize(deserializer)?;
+        C::from_wire(wire).map(Self::new).map_err(D::Error::custom)
+    }
+}
+
+/// A serde remote definition of a type `T` from another crate.
+///
+/// `#[derive(Versioned)]` implements this for the definition named by a `remote(...)` chain
+/// entry, forwarding to the functions generated by `#[serde(remote = "...")]`.
+pub trait RemoteDef<T> {
+    /// Serialize a value through the definition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the serializer fails.
+    fn serialize<S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error>;
+
+    /// Deserialize a value through the definition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input does not match the definition.
+    fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error>;
+}
+
+/// A value of the foreign type `T`, serialized through its remote definition `D`.
+pub struct Remote<T, D> {
+    value: T,
+    def: PhantomData<fn() -> D>,
+}
+
+impl<T, D> Remote<T, D> {
+    /// Wrap a value.
+    pub const fn new(value: T) -> Self {
+        Self {
+            value,
+            def: PhantomData,
+        }
+    }
+
+    /// The wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, D> From<T> for Remote<T, D> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T, D> Deref for Remote<T, D> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, D> DerefMut for Remote<T, D> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: Clone, D> Clone for Remote<T, D> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<T: fmt::Debug, D> fmt::Debug for Remote<T, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: PartialEq, D> PartialEq for Remote<T, D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, D> Eq for Remote<T, D> {}
+
+impl<T: Hash, D> Hash for Remote<T, D> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<T, D: RemoteDef<T>> Serialize for Remote<T, D> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        D::serialize(&self.value, serializer)
+    }
+}
+
+impl<'de, T, D: RemoteDef<T>> Deserialize<'de> for Remote<T, D> {
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        D::deserialize(deserializer).map(Self::new)
+    }
+}
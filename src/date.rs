@@ -0,0 +1,137 @@
+//! Dates identifying versions, for chains versioned by release date.
+//!
+//! With `dated = true`, every chain entry is tagged with the date it was released
+//! (`V1 as "2024-03-01"`, or `V1 as "2024-03"` for the first of the month), and the dates
+//! must increase along the chain. Version numbers still count the entries; the
+//! representation enum gains `date()`, `date_of_version(version)` and
+//! `is_older_than(date)`, which compare versions chronologically:
+//!
+//! ```rust,ignore
+//! #[versioned(mode = "infallible", dated = true, chain(V1 as "2024-03", V2 as "2025-01-15"))]
+//! pub struct Report { /* ... */ }
+//!
+//! let cutoff: VersionDate = "2025-01-01".parse()?;
+//! if rep.is_older_than(cutoff) {
+//!     // written before the 2025 schema
+//! }
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A calendar date identifying a version, ordered chronologically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VersionDate {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl VersionDate {
+    /// The date `year-month-day`, or `None` if there is no such date.
+    #[must_use]
+    pub const fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let leap =
+            year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return None,
+        };
+        if day == 0 || day > days_in_month {
+            return None;
+        }
+        Some(Self { year, month, day })
+    }
+
+    /// The year.
+    #[must_use]
+    pub const fn year(self) -> u16 {
+        self.year
+    }
+
+    /// The month, from 1 to 12.
+    #[must_use]
+    pub const fn month(self) -> u8 {
+        self.month
+    }
+
+    /// The day of the month, from 1.
+    #[must_use]
+    pub const fn day(self) -> u8 {
+        self.day
+    }
+
+    /// Whether `self` is strictly before `other`.
+    #[must_use]
+    pub const fn is_before(self, other: Self) -> bool {
+        if self.year != other.year {
+            return self.year < other.year;
+        }
+        if self.month != other.month {
+            return self.month < other.month;
+        }
+        self.day < other.day
+    }
+}
+
+impl fmt::Display for VersionDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A string that is not a date in the form `YYYY-MM-DD` or `YYYY-MM`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDateError {
+    /// The string that failed to parse.
+    pub input: String,
+}
+
+impl fmt::Display for ParseDateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid version date '{}', expected YYYY-MM-DD or YYYY-MM",
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for ParseDateError {}
+
+impl FromStr for VersionDate {
+    type Err = ParseDateError;
+
+    /// Parse `YYYY-MM-DD`, or `YYYY-MM` as the first of the month.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseDateError {
+            input: s.to_owned(),
+        };
+        let mut parts = s.split('-');
+        let mut next = |len: usize| {
+            parts
+                .next()
+                .filter(|part| part.len() == len && part.bytes().all(|b| b.is_ascii_digit()))
+        };
+        let year = next(4).ok_or_else(error)?;
+        let month = next(2).ok_or_else(error)?;
+        let day = match parts.next() {
+            None => "01",
+            Some(day) if day.len() == 2 && day.bytes().all(|b| b.is_ascii_digit()) => day,
+            Some(_) => return Err(error()),
+        };
+        if parts.next().is_some() {
+            return Err(error());
+        }
+
+        let (year, month, day) = (
+            year.parse().map_err(|_| error())?,
+            month.parse().map_err(|_| error())?,
+            day.parse().map_err(|_| error())?,
+        );
+        Self::new(year, month, day).ok_or_else(error)
+    }
+}
@@ -0,0 +1,353 @@
+//! Framings that carry the version number outside the payload.
+//!
+//! Internally tagged representation enums need a format that can describe its own structure,
+//! so they cannot be used with compact binary formats such as postcard or bincode. The
+//! envelope codec instead writes a fixed 8-byte header followed by the untagged payload of
+//! the version:
+//!
+//! | Bytes  | Contents                                 |
+//! |--------|------------------------------------------|
+//! | 0..4   | [`MAGIC`]                                |
+//! | 4..8   | version number, little-endian `u32`      |
+//! | 8..    | the version DTO, encoded by a [`Format`] |
+//!
+//! Decoding reads the header and dispatches straight to the matching version type.
+//!
+//! With the `checksum` feature, [`encode_checked`] starts the envelope with [`CHECKED_MAGIC`]
+//! instead, and inserts a little-endian CRC-32 of the payload after the version number.
+//! Decoding verifies it before the payload is read, so a corrupt blob is reported as
+//! [`Error::ChecksumMismatch`] rather than as a decoding or migration failure.
+//!
+//! The payload can be transformed independently of the header, for instance to compress
+//! large documents, by wrapping the format in [`Transformed`] with a [`Transform`]:
+//!
+//! ```rust,ignore
+//! let format = Transformed::new(Json::default(), Gzip);
+//! let bytes = envelope::encode(&rep, &format)?; // header readable, payload compressed
+//! ```
+//!
+//! For formats and databases where the tagged-enum shape is awkward but a structured
+//! document is still wanted, [`Envelope`] holds the version and payload side by side:
+//!
+//! ```rust,ignore
+//! use serde_evolve::Envelope;
+//!
+//! let envelope = Envelope::wrap(&user)?; // {"version":2,"payload":{...}}
+//! let user: User = envelope.open()?;
+//! ```
+
+use crate::migrate::{self, BoxError};
+use crate::payload::{self, Payload};
+use crate::representation::PayloadRef;
+use crate::{Format, Representation, UnsupportedVersion, Versioned};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Marks the start of an envelope.
+pub const MAGIC: [u8; 4] = *b"SEVO";
+
+/// Length of the envelope header in bytes.
+pub const HEADER_LEN: usize = 8;
+
+/// Marks the start of an envelope with a payload checksum.
+#[cfg(feature = "checksum")]
+pub const CHECKED_MAGIC: [u8; 4] = *b"SEVK";
+
+/// Length of the header of an envelope with a payload checksum, in bytes.
+#[cfg(feature = "checksum")]
+pub const CHECKED_HEADER_LEN: usize = 12;
+
+#[cfg(feature = "checksum")]
+const CRC: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+/// Error returned when decoding an envelope.
+#[derive(Debug)]
+pub enum Error<E> {
+    /// The input is shorter than the header.
+    Truncated,
+    /// The input does not start with [`MAGIC`].
+    BadMagic,
+    /// The payload does not match the checksum in the header.
+    ChecksumMismatch {
+        /// The checksum recorded in the header.
+        expected: u32,
+        /// The checksum of the payload as read.
+        found: u32,
+    },
+    /// The header names a version that is not part of the chain.
+    UnsupportedVersion(UnsupportedVersion),
+    /// The payload could not be decoded.
+    Format(E),
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "envelope is shorter than its {HEADER_LEN}-byte header"),
+            Self::BadMagic => f.write_str("envelope does not start with the expected magic bytes"),
+            Self::ChecksumMismatch { expected, found } => write!(
+                f,
+                "envelope payload is corrupt: checksum {found:#010x} does not match {expected:#010x}"
+            ),
+            Self::UnsupportedVersion(err) => err.fmt(f),
+            Self::Format(err) => write!(f, "format error: {err}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Truncated | Self::BadMagic | Self::ChecksumMismatch { .. } => None,
+            Self::UnsupportedVersion(err) => Some(err),
+            Self::Format(err) => Some(err),
+        }
+    }
+}
+
+/// A reversible transformation of encoded payload bytes, such as compression or encryption.
+pub trait Transform {
+    /// The error produced when the transformation fails.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Transform the encoded payload before it is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload cannot be transformed.
+    fn apply(&self, payload: Vec<u8>) -> Result<Vec<u8>, Self::Error>;
+
+    /// Undo [`apply`](Self::apply) on a payload that was read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is not a valid output of `apply`.
+    fn reverse(&self, payload: &[u8]) -> Result<Vec<u8>, Self::Error>;
+}
+
+/// A [`Format`] whose encoded bytes pass through a [`Transform`].
+///
+/// Used with the envelope codec, only the payload is transformed: the header stays readable,
+/// so version dispatch works as before.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Transformed<F, T> {
+    /// The format the payload is encoded in.
+    pub format: F,
+    /// The transformation applied to the encoded payload.
+    pub transform: T,
+}
+
+impl<F, T> Transformed<F, T> {
+    /// Transform the output of `format` with `transform`.
+    pub const fn new(format: F, transform: T) -> Self {
+        Self { format, transform }
+    }
+}
+
+impl<F: Format, T: Transform> Format for Transformed<F, T> {
+    type Error = TransformError<F::Error, T::Error>;
+
+    fn extension(&self) -> &'static str {
+        self.format.extension()
+    }
+
+    fn encode<V>(&self, value: &V) -> Result<Vec<u8>, Self::Error>
+    where
+        V: Serialize + ?Sized,
+    {
+        let bytes = self.format.encode(value).map_err(TransformError::Format)?;
+        self.transform
+            .apply(bytes)
+            .map_err(TransformError::Transform)
+    }
+
+    fn decode<V>(&self, bytes: &[u8]) -> Result<V, Self::Error>
+    where
+        V: DeserializeOwned,
+    {
+        let bytes = self
+            .transform
+            .reverse(bytes)
+            .map_err(TransformError::Transform)?;
+        self.format.decode(&bytes).map_err(TransformError::Format)
+    }
+}
+
+/// Error returned by a [`Transformed`] format.
+#[derive(Debug)]
+pub enum TransformError<F, T> {
+    /// The payload could not be encoded or decoded.
+    Format(F),
+    /// The payload could not be transformed.
+    Transform(T),
+}
+
+impl<F: fmt::Display, T: fmt::Display> fmt::Display for TransformError<F, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Format(err) => err.fmt(f),
+            Self::Transform(err) => write!(f, "payload transform failed: {err}"),
+        }
+    }
+}
+
+impl<F, T> std::error::Error for TransformError<F, T>
+where
+    F: std::error::Error + 'static,
+    T: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Format(err) => Some(err),
+            Self::Transform(err) => Some(err),
+        }
+    }
+}
+
+/// Encode a representation as an envelope.
+///
+/// # Errors
+///
+/// Returns an error if the payload cannot be encoded in `format`.
+pub fn encode<R, F>(rep: &R, format: &F) -> Result<Vec<u8>, F::Error>
+where
+    R: Representation,
+    F: Format,
+{
+    let payload = format.encode(&PayloadRef(rep))?;
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&MAGIC);
+    bytes.extend_from_slice(&rep.version().to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// Encode a representation as an envelope with a CRC-32 of the payload in its header.
+///
+/// # Errors
+///
+/// Returns an error if the payload cannot be encoded in `format`.
+#[cfg(feature = "checksum")]
+pub fn encode_checked<R, F>(rep: &R, format: &F) -> Result<Vec<u8>, F::Error>
+where
+    R: Representation,
+    F: Format,
+{
+    let payload = format.encode(&PayloadRef(rep))?;
+    let mut bytes = Vec::with_capacity(CHECKED_HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&CHECKED_MAGIC);
+    bytes.extend_from_slice(&rep.version().to_le_bytes());
+    bytes.extend_from_slice(&CRC.checksum(&payload).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// Split an envelope into its version number and payload bytes.
+///
+/// Envelopes written by `encode_checked` have their checksum verified here.
+///
+/// # Errors
+///
+/// Returns an error if the header is missing or malformed, or the payload does not match
+/// its checksum.
+pub fn read_header<E>(bytes: &[u8]) -> Result<(u32, &[u8]), Error<E>> {
+    if bytes.len() < HEADER_LEN {
+        return Err(Error::Truncated);
+    }
+    let (header, payload) = bytes.split_at(HEADER_LEN);
+    let version = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    #[cfg(feature = "checksum")]
+    if header[..4] == CHECKED_MAGIC {
+        let Some((checksum, payload)) = payload.split_first_chunk::<4>() else {
+            return Err(Error::Truncated);
+        };
+        let expected = u32::from_le_bytes(*checksum);
+        let found = CRC.checksum(payload);
+        if found != expected {
+            return Err(Error::ChecksumMismatch { expected, found });
+        }
+        return Ok((version, payload));
+    }
+    if header[..4] != MAGIC {
+        return Err(Error::BadMagic);
+    }
+    Ok((version, payload))
+}
+
+/// Decode an envelope, dispatching on its header to the matching version.
+///
+/// # Errors
+///
+/// Returns an error if the header is malformed, names an unknown version, or the payload
+/// cannot be decoded.
+pub fn decode<R, F>(bytes: &[u8], format: &F) -> Result<R, Error<F::Error>>
+where
+    R: Representation,
+    F: Format,
+{
+    let (version, payload) = read_header(bytes)?;
+    R::decode_payload(version, payload, format)
+        .ok_or(Error::UnsupportedVersion(UnsupportedVersion {
+            found: version,
+            min: R::MIN_SUPPORTED,
+            max: R::CURRENT,
+        }))?
+        .map_err(Error::Format)
+}
+
+/// A version number and the untagged payload of that version, as separate fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope<P = Payload> {
+    /// The version number of the payload.
+    pub version: u32,
+    /// The version DTO, without a version tag.
+    pub payload: P,
+}
+
+impl<P> Envelope<P> {
+    /// Frame a payload with its version number.
+    pub const fn new(version: u32, payload: P) -> Self {
+        Self { version, payload }
+    }
+}
+
+impl Envelope {
+    /// Frame a domain value at the latest version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version DTO cannot be serialized.
+    pub fn wrap<D>(domain: &D) -> Result<Self, payload::SerializeError>
+    where
+        D: Versioned,
+        for<'a> D::Representation: From<&'a D>,
+    {
+        let rep = D::Representation::from(domain);
+        Ok(Self {
+            version: rep.version(),
+            payload: Payload::from_serialize(&PayloadRef(&rep))?,
+        })
+    }
+
+    /// Decode the payload at its version, and migrate it to the domain type.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version is not part of the chain, the payload does not match
+    /// it, or a migration step fails.
+    pub fn open<D>(self) -> Result<D, migrate::Error>
+    where
+        D: Versioned + TryFrom<D::Representation>,
+        D::Error: Into<BoxError>,
+    {
+        let rep = D::Representation::from_payload(self.version, self.payload)
+            .ok_or(migrate::Error::UnsupportedVersion(UnsupportedVersion {
+                found: self.version,
+                min: D::Representation::MIN_SUPPORTED,
+                max: D::Representation::CURRENT,
+            }))?
+            .map_err(migrate::Error::Payload)?;
+        D::try_from(rep).map_err(|err| migrate::Error::Migration(err.into()))
+    }
+}
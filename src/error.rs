@@ -0,0 +1,508 @@
+//! Errors shared by generated code and the runtime helpers.
+//!
+//! [`VersionedError`] is a canonical, serializable summary of any of them, for services that
+//! report failures by category rather than by message:
+//!
+//! ```rust,ignore
+//! match VersionedError::from(err) {
+//!     VersionedError::UnknownTag { tag } => reject(format!("unknown schema '{tag}'")),
+//!     VersionedError::Migration { source_version, .. } => quarantine(source_version),
+//!     other => log_failure(&other),
+//! }
+//! ```
+//!
+//! Deserializers report failures as messages, so documents are read with
+//! `serde_evolve::read_json` where the category matters: it categorises a failure by the
+//! document's tag.
+
+use crate::payload;
+use crate::{Representation, envelope, format, fs, migrate, pinned, store};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// A version number outside the range supported by a representation enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsupportedVersion {
+    /// The requested version.
+    pub found: u32,
+    /// The oldest supported version.
+    pub min: u32,
+    /// The newest supported version.
+    pub max: u32,
+}
+
+impl fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unsupported version {}, expected {}..={}",
+            self.found, self.min, self.max
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedVersion {}
+
+/// A failed migration step, with the version that was read and the versions the step was
+/// converting between.
+///
+/// With `step_context = true`, fallible chains wrap the error of the failing step in a
+/// `StepError`, so that failures in long chains name the hop that broke, and can be triaged
+/// by version rather than by matching on their messages. It dereferences to the step's error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepError<E> {
+    /// Name of the domain type.
+    pub type_name: &'static str,
+    /// The version of the value being migrated.
+    pub source_version: u32,
+    /// The version being migrated from.
+    pub from: u32,
+    /// The version being migrated to, or `None` for the final conversion into the domain
+    /// type.
+    pub to: Option<u32>,
+    /// The error returned by the step.
+    pub error: E,
+}
+
+impl<E> StepError<E> {
+    /// Wrap the error of a failed migration step, migrating a value read as `from`.
+    pub const fn new(type_name: &'static str, from: u32, to: Option<u32>, error: E) -> Self {
+        Self {
+            type_name,
+            source_version: from,
+            from,
+            to,
+            error,
+        }
+    }
+
+    /// Record the version of the value being migrated, when the failed step started from an
+    /// intermediate version.
+    #[must_use]
+    pub const fn with_source_version(mut self, source_version: u32) -> Self {
+        self.source_version = source_version;
+        self
+    }
+
+    /// The step that failed.
+    #[must_use]
+    pub const fn failed_step(&self) -> MigrationStep {
+        MigrationStep {
+            from: self.from,
+            to: self.to,
+        }
+    }
+
+    /// Discard the context, returning the step's error.
+    pub fn into_inner(self) -> E {
+        self.error
+    }
+}
+
+impl<E> Deref for StepError<E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        &self.error
+    }
+}
+
+impl<E> DerefMut for StepError<E> {
+    fn deref_mut(&mut self) -> &mut E {
+        &mut self.error
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for StepError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to {
+            Some(to) => write!(
+                f,
+                "migrating {} from V{} to V{to}",
+                self.type_name, self.from
+            )?,
+            None => write!(f, "converting {} from V{}", self.type_name, self.from)?,
+        }
+        if self.source_version != self.from {
+            write!(f, " (read as V{})", self.source_version)?;
+        }
+        write!(f, ": {}", self.error)
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for StepError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A step of a migration chain that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationStep {
+    /// The version being migrated from.
+    pub from: u32,
+    /// The version being migrated to, or `None` for the final conversion into the domain
+    /// type.
+    pub to: Option<u32>,
+}
+
+/// Error returned when a representation cannot be converted to a requested version.
+///
+/// Only fallible chains produce it: infallible chains can only fail on the version itself,
+/// and report an [`UnsupportedVersion`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConvertError<E> {
+    /// The requested version is not part of the chain.
+    Unsupported(UnsupportedVersion),
+    /// A migration step towards the requested version failed.
+    Step(E),
+}
+
+impl<E: fmt::Display> fmt::Display for ConvertError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(err) => err.fmt(f),
+            Self::Step(err) => err.fmt(f),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for ConvertError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Unsupported(err) => Some(err),
+            Self::Step(err) => Some(err),
+        }
+    }
+}
+
+/// A failure to read or migrate a versioned value, by category.
+///
+/// Every error of this crate converts into a `VersionedError`, keeping its version context and
+/// the message of its cause. Errors of a user's format or storage backend are kept as
+/// messages. It serializes with its category under `kind`, as in
+/// `{"kind": "unknown_tag", "tag": "v9"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VersionedError {
+    /// The version tag names no version of the chain.
+    UnknownTag {
+        /// The tag as read.
+        tag: String,
+    },
+    /// The version number is outside the range the chain supports.
+    UnsupportedVersion(UnsupportedVersion),
+    /// The payload does not match the type of its version.
+    Payload {
+        /// The version the payload was read as, if known.
+        version: Option<u32>,
+        /// The reason the payload was rejected.
+        message: String,
+    },
+    /// A migration step rejected the value.
+    Migration {
+        /// The version of the value being migrated, if known.
+        source_version: Option<u32>,
+        /// The step that failed, if known.
+        step: Option<MigrationStep>,
+        /// The error of the step.
+        message: String,
+    },
+    /// The value could not be encoded.
+    Encode {
+        /// The reason encoding failed.
+        message: String,
+    },
+    /// An envelope is shorter than its header.
+    Truncated,
+    /// An envelope does not start with the expected magic bytes.
+    BadMagic,
+    /// An envelope's payload does not match the checksum in its header.
+    ChecksumMismatch {
+        /// The checksum recorded in the header.
+        expected: u32,
+        /// The checksum of the payload as read.
+        found: u32,
+    },
+    /// Reading or writing a file failed.
+    Io {
+        /// The I/O error.
+        message: String,
+    },
+    /// A storage backend failed.
+    Store {
+        /// The error of the backend.
+        message: String,
+    },
+    /// No type is registered under the name a document is tagged with.
+    UnknownType {
+        /// The type name as read.
+        name: String,
+    },
+    /// A document is of another registered type than the one requested.
+    UnexpectedType {
+        /// The type name as read.
+        name: String,
+    },
+    /// A version's JSON Schema has no Avro translation.
+    Schema {
+        /// Where the untranslatable construct is.
+        path: String,
+        /// Why it has no translation.
+        reason: String,
+    },
+}
+
+impl VersionedError {
+    pub(crate) fn payload(version: Option<u32>, err: impl fmt::Display) -> Self {
+        Self::Payload {
+            version,
+            message: err.to_string(),
+        }
+    }
+
+    fn migration(err: impl fmt::Display) -> Self {
+        Self::Migration {
+            source_version: None,
+            step: None,
+            message: err.to_string(),
+        }
+    }
+
+    fn encode(err: impl fmt::Display) -> Self {
+        Self::Encode {
+            message: err.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for VersionedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownTag { tag } => write!(f, "unknown version tag '{tag}'"),
+            Self::UnsupportedVersion(err) => err.fmt(f),
+            Self::Payload {
+                version: Some(version),
+                message,
+            } => write!(f, "invalid payload for version {version}: {message}"),
+            Self::Payload {
+                version: None,
+                message,
+            } => write!(f, "invalid payload: {message}"),
+            Self::Migration {
+                source_version,
+                step,
+                message,
+            } => {
+                f.write_str("migration failed")?;
+                if let Some(source_version) = source_version {
+                    write!(f, " reading V{source_version}")?;
+                }
+                match step {
+                    Some(MigrationStep { from, to: Some(to) }) => {
+                        write!(f, " at V{from} -> V{to}")?;
+                    }
+                    Some(MigrationStep { from, to: None }) => write!(f, " converting V{from}")?,
+                    None => {}
+                }
+                write!(f, ": {message}")
+            }
+            Self::Encode { message } => write!(f, "encoding failed: {message}"),
+            Self::Truncated => f.write_str("envelope is shorter than its header"),
+            Self::BadMagic => f.write_str("envelope does not start with the expected magic bytes"),
+            Self::ChecksumMismatch { expected, found } => write!(
+                f,
+                "envelope payload is corrupt: checksum {found:#010x} does not match {expected:#010x}"
+            ),
+            Self::Io { message } => write!(f, "I/O error: {message}"),
+            Self::Store { message } => write!(f, "store error: {message}"),
+            Self::UnknownType { name } => write!(f, "no versioned type is registered as '{name}'"),
+            Self::UnexpectedType { name } => {
+                write!(f, "document is a '{name}', not the requested type")
+            }
+            Self::Schema { path, reason } => write!(f, "no Avro schema for '{path}': {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for VersionedError {}
+
+impl From<UnsupportedVersion> for VersionedError {
+    fn from(err: UnsupportedVersion) -> Self {
+        Self::UnsupportedVersion(err)
+    }
+}
+
+impl<E: fmt::Display> From<StepError<E>> for VersionedError {
+    fn from(err: StepError<E>) -> Self {
+        Self::Migration {
+            source_version: Some(err.source_version),
+            step: Some(err.failed_step()),
+            message: err.error.to_string(),
+        }
+    }
+}
+
+impl<E: Into<Self>> From<ConvertError<E>> for VersionedError {
+    fn from(err: ConvertError<E>) -> Self {
+        match err {
+            ConvertError::Unsupported(err) => err.into(),
+            ConvertError::Step(err) => err.into(),
+        }
+    }
+}
+
+impl From<payload::Error> for VersionedError {
+    fn from(err: payload::Error) -> Self {
+        Self::payload(None, err)
+    }
+}
+
+impl From<migrate::Error> for VersionedError {
+    fn from(err: migrate::Error) -> Self {
+        match err {
+            migrate::Error::UnsupportedVersion(err) => err.into(),
+            migrate::Error::Payload(err) => err.into(),
+            migrate::Error::Migration(err) => Self::migration(err),
+        }
+    }
+}
+
+impl<E: fmt::Display> From<envelope::Error<E>> for VersionedError {
+    fn from(err: envelope::Error<E>) -> Self {
+        match err {
+            envelope::Error::Truncated => Self::Truncated,
+            envelope::Error::BadMagic => Self::BadMagic,
+            envelope::Error::ChecksumMismatch { expected, found } => {
+                Self::ChecksumMismatch { expected, found }
+            }
+            envelope::Error::UnsupportedVersion(err) => err.into(),
+            envelope::Error::Format(err) => Self::payload(None, err),
+        }
+    }
+}
+
+impl<S: fmt::Display, D: fmt::Display> From<format::Error<S, D>> for VersionedError {
+    fn from(err: format::Error<S, D>) -> Self {
+        match err {
+            format::Error::Serialize(err) => Self::encode(err),
+            format::Error::Deserialize(err) => Self::payload(None, err),
+        }
+    }
+}
+
+impl<E: fmt::Display> From<fs::Error<E>> for VersionedError {
+    fn from(err: fs::Error<E>) -> Self {
+        // As in `store`, a format error may come from decoding or encoding: it is reported as
+        // a payload error.
+        match err {
+            fs::Error::Io(err) => Self::Io {
+                message: err.to_string(),
+            },
+            fs::Error::Format(err) => Self::payload(None, err),
+            fs::Error::Migration(err) => Self::migration(err),
+        }
+    }
+}
+
+impl<S: fmt::Display, F: fmt::Display> From<store::Error<S, F>> for VersionedError {
+    fn from(err: store::Error<S, F>) -> Self {
+        match err {
+            store::Error::Store(err) => Self::Store {
+                message: err.to_string(),
+            },
+            store::Error::Format(err) => Self::payload(None, err),
+            store::Error::Migration(err) => Self::migration(err),
+        }
+    }
+}
+
+impl<E: fmt::Display> From<pinned::Error<E>> for VersionedError {
+    fn from(err: pinned::Error<E>) -> Self {
+        match err {
+            pinned::Error::Unsupported(err) => err.into(),
+            pinned::Error::Format(err) => Self::encode(err),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<crate::registry::Error> for VersionedError {
+    fn from(err: crate::registry::Error) -> Self {
+        use crate::registry::Error;
+        match err {
+            Error::UnknownType(name) => Self::UnknownType { name },
+            Error::Json(err) => Self::payload(None, err),
+            Error::Migration(err) => Self::migration(err),
+            Error::UnexpectedType(name) => Self::UnexpectedType { name },
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<crate::sniff::Error> for VersionedError {
+    fn from(err: crate::sniff::Error) -> Self {
+        match err {
+            crate::sniff::Error::Unrecognised => Self::payload(None, err),
+            crate::sniff::Error::Migration(err) => Self::migration(err),
+        }
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl From<crate::avro::Error> for VersionedError {
+    fn from(err: crate::avro::Error) -> Self {
+        Self::Schema {
+            path: err.path,
+            reason: err.reason,
+        }
+    }
+}
+
+#[cfg(feature = "avro")]
+impl From<crate::avro::ContainerError> for VersionedError {
+    fn from(err: crate::avro::ContainerError) -> Self {
+        use crate::avro::ContainerError;
+        // As in `fs`, an Avro error may come from writing or reading: it is reported as a
+        // payload error.
+        match err {
+            ContainerError::UnsupportedVersion(err) => err.into(),
+            ContainerError::Payload(err) => err.into(),
+            ContainerError::VersionMismatch { .. } => Self::encode(err),
+            ContainerError::Avro(_) | ContainerError::MissingVersion => Self::payload(None, err),
+        }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl From<crate::reqwest::Error> for VersionedError {
+    fn from(err: crate::reqwest::Error) -> Self {
+        match err {
+            crate::reqwest::Error::Http(err) if err.is_decode() => Self::payload(None, err),
+            crate::reqwest::Error::Http(err) => Self::Io {
+                message: err.to_string(),
+            },
+            crate::reqwest::Error::Migration(err) => Self::migration(err),
+        }
+    }
+}
+
+/// The version of `R` written with `tag`, or the category of error for a tag naming none.
+pub(crate) fn version_of_tag<R: Representation>(tag: &str) -> Result<u32, VersionedError> {
+    R::version_of_tag(tag).ok_or_else(|| {
+        tag.parse().map_or_else(
+            |_| VersionedError::UnknownTag {
+                tag: tag.to_owned(),
+            },
+            |found| {
+                UnsupportedVersion {
+                    found,
+                    min: R::MIN_SUPPORTED,
+                    max: R::CURRENT,
+                }
+                .into()
+            },
+        )
+    })
+}
@@ -0,0 +1,208 @@
+//! Reading a single field of a document, whatever its version, without migrating it.
+//!
+//! Indexers and routers often need one logical field, such as a primary key, that has moved
+//! between versions. A [`Field`] maps each version to the [JSON Pointer] of the field, and
+//! reads only that value: the version is peeked from the tag, the path is followed without
+//! building the values around it, and the chain is never run.
+//!
+//! ```rust,ignore
+//! use serde_evolve::extract::Field;
+//!
+//! // `user_id` until version 3 moved it into `account.id`.
+//! let id = Field::<u64>::new().since(1, "/user_id").since(3, "/account/id");
+//! let key = id.extract::<UserVersions>(&json)?;
+//! ```
+//!
+//! [JSON Pointer]: https://www.rfc-editor.org/rfc/rfc6901
+
+use crate::Representation;
+use crate::error::VersionedError;
+use crate::peek::{KeyIs, peek_version};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, IgnoredAny, MapAccess};
+use serde::de::{SeqAccess, Visitor};
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// The location of a logical field in each version of a chain.
+pub struct Field<T> {
+    /// The first version each pointer applies to, in increasing order.
+    paths: Vec<(u32, &'static str)>,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<T> Field<T> {
+    /// A field with no locations.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            paths: Vec::new(),
+            value: PhantomData,
+        }
+    }
+
+    /// Locate the field at the JSON Pointer `pointer` from `version` on, until a later
+    /// version moves it.
+    #[must_use]
+    pub fn since(mut self, version: u32, pointer: &'static str) -> Self {
+        let idx = self.paths.partition_point(|(since, _)| *since < version);
+        if self
+            .paths
+            .get(idx)
+            .is_some_and(|(since, _)| *since == version)
+        {
+            self.paths[idx].1 = pointer;
+        } else {
+            self.paths.insert(idx, (version, pointer));
+        }
+        self
+    }
+
+    /// The pointer locating the field in `version`, or `None` before its first location.
+    #[must_use]
+    pub fn pointer(&self, version: u32) -> Option<&'static str> {
+        let idx = self.paths.partition_point(|(since, _)| *since <= version);
+        idx.checked_sub(1).map(|idx| self.paths[idx].1)
+    }
+}
+
+impl<T: DeserializeOwned> Field<T> {
+    /// Read the field from the JSON document `json`, at the location for its version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document's version cannot be read, the field has no location
+    /// in that version, or the value there is missing or not a `T`.
+    pub fn extract<R: Representation>(&self, json: &str) -> Result<T, VersionedError> {
+        let version = peek_version::<R>(json)?;
+        self.extract_at(version, json)
+    }
+
+    /// Read the field from the JSON document `json`, already known to be at `version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the field has no location in `version`, or the value there is
+    /// missing or not a `T`.
+    pub fn extract_at(&self, version: u32, json: &str) -> Result<T, VersionedError> {
+        let error = |message: String| VersionedError::Payload {
+            version: Some(version),
+            message,
+        };
+        let pointer = self
+            .pointer(version)
+            .ok_or_else(|| error(format!("the field has no location in version {version}")))?;
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return Err(error(format!("invalid JSON pointer `{pointer}`")));
+        }
+        let path: Vec<_> = pointer.split('/').skip(1).map(unescape).collect();
+
+        let mut found = None;
+        let walked = Descend {
+            path: &path,
+            found: &mut found,
+        }
+        .deserialize(&mut serde_json::Deserializer::from_str(json));
+        // The walk is abandoned with an error once the value is read.
+        match (found, walked) {
+            (Some(value), _) => Ok(value),
+            (None, Err(err)) => Err(error(err.to_string())),
+            (None, Ok(())) => Err(error(format!("no value at `{pointer}`"))),
+        }
+    }
+}
+
+impl<T> Default for Field<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Field<T> {
+    fn clone(&self) -> Self {
+        Self {
+            paths: self.paths.clone(),
+            value: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Field<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Field").field("paths", &self.paths).finish()
+    }
+}
+
+/// A JSON Pointer reference token, with `~1` and `~0` unescaped.
+fn unescape(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// Follow `path` into a value, reading the value at its end into `found`.
+struct Descend<'a, T> {
+    path: &'a [Cow<'a, str>],
+    found: &'a mut Option<T>,
+}
+
+impl<'de, T: DeserializeOwned> DeserializeSeed<'de> for Descend<'_, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        if self.path.is_empty() {
+            *self.found = Some(T::deserialize(deserializer)?);
+            return Err(de::Error::custom("field found"));
+        }
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for Descend<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "an object or array containing `{}`", self.path[0])
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let (key, rest) = self
+            .path
+            .split_first()
+            .expect("descends along a non-empty path");
+        while let Some(is_key) = map.next_key_seed(KeyIs(key))? {
+            if is_key {
+                return map.next_value_seed(Descend {
+                    path: rest,
+                    found: self.found,
+                });
+            }
+            map.next_value::<IgnoredAny>()?;
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let (index, rest) = self
+            .path
+            .split_first()
+            .expect("descends along a non-empty path");
+        if let Ok(index) = index.parse::<usize>() {
+            let mut skipped = 0;
+            while skipped < index && seq.next_element::<IgnoredAny>()?.is_some() {
+                skipped += 1;
+            }
+            if skipped == index {
+                seq.next_element_seed(Descend {
+                    path: rest,
+                    found: self.found,
+                })?;
+            }
+        }
+        // The deserializer checks that the sequence was read to its end.
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(())
+    }
+}
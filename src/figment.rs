@@ -0,0 +1,116 @@
+//! Layered configuration with `figment`, migrating the file layer.
+//!
+//! [`VersionedFile`] is a `figment` provider for a settings file of any historical version. It
+//! migrates the file to the domain type and provides it at the current version, with its
+//! version tag, so that environment variables and other layers merge over the current shape.
+//! [`extract`] then reads the merged configuration back through the representation:
+//!
+//! ```rust,ignore
+//! use figment::{Figment, providers::{Env, Serialized}};
+//! use serde_evolve::{figment::VersionedFile, format::Toml};
+//!
+//! let figment = Figment::new()
+//!     .merge(Serialized::defaults(SettingsVersions::from(&Settings::default())))
+//!     .merge(VersionedFile::<Settings, _>::new("app.toml", Toml))
+//!     .merge(Env::prefixed("APP_"));
+//! let settings: Settings = serde_evolve::figment::extract(&figment)?;
+//! ```
+
+use crate::migrate::BoxError;
+use crate::{Format, Versioned};
+use ::figment::providers::Serialized;
+use ::figment::value::{Dict, Map};
+use ::figment::{Error, Figment, Metadata, Profile, Provider};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::io::ErrorKind;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+/// A `figment` provider for a settings file of any version of `T`, encoded with `F`.
+///
+/// A missing file provides nothing, unless the file is [`required`](Self::required).
+#[derive(Debug, Clone)]
+pub struct VersionedFile<T, F> {
+    path: PathBuf,
+    format: F,
+    required: bool,
+    profile: Profile,
+    domain: PhantomData<fn() -> T>,
+}
+
+impl<T, F> VersionedFile<T, F> {
+    /// A provider for the file at `path`, decoded with `format`, for the default profile.
+    pub fn new(path: impl Into<PathBuf>, format: F) -> Self {
+        Self {
+            path: path.into(),
+            format,
+            required: false,
+            profile: Profile::Default,
+            domain: PhantomData,
+        }
+    }
+
+    /// Whether a missing file is an error.
+    #[must_use]
+    pub const fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// The profile to provide the file's settings for.
+    #[must_use]
+    pub fn profile(mut self, profile: impl Into<Profile>) -> Self {
+        self.profile = profile.into();
+        self
+    }
+}
+
+impl<T, F> Provider for VersionedFile<T, F>
+where
+    T: Versioned + TryFrom<T::Representation>,
+    T::Error: Into<BoxError>,
+    T::Representation: DeserializeOwned + Serialize + for<'a> From<&'a T>,
+    F: Format,
+{
+    fn metadata(&self) -> Metadata {
+        Metadata::from("versioned file", self.path.as_path())
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let bytes = match std::fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound && !self.required => {
+                return Ok(Map::new());
+            }
+            Err(err) => {
+                return Err(format!("cannot read {}: {err}", self.path.display()).into());
+            }
+        };
+        let rep: T::Representation = self
+            .format
+            .decode(&bytes)
+            .map_err(|err| format!("cannot decode {}: {err}", self.path.display()))?;
+        let domain = T::try_from(rep)
+            .map_err(|err| format!("cannot migrate {}: {}", self.path.display(), err.into()))?;
+        Serialized::from(T::Representation::from(&domain), self.profile.clone()).data()
+    }
+}
+
+/// Extract the merged configuration of `figment` as the representation of `T`, and migrate it
+/// to `T`.
+///
+/// # Errors
+///
+/// Returns an error if the configuration is not a valid document of any version, or a
+/// migration step rejects it.
+#[allow(clippy::result_large_err)] // the error of `Figment::extract`
+pub fn extract<T>(figment: &Figment) -> Result<T, Error>
+where
+    T: Versioned + TryFrom<T::Representation>,
+    T::Error: Into<BoxError>,
+    T::Representation: DeserializeOwned,
+{
+    let rep: T::Representation = figment.extract()?;
+    T::try_from(rep).map_err(|err| format!("migration failed: {}", err.into()).into())
+}
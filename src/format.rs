@@ -0,0 +1,298 @@
+//! Pluggable serialization formats.
+//!
+//! The [`Format`] trait abstracts over the byte-level encoding of versioned data, so that
+//! integrations (file I/O, stores, command-line tooling) can be written once and used with
+//! any serde format. Built-in implementations are provided behind cargo features:
+//!
+//! | Format        | Feature    | Type            |
+//! |---------------|------------|-----------------|
+//! | JSON          | `json`     | [`Json`]        |
+//! | YAML          | `yaml`     | [`Yaml`]        |
+//! | TOML          | `toml`     | [`Toml`]        |
+//! | CBOR          | `cbor`     | [`Cbor`]        |
+//! | `MessagePack` | `msgpack`  | [`MessagePack`] |
+//! | postcard      | `postcard` | [`Postcard`]    |
+//!
+//! The `json` feature is enabled by default. Postcard is not self-describing, so it cannot
+//! decode internally tagged representation enums; use it with the
+//! [`envelope`](crate::envelope) codec, or `tag_style = "adjacent"`.
+
+use serde::{Serialize, de::DeserializeOwned};
+use std::fmt;
+
+/// A serialization format that can encode and decode any serde type to and from bytes.
+pub trait Format {
+    /// The error produced when encoding or decoding fails.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// The file extension conventionally used for this format (without the leading dot).
+    fn extension(&self) -> &'static str;
+
+    /// Serialize a value to bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be represented in this format.
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>, Self::Error>
+    where
+        T: Serialize + ?Sized;
+
+    /// Deserialize a value from bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not a valid encoding of `T`.
+    fn decode<T>(&self, bytes: &[u8]) -> Result<T, Self::Error>
+    where
+        T: DeserializeOwned;
+}
+
+impl<F: Format + ?Sized> Format for &F {
+    type Error = F::Error;
+
+    fn extension(&self) -> &'static str {
+        (**self).extension()
+    }
+
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        (**self).encode(value)
+    }
+
+    fn decode<T>(&self, bytes: &[u8]) -> Result<T, Self::Error>
+    where
+        T: DeserializeOwned,
+    {
+        (**self).decode(bytes)
+    }
+}
+
+/// Error for formats whose serializer and deserializer report different error types.
+#[derive(Debug)]
+pub enum Error<S, D> {
+    /// Encoding a value failed.
+    Serialize(S),
+    /// Decoding a value failed.
+    Deserialize(D),
+}
+
+impl<S: fmt::Display, D: fmt::Display> fmt::Display for Error<S, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serialize(err) => write!(f, "serialization failed: {err}"),
+            Self::Deserialize(err) => write!(f, "deserialization failed: {err}"),
+        }
+    }
+}
+
+impl<S, D> std::error::Error for Error<S, D>
+where
+    S: std::error::Error + 'static,
+    D: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Serialize(err) => Some(err),
+            Self::Deserialize(err) => Some(err),
+        }
+    }
+}
+
+/// JSON, via `serde_json`.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Json {
+    /// Emit indented, human-friendly output.
+    pub pretty: bool,
+}
+
+#[cfg(feature = "json")]
+impl Json {
+    /// JSON with indented output.
+    #[must_use]
+    pub const fn pretty() -> Self {
+        Self { pretty: true }
+    }
+}
+
+#[cfg(feature = "json")]
+impl Format for Json {
+    type Error = serde_json::Error;
+
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        if self.pretty {
+            serde_json::to_vec_pretty(value)
+        } else {
+            serde_json::to_vec(value)
+        }
+    }
+
+    fn decode<T>(&self, bytes: &[u8]) -> Result<T, Self::Error>
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice(bytes)
+    }
+}
+
+/// YAML, via `serde_yaml`.
+#[cfg(feature = "yaml")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Yaml;
+
+#[cfg(feature = "yaml")]
+impl Format for Yaml {
+    type Error = serde_yaml::Error;
+
+    fn extension(&self) -> &'static str {
+        "yaml"
+    }
+
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        serde_yaml::to_string(value).map(String::into_bytes)
+    }
+
+    fn decode<T>(&self, bytes: &[u8]) -> Result<T, Self::Error>
+    where
+        T: DeserializeOwned,
+    {
+        serde_yaml::from_slice(bytes)
+    }
+}
+
+/// TOML, via `toml`.
+///
+/// TOML documents must be tables, so only types that serialize as maps or structs (such as
+/// the internally tagged representation enums) can be encoded.
+#[cfg(feature = "toml")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Toml;
+
+#[cfg(feature = "toml")]
+impl Format for Toml {
+    type Error = Error<toml::ser::Error, toml::de::Error>;
+
+    fn extension(&self) -> &'static str {
+        "toml"
+    }
+
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        toml::to_string(value)
+            .map(String::into_bytes)
+            .map_err(Error::Serialize)
+    }
+
+    fn decode<T>(&self, bytes: &[u8]) -> Result<T, Self::Error>
+    where
+        T: DeserializeOwned,
+    {
+        toml::from_slice(bytes).map_err(Error::Deserialize)
+    }
+}
+
+/// CBOR, via `ciborium`.
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl Format for Cbor {
+    type Error = Error<ciborium::ser::Error<std::io::Error>, ciborium::de::Error<std::io::Error>>;
+
+    fn extension(&self) -> &'static str {
+        "cbor"
+    }
+
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(value, &mut bytes).map_err(Error::Serialize)?;
+        Ok(bytes)
+    }
+
+    fn decode<T>(&self, bytes: &[u8]) -> Result<T, Self::Error>
+    where
+        T: DeserializeOwned,
+    {
+        ciborium::from_reader(bytes).map_err(Error::Deserialize)
+    }
+}
+
+/// `MessagePack`, via `rmp-serde`.
+///
+/// Structs are encoded as maps (with field names) so that internally tagged representation
+/// enums can locate their version tag.
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MessagePack;
+
+#[cfg(feature = "msgpack")]
+impl Format for MessagePack {
+    type Error = Error<rmp_serde::encode::Error, rmp_serde::decode::Error>;
+
+    fn extension(&self) -> &'static str {
+        "msgpack"
+    }
+
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        rmp_serde::to_vec_named(value).map_err(Error::Serialize)
+    }
+
+    fn decode<T>(&self, bytes: &[u8]) -> Result<T, Self::Error>
+    where
+        T: DeserializeOwned,
+    {
+        rmp_serde::from_slice(bytes).map_err(Error::Deserialize)
+    }
+}
+
+/// postcard, via `postcard`.
+///
+/// postcard is not self-describing: encode representations with the
+/// [`envelope`](crate::envelope) codec, or adjacently tagged, rather than directly.
+#[cfg(feature = "postcard")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Postcard;
+
+#[cfg(feature = "postcard")]
+impl Format for Postcard {
+    type Error = postcard::Error;
+
+    fn extension(&self) -> &'static str {
+        "postcard"
+    }
+
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        postcard::to_allocvec(value)
+    }
+
+    fn decode<T>(&self, bytes: &[u8]) -> Result<T, Self::Error>
+    where
+        T: DeserializeOwned,
+    {
+        postcard::from_bytes(bytes)
+    }
+}
@@ -0,0 +1,364 @@
+//! Reading versioned documents from the filesystem.
+//!
+//! All functions are generic over a [`Format`], so the same code path serves JSON, YAML,
+//! CBOR and any user-provided format.
+//!
+//! With the `mmap` feature enabled, [`read_mmap`] and [`Mapped`] decode documents directly
+//! from a memory-mapped file instead of first copying it into a heap buffer, which keeps
+//! peak memory usage close to the size of the decoded value for very large documents.
+//!
+//! [`load_and_upgrade`] reads a document into its domain type, optionally repairing the
+//! stored copy, and [`migrate_file`] and [`migrate_path`] rewrite stored documents at the
+//! current version, for bulk upgrades of a file or a whole directory tree.
+
+use crate::migrate::BoxError;
+use crate::{Format, Representation};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::{fmt, io};
+
+/// Error returned by filesystem operations.
+#[derive(Debug)]
+pub enum Error<E> {
+    /// Reading or writing the file failed.
+    Io(io::Error),
+    /// The file contents could not be decoded (or a value could not be encoded).
+    Format(E),
+    /// A migration step rejected the document.
+    Migration(BoxError),
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "I/O error: {err}"),
+            Self::Format(err) => write!(f, "format error: {err}"),
+            Self::Migration(err) => write!(f, "migration error: {err}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Format(err) => Some(err),
+            Self::Migration(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl<E> From<io::Error> for Error<E> {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Read and decode a file.
+///
+/// `T` is typically a representation enum, so that any historical version is accepted.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or decoded.
+pub fn read<T, F>(path: impl AsRef<Path>, format: &F) -> Result<T, Error<F::Error>>
+where
+    T: DeserializeOwned,
+    F: Format,
+{
+    let bytes = std::fs::read(path)?;
+    format.decode(&bytes).map_err(Error::Format)
+}
+
+/// Rewrite a file at the current version.
+///
+/// The file is decoded as the representation `R`, migrated through the domain type `D` and,
+/// unless it was already current, encoded again and written back. The new contents are
+/// written and synced to a sibling temporary file which then replaces the original, so a
+/// failed write or a crash does not leave a truncated document behind.
+///
+/// Returns whether the file was rewritten.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, decoded, migrated or written.
+pub fn migrate_file<R, D, F>(path: impl AsRef<Path>, format: &F) -> Result<bool, Error<F::Error>>
+where
+    R: Representation + DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    D: TryFrom<R>,
+    D::Error: Into<BoxError>,
+    F: Format,
+{
+    let path = path.as_ref();
+    let rep: R = read(path, format)?;
+    if rep.version() == R::CURRENT {
+        return Ok(false);
+    }
+
+    let domain = D::try_from(rep).map_err(|err| Error::Migration(err.into()))?;
+    let bytes = format.encode(&R::from(&domain)).map_err(Error::Format)?;
+
+    replace(path, &bytes, false)?;
+    Ok(true)
+}
+
+/// Whether [`load_and_upgrade`] rewrites files that were stored at an older version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WriteBack {
+    /// Leave the file untouched.
+    #[default]
+    Never,
+    /// Replace the file with its current-version encoding.
+    Rewrite,
+    /// Replace the file, first copying the original to a sibling with a `.bak` suffix.
+    RewriteWithBackup,
+}
+
+/// Read a file, migrate it to the domain type and, depending on `write_back`, repair the
+/// stored copy.
+///
+/// Files that were already at the current version are never rewritten. Rewrites go through
+/// a sibling temporary file, as with [`migrate_file`]; with
+/// [`WriteBack::RewriteWithBackup`] the original is kept as `<name>.bak`, replacing any
+/// previous backup.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, decoded or migrated, or if the write-back
+/// fails.
+pub fn load_and_upgrade<R, D, F>(
+    path: impl AsRef<Path>,
+    format: &F,
+    write_back: WriteBack,
+) -> Result<D, Error<F::Error>>
+where
+    R: Representation + DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    D: TryFrom<R>,
+    D::Error: Into<BoxError>,
+    F: Format,
+{
+    let path = path.as_ref();
+    let rep: R = read(path, format)?;
+    let outdated = rep.version() != R::CURRENT;
+    let domain = D::try_from(rep).map_err(|err| Error::Migration(err.into()))?;
+
+    if outdated && write_back != WriteBack::Never {
+        let bytes = format.encode(&R::from(&domain)).map_err(Error::Format)?;
+        replace(path, &bytes, write_back == WriteBack::RewriteWithBackup)?;
+    }
+    Ok(domain)
+}
+
+/// Atomically and durably replace the contents of `path`, optionally keeping the original
+/// as a backup.
+///
+/// The backup is taken, and the new contents synced to a sibling temporary file, before the
+/// original is replaced; the temporary file is removed if it cannot take the original's place.
+fn replace(path: &Path, bytes: &[u8], backup: bool) -> io::Result<()> {
+    let sibling = |suffix: &str| {
+        let mut name = path.file_name().unwrap_or_default().to_owned();
+        name.push(suffix);
+        path.with_file_name(name)
+    };
+
+    if backup {
+        let backup = sibling(".bak");
+        std::fs::copy(path, &backup)?;
+        File::open(&backup)?.sync_all()?;
+    }
+    let temp = sibling(".migrating");
+    let replaced = File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&temp, path));
+    if let Err(err) = replaced {
+        let _ = std::fs::remove_file(&temp);
+        return Err(err);
+    }
+    sync_parent(path)
+}
+
+/// Sync the directory holding `path`, so that a rename into it survives a crash.
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()
+}
+
+/// Directories cannot be opened for syncing outside Unix; renames are left to the platform.
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+const fn sync_parent(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// The outcome of [`migrate_path`].
+#[derive(Debug)]
+pub struct MigrationReport<E> {
+    /// Files rewritten at the current version.
+    pub migrated: Vec<PathBuf>,
+    /// Files that were already current.
+    pub unchanged: Vec<PathBuf>,
+    /// Files (or directories) that could not be migrated, with the reason.
+    pub failed: Vec<(PathBuf, Error<E>)>,
+}
+
+impl<E> Default for MigrationReport<E> {
+    fn default() -> Self {
+        Self {
+            migrated: Vec::new(),
+            unchanged: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+}
+
+/// Rewrite a file, or every file in a directory tree, at the current version.
+///
+/// A directory is walked recursively, and only files with the format's
+/// [extension](Format::extension) are migrated. Each file is migrated with
+/// [`migrate_file`]; failures are collected in the report rather than stopping the walk.
+///
+/// # Errors
+///
+/// Returns an error only if `path` itself cannot be accessed.
+pub fn migrate_path<R, D, F>(
+    path: impl AsRef<Path>,
+    format: &F,
+) -> io::Result<MigrationReport<F::Error>>
+where
+    R: Representation + DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    D: TryFrom<R>,
+    D::Error: Into<BoxError>,
+    F: Format,
+{
+    let path = path.as_ref();
+    let mut report = MigrationReport::default();
+    if std::fs::metadata(path)?.is_dir() {
+        migrate_dir::<R, D, F>(path, format, &mut report);
+    } else {
+        migrate_into_report::<R, D, F>(path.to_owned(), format, &mut report);
+    }
+    Ok(report)
+}
+
+fn migrate_dir<R, D, F>(dir: &Path, format: &F, report: &mut MigrationReport<F::Error>)
+where
+    R: Representation + DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    D: TryFrom<R>,
+    D::Error: Into<BoxError>,
+    F: Format,
+{
+    let mut entries = match std::fs::read_dir(dir).and_then(Iterator::collect::<io::Result<Vec<_>>>)
+    {
+        Ok(entries) => entries,
+        Err(err) => {
+            report.failed.push((dir.to_owned(), Error::Io(err)));
+            return;
+        }
+    };
+    entries.sort_by_key(std::fs::DirEntry::path);
+
+    for entry in entries {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => migrate_dir::<R, D, F>(&path, format, report),
+            Ok(_)
+                if path
+                    .extension()
+                    .is_some_and(|ext| ext == format.extension()) =>
+            {
+                migrate_into_report::<R, D, F>(path, format, report);
+            }
+            Ok(_) => {}
+            Err(err) => report.failed.push((path, Error::Io(err))),
+        }
+    }
+}
+
+fn migrate_into_report<R, D, F>(path: PathBuf, format: &F, report: &mut MigrationReport<F::Error>)
+where
+    R: Representation + DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    D: TryFrom<R>,
+    D::Error: Into<BoxError>,
+    F: Format,
+{
+    match migrate_file::<R, D, F>(&path, format) {
+        Ok(true) => report.migrated.push(path),
+        Ok(false) => report.unchanged.push(path),
+        Err(err) => report.failed.push((path, err)),
+    }
+}
+
+/// Read and decode a file through a memory map, avoiding an intermediate heap copy.
+///
+/// See [`Mapped`] for the safety caveats of memory-mapped files.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be mapped or decoded.
+#[cfg(feature = "mmap")]
+pub fn read_mmap<T, F>(path: impl AsRef<Path>, format: &F) -> Result<T, Error<F::Error>>
+where
+    T: DeserializeOwned,
+    F: Format,
+{
+    Mapped::open(path)?.decode(format)
+}
+
+/// A read-only memory map of a file.
+///
+/// The mapped bytes can be decoded with any [`Format`], or borrowed directly (via
+/// [`Mapped::bytes`]) by a deserializer that supports zero-copy decoding of borrowed DTOs.
+///
+/// Memory maps reflect the file as it changes on disk. If another process truncates or
+/// modifies the file while it is mapped, reads may observe inconsistent data or fault, so
+/// only map files that are not being concurrently written.
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct Mapped {
+    map: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl Mapped {
+    /// Memory-map the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or mapped.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the map is read-only and owned by `Self`; the caveats about concurrent
+        // modification of the underlying file are documented on the type.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Self { map })
+    }
+
+    /// The mapped file contents.
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Decode the mapped file contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the contents cannot be decoded as `T`.
+    pub fn decode<T, F>(&self, format: &F) -> Result<T, Error<F::Error>>
+    where
+        T: DeserializeOwned,
+        F: Format,
+    {
+        format.decode(self.bytes()).map_err(Error::Format)
+    }
+}
@@ -0,0 +1,68 @@
+//! Observing migrations.
+//!
+//! Every generated `From`/`TryFrom` conversion from a representation enum reports migrations
+//! of old versions, after they succeed, to a process-wide hook (see [`set_global`]) and to
+//! the type's own `on_migrate` hook, if any. Conversions of the current version are not
+//! reported.
+//!
+//! ```rust,ignore
+//! serde_evolve::hook::set_global(|event| {
+//!     READ_REPAIR.enqueue(event.type_name);
+//! });
+//! ```
+//!
+//! With the `metrics` feature, every reported migration also increments the
+//! `serde_evolve_migrations_total` counter of the installed `metrics` recorder, labelled with
+//! the `type` and its `from_version`, so that old versions can be watched draining out of
+//! storage.
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// A completed migration of an old version to the domain type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MigrationEvent {
+    /// Name of the domain type.
+    pub type_name: &'static str,
+    /// Version the value was read at.
+    pub from: u32,
+    /// The current version, which the value was migrated to.
+    pub to: u32,
+}
+
+type Hook = Arc<dyn Fn(&MigrationEvent) + Send + Sync>;
+
+static GLOBAL: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// Install a hook that observes migrations of every versioned type, replacing any previous
+/// global hook.
+pub fn set_global(hook: impl Fn(&MigrationEvent) + Send + Sync + 'static) {
+    *GLOBAL.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(hook));
+}
+
+/// Remove the global hook.
+pub fn clear_global() {
+    *GLOBAL.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Report a migration to the type's hook (if any), the global hook, and with the `metrics`
+/// feature, the migration counter.
+pub(crate) fn notify(event: &MigrationEvent, local: Option<fn(&MigrationEvent)>) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(
+        "serde_evolve_migrations_total",
+        "type" => event.type_name,
+        "from_version" => event.from.to_string(),
+    )
+    .increment(1);
+    if let Some(hook) = local {
+        hook(event);
+    }
+    // Clone the hook out of the lock so that it may itself replace the global hook.
+    let global = GLOBAL
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    if let Some(hook) = global {
+        hook(event);
+    }
+}
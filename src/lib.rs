@@ -1,4 +1,925 @@
 #![doc = include_str!("../README.md")]
 
-// Re-export the proc macro
-pub use serde_evolve_macros::Versioned;
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "json")]
+pub mod any;
+#[cfg(feature = "schemars")]
+pub mod avro;
+#[cfg(feature = "axum")]
+pub mod axum;
+pub mod batch;
+pub mod codec;
+pub mod date;
+pub mod envelope;
+pub mod error;
+#[cfg(feature = "json")]
+pub mod extract;
+#[cfg(feature = "figment")]
+pub mod figment;
+pub mod format;
+pub mod fs;
+pub mod hook;
+pub mod migrate;
+pub mod migrated;
+#[cfg(feature = "json")]
+pub mod mongo;
+#[cfg(feature = "json")]
+pub mod ndjson;
+pub mod payload;
+#[cfg(feature = "json")]
+pub mod peek;
+pub mod pinned;
+#[cfg(feature = "proptest")]
+pub mod prop;
+pub mod provenance;
+#[cfg(feature = "json")]
+pub mod raw;
+#[cfg(feature = "json")]
+pub mod registry;
+pub mod representation;
+#[cfg(feature = "reqwest")]
+pub mod reqwest;
+#[cfg(feature = "json")]
+pub mod sniff;
+pub mod store;
+pub mod testing;
+
+// Re-export the proc macros
+pub use serde_evolve_macros::{Evolve, Versioned, migrate, versioned_module};
+
+pub use date::VersionDate;
+#[cfg(feature = "diesel")]
+pub use diesel;
+pub use envelope::Envelope;
+pub use error::{ConvertError, MigrationStep, StepError, UnsupportedVersion, VersionedError};
+pub use format::Format;
+#[cfg(feature = "insta")]
+#[doc(hidden)]
+pub use insta;
+#[cfg(feature = "inventory")]
+#[doc(hidden)]
+pub use inventory;
+pub use migrate::{TryFromAsync, Upcast};
+pub use migrated::Migrated;
+#[cfg(feature = "json")]
+pub use migrated::Preserved;
+pub use payload::Payload;
+#[cfg(feature = "json")]
+pub use payload::{read_json, read_json_slice};
+#[cfg(feature = "json")]
+pub use peek::{peek_version, peek_version_reader, peek_version_slice};
+#[cfg(feature = "proptest")]
+pub use proptest;
+pub use provenance::{Provenance, Stamped};
+pub use representation::{Downgrade, Representation, VersionInfo, Versioned};
+#[cfg(feature = "schemars")]
+pub use schemars;
+#[cfg(feature = "sqlx")]
+pub use sqlx;
+
+#[doc(hidden)]
+pub mod __private {
+    //! Support items for generated code. Not part of the public API.
+
+    use crate::Payload;
+    use serde::de::Error as _;
+
+    /// Split a document into its version tag (if present) and the remaining payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is not a map, or its tag is not a string.
+    pub fn split_tagged<'de, D>(
+        deserializer: D,
+        tag: &'static str,
+    ) -> Result<(Option<String>, Payload), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut payload = <Payload as serde::Deserialize>::deserialize(deserializer)?;
+        let version = payload.take_str(tag)?;
+        Ok((version, payload))
+    }
+
+    /// Split an adjacently tagged document into its version tag and payload.
+    ///
+    /// A document without a tag is returned whole, so that untagged legacy documents can be
+    /// treated as a bare payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is not a map, or is tagged but lacks the content field.
+    pub fn split_adjacent<'de, D>(
+        deserializer: D,
+        tag: &'static str,
+        content: &'static str,
+    ) -> Result<(Option<String>, Payload), D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut document = <Payload as serde::Deserialize>::deserialize(deserializer)?;
+        let Some(version) = document.take_str(tag)? else {
+            return Ok((None, document));
+        };
+        let payload = document
+            .take(content)?
+            .ok_or_else(|| D::Error::missing_field(content))?;
+        Ok((Some(version), payload))
+    }
+
+    /// Adjacently tagged representations, read and written as a struct of the tag and then
+    /// the content, so that non-self-describing formats read them as a sequence.
+    pub mod adjacent {
+        use crate::Payload;
+        use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess};
+        use serde::ser::{SerializeStruct, Serializer};
+        use std::fmt;
+        use std::marker::PhantomData;
+
+        /// A representation that reads the content of a version from its tag.
+        pub trait Variants<'de>: Sized {
+            /// Read `content` as the version named by `tag`.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the tag names no version, or the content does not match it.
+            fn read_content<D: Deserializer<'de>>(tag: &str, content: D) -> Result<Self, D::Error>;
+        }
+
+        /// Serialize a version DTO as a struct of its tag and then its content.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the DTO cannot be serialized.
+        pub fn serialize<S, T>(
+            serializer: S,
+            name: &'static str,
+            fields: &'static [&'static str; 2],
+            version: &'static str,
+            value: &T,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+            T: serde::Serialize + ?Sized,
+        {
+            let mut state = serializer.serialize_struct(name, 2)?;
+            state.serialize_field(fields[0], version)?;
+            state.serialize_field(fields[1], value)?;
+            state.end()
+        }
+
+        /// Deserialize a representation written by [`serialize`].
+        ///
+        /// Maps may name the content before the tag, in which case the content is buffered,
+        /// and keys other than the tag and content are ignored.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the tag or content is missing, or the content does not match
+        /// the version the tag names.
+        pub fn deserialize<'de, R, D>(
+            deserializer: D,
+            name: &'static str,
+            fields: &'static [&'static str; 2],
+        ) -> Result<R, D::Error>
+        where
+            R: Variants<'de>,
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_struct(
+                name,
+                fields,
+                Adjacent {
+                    fields,
+                    rep: PhantomData,
+                },
+            )
+        }
+
+        struct Adjacent<R> {
+            fields: &'static [&'static str; 2],
+            rep: PhantomData<fn() -> R>,
+        }
+
+        impl<'de, R: Variants<'de>> de::Visitor<'de> for Adjacent<R> {
+            type Value = R;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "a version tag `{}` and its content `{}`",
+                    self.fields[0], self.fields[1]
+                )
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<R, A::Error> {
+                let tag: String = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                seq.next_element_seed(Content::<R>(&tag, PhantomData))?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<R, A::Error> {
+                let [tag_field, content_field] = *self.fields;
+                let mut tag: Option<String> = None;
+                let mut rep = None;
+                let mut buffered: Option<Payload> = None;
+                while let Some(key) = map.next_key_seed(Key(self.fields))? {
+                    match key {
+                        Some(0) if tag.is_some() => {
+                            return Err(de::Error::duplicate_field(tag_field));
+                        }
+                        Some(0) => tag = Some(map.next_value()?),
+                        Some(_) if rep.is_some() || buffered.is_some() => {
+                            return Err(de::Error::duplicate_field(content_field));
+                        }
+                        Some(_) => match &tag {
+                            Some(tag) => {
+                                rep = Some(map.next_value_seed(Content::<R>(tag, PhantomData))?);
+                            }
+                            None => buffered = Some(map.next_value()?),
+                        },
+                        None => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                let tag = tag.ok_or_else(|| de::Error::missing_field(tag_field))?;
+                match (rep, buffered) {
+                    (Some(rep), _) => Ok(rep),
+                    (None, Some(content)) => R::read_content(&tag, content.into_deserializer()),
+                    (None, None) => Err(de::Error::missing_field(content_field)),
+                }
+            }
+        }
+
+        /// The content of the version named by a tag.
+        struct Content<'a, R>(&'a str, PhantomData<fn() -> R>);
+
+        impl<'de, R: Variants<'de>> DeserializeSeed<'de> for Content<'_, R> {
+            type Value = R;
+
+            fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<R, D::Error> {
+                R::read_content(self.0, deserializer)
+            }
+        }
+
+        /// The index of a key among the tag and content fields, or `None` for other keys.
+        struct Key(&'static [&'static str; 2]);
+
+        impl<'de> DeserializeSeed<'de> for Key {
+            type Value = Option<usize>;
+
+            fn deserialize<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<Self::Value, D::Error> {
+                deserializer.deserialize_identifier(self)
+            }
+        }
+
+        impl de::Visitor<'_> for Key {
+            type Value = Option<usize>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a field name")
+            }
+
+            fn visit_str<E: de::Error>(self, key: &str) -> Result<Self::Value, E> {
+                Ok(self.0.iter().position(|field| *field == key))
+            }
+
+            fn visit_bytes<E: de::Error>(self, key: &[u8]) -> Result<Self::Value, E> {
+                Ok(self.0.iter().position(|field| field.as_bytes() == key))
+            }
+
+            fn visit_u64<E: de::Error>(self, index: u64) -> Result<Self::Value, E> {
+                Ok(usize::try_from(index).ok().filter(|index| *index < 2))
+            }
+        }
+    }
+
+    /// Serialize a version DTO with its version tag inserted as a field.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the DTO does not serialize as a map.
+    pub fn serialize_tagged<S, T>(
+        serializer: S,
+        tag: &'static str,
+        version: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+        T: serde::Serialize + ?Sized,
+    {
+        use serde::ser::Error;
+        use serde_value::Value;
+
+        let Value::Map(mut map) = serde_value::to_value(value).map_err(S::Error::custom)? else {
+            return Err(S::Error::custom(
+                "internally tagged versions must serialize as a map",
+            ));
+        };
+        map.insert(
+            Value::String(tag.to_owned()),
+            Value::String(version.to_owned()),
+        );
+        serde::Serialize::serialize(&map, serializer)
+    }
+
+    /// Type-erased access to representation enums.
+    #[cfg(feature = "json")]
+    pub mod any {
+        use crate::migrate::BoxError;
+        use crate::registry::{self, Error};
+        use serde::Serialize;
+        use serde::de::DeserializeOwned;
+        pub use serde_json::Value;
+
+        /// Serialize a representation as a tagged JSON document.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the representation cannot be serialized.
+        pub fn to_json<R: Serialize>(rep: &R) -> Result<Value, Error> {
+            serde_json::to_value(rep).map_err(Error::Json)
+        }
+
+        /// Migrate a borrowed representation to the current version of the domain type `D`.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the representation cannot be round-tripped through JSON, or a
+        /// migration step rejects it.
+        pub fn migrate_to_latest_json<R, D>(rep: &R) -> Result<Value, Error>
+        where
+            R: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+            D: TryFrom<R>,
+            D::Error: Into<BoxError>,
+        {
+            registry::migrate::<R, D>(to_json(rep)?)
+        }
+    }
+
+    /// JSON Schemas for representation enums.
+    #[cfg(feature = "schemars")]
+    pub mod schema {
+        use schemars::{JsonSchema, Schema, SchemaGenerator};
+        use serde_json::{Value, json};
+
+        /// An internally tagged version: the DTO's schema, plus the tag field.
+        pub fn internal<T: JsonSchema>(
+            generator: &mut SchemaGenerator,
+            tag: &str,
+            version: &str,
+        ) -> Schema {
+            let payload = generator.subschema_for::<T>();
+            let mut tag_schema = json!({ "type": "object", "required": [tag] });
+            tag_schema["properties"][tag] = json!({ "const": version });
+            schema(json!({ "allOf": [payload, tag_schema] }))
+        }
+
+        /// An adjacently tagged version: an object holding the tag and the DTO.
+        pub fn adjacent<T: JsonSchema>(
+            generator: &mut SchemaGenerator,
+            tag: &str,
+            content: &str,
+            version: &str,
+        ) -> Schema {
+            let mut object = json!({ "type": "object", "required": [tag, content] });
+            object["properties"][tag] = json!({ "const": version });
+            object["properties"][content] = generator.subschema_for::<T>().into();
+            schema(object)
+        }
+
+        /// An untagged version: the DTO's schema alone.
+        pub fn untagged<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+            generator.subschema_for::<T>()
+        }
+
+        /// A schema matching exactly one of `versions`.
+        #[must_use]
+        pub fn one_of(versions: &[Schema]) -> Schema {
+            schema(json!({ "oneOf": versions }))
+        }
+
+        /// The root schema of a single version DTO.
+        #[must_use]
+        pub fn root<T: JsonSchema>() -> Schema {
+            SchemaGenerator::default().into_root_schema_for::<T>()
+        }
+
+        /// A stable 64-bit FNV-1a hash of a schema, independent of object key order.
+        #[must_use]
+        pub fn fingerprint(schema: &Schema) -> u64 {
+            let mut hash = Fnv(0xcbf2_9ce4_8422_2325);
+            hash_value(schema.as_value(), &mut hash);
+            hash.0
+        }
+
+        struct Fnv(u64);
+
+        impl Fnv {
+            fn write(&mut self, bytes: &[u8]) {
+                for byte in bytes {
+                    self.0 ^= u64::from(*byte);
+                    self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+                }
+            }
+
+            fn write_str(&mut self, s: &str) {
+                self.write(&(s.len() as u64).to_le_bytes());
+                self.write(s.as_bytes());
+            }
+        }
+
+        fn hash_value(value: &Value, hash: &mut Fnv) {
+            match value {
+                Value::Null => hash.write(b"n"),
+                Value::Bool(b) => hash.write(if *b { b"t" } else { b"f" }),
+                Value::Number(n) => {
+                    hash.write(b"#");
+                    hash.write_str(&n.to_string());
+                }
+                Value::String(s) => {
+                    hash.write(b"s");
+                    hash.write_str(s);
+                }
+                Value::Array(items) => {
+                    hash.write(b"[");
+                    hash.write(&(items.len() as u64).to_le_bytes());
+                    for item in items {
+                        hash_value(item, hash);
+                    }
+                }
+                Value::Object(map) => {
+                    let mut entries: Vec<_> = map.iter().collect();
+                    entries.sort_unstable_by_key(|(key, _)| *key);
+                    hash.write(b"{");
+                    hash.write(&(entries.len() as u64).to_le_bytes());
+                    for (key, value) in entries {
+                        hash.write_str(key);
+                        hash_value(value, hash);
+                    }
+                }
+            }
+        }
+
+        fn schema(value: Value) -> Schema {
+            Schema::try_from(value).expect("schema literals are JSON objects")
+        }
+    }
+
+    /// Assertions that the conversions of a migration chain exist, so that a missing impl is
+    /// reported against the offending chain entry rather than inside generated code.
+    pub mod assert {
+        use crate::TryFromAsync;
+
+        /// `Self` can be migrated to infallibly from `Prev`.
+        #[diagnostic::on_unimplemented(
+            message = "no migration from `{Prev}` to `{Self}`",
+            label = "missing `impl From<{Prev}> for {Self}`"
+        )]
+        pub trait MigratesFrom<Prev> {}
+
+        impl<Prev, Next: From<Prev>> MigratesFrom<Prev> for Next {}
+
+        /// `Self` can be migrated to from `Prev`, failing with an error convertible into `E`.
+        #[diagnostic::on_unimplemented(
+            message = "no fallible migration from `{Prev}` to `{Self}`",
+            label = "missing `impl TryFrom<{Prev}> for {Self}` with an error convertible into `{E}`"
+        )]
+        pub trait TryMigratesFrom<Prev, E> {}
+
+        impl<Prev, E, Next> TryMigratesFrom<Prev, E> for Next
+        where
+            Next: TryFrom<Prev>,
+            Next::Error: Into<E>,
+        {
+        }
+
+        /// `Self` can be migrated to asynchronously from `Prev`, failing with an error
+        /// convertible into `E`.
+        #[diagnostic::on_unimplemented(
+            message = "no async migration from `{Prev}` to `{Self}`",
+            label = "missing `impl TryFromAsync<{Prev}> for {Self}` with an error convertible into `{E}`"
+        )]
+        pub trait AsyncMigratesFrom<Prev, E> {}
+
+        impl<Prev, E, Next> AsyncMigratesFrom<Prev, E> for Next
+        where
+            Next: TryFromAsync<Prev>,
+            Next::Error: Into<E>,
+        {
+        }
+
+        /// A field of the latest version has the type of the domain type's field.
+        #[diagnostic::on_unimplemented(
+            message = "the latest version declares this field as `{Self}`, not `{Domain}`",
+            label = "the domain type and the latest version disagree on this field's type"
+        )]
+        pub trait SameFieldType<Domain> {}
+
+        impl<T> SameFieldType<T> for T {}
+
+        /// Assert that `Next: From<Prev>`.
+        pub const fn migrates_from<Prev, Next: MigratesFrom<Prev>>() {}
+
+        /// Assert that `Next: TryFrom<Prev>`, with an error convertible into `E`.
+        pub const fn try_migrates_from<Prev, E, Next: TryMigratesFrom<Prev, E>>() {}
+
+        /// Assert that `Next: TryFromAsync<Prev>`, with an error convertible into `E`.
+        pub const fn async_migrates_from<Prev, E, Next: AsyncMigratesFrom<Prev, E>>() {}
+
+        /// Assert that the borrowed field of the latest version has the type `Domain`.
+        pub const fn same_field_type<Domain, Latest: SameFieldType<Domain>>(_latest: &Latest) {}
+    }
+
+    /// Support for tests generated with `generate_tests = true`.
+    pub mod testing {
+        use crate::Representation;
+        use std::marker::PhantomData;
+
+        /// Produces a sample value of `T` through [`DefaultSample`] when `T: Default`, and
+        /// falls back to [`NoSample`] otherwise.
+        ///
+        /// Call as `(&Probe::<T>::new()).sample()` with both traits in scope: method
+        /// resolution prefers the by-reference impl, which only applies when `T: Default`.
+        #[derive(Debug)]
+        pub struct Probe<T>(PhantomData<T>);
+
+        impl<T> Probe<T> {
+            /// Create a probe for `T`.
+            #[must_use]
+            pub const fn new() -> Self {
+                Self(PhantomData)
+            }
+        }
+
+        impl<T> Default for Probe<T> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        /// Sample values for types implementing `Default`.
+        pub trait DefaultSample<T> {
+            /// The default value of `T`.
+            fn sample(&self) -> Option<T>;
+        }
+
+        impl<T: Default> DefaultSample<T> for Probe<T> {
+            fn sample(&self) -> Option<T> {
+                Some(T::default())
+            }
+        }
+
+        /// The fallback for types without a `Default` impl.
+        pub trait NoSample<T> {
+            /// Always `None`.
+            fn sample(&self) -> Option<T>;
+        }
+
+        impl<T> NoSample<T> for &Probe<T> {
+            fn sample(&self) -> Option<T> {
+                None
+            }
+        }
+
+        /// Assert that `rep` survives a serialize/deserialize round trip unchanged.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `rep` cannot be serialized or deserialized, or if the decoded value
+        /// differs from the original.
+        pub fn assert_round_trip<R>(rep: &R)
+        where
+            R: Representation + serde::Serialize + serde::de::DeserializeOwned,
+        {
+            if let Err(message) = round_trip(rep) {
+                panic!("{message}");
+            }
+        }
+
+        /// Check that `rep` survives a serialize/deserialize round trip unchanged.
+        pub(crate) fn round_trip<R>(rep: &R) -> Result<(), String>
+        where
+            R: Representation + serde::Serialize + serde::de::DeserializeOwned,
+        {
+            let encoded = serde_value::to_value(rep)
+                .map_err(|err| format!("representation failed to serialize: {err}"))?;
+            let decoded: R = serde::Deserialize::deserialize(encoded.clone())
+                .map_err(|err| format!("representation failed to deserialize: {err}"))?;
+            if decoded.version() != rep.version() {
+                return Err(format!(
+                    "version changed in round trip: {} became {}",
+                    rep.version(),
+                    decoded.version()
+                ));
+            }
+            let reencoded = serde_value::to_value(&decoded)
+                .map_err(|err| format!("representation failed to serialize: {err}"))?;
+            if reencoded != encoded {
+                return Err("payload changed in round trip".to_string());
+            }
+            Ok(())
+        }
+    }
+
+    /// `diesel` impls storing representations in Postgres `jsonb` columns.
+    #[cfg(feature = "diesel")]
+    pub mod diesel {
+        use diesel::pg::{Pg, PgValue};
+        use diesel::serialize::{IsNull, Output};
+        use diesel::{deserialize, serialize};
+        use serde::Serialize;
+        use serde::de::DeserializeOwned;
+        use std::io::Write;
+
+        /// Version byte of the binary `jsonb` encoding.
+        const JSONB_VERSION: u8 = 1;
+
+        /// Decode a `jsonb` value.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the value is not binary `jsonb`, or does not deserialize as
+        /// `T`.
+        pub fn from_sql<T: DeserializeOwned>(value: PgValue<'_>) -> deserialize::Result<T> {
+            match value.as_bytes().split_first() {
+                Some((&JSONB_VERSION, json)) => Ok(serde_json::from_slice(json)?),
+                Some(_) => Err("unsupported jsonb encoding version".into()),
+                None => Err("received an empty jsonb value".into()),
+            }
+        }
+
+        /// Encode a value as binary `jsonb`.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the value cannot be serialized.
+        pub fn to_sql<T: Serialize + ?Sized>(
+            value: &T,
+            out: &mut Output<'_, '_, Pg>,
+        ) -> serialize::Result {
+            out.write_all(&[JSONB_VERSION])?;
+            serde_json::to_writer(out, value)?;
+            Ok(IsNull::No)
+        }
+    }
+
+    /// `sqlx` impls storing domain types in Postgres `jsonb` columns.
+    #[cfg(feature = "sqlx")]
+    pub mod sqlx {
+        use serde::Serialize;
+        use serde::de::DeserializeOwned;
+        use sqlx::encode::IsNull;
+        use sqlx::error::BoxDynError;
+        use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef, Postgres};
+        use sqlx::types::Json;
+        use sqlx::{Decode, Encode, Type};
+
+        /// The `jsonb` type.
+        #[must_use]
+        pub fn type_info() -> PgTypeInfo {
+            <Json<()> as Type<Postgres>>::type_info()
+        }
+
+        /// Whether a column of type `ty` holds JSON (`json` or `jsonb`).
+        #[must_use]
+        pub fn compatible(ty: &PgTypeInfo) -> bool {
+            <Json<()> as Type<Postgres>>::compatible(ty)
+        }
+
+        /// Decode a JSON column as the representation `R` and migrate it to `D`.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the column is not a valid document of any version, or the
+        /// migration fails.
+        pub fn decode<R, D>(value: PgValueRef<'_>) -> Result<D, BoxDynError>
+        where
+            R: DeserializeOwned,
+            D: TryFrom<R>,
+            D::Error: Into<BoxDynError>,
+        {
+            let Json(rep) = <Json<R> as Decode<'_, Postgres>>::decode(value)?;
+            D::try_from(rep).map_err(Into::into)
+        }
+
+        /// Encode a representation as `jsonb`.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the representation cannot be serialized.
+        pub fn encode<R: Serialize>(
+            rep: R,
+            buf: &mut PgArgumentBuffer,
+        ) -> Result<IsNull, BoxDynError> {
+            Encode::<'_, Postgres>::encode_by_ref(&Json(rep), buf)
+        }
+    }
+
+    /// Version marking with CBOR semantic tags.
+    #[cfg(feature = "cbor")]
+    pub mod cbor {
+        use crate::Payload;
+        use ciborium::tag::Captured;
+        use serde::de::Error as _;
+
+        /// Serialize a version DTO wrapped in the CBOR tag `tag`.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the DTO cannot be serialized.
+        pub fn serialize<S, T>(serializer: S, tag: u64, value: &T) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+            T: serde::Serialize + ?Sized,
+        {
+            serde::Serialize::serialize(&Captured(Some(tag), value), serializer)
+        }
+
+        /// Split a CBOR-tagged document into its version (the tag number less `base`) and
+        /// payload.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the document is untagged, or its tag is below `base`.
+        pub fn split<'de, D>(deserializer: D, base: u64) -> Result<(u32, Payload), D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            let Captured(tag, payload) =
+                <Captured<Payload> as serde::Deserialize>::deserialize(deserializer)?;
+            let tag =
+                tag.ok_or_else(|| D::Error::custom("expected a CBOR tag carrying the version"))?;
+            let version = tag
+                .checked_sub(base)
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| {
+                    D::Error::custom(format_args!("CBOR tag {tag} is not a version tag"))
+                })?;
+            Ok((version, payload))
+        }
+    }
+
+    /// The date of a version, validated by the derive.
+    ///
+    /// # Panics
+    ///
+    /// Panics (at compile time, in a const context) if the date does not exist.
+    #[must_use]
+    pub const fn version_date(year: u16, month: u8, day: u8) -> crate::VersionDate {
+        match crate::VersionDate::new(year, month, day) {
+            Some(date) => date,
+            None => panic!("invalid version date"),
+        }
+    }
+
+    /// Reject a version tag below `min`, the oldest version a chain can still read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`UnsupportedVersion`](crate::UnsupportedVersion) error if `tag` is a
+    /// version number below `min`.
+    pub fn reject_retired<E: serde::de::Error>(tag: &str, min: u32, max: u32) -> Result<(), E> {
+        match tag.parse::<u32>() {
+            Ok(found) if found < min => {
+                Err(E::custom(crate::UnsupportedVersion { found, min, max }))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reject a version tag outside `min..=max`, the versions a chain can read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`UnsupportedVersion`](crate::UnsupportedVersion) error if `tag` is a
+    /// version number below `min` or above `max`.
+    pub fn reject_unsupported<E: serde::de::Error>(tag: &str, min: u32, max: u32) -> Result<(), E> {
+        match tag.parse::<u32>() {
+            Ok(found) if found > max => {
+                Err(E::custom(crate::UnsupportedVersion { found, min, max }))
+            }
+            _ => reject_retired(tag, min, max),
+        }
+    }
+
+    /// Report that a deprecated version was read, through `log` and/or `tracing` when the
+    /// corresponding features are enabled.
+    #[cfg_attr(
+        not(any(feature = "log", feature = "tracing")),
+        allow(clippy::missing_const_for_fn)
+    )]
+    pub fn warn_deprecated(type_name: &'static str, version: &str) {
+        #[cfg(feature = "log")]
+        log::warn!("read deprecated version {version} of {type_name}");
+        #[cfg(feature = "tracing")]
+        tracing::warn!(type_name, version, "read deprecated version");
+        #[cfg(not(any(feature = "log", feature = "tracing")))]
+        let _ = (type_name, version);
+    }
+
+    /// The error reported for documents at a deprecated version under the `error` policy.
+    #[must_use]
+    pub fn deprecated_version<E: serde::de::Error>(type_name: &'static str, version: &str) -> E {
+        E::custom(format_args!(
+            "version {version} of {type_name} is deprecated"
+        ))
+    }
+
+    /// Report a conversion from `from` to the current version `to`, unless it was already
+    /// current.
+    pub fn migrated(
+        type_name: &'static str,
+        from: u32,
+        to: u32,
+        hook: Option<fn(&crate::hook::MigrationEvent)>,
+    ) {
+        if from != to {
+            let event = crate::hook::MigrationEvent {
+                type_name,
+                from,
+                to,
+            };
+            crate::hook::notify(&event, hook);
+        }
+    }
+
+    /// Start timing a migration step from version `from` to version `to`.
+    ///
+    /// `to` is `None` for the final step, into the domain type. With the `tracing` feature,
+    /// the step is reported as a `migration_step` span, with its duration, when the returned
+    /// guard is dropped.
+    #[cfg(feature = "tracing")]
+    #[must_use]
+    pub fn trace_step(type_name: &'static str, from: u32, to: Option<u32>) -> StepTrace {
+        StepTrace {
+            span: tracing::debug_span!("migration_step", type_name, from, to),
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Start timing a migration step from version `from` to version `to`.
+    ///
+    /// Without the `tracing` feature, steps are not reported.
+    #[cfg(not(feature = "tracing"))]
+    #[must_use]
+    pub const fn trace_step(_type_name: &'static str, _from: u32, _to: Option<u32>) -> StepTrace {
+        StepTrace {}
+    }
+
+    /// Guard returned by [`trace_step`].
+    #[derive(Debug)]
+    pub struct StepTrace {
+        #[cfg(feature = "tracing")]
+        span: tracing::Span,
+        #[cfg(feature = "tracing")]
+        start: std::time::Instant,
+    }
+
+    impl Drop for StepTrace {
+        fn drop(&mut self) {
+            #[cfg(feature = "tracing")]
+            self.span.in_scope(|| {
+                let elapsed_us =
+                    u64::try_from(self.start.elapsed().as_micros()).unwrap_or(u64::MAX);
+                tracing::debug!(elapsed_us, "migration step finished");
+            });
+        }
+    }
+
+    /// Deserialize a payload as one of the chain's version types.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload does not match the version type.
+    pub fn deserialize_payload<'de, T, E>(payload: Payload) -> Result<T, E>
+    where
+        T: serde::Deserialize<'de>,
+        E: serde::de::Error,
+    {
+        payload.deserialize_as()
+    }
+
+    /// Deserialize a payload as one of the chain's version types, rejecting keys the type
+    /// does not declare.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload has an unknown key, or does not match the version
+    /// type.
+    pub fn deserialize_payload_strict<'de, T, E>(payload: Payload) -> Result<T, E>
+    where
+        T: serde::Deserialize<'de>,
+        E: serde::de::Error,
+    {
+        payload.deserialize_strict()
+    }
+}
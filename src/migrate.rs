@@ -0,0 +1,293 @@
+//! Migration traits, and manually assembled migration chains.
+//!
+//! `#[derive(Versioned)]` covers types you can annotate. For types you cannot (for example
+//! generated code), a [`Chain`] assembles the same migration path at runtime from the
+//! `From`/`TryFrom` impls between versions:
+//!
+//! ```rust,ignore
+//! use serde_evolve::migrate::Chain;
+//!
+//! let chain = Chain::<ConfigV1>::start().then::<ConfigV2>().finish::<Config>();
+//! assert_eq!(chain.current(), 2);
+//!
+//! let config = chain.migrate_value(1, payload)?;
+//! ```
+//!
+//! Versions are tagged with their numbers. To keep an established naming convention, give
+//! the version just appended its own tag with [`tagged`](Chain::tagged), as `V1 as "v1alpha1"`
+//! does in a derived chain:
+//!
+//! ```rust,ignore
+//! let chain = Chain::<ConfigV1>::start()
+//!     .tagged("v1alpha1")
+//!     .then::<ConfigV2>()
+//!     .tagged("v1beta1")
+//!     .finish::<Config>();
+//! ```
+//!
+//! When the types are only known at runtime (for example in dynamically loaded plugins),
+//! supply each step as a closure with [`then_with`](Chain::then_with) and
+//! [`finish_with`](Chain::finish_with) instead.
+
+use crate::UnsupportedVersion;
+use crate::payload::{self, Payload};
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+/// A boxed migration error.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A single migration step from `Self` to `Next`.
+///
+/// Implemented for every pair of types related by `From` or `TryFrom`, so that migrations
+/// are written with the standard conversion traits.
+pub trait Upcast<Next> {
+    /// The error returned when the step fails.
+    type Error;
+
+    /// Migrate `self` to the next version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the migration fails.
+    fn upcast(self) -> Result<Next, Self::Error>;
+}
+
+impl<T, Next: TryFrom<T>> Upcast<Next> for T {
+    type Error = Next::Error;
+
+    fn upcast(self) -> Result<Next, Self::Error> {
+        Next::try_from(self)
+    }
+}
+
+/// An asynchronous, fallible conversion, for migration steps that must consult external
+/// state such as an ID-remapping service.
+///
+/// Mark a chain entry `async` (`chain(V1, V2 async)`) to migrate into it with this trait.
+/// The derive then generates an `async fn from_rep` on the domain type in place of the
+/// `TryFrom` impl.
+pub trait TryFromAsync<T>: Sized {
+    /// The error returned when the conversion fails.
+    type Error;
+
+    /// Convert `value`, asynchronously.
+    fn try_from_async(value: T) -> impl Future<Output = Result<Self, Self::Error>> + Send;
+}
+
+/// Error returned when a [`Chain`] cannot migrate a value.
+#[derive(Debug)]
+pub enum Error {
+    /// The version is not part of the chain.
+    UnsupportedVersion(UnsupportedVersion),
+    /// The payload does not match the version type.
+    Payload(payload::Error),
+    /// A migration step failed.
+    Migration(BoxError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(err) => err.fmt(f),
+            Self::Payload(err) => write!(f, "invalid payload: {err}"),
+            Self::Migration(err) => write!(f, "migration failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::UnsupportedVersion(err) => Some(err),
+            Self::Payload(err) => Some(err),
+            Self::Migration(err) => Some(&**err),
+        }
+    }
+}
+
+type Reader<T> = Box<dyn Fn(Payload) -> Result<T, Error> + Send + Sync>;
+
+/// A migration chain assembled at runtime, producing values of type `T`.
+///
+/// Each call to [`then`](Self::then) appends a version; [`finish`](Self::finish) adds the
+/// final conversion into the domain type without adding a version.
+pub struct Chain<T> {
+    readers: Vec<Reader<T>>,
+    /// The tag of each version, where it is not the version number.
+    tags: Vec<Option<&'static str>>,
+}
+
+impl<T> fmt::Debug for Chain<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Chain")
+            .field("current", &self.current())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: DeserializeOwned + 'static> Chain<T> {
+    /// Start a chain whose first version (version 1) is `T`.
+    #[must_use]
+    pub fn start() -> Self {
+        Self {
+            readers: vec![Box::new(read::<T>)],
+            tags: vec![None],
+        }
+    }
+}
+
+impl<T: 'static> Chain<T> {
+    /// Append the next version, `N`, migrated to from `T`.
+    #[must_use]
+    pub fn then<N>(self) -> Chain<N>
+    where
+        T: Upcast<N>,
+        T::Error: Into<BoxError>,
+        N: DeserializeOwned + 'static,
+    {
+        let mut chain = self.finish::<N>();
+        chain.readers.push(Box::new(read::<N>));
+        chain.tags.push(None);
+        chain
+    }
+
+    /// Convert every version into `D` (typically the domain type), without adding a version.
+    #[must_use]
+    pub fn finish<D>(self) -> Chain<D>
+    where
+        T: Upcast<D>,
+        T::Error: Into<BoxError>,
+        D: 'static,
+    {
+        self.finish_with(T::upcast)
+    }
+
+    /// Append the next version, `N`, migrated to from `T` by `step` rather than by an
+    /// [`Upcast`] impl.
+    #[must_use]
+    pub fn then_with<N, E>(
+        self,
+        step: impl Fn(T) -> Result<N, E> + Send + Sync + 'static,
+    ) -> Chain<N>
+    where
+        E: Into<BoxError>,
+        N: DeserializeOwned + 'static,
+    {
+        let mut chain = self.finish_with(step);
+        chain.readers.push(Box::new(read::<N>));
+        chain.tags.push(None);
+        chain
+    }
+
+    /// Convert every version into `D` with `step`, without adding a version.
+    #[must_use]
+    pub fn finish_with<D, E>(
+        self,
+        step: impl Fn(T) -> Result<D, E> + Send + Sync + 'static,
+    ) -> Chain<D>
+    where
+        E: Into<BoxError>,
+        D: 'static,
+    {
+        let step = Arc::new(step);
+        let readers = self
+            .readers
+            .into_iter()
+            .map(|reader| -> Reader<D> {
+                let step = Arc::clone(&step);
+                Box::new(move |payload| {
+                    step(reader(payload)?).map_err(|err| Error::Migration(err.into()))
+                })
+            })
+            .collect();
+        Chain {
+            readers,
+            tags: self.tags,
+        }
+    }
+}
+
+impl<T> Chain<T> {
+    /// The current (latest) version number.
+    #[must_use]
+    pub fn current(&self) -> u32 {
+        u32::try_from(self.readers.len()).unwrap_or(u32::MAX)
+    }
+
+    /// Tag the latest version with `tag` in place of its version number.
+    ///
+    /// The number itself is then no longer accepted as the tag of that version.
+    #[must_use]
+    pub fn tagged(mut self, tag: &'static str) -> Self {
+        if let Some(last) = self.tags.last_mut() {
+            *last = Some(tag);
+        }
+        self
+    }
+
+    /// The version number written with `tag`: a version's own tag, or its number if it has
+    /// none.
+    #[must_use]
+    pub fn version_of_tag(&self, tag: &str) -> Option<u32> {
+        let idx = self
+            .tags
+            .iter()
+            .position(|own| *own == Some(tag))
+            .or_else(|| {
+                let idx = tag.parse::<usize>().ok()?.checked_sub(1)?;
+                self.tags.get(idx)?.is_none().then_some(idx)
+            })?;
+        u32::try_from(idx + 1).ok()
+    }
+
+    /// Decode a payload of the given version and migrate it through the rest of the chain.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version is unknown, the payload does not match it, or a
+    /// migration step fails.
+    pub fn migrate_value(&self, version: u32, payload: Payload) -> Result<T, Error> {
+        let reader = version
+            .checked_sub(1)
+            .and_then(|idx| self.readers.get(idx as usize))
+            .ok_or(Error::UnsupportedVersion(UnsupportedVersion {
+                found: version,
+                min: 1,
+                max: self.current(),
+            }))?;
+        reader(payload)
+    }
+
+    /// Deserialize an internally tagged document (with its version in the `tag` field) and
+    /// migrate it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document has no valid tag, or cannot be migrated.
+    pub fn deserialize<'de, D>(&self, tag: &'static str, deserializer: D) -> Result<T, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error as _;
+
+        let (version, payload) = crate::__private::split_tagged(deserializer, tag)?;
+        let version = version.ok_or_else(|| D::Error::missing_field(tag))?;
+        // Numbers outside the chain are reported as unsupported versions, not invalid tags.
+        let version = self
+            .version_of_tag(&version)
+            .or_else(|| {
+                let number = version.parse().ok()?;
+                (!(1..=self.current()).contains(&number)).then_some(number)
+            })
+            .ok_or_else(|| D::Error::custom(format_args!("invalid version tag `{version}`")))?;
+        self.migrate_value(version, payload)
+            .map_err(D::Error::custom)
+    }
+}
+
+fn read<T: DeserializeOwned>(payload: Payload) -> Result<T, Error> {
+    payload.deserialize().map_err(Error::Payload)
+}
@@ -0,0 +1,126 @@
+//! Deserialization with migration provenance.
+//!
+//! [`Migrated`] deserializes a domain type like the transparent mode does, and also records
+//! which version the document was stored at, so that callers can decide whether to write
+//! the record back:
+//!
+//! ```rust,ignore
+//! use serde_evolve::Migrated;
+//!
+//! let product: Migrated<Product> = serde_json::from_str(json)?;
+//! if product.migrated {
+//!     store.save(&product.value)?;
+//! }
+//! ```
+//!
+//! With the `json` feature, [`Preserved`] additionally keeps the exact text of the document
+//! as it was read, for archiving the pre-migration original.
+
+use crate::{Representation, Versioned};
+use serde::de::Error as _;
+use std::fmt;
+
+/// A domain value, with the version it was migrated from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migrated<T> {
+    /// The migrated domain value.
+    pub value: T,
+    /// The version of the stored document.
+    pub source_version: u32,
+    /// Whether any migration step ran, that is whether the document was stored at an older
+    /// version.
+    pub migrated: bool,
+}
+
+impl<T: Versioned> Migrated<T> {
+    /// Migrate a representation to the domain type, recording its version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any migration step fails.
+    pub fn from_rep(rep: T::Representation) -> Result<Self, T::Error>
+    where
+        T: TryFrom<T::Representation>,
+    {
+        let source_version = rep.version();
+        Ok(Self {
+            value: T::try_from(rep)?,
+            source_version,
+            migrated: source_version != T::Representation::CURRENT,
+        })
+    }
+
+    /// The number of migration steps taken.
+    #[must_use]
+    pub const fn steps(&self) -> u32 {
+        T::Representation::CURRENT - self.source_version
+    }
+
+    /// Discard the provenance, returning the domain value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<'de, T> serde::Deserialize<'de> for Migrated<T>
+where
+    T: Versioned + TryFrom<T::Representation>,
+    T::Representation: serde::Deserialize<'de>,
+    T::Error: fmt::Display,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rep = T::Representation::deserialize(deserializer)?;
+        Self::from_rep(rep).map_err(D::Error::custom)
+    }
+}
+
+/// A domain value, with the exact JSON document it was migrated from.
+///
+/// Deserializes only from `serde_json`, which can hand over the raw text of a value.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone)]
+pub struct Preserved<T> {
+    /// The migrated domain value.
+    pub value: T,
+    /// The version of the stored document.
+    pub source_version: u32,
+    /// The document exactly as it was read, before migration.
+    pub raw: Box<serde_json::value::RawValue>,
+}
+
+#[cfg(feature = "json")]
+impl<T> Preserved<T> {
+    /// Whether the document was stored at an older version.
+    #[must_use]
+    pub const fn migrated(&self) -> bool
+    where
+        T: Versioned,
+    {
+        self.source_version != T::Representation::CURRENT
+    }
+
+    /// Discard the original document, returning the domain value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'de, T> serde::Deserialize<'de> for Preserved<T>
+where
+    T: Versioned + TryFrom<T::Representation>,
+    T::Representation: serde::de::DeserializeOwned,
+    T::Error: fmt::Display,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = Box::<serde_json::value::RawValue>::deserialize(deserializer)?;
+        let rep: T::Representation = serde_json::from_str(raw.get()).map_err(D::Error::custom)?;
+        let source_version = rep.version();
+        let value = T::try_from(rep).map_err(D::Error::custom)?;
+        Ok(Self {
+            value,
+            source_version,
+            raw,
+        })
+    }
+}
@@ -0,0 +1,89 @@
+//! Filters for bulk-migrating Mongo collections by version.
+//!
+//! Representation enums store their version tag as a string field, which a Mongo query can
+//! match on. The filters are built as JSON; with the `bson` feature, [`documents`] builds the
+//! same filters as `bson::Document`s, ready for the driver:
+//!
+//! ```rust,ignore
+//! use serde_evolve::mongo::documents;
+//!
+//! let filter = documents::filter_outdated::<UserVersions>().unwrap();
+//! let mut stale = users.find(filter).await?;
+//! while let Some(rep) = stale.try_next().await? {
+//!     users.replace_one(doc! { "_id": id }, UserVersions::from(&User::from(rep))).await?;
+//! }
+//! ```
+
+use crate::Representation;
+use serde_json::{Value, json};
+
+/// A filter matching documents stored at any version but the current one, including
+/// documents without a version tag.
+///
+/// Returns `None` for untagged representations, whose documents carry no version to match.
+#[must_use]
+pub fn filter_outdated<R: Representation>() -> Option<Value> {
+    let tag = R::TAG?;
+    let current = R::tag_of_version(R::CURRENT)?;
+    Some(json!({ tag: { "$ne": current } }))
+}
+
+/// A filter matching documents stored at `version`.
+///
+/// Documents written with a [tag alias](crate::VersionInfo::tag) of the version are not
+/// matched. Returns `None` for untagged representations, or if this build cannot read
+/// `version`.
+#[must_use]
+pub fn filter_version<R: Representation>(version: u32) -> Option<Value> {
+    let tag = R::TAG?;
+    Some(json!({ tag: R::tag_of_version(version)? }))
+}
+
+/// A filter matching documents stored at any of `versions`.
+///
+/// Versions this build cannot read are skipped. Returns `None` for untagged representations.
+#[must_use]
+pub fn filter_versions<R: Representation>(versions: &[u32]) -> Option<Value> {
+    let tag = R::TAG?;
+    let tags: Vec<_> = versions
+        .iter()
+        .filter_map(|&version| R::tag_of_version(version))
+        .collect();
+    Some(json!({ tag: { "$in": tags } }))
+}
+
+/// The filters of this module as BSON documents.
+///
+/// Tags are BSON strings, so the filters match documents written by serializing a
+/// representation enum with `bson::to_document`, whether internally or adjacently tagged.
+#[cfg(feature = "bson")]
+pub mod documents {
+    use crate::Representation;
+    use bson::Document;
+    use serde_json::Value;
+
+    /// A filter matching documents stored at any version but the current one; see
+    /// [`filter_outdated`](super::filter_outdated).
+    #[must_use]
+    pub fn filter_outdated<R: Representation>() -> Option<Document> {
+        super::filter_outdated::<R>().map(|filter| to_document(&filter))
+    }
+
+    /// A filter matching documents stored at `version`; see
+    /// [`filter_version`](super::filter_version).
+    #[must_use]
+    pub fn filter_version<R: Representation>(version: u32) -> Option<Document> {
+        super::filter_version::<R>(version).map(|filter| to_document(&filter))
+    }
+
+    /// A filter matching documents stored at any of `versions`; see
+    /// [`filter_versions`](super::filter_versions).
+    #[must_use]
+    pub fn filter_versions<R: Representation>(versions: &[u32]) -> Option<Document> {
+        super::filter_versions::<R>(versions).map(|filter| to_document(&filter))
+    }
+
+    fn to_document(filter: &Value) -> Document {
+        bson::to_document(filter).expect("filters are JSON objects of strings")
+    }
+}
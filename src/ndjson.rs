@@ -0,0 +1,109 @@
+//! Streaming migration of newline-delimited JSON.
+//!
+//! [`migrate`] reads one document per line, migrates it through the domain type and writes
+//! it back at the current version. Only one line is held in memory at a time, so archives of
+//! any size can be migrated.
+
+use crate::migrate::BoxError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+/// Why a single line could not be migrated.
+#[derive(Debug)]
+pub enum LineError {
+    /// The line is not a valid document (or the migrated document could not be serialized).
+    Json(serde_json::Error),
+    /// A migration step rejected the document.
+    Migration(BoxError),
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "JSON error: {err}"),
+            Self::Migration(err) => write!(f, "migration failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(err) => Some(err),
+            Self::Migration(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// Counts of the lines processed by [`migrate`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Summary {
+    /// Lines written at the current version.
+    pub written: usize,
+    /// Lines that failed and were reported to the error callback.
+    pub failed: usize,
+}
+
+/// Migrate newline-delimited JSON documents of representation `R` to the current version.
+///
+/// Each non-blank line of `reader` is deserialized as `R`, converted into the domain type
+/// `D` and serialized back to `writer` as a single line at the current version. Lines that
+/// fail are passed to `on_error` with their 1-based line number and omitted from the output;
+/// the migration then continues with the next line.
+///
+/// # Errors
+///
+/// Returns an error if reading from `reader` or writing to `writer` fails.
+pub fn migrate<R, D>(
+    mut reader: impl BufRead,
+    mut writer: impl Write,
+    mut on_error: impl FnMut(usize, LineError),
+) -> io::Result<Summary>
+where
+    R: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    D: TryFrom<R>,
+    D::Error: Into<BoxError>,
+{
+    let mut summary = Summary::default();
+    let mut line = Vec::new();
+    let mut output = Vec::new();
+
+    for number in 1.. {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        if line.trim_ascii().is_empty() {
+            continue;
+        }
+
+        output.clear();
+        match migrate_line::<R, D>(&line, &mut output) {
+            Ok(()) => {
+                output.push(b'\n');
+                writer.write_all(&output)?;
+                summary.written += 1;
+            }
+            Err(err) => {
+                on_error(number, err);
+                summary.failed += 1;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(summary)
+}
+
+fn migrate_line<R, D>(line: &[u8], output: &mut Vec<u8>) -> Result<(), LineError>
+where
+    R: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    D: TryFrom<R>,
+    D::Error: Into<BoxError>,
+{
+    let rep: R = serde_json::from_slice(line).map_err(LineError::Json)?;
+    let domain = D::try_from(rep).map_err(|err| LineError::Migration(err.into()))?;
+    serde_json::to_writer(output, &R::from(&domain)).map_err(LineError::Json)
+}
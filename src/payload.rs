@@ -0,0 +1,245 @@
+//! Buffered, format-agnostic version payloads.
+
+use crate::error::{VersionedError, version_of_tag};
+use crate::{Representation, UnsupportedVersion};
+use serde::de::{DeserializeOwned, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_value::{Value, ValueDeserializer};
+use std::fmt;
+
+/// The body of a versioned document, buffered independently of the serialization format.
+///
+/// A `Payload` is everything in a document except its version tag. It is handed to custom
+/// handlers (such as `on_unknown`) so that they can decode the document as whichever type
+/// they choose.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payload(Value);
+
+impl Payload {
+    /// Buffer the serialized form of `value` as a payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` cannot be serialized.
+    pub fn from_serialize<T: Serialize + ?Sized>(value: &T) -> Result<Self, SerializeError> {
+        serde_value::to_value(value)
+            .map(Self)
+            .map_err(SerializeError)
+    }
+
+    /// Deserialize the payload as `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload does not match the shape of `T`.
+    pub fn deserialize<T: DeserializeOwned>(self) -> Result<T, Error> {
+        T::deserialize(ValueDeserializer::new(self.0)).map_err(Error)
+    }
+
+    /// Convert the payload into a JSON value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload contains data JSON cannot represent, such as maps
+    /// with non-string keys.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::to_value(&self.0)
+    }
+
+    /// Deserialize the payload, reporting failures through the caller's error type.
+    pub(crate) fn deserialize_as<'de, T, E>(self) -> Result<T, E>
+    where
+        T: Deserialize<'de>,
+        E: serde::de::Error,
+    {
+        T::deserialize(ValueDeserializer::<E>::new(self.0))
+    }
+
+    /// The payload as a deserializer reporting failures through the caller's error type.
+    pub(crate) fn into_deserializer<E: serde::de::Error>(self) -> ValueDeserializer<E> {
+        ValueDeserializer::new(self.0)
+    }
+
+    /// Deserialize the payload, rejecting top-level keys that `T` does not declare.
+    ///
+    /// The declared keys, aliases included, are those `T` passes to `deserialize_struct`.
+    /// Types that deserialize any other way, such as structs with flattened fields, are not
+    /// checked.
+    pub(crate) fn deserialize_strict<'de, T, E>(self) -> Result<T, E>
+    where
+        T: Deserialize<'de>,
+        E: serde::de::Error,
+    {
+        if let (Some(fields), Value::Map(map)) = (declared_fields::<T>(), &self.0) {
+            for key in map.keys() {
+                if let Value::String(key) = key {
+                    if !fields.contains(&key.as_str()) {
+                        return Err(E::unknown_field(key, fields));
+                    }
+                }
+            }
+        }
+        self.deserialize_as()
+    }
+
+    /// Remove an entry from the top level of a map payload.
+    pub(crate) fn take<E: serde::de::Error>(&mut self, key: &str) -> Result<Option<Self>, E> {
+        let Value::Map(map) = &mut self.0 else {
+            return Err(E::invalid_type(
+                serde::de::Unexpected::Other("non-map payload"),
+                &"a map containing a version tag",
+            ));
+        };
+
+        Ok(map.remove(&Value::String(key.to_owned())).map(Self))
+    }
+
+    /// Remove a string-valued entry from the top level of a map payload.
+    pub(crate) fn take_str<E: serde::de::Error>(&mut self, key: &str) -> Result<Option<String>, E> {
+        match self.take(key)? {
+            None => Ok(None),
+            Some(Self(Value::String(tag))) => Ok(Some(tag)),
+            Some(_) => Err(E::custom(format_args!("`{key}` must be a string"))),
+        }
+    }
+}
+
+impl Serialize for Payload {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Payload {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Value::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Read a payload written with `tag`, as stored apart from it in a database column or
+/// message header.
+///
+/// A tag naming no version of `R` is reported as [`VersionedError::UnknownTag`] (or as
+/// [`VersionedError::UnsupportedVersion`], for a number out of range), and a payload that does
+/// not match its version as [`VersionedError::Payload`]. Aliases are not matched.
+///
+/// # Errors
+///
+/// Returns an error if `tag` is not the tag of a supported version, or the payload does not
+/// match it.
+pub fn read_payload<R: Representation>(tag: &str, payload: Payload) -> Result<R, VersionedError> {
+    let version = version_of_tag::<R>(tag)?;
+    R::from_payload(version, payload)
+        .ok_or(UnsupportedVersion {
+            found: version,
+            min: R::MIN_SUPPORTED,
+            max: R::CURRENT,
+        })?
+        .map_err(|err| VersionedError::payload(Some(version), err))
+}
+
+/// Read the JSON document `json` as `R`, through its `Deserialize` impl, reporting failures
+/// by category.
+///
+/// Serde reports every failure as a message, so a failure is categorised by the document's
+/// tag: a tag naming no version of `R` is reported as [`VersionedError::UnknownTag`] or
+/// [`VersionedError::UnsupportedVersion`], and any other failure as
+/// [`VersionedError::Payload`], with the version the tag names. Aliases are not matched, so a
+/// document tagged with an alias that fails to read is reported as an unknown tag.
+///
+/// # Errors
+///
+/// Returns an error if the document cannot be read as `R`.
+#[cfg(feature = "json")]
+pub fn read_json<'a, R>(json: &'a str) -> Result<R, VersionedError>
+where
+    R: Representation + Deserialize<'a>,
+{
+    serde_json::from_str(json).map_err(|err| categorise(err, crate::peek_version::<R>(json)))
+}
+
+/// Read the JSON document `json` as `R`, as [`read_json`] does.
+///
+/// # Errors
+///
+/// Returns an error if the document cannot be read as `R`.
+#[cfg(feature = "json")]
+pub fn read_json_slice<'a, R>(json: &'a [u8]) -> Result<R, VersionedError>
+where
+    R: Representation + Deserialize<'a>,
+{
+    serde_json::from_slice(json)
+        .map_err(|err| categorise(err, crate::peek_version_slice::<R>(json)))
+}
+
+/// Categorise a failure to read a document by the version its tag names.
+#[cfg(feature = "json")]
+fn categorise(err: serde_json::Error, tag: Result<u32, VersionedError>) -> VersionedError {
+    match tag {
+        Ok(version) => VersionedError::payload(Some(version), err),
+        Err(err @ (VersionedError::UnknownTag { .. } | VersionedError::UnsupportedVersion(_))) => {
+            err
+        }
+        // Without a readable tag, the deserializer's own message says more.
+        Err(_) => VersionedError::payload(None, err),
+    }
+}
+
+/// The field names `T` declares, if it deserializes as a struct.
+fn declared_fields<'de, T: Deserialize<'de>>() -> Option<&'static [&'static str]> {
+    let mut fields = None;
+    let _ = T::deserialize(FieldProbe(&mut fields));
+    fields
+}
+
+/// A deserializer that records the fields of the struct asked of it, and fails.
+struct FieldProbe<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de> Deserializer<'de> for FieldProbe<'_> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(fields);
+        Err(serde::de::Error::custom("fields recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+/// Error returned when a [`Payload`] cannot be deserialized as the requested type.
+#[derive(Debug)]
+pub struct Error(serde_value::DeserializerError);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Error returned when a value cannot be buffered as a [`Payload`].
+#[derive(Debug)]
+pub struct SerializeError(serde_value::SerializerError);
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for SerializeError {}
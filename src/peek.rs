@@ -0,0 +1,342 @@
+//! Reading the version of a JSON document without deserializing its payload.
+//!
+//! Routers and partitioners often need only the version of a document. [`peek_version`]
+//! scans the top-level keys for the version tag, skipping over other values without
+//! building them, and stops as soon as the tag has been read:
+//!
+//! ```rust,ignore
+//! let version = serde_evolve::peek_version::<UserVersions>(&body)?;
+//! queues[version as usize].push(body);
+//! ```
+//!
+//! The tag is resolved as the representation reads it, so `as "..."` overrides are mapped to
+//! their version numbers. Aliases are not matched.
+//!
+//! [`peek_version_reader`] probes a document on an `io::Read` instead, for documents too
+//! large to load: it reads up to the tag and no further, without buffering or allocating.
+
+use crate::Representation;
+use crate::error::{VersionedError, version_of_tag};
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
+use std::fmt;
+use std::io::{self, Read};
+use std::marker::PhantomData;
+
+/// The version of the JSON document `json`, read from its tag alone.
+///
+/// # Errors
+///
+/// Returns an error if the document is not a JSON object with a version tag before any
+/// malformed input, or the tag names no version of `R`.
+pub fn peek_version<R: Representation>(json: &str) -> Result<u32, VersionedError> {
+    probe::<R, _>(&mut serde_json::Deserializer::from_str(json))
+}
+
+/// The version of the JSON document `json`, read from its tag alone.
+///
+/// # Errors
+///
+/// Returns an error if the document is not a JSON object with a version tag before any
+/// malformed input, or the tag names no version of `R`.
+pub fn peek_version_slice<R: Representation>(json: &[u8]) -> Result<u32, VersionedError> {
+    probe::<R, _>(&mut serde_json::Deserializer::from_slice(json))
+}
+
+/// The version of the JSON document read from `reader`, read from its tag alone.
+///
+/// Reads byte by byte up to the end of the tag, leaving the rest of the document unread, so
+/// wrap unbuffered sources such as files in a `BufReader`. Values before the tag are skipped
+/// without being checked, and tags longer than 64 bytes are rejected.
+///
+/// # Errors
+///
+/// Returns an error if reading fails, the document is not a JSON object with a version tag,
+/// or the tag names no version of `R`.
+pub fn peek_version_reader<R: Representation>(reader: impl Read) -> Result<u32, VersionedError> {
+    let tag = R::TAG.ok_or_else(|| malformed("the representation has no version tag"))?;
+    let mut scanner = Scanner {
+        reader,
+        peeked: None,
+    };
+    if scanner.next_token()? != b'{' {
+        return Err(malformed("expected a JSON object"));
+    }
+    let mut first = true;
+    loop {
+        match scanner.next_token()? {
+            b'}' if first => break,
+            b'"' => {}
+            _ => return Err(malformed("expected a field name")),
+        }
+        first = false;
+        let is_tag = scanner.key_is(tag.as_bytes())?;
+        if scanner.next_token()? != b':' {
+            return Err(malformed("expected `:` after a field name"));
+        }
+        if is_tag {
+            let mut buf = TagBuf::new();
+            if scanner.next_token()? != b'"' {
+                return Err(malformed("expected a string version tag"));
+            }
+            scanner.read_string(|byte| buf.push(byte))?;
+            return version_of_tag::<R>(buf.as_str()?);
+        }
+        let first_byte = scanner.next_token()?;
+        scanner.skip_value(first_byte)?;
+        match scanner.next_token()? {
+            b',' => {}
+            b'}' => break,
+            _ => return Err(malformed("expected `,` or `}` after a field")),
+        }
+    }
+    Err(malformed(format!("document has no version tag `{tag}`")))
+}
+
+fn malformed(message: impl Into<String>) -> VersionedError {
+    VersionedError::Payload {
+        version: None,
+        message: message.into(),
+    }
+}
+
+/// A byte-at-a-time JSON scanner, with one byte of lookahead.
+struct Scanner<Rd> {
+    reader: Rd,
+    peeked: Option<u8>,
+}
+
+impl<Rd: Read> Scanner<Rd> {
+    fn next_byte(&mut self) -> Result<u8, VersionedError> {
+        if let Some(byte) = self.peeked.take() {
+            return Ok(byte);
+        }
+        let mut byte = [0];
+        loop {
+            match self.reader.read(&mut byte) {
+                Ok(0) => return Err(malformed("unexpected end of document")),
+                Ok(_) => return Ok(byte[0]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(malformed(err.to_string())),
+            }
+        }
+    }
+
+    /// The next byte that is not whitespace.
+    fn next_token(&mut self) -> Result<u8, VersionedError> {
+        loop {
+            let byte = self.next_byte()?;
+            if !byte.is_ascii_whitespace() {
+                return Ok(byte);
+            }
+        }
+    }
+
+    /// Read the rest of a string after its opening quote, passing each unescaped byte to
+    /// `push`.
+    fn read_string(&mut self, mut push: impl FnMut(u8)) -> Result<(), VersionedError> {
+        loop {
+            match self.next_byte()? {
+                b'"' => return Ok(()),
+                b'\\' => {
+                    let unescaped = match self.next_byte()? {
+                        b'n' => '\n',
+                        b't' => '\t',
+                        b'r' => '\r',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'u' => {
+                            let mut code = 0;
+                            for _ in 0..4 {
+                                let digit = char::from(self.next_byte()?)
+                                    .to_digit(16)
+                                    .ok_or_else(|| malformed("invalid unicode escape"))?;
+                                code = code * 16 + digit;
+                            }
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        byte => char::from(byte),
+                    };
+                    let mut utf8 = [0; 4];
+                    unescaped.encode_utf8(&mut utf8).bytes().for_each(&mut push);
+                }
+                byte => push(byte),
+            }
+        }
+    }
+
+    /// Read the rest of a number or literal, passing each byte to `push`, and leave the
+    /// delimiter after it to be read next.
+    fn read_scalar(&mut self, mut push: impl FnMut(u8)) -> Result<(), VersionedError> {
+        loop {
+            let byte = self.next_byte()?;
+            if matches!(byte, b',' | b'}' | b']') || byte.is_ascii_whitespace() {
+                self.peeked = Some(byte);
+                return Ok(());
+            }
+            push(byte);
+        }
+    }
+
+    /// Whether the rest of a field name after its opening quote is `tag`.
+    fn key_is(&mut self, tag: &[u8]) -> Result<bool, VersionedError> {
+        let mut matched = Some(0);
+        self.read_string(|byte| {
+            matched = matched
+                .filter(|&len| tag.get(len) == Some(&byte))
+                .map(|len| len + 1);
+        })?;
+        Ok(matched == Some(tag.len()))
+    }
+
+    /// Skip the value starting with `first`, without checking it.
+    fn skip_value(&mut self, first: u8) -> Result<(), VersionedError> {
+        match first {
+            b'"' => self.read_string(|_| {}),
+            b'{' | b'[' => {
+                let mut depth = 1_usize;
+                while depth > 0 {
+                    match self.next_byte()? {
+                        b'"' => self.read_string(|_| {})?,
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => depth -= 1,
+                        _ => {}
+                    }
+                }
+                Ok(())
+            }
+            _ => self.read_scalar(|_| {}),
+        }
+    }
+}
+
+/// A version tag, buffered on the stack.
+struct TagBuf {
+    bytes: [u8; 64],
+    len: usize,
+    overflowed: bool,
+}
+
+impl TagBuf {
+    const fn new() -> Self {
+        Self {
+            bytes: [0; 64],
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    fn push(&mut self, byte: u8) {
+        match self.bytes.get_mut(self.len) {
+            Some(slot) => {
+                *slot = byte;
+                self.len += 1;
+            }
+            None => self.overflowed = true,
+        }
+    }
+
+    fn as_str(&self) -> Result<&str, VersionedError> {
+        if self.overflowed {
+            return Err(malformed("version tag is longer than 64 bytes"));
+        }
+        std::str::from_utf8(&self.bytes[..self.len])
+            .map_err(|_| malformed("version tag is not valid UTF-8"))
+    }
+}
+
+/// Scan a document for the tag of `R`, stopping once it has been read.
+fn probe<'de, R, D>(deserializer: D) -> Result<u32, VersionedError>
+where
+    R: Representation,
+    D: Deserializer<'de>,
+{
+    let missing = |message: String| VersionedError::Payload {
+        version: None,
+        message,
+    };
+    let tag = R::TAG.ok_or_else(|| missing("the representation has no version tag".into()))?;
+
+    let mut found = None;
+    let scanned = deserializer.deserialize_map(Probe::<R> {
+        tag,
+        found: &mut found,
+        rep: PhantomData,
+    });
+    // The scan is abandoned with an error once the tag is read.
+    match (found, scanned) {
+        (Some(version), _) => version,
+        (None, Err(err)) => Err(missing(err.to_string())),
+        (None, Ok(())) => Err(missing(format!("document has no version tag `{tag}`"))),
+    }
+}
+
+struct Probe<'a, R> {
+    tag: &'static str,
+    found: &'a mut Option<Result<u32, VersionedError>>,
+    rep: PhantomData<fn() -> R>,
+}
+
+impl<'de, R: Representation> Visitor<'de> for Probe<'_, R> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a versioned document")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(is_tag) = map.next_key_seed(KeyIs(self.tag))? {
+            if is_tag {
+                *self.found = Some(map.next_value_seed(TagValue::<R>(PhantomData))?);
+                return Err(de::Error::custom("version tag found"));
+            }
+            map.next_value::<IgnoredAny>()?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a map key is the given field, compared without allocating.
+pub(crate) struct KeyIs<'a>(pub(crate) &'a str);
+
+impl<'de> DeserializeSeed<'de> for KeyIs<'_> {
+    type Value = bool;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<bool, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl Visitor<'_> for KeyIs<'_> {
+    type Value = bool;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a field name")
+    }
+
+    fn visit_str<E: de::Error>(self, key: &str) -> Result<bool, E> {
+        Ok(key == self.0)
+    }
+}
+
+/// The version a tag value names.
+struct TagValue<R>(PhantomData<fn() -> R>);
+
+impl<'de, R: Representation> DeserializeSeed<'de> for TagValue<R> {
+    type Value = Result<u32, VersionedError>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<R: Representation> Visitor<'_> for TagValue<R> {
+    type Value = Result<u32, VersionedError>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string version tag")
+    }
+
+    fn visit_str<E: de::Error>(self, tag: &str) -> Result<Self::Value, E> {
+        Ok(version_of_tag::<R>(tag))
+    }
+}
@@ -0,0 +1,73 @@
+//! Serializing domain values at a version chosen by the caller.
+//!
+//! An API server honoring an `Accept-Version` header has to write each response at the
+//! requested version. With `downgrade = true`, the domain type implements [`Downgrade`], and
+//! the helpers here step it down and encode it in one call:
+//!
+//! ```rust,ignore
+//! use serde_evolve::pinned;
+//!
+//! let body = pinned::to_version_string(&order, requested_version)?;
+//! ```
+
+use crate::{Downgrade, Format, UnsupportedVersion};
+use serde::Serialize;
+use std::fmt;
+
+/// Error returned when a value cannot be written at the requested version.
+#[derive(Debug)]
+pub enum Error<E> {
+    /// The requested version is not part of the chain.
+    Unsupported(UnsupportedVersion),
+    /// The value could not be encoded.
+    Format(E),
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(err) => err.fmt(f),
+            Self::Format(err) => write!(f, "format error: {err}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Unsupported(err) => Some(err),
+            Self::Format(err) => Some(err),
+        }
+    }
+}
+
+/// Encode `domain` with `format`, at `version`.
+///
+/// # Errors
+///
+/// Returns an error if `version` is not part of the chain, or the value cannot be encoded.
+pub fn encode<D, F>(domain: &D, version: u32, format: &F) -> Result<Vec<u8>, Error<F::Error>>
+where
+    D: Downgrade,
+    D::Representation: Serialize,
+    F: Format,
+{
+    let rep = domain.at_version(version).map_err(Error::Unsupported)?;
+    format.encode(&rep).map_err(Error::Format)
+}
+
+/// Serialize `domain` to a JSON string, at `version`.
+///
+/// # Errors
+///
+/// Returns an error if `version` is not part of the chain, or the value cannot be
+/// serialized.
+#[cfg(feature = "json")]
+pub fn to_version_string<D>(domain: &D, version: u32) -> Result<String, Error<serde_json::Error>>
+where
+    D: Downgrade,
+    D::Representation: Serialize,
+{
+    let rep = domain.at_version(version).map_err(Error::Unsupported)?;
+    serde_json::to_string(&rep).map_err(Error::Format)
+}
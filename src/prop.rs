@@ -0,0 +1,73 @@
+//! Property testing of migration chains with `proptest`.
+//!
+//! With `arbitrary = true`, the derive implements [`Arbitrary`] for the representation enum,
+//! drawing from each version DTO's own `Arbitrary` impl. [`check_chain`] then migrates
+//! generated values of every version:
+//!
+//! ```rust,ignore
+//! #[test]
+//! fn product_chain() {
+//!     serde_evolve::prop::check_chain::<ProductVersions, Product>();
+//! }
+//! ```
+
+use crate::Representation;
+use proptest::arbitrary::{Arbitrary, any};
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+
+/// Check the migration chain of `R` into the domain type `D` against generated values.
+///
+/// For each generated representation, of any version:
+///
+/// - representations at the current version must survive a serde round trip unchanged;
+/// - migrating to `D` must not panic;
+/// - a successfully migrated value must convert back to the current version.
+///
+/// Migration errors are not failures, since fallible steps may reject generated values. The
+/// number of cases is configured through proptest's environment variables (such as
+/// `PROPTEST_CASES`).
+///
+/// # Panics
+///
+/// Panics with the minimal failing input if any check fails.
+pub fn check_chain<R, D>()
+where
+    R: Arbitrary + Representation + Serialize + DeserializeOwned + Debug + for<'a> From<&'a D>,
+    D: TryFrom<R>,
+{
+    let config = Config {
+        failure_persistence: None,
+        ..Config::default()
+    };
+    let result = TestRunner::new(config).run(&any::<R>(), |rep| check::<R, D>(rep));
+    if let Err(err) = result {
+        panic!("migration chain check failed: {err}");
+    }
+}
+
+fn check<R, D>(rep: R) -> Result<(), TestCaseError>
+where
+    R: Representation + Serialize + DeserializeOwned + for<'a> From<&'a D>,
+    D: TryFrom<R>,
+{
+    if rep.version() == R::CURRENT {
+        crate::__private::testing::round_trip(&rep).map_err(TestCaseError::fail)?;
+    }
+
+    let from = rep.version();
+    let Ok(domain) = D::try_from(rep) else {
+        return Ok(());
+    };
+    let latest = R::from(&domain);
+    if latest.version() != R::CURRENT {
+        return Err(TestCaseError::fail(format!(
+            "version {from} migrated to version {} rather than {}",
+            latest.version(),
+            R::CURRENT
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,134 @@
+//! Provenance metadata recorded alongside the version tag.
+//!
+//! [`Stamped`] flattens a [`Provenance`] record into the tagged document of a representation,
+//! so audit fields travel with the data without a hand-written wrapper:
+//!
+//! ```rust,ignore
+//! use serde_evolve::{Provenance, Stamped};
+//!
+//! let stamped = Stamped::wrap(&user, Provenance::new("svc-a", "user"));
+//! // {"_producer":"svc-a","_schema":"user","_created_at":1760486400,"_version":"2",...}
+//!
+//! let stamped: Stamped<UserVersions> = serde_json::from_str(&json)?;
+//! assert_eq!(stamped.producer(), "svc-a");
+//! let user: User = stamped.into_inner().try_into()?;
+//! ```
+//!
+//! The provenance keys are consumed before the representation sees the document, so version
+//! DTOs with `#[serde(deny_unknown_fields)]` are unaffected.
+
+use crate::{Representation, Versioned};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Who wrote a document, under which schema, and when.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// The service or tool that wrote the document.
+    #[serde(rename = "_producer")]
+    pub producer: String,
+    /// The name of the schema the document belongs to.
+    #[serde(rename = "_schema")]
+    pub schema: String,
+    /// When the document was written, in seconds since the Unix epoch.
+    #[serde(rename = "_created_at")]
+    pub created_at: u64,
+}
+
+impl Provenance {
+    /// Record provenance for a document written now.
+    pub fn new(producer: impl Into<String>, schema: impl Into<String>) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self {
+            producer: producer.into(),
+            schema: schema.into(),
+            created_at,
+        }
+    }
+
+    /// Override the creation timestamp, in seconds since the Unix epoch.
+    #[must_use]
+    pub const fn with_created_at(mut self, created_at: u64) -> Self {
+        self.created_at = created_at;
+        self
+    }
+}
+
+/// A representation, serialized together with its [`Provenance`].
+///
+/// Dereferences to the representation, so its version accessors remain available.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stamped<R> {
+    #[serde(flatten)]
+    provenance: Provenance,
+    #[serde(flatten)]
+    rep: R,
+}
+
+impl<R: Representation> Stamped<R> {
+    /// Attach provenance to a representation.
+    pub const fn new(rep: R, provenance: Provenance) -> Self {
+        Self { provenance, rep }
+    }
+
+    /// The service or tool that wrote the document.
+    #[must_use]
+    pub fn producer(&self) -> &str {
+        &self.provenance.producer
+    }
+
+    /// The name of the schema the document belongs to.
+    #[must_use]
+    pub fn schema(&self) -> &str {
+        &self.provenance.schema
+    }
+
+    /// When the document was written, in seconds since the Unix epoch.
+    #[must_use]
+    pub const fn created_at(&self) -> u64 {
+        self.provenance.created_at
+    }
+
+    /// The provenance record.
+    #[must_use]
+    pub const fn provenance(&self) -> &Provenance {
+        &self.provenance
+    }
+
+    /// Discard the provenance, and return the representation.
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.rep
+    }
+
+    /// Split into the representation and its provenance.
+    #[must_use]
+    pub fn into_parts(self) -> (R, Provenance) {
+        (self.rep, self.provenance)
+    }
+}
+
+impl<R> Stamped<R> {
+    /// Stamp a domain value at the latest version.
+    pub fn wrap<D>(domain: &D, provenance: Provenance) -> Self
+    where
+        D: Versioned<Representation = R>,
+        for<'a> R: From<&'a D>,
+    {
+        Self {
+            provenance,
+            rep: R::from(domain),
+        }
+    }
+}
+
+impl<R> Deref for Stamped<R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        &self.rep
+    }
+}
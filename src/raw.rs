@@ -0,0 +1,201 @@
+//! Documents held as raw JSON, to inspect and repair before they are migrated.
+//!
+//! Cleanup jobs often need to fix known-bad historical records that no version DTO would
+//! accept. [`RawVersioned`] splits a tagged document into its version and payload, lets the
+//! payload be read and patched as a `serde_json::Value`, and then runs it through the chain:
+//!
+//! ```rust,ignore
+//! use serde_evolve::raw::RawVersioned;
+//!
+//! let mut raw: RawVersioned<User> = serde_json::from_str(stored)?;
+//! if raw.version() == 1 && raw.payload()["email"] == "" {
+//!     raw.payload_mut()["email"] = serde_json::Value::Null;
+//! }
+//! let user = raw.migrate()?;
+//! ```
+//!
+//! Documents must be internally tagged (the default `tag_style`), with the tag written as
+//! the derive writes it.
+
+use crate::error::{VersionedError, version_of_tag};
+use crate::payload::{Payload, read_payload};
+use crate::{Representation, UnsupportedVersion, Versioned};
+use serde::de::Error as _;
+use serde_json::Value;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A version number and the raw payload of that version, not yet read as its DTO.
+pub struct RawVersioned<T> {
+    version: u32,
+    payload: Value,
+    domain: PhantomData<fn() -> T>,
+}
+
+impl<T: Versioned> RawVersioned<T> {
+    /// Hold a payload of the given version.
+    #[must_use]
+    pub const fn new(version: u32, payload: Value) -> Self {
+        Self {
+            version,
+            payload,
+            domain: PhantomData,
+        }
+    }
+
+    /// Split a tagged document into its version and payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is not an object with a version tag, or the tag
+    /// names no version of the chain.
+    pub fn from_document(document: Value) -> Result<Self, VersionedError> {
+        let missing = || VersionedError::Payload {
+            version: None,
+            message: "document has no version tag".to_string(),
+        };
+        let tag_field = <T::Representation as Representation>::TAG.ok_or_else(missing)?;
+        let Value::Object(mut payload) = document else {
+            return Err(missing());
+        };
+        let tag = match payload.remove(tag_field) {
+            Some(Value::String(tag)) => tag,
+            Some(Value::Number(number)) => number.to_string(),
+            _ => return Err(missing()),
+        };
+        let version = version_of_tag::<T::Representation>(&tag)?;
+        Ok(Self::new(version, Value::Object(payload)))
+    }
+
+    /// The version of the payload.
+    #[must_use]
+    pub const fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The payload, without its version tag.
+    #[must_use]
+    pub const fn payload(&self) -> &Value {
+        &self.payload
+    }
+
+    /// The payload, to patch in place.
+    pub const fn payload_mut(&mut self) -> &mut Value {
+        &mut self.payload
+    }
+
+    /// Patch the payload with `patch`.
+    #[must_use]
+    pub fn patch(mut self, patch: impl FnOnce(&mut Value)) -> Self {
+        patch(&mut self.payload);
+        self
+    }
+
+    /// Treat the payload as a different version, for records stored with the wrong tag.
+    #[must_use]
+    pub const fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// The document with its version tag restored, to write the repaired record back
+    /// without migrating it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version is not part of the chain, or the payload is not an
+    /// object.
+    pub fn to_document(&self) -> Result<Value, VersionedError> {
+        let tag = self.tag()?;
+        let (Some(tag_field), Value::Object(payload)) =
+            (<T::Representation as Representation>::TAG, &self.payload)
+        else {
+            return Err(VersionedError::Payload {
+                version: Some(self.version),
+                message: "payload is not an object".to_string(),
+            });
+        };
+        let mut document = payload.clone();
+        document.insert(tag_field.to_string(), Value::String(tag.to_string()));
+        Ok(Value::Object(document))
+    }
+
+    /// Read the payload as the version DTO.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version is not part of the chain, or the payload does not
+    /// match it.
+    pub fn into_representation(self) -> Result<T::Representation, VersionedError> {
+        let tag = self.tag()?;
+        let payload =
+            Payload::from_serialize(&self.payload).map_err(|err| VersionedError::Payload {
+                version: Some(self.version),
+                message: err.to_string(),
+            })?;
+        read_payload(tag, payload)
+    }
+
+    /// Read the payload, and migrate it to the domain type.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload cannot be read as its version, or a migration step
+    /// fails.
+    pub fn migrate(self) -> Result<T, VersionedError>
+    where
+        T: TryFrom<T::Representation>,
+        T::Error: fmt::Display,
+    {
+        let source_version = self.version;
+        let rep = self.into_representation()?;
+        T::try_from(rep).map_err(|err| VersionedError::Migration {
+            source_version: Some(source_version),
+            step: None,
+            message: err.to_string(),
+        })
+    }
+
+    /// The tag the payload's version is written with.
+    fn tag(&self) -> Result<&'static str, VersionedError> {
+        <T::Representation as Representation>::tag_of_version(self.version).ok_or(
+            VersionedError::UnsupportedVersion(UnsupportedVersion {
+                found: self.version,
+                min: <T::Representation as Representation>::MIN_SUPPORTED,
+                max: <T::Representation as Representation>::CURRENT,
+            }),
+        )
+    }
+}
+
+impl<T> Clone for RawVersioned<T> {
+    fn clone(&self) -> Self {
+        Self {
+            version: self.version,
+            payload: self.payload.clone(),
+            domain: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for RawVersioned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawVersioned")
+            .field("version", &self.version)
+            .field("payload", &self.payload)
+            .finish()
+    }
+}
+
+impl<T> PartialEq for RawVersioned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version && self.payload == other.payload
+    }
+}
+
+impl<'de, T: Versioned> serde::Deserialize<'de> for RawVersioned<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let document = <Value as serde::Deserialize>::deserialize(deserializer)?;
+        Self::from_document(document).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,267 @@
+//! Type-erased migration of JSON documents by type name.
+//!
+//! A [`Registry`] maps names to versioned types, so that storage layers holding documents of
+//! many types can migrate any of them through one code path:
+//!
+//! ```rust,ignore
+//! let mut registry = Registry::new();
+//! registry
+//!     .register::<UserVersions, User>("user")
+//!     .register::<ProductVersions, Product>("product");
+//!
+//! let latest = registry.migrate_json("user", document)?;
+//! ```
+//!
+//! With the `inventory` feature, types can instead register themselves with
+//! `#[versioned(register = "user")]`, and [`migrate_json`] dispatches through every such
+//! registration in the program.
+//!
+//! Documents that carry their type name in a [`TYPE_FIELD`] next to the version tag (as
+//! written by [`AnyVersioned::to_typed_json`]) can be read without knowing their type:
+//!
+//! ```rust,ignore
+//! let event: Box<dyn AnyVersioned> = registry.deserialize_any_versioned(row)?;
+//! let user: User = registry.deserialize_versioned(other_row)?;
+//! ```
+
+use crate::Versioned;
+use crate::any::{AnyVersioned, TYPE_FIELD};
+use crate::migrate::BoxError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Error returned when a document cannot be migrated through a [`Registry`].
+#[derive(Debug)]
+pub enum Error {
+    /// No type is registered under the name.
+    UnknownType(String),
+    /// The document does not match any version of the type.
+    Json(serde_json::Error),
+    /// A migration step rejected the document.
+    Migration(BoxError),
+    /// The document is of the named type, rather than the one requested.
+    UnexpectedType(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownType(name) => write!(f, "no versioned type is registered as '{name}'"),
+            Self::Json(err) => write!(f, "JSON error: {err}"),
+            Self::Migration(err) => write!(f, "migration failed: {err}"),
+            Self::UnexpectedType(name) => {
+                write!(f, "document is a '{name}', not the requested type")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::UnknownType(_) | Self::UnexpectedType(_) => None,
+            Self::Json(err) => Some(err),
+            Self::Migration(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+type MigrateFn = fn(Value) -> Result<Value, Error>;
+type DecodeFn = fn(Value) -> Result<Box<dyn AnyVersioned>, Error>;
+
+/// A versioned type under a name: its representation `R` and domain type `D`.
+#[derive(Debug, Clone, Copy)]
+pub struct Registration {
+    name: &'static str,
+    migrate: MigrateFn,
+    decode: DecodeFn,
+}
+
+impl Registration {
+    /// Register the representation `R` of the domain type `D` as `name`.
+    #[must_use]
+    pub const fn new<R, D>(name: &'static str) -> Self
+    where
+        R: AnyVersioned + DeserializeOwned + Serialize + for<'a> From<&'a D>,
+        D: TryFrom<R>,
+        D::Error: Into<BoxError>,
+    {
+        Self {
+            name,
+            migrate: migrate::<R, D>,
+            decode: decode::<R>,
+        }
+    }
+
+    /// The name the type is registered as.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+#[cfg(feature = "inventory")]
+inventory::collect!(Registration);
+
+/// Versioned types by name.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    types: HashMap<&'static str, Registration>,
+}
+
+impl Registry {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry of every type registered with `#[versioned(register = "...")]`.
+    #[cfg(feature = "inventory")]
+    #[must_use]
+    pub fn collected() -> Self {
+        let mut registry = Self::new();
+        for registration in inventory::iter::<Registration> {
+            registry.add(*registration);
+        }
+        registry
+    }
+
+    /// Register the representation `R` of the domain type `D` as `name`, replacing any type
+    /// previously registered under that name.
+    pub fn register<R, D>(&mut self, name: &'static str) -> &mut Self
+    where
+        R: AnyVersioned + DeserializeOwned + Serialize + for<'a> From<&'a D>,
+        D: TryFrom<R>,
+        D::Error: Into<BoxError>,
+    {
+        self.add(Registration::new::<R, D>(name))
+    }
+
+    /// Add a registration, replacing any type previously registered under its name.
+    pub fn add(&mut self, registration: Registration) -> &mut Self {
+        self.types.insert(registration.name, registration);
+        self
+    }
+
+    /// The registered type names, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.types.keys().copied()
+    }
+
+    /// Migrate a JSON document of the type registered as `type_name` to its current version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no type is registered as `type_name`, or the document cannot be
+    /// deserialized or migrated.
+    pub fn migrate_json(&self, type_name: &str, value: Value) -> Result<Value, Error> {
+        (self.get(type_name)?.migrate)(value)
+    }
+
+    /// Deserialize a document tagged with its type name in [`TYPE_FIELD`], at its own version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document has no type name, no type is registered under it, or
+    /// the rest of the document cannot be deserialized.
+    pub fn deserialize_any_versioned(&self, value: Value) -> Result<Box<dyn AnyVersioned>, Error> {
+        let (type_name, value) = split_type(value)?;
+        (self.get(&type_name)?.decode)(value)
+    }
+
+    /// Deserialize a document tagged with its type name in [`TYPE_FIELD`], and migrate it to
+    /// the domain type `D`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document cannot be deserialized, is of a type other than `D`,
+    /// or a migration step rejects it.
+    pub fn deserialize_versioned<D>(&self, value: Value) -> Result<D, Error>
+    where
+        D: Versioned + TryFrom<D::Representation>,
+        D::Representation: AnyVersioned,
+        D::Error: Into<BoxError>,
+    {
+        let rep = self
+            .deserialize_any_versioned(value)?
+            .downcast::<D::Representation>()
+            .map_err(|other| Error::UnexpectedType(other.type_name().to_owned()))?;
+        D::try_from(*rep).map_err(|err| Error::Migration(err.into()))
+    }
+
+    fn get(&self, type_name: &str) -> Result<&Registration, Error> {
+        self.types
+            .get(type_name)
+            .ok_or_else(|| Error::UnknownType(type_name.to_owned()))
+    }
+}
+
+/// Migrate a JSON document of a self-registered type to its current version.
+///
+/// Dispatches through [`Registry::collected`], which is built on first use.
+///
+/// # Errors
+///
+/// Returns an error if no type is registered as `type_name`, or the document cannot be
+/// deserialized or migrated.
+#[cfg(feature = "inventory")]
+pub fn migrate_json(type_name: &str, value: Value) -> Result<Value, Error> {
+    collected().migrate_json(type_name, value)
+}
+
+/// Deserialize a document of a self-registered type, tagged with its type name in
+/// [`TYPE_FIELD`].
+///
+/// Dispatches through [`Registry::collected`], which is built on first use.
+///
+/// # Errors
+///
+/// Returns an error if the document has no type name, no type is registered under it, or the
+/// rest of the document cannot be deserialized.
+#[cfg(feature = "inventory")]
+pub fn deserialize_any_versioned(value: Value) -> Result<Box<dyn AnyVersioned>, Error> {
+    collected().deserialize_any_versioned(value)
+}
+
+#[cfg(feature = "inventory")]
+fn collected() -> &'static Registry {
+    static COLLECTED: std::sync::OnceLock<Registry> = std::sync::OnceLock::new();
+    COLLECTED.get_or_init(Registry::collected)
+}
+
+/// Remove the type name from a document.
+fn split_type(mut value: Value) -> Result<(String, Value), Error> {
+    let type_name = value
+        .as_object_mut()
+        .and_then(|document| document.remove(TYPE_FIELD));
+    match type_name {
+        Some(Value::String(type_name)) => Ok((type_name, value)),
+        Some(_) => Err(Error::Json(serde::de::Error::custom(format!(
+            "'{TYPE_FIELD}' must be a string"
+        )))),
+        None => Err(Error::Json(serde::de::Error::missing_field(TYPE_FIELD))),
+    }
+}
+
+fn decode<R>(value: Value) -> Result<Box<dyn AnyVersioned>, Error>
+where
+    R: AnyVersioned + DeserializeOwned,
+{
+    let rep: R = serde_json::from_value(value).map_err(Error::Json)?;
+    Ok(Box::new(rep))
+}
+
+pub(crate) fn migrate<R, D>(value: Value) -> Result<Value, Error>
+where
+    R: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    D: TryFrom<R>,
+    D::Error: Into<BoxError>,
+{
+    let rep: R = serde_json::from_value(value).map_err(Error::Json)?;
+    let domain = D::try_from(rep).map_err(|err| Error::Migration(err.into()))?;
+    serde_json::to_value(R::from(&domain)).map_err(Error::Json)
+}
@@ -0,0 +1,119 @@
+//! The runtime view of a representation enum.
+//!
+//! `#[derive(Versioned)]` implements [`Representation`] for every representation enum it
+//! generates, so that integrations such as the [`envelope`](crate::envelope) codec can work
+//! with the payload of any version without knowing the concrete enum. It also implements
+//! [`Versioned`] for the domain type, linking it to its representation.
+
+use crate::payload::{self, Payload};
+use crate::{Format, UnsupportedVersion};
+use serde::Serializer;
+
+/// A representation enum: one variant per version in a migration chain.
+pub trait Representation: Sized {
+    /// The current (latest) version number.
+    const CURRENT: u32;
+
+    /// The oldest version number that can still be read.
+    const MIN_SUPPORTED: u32 = 1;
+
+    /// The field holding the version tag, or `None` for untagged representations.
+    const TAG: Option<&'static str>;
+
+    /// Describes each version this build can read, oldest first.
+    const VERSIONS: &'static [VersionInfo];
+
+    /// The version number of this value.
+    fn version(&self) -> u32;
+
+    /// Serialize the version DTO alone, without a version tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the DTO cannot be serialized.
+    fn serialize_payload<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+
+    /// Decode an untagged payload of the given version from bytes.
+    ///
+    /// Returns `None` if `version` is not part of the chain.
+    fn decode_payload<F: Format>(
+        version: u32,
+        bytes: &[u8],
+        format: &F,
+    ) -> Option<Result<Self, F::Error>>;
+
+    /// Decode a buffered, untagged payload of the given version.
+    ///
+    /// Returns `None` if `version` is not part of the chain.
+    fn from_payload(version: u32, payload: Payload) -> Option<Result<Self, payload::Error>>;
+
+    /// The version number serialized with `tag` (its number, or its `as "..."` override).
+    ///
+    /// Returns `None` if no version this build can read is written with `tag`. Aliases are
+    /// not matched.
+    #[must_use]
+    fn version_of_tag(tag: &str) -> Option<u32> {
+        Self::VERSIONS
+            .iter()
+            .find(|info| info.tag == tag)
+            .map(|info| info.version)
+    }
+
+    /// The tag `version` is serialized with.
+    ///
+    /// Returns `None` if this build cannot read `version`.
+    #[must_use]
+    fn tag_of_version(version: u32) -> Option<&'static str> {
+        Self::VERSIONS
+            .iter()
+            .find(|info| info.version == version)
+            .map(|info| info.tag)
+    }
+}
+
+/// A domain type with a versioned representation.
+///
+/// Implemented by `#[derive(Versioned)]`, so that integrations can find the representation
+/// enum of a domain type (as in `axum::VersionedJson<Product>`).
+pub trait Versioned: Sized {
+    /// The representation enum of the migration chain.
+    type Representation: Representation;
+}
+
+/// A domain type that can be written at any version of its chain.
+///
+/// Implemented by `#[derive(Versioned)]` with `downgrade = true`, so that helpers such as
+/// [`pinned::encode`](crate::pinned::encode) can honor a version requested by a client.
+pub trait Downgrade: Versioned {
+    /// The representation of this value at `version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `version` is not part of the chain.
+    fn at_version(&self, version: u32) -> Result<Self::Representation, UnsupportedVersion>;
+}
+
+/// Describes one version of a migration chain.
+///
+/// Generated for every representation enum as its `VERSIONS` table, oldest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VersionInfo {
+    /// The version number.
+    pub version: u32,
+    /// The Rust path of the version DTO, as written in the chain.
+    pub type_name: &'static str,
+    /// The tag the version is serialized with.
+    pub tag: &'static str,
+    /// Whether the version is marked `deprecated`.
+    pub deprecated: bool,
+}
+
+/// Serializes the payload of a representation, without its version tag.
+#[derive(Debug)]
+pub struct PayloadRef<'a, R>(pub &'a R);
+
+impl<R: Representation> serde::Serialize for PayloadRef<'_, R> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_payload(serializer)
+    }
+}
@@ -0,0 +1,61 @@
+//! `reqwest` response bodies in any version.
+//!
+//! [`json`] reads a JSON response body of any historical version and migrates it to the domain
+//! type, so clients of older server deployments receive current-shape values:
+//!
+//! ```rust,ignore
+//! let response = client.get(url).send().await?.error_for_status()?;
+//! let order: Order = serde_evolve::reqwest::json(response).await?;
+//! ```
+
+use crate::Versioned;
+use crate::migrate::BoxError;
+use serde::de::DeserializeOwned;
+use std::fmt;
+
+/// Read the JSON body of `response` as any version of the representation of `T`, and migrate
+/// it to `T`.
+///
+/// # Errors
+///
+/// Returns an error if the body cannot be read, is not valid JSON for any version, or a
+/// migration step rejects it.
+pub async fn json<T>(response: ::reqwest::Response) -> Result<T, Error>
+where
+    T: Versioned + TryFrom<T::Representation>,
+    T::Representation: DeserializeOwned,
+    T::Error: Into<BoxError>,
+{
+    let rep = response
+        .json::<T::Representation>()
+        .await
+        .map_err(Error::Http)?;
+    T::try_from(rep).map_err(|err| Error::Migration(err.into()))
+}
+
+/// Error returned by [`json`].
+#[derive(Debug)]
+pub enum Error {
+    /// The body could not be read, or is not valid JSON for any version.
+    Http(::reqwest::Error),
+    /// The body is a valid document, but a migration step rejected it.
+    Migration(BoxError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(err) => write!(f, "{err}"),
+            Self::Migration(err) => write!(f, "migration failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(err) => Some(err),
+            Self::Migration(err) => Some(err.as_ref()),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Heuristic version detection for documents written without a version tag.
+//!
+//! When importing data that predates versioning, [`classify`] probes each version DTO in
+//! turn and picks the one the document structurally matches: the payload must deserialize
+//! as the DTO, and among the candidates that do, the one that accounts for the most of the
+//! document's top-level keys wins. Ties go to the newest version.
+//!
+//! ```rust,ignore
+//! use serde_evolve::sniff;
+//!
+//! let legacy: serde_json::Value = serde_json::from_str(r#"{"name":"Ada"}"#)?;
+//! let user: User = sniff::migrate(&legacy)?;
+//! ```
+//!
+//! This is a heuristic: DTOs that differ only in optional fields are indistinguishable, so
+//! prefer a tag (or `default_version`) wherever the data allows it.
+
+use crate::Versioned;
+use crate::migrate::BoxError;
+use crate::payload::Payload;
+use crate::representation::{PayloadRef, Representation};
+use serde_json::Value;
+use std::fmt;
+
+/// Error returned by [`migrate`].
+#[derive(Debug)]
+pub enum Error {
+    /// The document matches no version of the chain.
+    Unrecognised,
+    /// A migration step rejected the document.
+    Migration(BoxError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unrecognised => f.write_str("document matches no version of the chain"),
+            Self::Migration(err) => write!(f, "migration failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Unrecognised => None,
+            Self::Migration(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// Classify an untagged document as the version of `R` it best matches.
+///
+/// Returns `None` if the document deserializes as none of the versions.
+#[must_use]
+pub fn classify<R: Representation>(document: &Value) -> Option<R> {
+    let payload = Payload::from_serialize(document).ok()?;
+    let mut best: Option<(usize, R)> = None;
+
+    for version in (R::MIN_SUPPORTED..=R::CURRENT).rev() {
+        let Some(Ok(rep)) = R::from_payload(version, payload.clone()) else {
+            continue;
+        };
+        let score = coverage(document, &rep);
+        if best.as_ref().is_none_or(|(best, _)| score > *best) {
+            best = Some((score, rep));
+        }
+    }
+    best.map(|(_, rep)| rep)
+}
+
+/// Classify an untagged document, and migrate it to the domain type.
+///
+/// # Errors
+///
+/// Returns an error if the document matches no version, or a migration step fails.
+pub fn migrate<D>(document: &Value) -> Result<D, Error>
+where
+    D: Versioned + TryFrom<D::Representation>,
+    D::Error: Into<BoxError>,
+{
+    let rep = classify::<D::Representation>(document).ok_or(Error::Unrecognised)?;
+    D::try_from(rep).map_err(|err| Error::Migration(err.into()))
+}
+
+/// The number of the document's top-level keys that survive a round trip through `rep`.
+fn coverage<R: Representation>(document: &Value, rep: &R) -> usize {
+    let (Value::Object(document), Ok(Value::Object(decoded))) =
+        (document, serde_json::to_value(PayloadRef(rep)))
+    else {
+        return 0;
+    };
+    document
+        .keys()
+        .filter(|key| decoded.contains_key(*key))
+        .count()
+}
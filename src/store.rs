@@ -0,0 +1,374 @@
+//! Key-value storage of versioned values, migrated on read.
+//!
+//! [`EvolveStore`] is a minimal byte-oriented key-value interface (`get`, `put`, `scan`) that
+//! storage backends implement. [`VersionedStore`] layers a domain type and a [`Format`] over
+//! any backend: values are decoded through the representation enum, whatever version they
+//! were written at, and migrated to the domain type. Writes are always at the current
+//! version, and stale values can optionally be written back as they are read:
+//!
+//! ```rust,ignore
+//! use serde_evolve::format::Json;
+//! use serde_evolve::store::{MemoryStore, VersionedStore};
+//!
+//! let users = VersionedStore::<_, User, _>::new(MemoryStore::default(), Json::default())
+//!     .write_back(true);
+//!
+//! users.put(b"user:1", &user)?;
+//! let user: Option<User> = users.get(b"user:1")?;
+//! let rewritten = users.migrate_all(b"user:")?;
+//! ```
+//!
+//! With the `sled` feature, a `sled::Tree` (or `sled::Db`, through its default tree) is an
+//! [`EvolveStore`]. With the `redb` feature, [`RedbStore`] stores values in a table of a
+//! `redb::Database`, one write transaction per `put`.
+
+use crate::migrate::BoxError;
+use crate::{Format, Representation, Versioned};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::{Mutex, PoisonError};
+
+/// A key and the value stored under it: its bytes, or a decoded value.
+pub type Entry<V = Vec<u8>> = (Vec<u8>, V);
+
+/// A byte-oriented key-value store.
+pub trait EvolveStore {
+    /// The error returned by the backend.
+    type Error;
+
+    /// The value stored under `key`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend cannot be read.
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    /// Store `value` under `key`, replacing any previous value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend cannot be written.
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), Self::Error>;
+
+    /// Every entry whose key starts with `prefix`, in key order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend cannot be read.
+    fn scan(&self, prefix: &[u8]) -> Result<Vec<Entry>, Self::Error>;
+}
+
+/// An in-memory [`EvolveStore`], for tests and as a reference backend.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    entries: Mutex<BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl MemoryStore {
+    fn entries(&self) -> std::sync::MutexGuard<'_, BTreeMap<Vec<u8>, Vec<u8>>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl EvolveStore for MemoryStore {
+    type Error = Infallible;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self.entries().get(key).cloned())
+    }
+
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
+        self.entries().insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn scan(&self, prefix: &[u8]) -> Result<Vec<Entry>, Self::Error> {
+        Ok(self
+            .entries()
+            .range(prefix.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+#[cfg(feature = "sled")]
+impl EvolveStore for sled::Tree {
+    type Error = sled::Error;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(Self::get(self, key)?.map(|value| value.to_vec()))
+    }
+
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
+        self.insert(key, value)?;
+        Ok(())
+    }
+
+    fn scan(&self, prefix: &[u8]) -> Result<Vec<Entry>, Self::Error> {
+        self.scan_prefix(prefix)
+            .map(|entry| entry.map(|(key, value)| (key.to_vec(), value.to_vec())))
+            .collect()
+    }
+}
+
+#[cfg(feature = "sled")]
+impl EvolveStore for sled::Db {
+    type Error = sled::Error;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        EvolveStore::get(&**self, key)
+    }
+
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
+        EvolveStore::put(&**self, key, value)
+    }
+
+    fn scan(&self, prefix: &[u8]) -> Result<Vec<Entry>, Self::Error> {
+        EvolveStore::scan(&**self, prefix)
+    }
+}
+
+#[cfg(feature = "redb")]
+type RedbTable = redb::ReadOnlyTable<&'static [u8], &'static [u8]>;
+
+/// An [`EvolveStore`] over a table of a `redb` database, with byte-string keys and values.
+///
+/// The table is created by the first `put`; until then, it reads as empty.
+#[cfg(feature = "redb")]
+#[derive(Debug, Clone)]
+pub struct RedbStore {
+    db: std::sync::Arc<redb::Database>,
+    table: String,
+}
+
+#[cfg(feature = "redb")]
+impl RedbStore {
+    /// A store over the table named `table` of `db`.
+    pub fn new(db: impl Into<std::sync::Arc<redb::Database>>, table: impl Into<String>) -> Self {
+        Self {
+            db: db.into(),
+            table: table.into(),
+        }
+    }
+
+    /// The underlying database.
+    #[must_use]
+    pub fn database(&self) -> &redb::Database {
+        &self.db
+    }
+
+    fn definition(&self) -> redb::TableDefinition<'_, &'static [u8], &'static [u8]> {
+        redb::TableDefinition::new(&self.table)
+    }
+
+    /// The table opened for reading, or `None` if it has not been created yet.
+    #[allow(clippy::result_large_err)] // the error type of `EvolveStore::get`
+    fn read_table(&self) -> Result<Option<RedbTable>, redb::Error> {
+        match self.db.begin_read()?.open_table(self.definition()) {
+            Ok(table) => Ok(Some(table)),
+            Err(redb::TableError::TableDoesNotExist(_)) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(feature = "redb")]
+impl EvolveStore for RedbStore {
+    type Error = redb::Error;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let Some(table) = self.read_table()? else {
+            return Ok(None);
+        };
+        Ok(table.get(key)?.map(|value| value.value().to_vec()))
+    }
+
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
+        let txn = self.db.begin_write()?;
+        txn.open_table(self.definition())?.insert(key, value)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn scan(&self, prefix: &[u8]) -> Result<Vec<Entry>, Self::Error> {
+        let Some(table) = self.read_table()? else {
+            return Ok(Vec::new());
+        };
+        let mut entries = Vec::new();
+        for entry in table.range::<&[u8]>(prefix..)? {
+            let (key, value) = entry?;
+            if !key.value().starts_with(prefix) {
+                break;
+            }
+            entries.push((key.value().to_vec(), value.value().to_vec()));
+        }
+        Ok(entries)
+    }
+}
+
+/// Error returned by a [`VersionedStore`].
+#[derive(Debug)]
+pub enum Error<S, F> {
+    /// The backend failed.
+    Store(S),
+    /// A value could not be decoded or encoded.
+    Format(F),
+    /// A migration step rejected a value.
+    Migration(BoxError),
+}
+
+impl<S: fmt::Display, F: fmt::Display> fmt::Display for Error<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Store(err) => write!(f, "store error: {err}"),
+            Self::Format(err) => write!(f, "format error: {err}"),
+            Self::Migration(err) => write!(f, "migration error: {err}"),
+        }
+    }
+}
+
+impl<S, F> std::error::Error for Error<S, F>
+where
+    S: std::error::Error + 'static,
+    F: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Store(err) => Some(err),
+            Self::Format(err) => Some(err),
+            Self::Migration(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+type StoreError<S, F> = Error<<S as EvolveStore>::Error, <F as Format>::Error>;
+
+/// A typed view of an [`EvolveStore`] holding values of the domain type `D`, encoded with
+/// the format `F`.
+#[derive(Debug)]
+pub struct VersionedStore<S, D, F> {
+    store: S,
+    format: F,
+    write_back: bool,
+    domain: PhantomData<fn() -> D>,
+}
+
+impl<S, D, F> VersionedStore<S, D, F> {
+    /// A view of `store`, encoding values with `format`.
+    pub const fn new(store: S, format: F) -> Self {
+        Self {
+            store,
+            format,
+            write_back: false,
+            domain: PhantomData,
+        }
+    }
+
+    /// Whether values read at an older version are written back at the current version.
+    #[must_use]
+    pub const fn write_back(mut self, write_back: bool) -> Self {
+        self.write_back = write_back;
+        self
+    }
+
+    /// The underlying store.
+    pub const fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// The underlying store, consuming the view.
+    pub fn into_store(self) -> S {
+        self.store
+    }
+}
+
+impl<S, D, F> VersionedStore<S, D, F>
+where
+    S: EvolveStore,
+    D: Versioned + TryFrom<D::Representation>,
+    D::Error: Into<BoxError>,
+    D::Representation: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    F: Format,
+{
+    /// The value stored under `key`, migrated to the domain type.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend fails, or the value cannot be decoded or migrated.
+    pub fn get(&self, key: &[u8]) -> Result<Option<D>, StoreError<S, F>> {
+        self.store
+            .get(key)
+            .map_err(Error::Store)?
+            .map(|bytes| self.load(key, &bytes).map(|(domain, _)| domain))
+            .transpose()
+    }
+
+    /// Store `domain` under `key`, at the current version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value cannot be encoded, or the backend fails.
+    pub fn put(&self, key: &[u8], domain: &D) -> Result<(), StoreError<S, F>> {
+        let bytes = self
+            .format
+            .encode(&D::Representation::from(domain))
+            .map_err(Error::Format)?;
+        self.store.put(key, &bytes).map_err(Error::Store)
+    }
+
+    /// Every value whose key starts with `prefix`, in key order, migrated to the domain type.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from the backend, or from a value that cannot be decoded or
+    /// migrated.
+    pub fn scan(&self, prefix: &[u8]) -> Result<Vec<Entry<D>>, StoreError<S, F>> {
+        self.store
+            .scan(prefix)
+            .map_err(Error::Store)?
+            .into_iter()
+            .map(|(key, bytes)| {
+                let (domain, _) = self.load(&key, &bytes)?;
+                Ok((key, domain))
+            })
+            .collect()
+    }
+
+    /// Rewrite every value whose key starts with `prefix` at the current version, whether or
+    /// not write-back is enabled.
+    ///
+    /// Returns the number of values rewritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from the backend, or from a value that cannot be decoded,
+    /// migrated or encoded.
+    pub fn migrate_all(&self, prefix: &[u8]) -> Result<usize, StoreError<S, F>> {
+        let mut rewritten = 0;
+        for (key, bytes) in self.store.scan(prefix).map_err(Error::Store)? {
+            let (domain, outdated) = self.load(&key, &bytes)?;
+            if outdated && !self.write_back {
+                self.put(&key, &domain)?;
+            }
+            rewritten += usize::from(outdated);
+        }
+        Ok(rewritten)
+    }
+
+    /// Decode and migrate a stored value, writing it back if it was outdated and write-back
+    /// is enabled. Also returns whether it was outdated.
+    fn load(&self, key: &[u8], bytes: &[u8]) -> Result<(D, bool), StoreError<S, F>> {
+        let rep: D::Representation = self.format.decode(bytes).map_err(Error::Format)?;
+        let outdated = rep.version() != D::Representation::CURRENT;
+        let domain = D::try_from(rep).map_err(|err| Error::Migration(err.into()))?;
+        if outdated && self.write_back {
+            self.put(key, &domain)?;
+        }
+        Ok((domain, outdated))
+    }
+}
@@ -0,0 +1,402 @@
+//! Compatibility tests against a corpus of stored documents.
+//!
+//! Keep a directory of real documents of every historical version, and check that each one
+//! still decodes and migrates:
+//!
+//! ```rust,ignore
+//! use serde_evolve::{corpus_dir, format::Json};
+//!
+//! #[test]
+//! fn product_fixtures_migrate() {
+//!     corpus_dir!("tests/fixtures/product").check::<Product, _>(&Json::default());
+//! }
+//! ```
+//!
+//! Every file with the format's extension is a fixture. A fixture `name.json` may be paired
+//! with `name.expected.json`, holding the document it must migrate to at the current version.
+//!
+//! For one-line regression tests of individual payloads, [`assert_compat!`](crate::assert_compat)
+//! checks a literal JSON document, optionally against the document it must migrate to, and
+//! shows a line diff when they differ:
+//!
+//! ```rust,ignore
+//! #[test]
+//! fn v1_payload_from_release_3_2() {
+//!     serde_evolve::assert_compat!(Product, r#"{"_version":"1","name":"pen"}"#);
+//! }
+//! ```
+//!
+//! For snapshot testing, [`latest_snapshot`] and [`Corpus::snapshots`] render the
+//! current-version encoding of a value and of each migrated fixture, so that schema drift shows
+//! up as a reviewable snapshot diff. With the `insta` feature,
+//! [`assert_latest_snapshot!`](crate::assert_latest_snapshot) and
+//! [`assert_corpus_snapshots!`](crate::assert_corpus_snapshots) record them as `insta` snapshots
+//! of the pretty-printed JSON, next to the calling test:
+//!
+//! ```rust,ignore
+//! use serde_evolve::{assert_corpus_snapshots, assert_latest_snapshot, corpus_dir};
+//!
+//! #[test]
+//! fn product_schema() {
+//!     assert_latest_snapshot!(Product::sample());
+//!     assert_corpus_snapshots!(Product, corpus_dir!("tests/fixtures/product"));
+//! }
+//! ```
+//!
+//! The plain helpers return strings, for other snapshot tools or other formats:
+//!
+//! ```rust,ignore
+//! insta::assert_snapshot!(latest_snapshot(&product, &Yaml));
+//! ```
+
+use crate::migrate::BoxError;
+use crate::{Format, Representation, Versioned};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// The infix naming the expected output of a fixture, as in `name.expected.json`.
+pub const EXPECTED_INFIX: &str = "expected";
+
+/// A [`Corpus`] in a directory relative to the crate being tested.
+#[macro_export]
+macro_rules! corpus_dir {
+    ($dir:expr) => {
+        $crate::testing::Corpus::new(::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($dir))
+    };
+}
+
+/// Assert that a JSON document still decodes and migrates to a domain type, returning the
+/// migrated value.
+///
+/// With a third argument, the migrated value must also encode to that JSON document at the
+/// current version. Whitespace and key order do not matter, and differences are shown as a
+/// line diff.
+#[cfg(feature = "json")]
+#[macro_export]
+macro_rules! assert_compat {
+    ($ty:ty, $fixture:expr $(,)?) => {
+        $crate::testing::check_json::<$ty>($fixture, None)
+    };
+    ($ty:ty, $fixture:expr, $expected:expr $(,)?) => {
+        $crate::testing::check_json::<$ty>($fixture, Some($expected))
+    };
+}
+
+/// Assert that the current-version encoding of a value matches its `insta` snapshot.
+///
+/// The value is encoded as pretty-printed JSON, as by [`latest_snapshot`]. An optional first
+/// argument names the snapshot; otherwise `insta` names it after the calling test.
+#[cfg(feature = "insta")]
+#[macro_export]
+macro_rules! assert_latest_snapshot {
+    ($value:expr $(,)?) => {{
+        let snapshot = $crate::testing::latest_snapshot(&$value, &$crate::format::Json::pretty());
+        $crate::insta::assert_snapshot!(snapshot);
+    }};
+    ($name:expr, $value:expr $(,)?) => {{
+        let snapshot = $crate::testing::latest_snapshot(&$value, &$crate::format::Json::pretty());
+        $crate::insta::assert_snapshot!($name, snapshot);
+    }};
+}
+
+/// Assert that every fixture of a [`Corpus`] migrates to the document in its `insta` snapshot.
+///
+/// Each fixture is recorded as a snapshot named after its file stem, holding the
+/// pretty-printed JSON of its migrated value, as by [`Corpus::snapshots`].
+#[cfg(feature = "insta")]
+#[macro_export]
+macro_rules! assert_corpus_snapshots {
+    ($ty:ty, $corpus:expr $(,)?) => {
+        for (name, snapshot) in $corpus.snapshots::<$ty, _>(&$crate::format::Json::pretty()) {
+            $crate::insta::assert_snapshot!(name, snapshot);
+        }
+    };
+}
+
+/// A directory of stored documents, each of which must still migrate to the domain type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Corpus {
+    dir: PathBuf,
+}
+
+impl Corpus {
+    /// A corpus of the documents in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The fixtures of the corpus with the given extension, in name order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the directory cannot be read.
+    #[must_use]
+    pub fn fixtures(&self, extension: &str) -> Vec<PathBuf> {
+        let entries = std::fs::read_dir(&self.dir).unwrap_or_else(|err| {
+            panic!("cannot read corpus directory {}: {err}", self.dir.display())
+        });
+        let mut fixtures: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && is_fixture(path, extension))
+            .collect();
+        fixtures.sort();
+        fixtures
+    }
+
+    /// Check that every fixture decodes, migrates to `D`, and matches its expected output.
+    ///
+    /// Returns the number of fixtures checked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the corpus is empty, or listing every fixture that fails.
+    pub fn check<D, F>(&self, format: &F) -> usize
+    where
+        D: Versioned + TryFrom<D::Representation>,
+        D::Error: Into<BoxError>,
+        D::Representation: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+        F: Format,
+    {
+        let fixtures = self.fixtures(format.extension());
+        assert!(
+            !fixtures.is_empty(),
+            "corpus directory {} has no .{} fixtures",
+            self.dir.display(),
+            format.extension()
+        );
+
+        let mut failures = String::new();
+        let mut failed = 0;
+        for fixture in &fixtures {
+            if let Err(reason) = check_fixture::<D, F>(fixture, format) {
+                failed += 1;
+                let _ = write!(failures, "\n  {}: {reason}", fixture.display());
+            }
+        }
+        assert!(
+            failed == 0,
+            "{failed} of {} fixtures failed:{failures}",
+            fixtures.len()
+        );
+        fixtures.len()
+    }
+
+    /// The current-version encoding of every fixture after migration, keyed by file stem.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a fixture fails to migrate, or `format` does not produce UTF-8.
+    #[must_use]
+    pub fn snapshots<D, F>(&self, format: &F) -> Vec<(String, String)>
+    where
+        D: Versioned + TryFrom<D::Representation>,
+        D::Error: Into<BoxError>,
+        D::Representation: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+        F: Format,
+    {
+        self.fixtures(format.extension())
+            .iter()
+            .map(|fixture| {
+                let latest = migrate_fixture::<D, F>(fixture, format)
+                    .unwrap_or_else(|reason| panic!("{}: {reason}", fixture.display()));
+                let name = fixture
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                (name, render(&latest, format))
+            })
+            .collect()
+    }
+}
+
+/// Migrate a JSON document to `D`, checking its current-version encoding against `expected`.
+///
+/// Called by [`assert_compat!`](crate::assert_compat).
+///
+/// # Panics
+///
+/// Panics if the document does not decode or migrate, or does not migrate to `expected`.
+#[cfg(feature = "json")]
+#[track_caller]
+#[allow(clippy::must_use_candidate)] // `assert_compat!` is mostly used as a statement
+pub fn check_json<D>(fixture: &str, expected: Option<&str>) -> D
+where
+    D: Versioned + TryFrom<D::Representation>,
+    D::Error: Into<BoxError>,
+    D::Representation: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+{
+    let rep: D::Representation = serde_json::from_str(fixture)
+        .unwrap_or_else(|err| panic!("fixture does not decode: {err}\n{fixture}"));
+    let version = rep.version();
+    let domain = D::try_from(rep).unwrap_or_else(|err| {
+        panic!(
+            "fixture at version {version} failed to migrate: {}",
+            err.into()
+        )
+    });
+    let Some(expected) = expected else {
+        return domain;
+    };
+
+    let expected: serde_json::Value = serde_json::from_str(expected)
+        .unwrap_or_else(|err| panic!("expected document is not JSON: {err}"));
+    let actual = serde_json::to_value(D::Representation::from(&domain))
+        .unwrap_or_else(|err| panic!("cannot encode the migrated value: {err}"));
+    if actual != expected {
+        let pretty = |value| serde_json::to_string_pretty(&sorted(value)).unwrap_or_default();
+        panic!(
+            "fixture at version {version} migrated to a different document \
+             (- expected, + actual):\n{}",
+            line_diff(&pretty(&expected), &pretty(&actual))
+        );
+    }
+    domain
+}
+
+/// `value` with the keys of every object in order, whether or not `serde_json/preserve_order`
+/// is enabled, so that diffs show changed values rather than moved keys.
+#[cfg(feature = "json")]
+fn sorted(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map
+                .iter()
+                .map(|(key, value)| (key, sorted(value)))
+                .collect();
+            entries.sort_by_key(|(key, _)| *key);
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), value))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sorted).collect()),
+        other => other.clone(),
+    }
+}
+
+/// The lines of `expected` and `actual`, marked `-` or `+` where they differ.
+#[cfg(feature = "json")]
+fn line_diff(expected: &str, actual: &str) -> String {
+    let old: Vec<_> = expected.lines().collect();
+    let new: Vec<_> = actual.lines().collect();
+    // common[i][j]: the longest common subsequence of old[i..] and new[j..].
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            let _ = writeln!(diff, "  {}", old[i]);
+            (i, j) = (i + 1, j + 1);
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            let _ = writeln!(diff, "- {}", old[i]);
+            i += 1;
+        } else {
+            let _ = writeln!(diff, "+ {}", new[j]);
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// The current-version encoding of a domain value, as text.
+///
+/// # Panics
+///
+/// Panics if the value cannot be encoded, or `format` does not produce UTF-8.
+#[must_use]
+pub fn latest_snapshot<D, F>(domain: &D, format: &F) -> String
+where
+    D: Versioned,
+    D::Representation: Serialize + for<'a> From<&'a D>,
+    F: Format,
+{
+    render(&D::Representation::from(domain), format)
+}
+
+fn render<R: Serialize, F: Format>(rep: &R, format: &F) -> String {
+    let bytes = format
+        .encode(rep)
+        .unwrap_or_else(|err| panic!("cannot encode the snapshot: {err}"));
+    String::from_utf8(bytes).expect("snapshots require a text format")
+}
+
+/// Whether `path` has the given extension, and is not the expected output of a fixture.
+fn is_fixture(path: &Path, extension: &str) -> bool {
+    path.extension().is_some_and(|ext| ext == extension)
+        && path
+            .file_stem()
+            .and_then(|stem| Path::new(stem).extension())
+            .is_none_or(|infix| infix != EXPECTED_INFIX)
+}
+
+/// Decode a fixture, and migrate it to the current version.
+fn migrate_fixture<D, F>(fixture: &Path, format: &F) -> Result<D::Representation, String>
+where
+    D: Versioned + TryFrom<D::Representation>,
+    D::Error: Into<BoxError>,
+    D::Representation: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    F: Format,
+{
+    let bytes = std::fs::read(fixture).map_err(|err| format!("cannot read: {err}"))?;
+    let rep: D::Representation = format
+        .decode(&bytes)
+        .map_err(|err| format!("cannot decode: {err}"))?;
+    let version = rep.version();
+    let domain = D::try_from(rep)
+        .map_err(|err| format!("version {version} failed to migrate: {}", err.into()))?;
+    let latest = D::Representation::from(&domain);
+    if latest.version() != D::Representation::CURRENT {
+        return Err(format!(
+            "migrated to version {} rather than {}",
+            latest.version(),
+            D::Representation::CURRENT
+        ));
+    }
+    Ok(latest)
+}
+
+fn check_fixture<D, F>(fixture: &Path, format: &F) -> Result<(), String>
+where
+    D: Versioned + TryFrom<D::Representation>,
+    D::Error: Into<BoxError>,
+    D::Representation: DeserializeOwned + Serialize + for<'a> From<&'a D>,
+    F: Format,
+{
+    let latest = migrate_fixture::<D, F>(fixture, format)?;
+    let expected_path = fixture.with_extension(format!("{EXPECTED_INFIX}.{}", format.extension()));
+    if !expected_path.is_file() {
+        return Ok(());
+    }
+    let expected = std::fs::read(&expected_path)
+        .map_err(|err| format!("cannot read {}: {err}", expected_path.display()))?;
+    let expected: serde_value::Value = format
+        .decode(&expected)
+        .map_err(|err| format!("cannot decode {}: {err}", expected_path.display()))?;
+    let actual = format
+        .encode(&latest)
+        .and_then(|bytes| format.decode::<serde_value::Value>(&bytes))
+        .map_err(|err| format!("cannot encode the migrated value: {err}"))?;
+    if actual != expected {
+        return Err(format!(
+            "migrated value does not match {}: {actual:?}",
+            expected_path.display()
+        ));
+    }
+    Ok(())
+}
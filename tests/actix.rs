@@ -0,0 +1,161 @@
+//! Tests for the actix-web extractor and responder
+
+#![cfg(feature = "actix")]
+#![allow(missing_docs)]
+
+use actix_web::body::{MessageBody, to_bytes};
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use actix_web::{FromRequest, Responder, ResponseError};
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_evolve::actix::{Rejection, VersionedJson};
+use std::convert::TryFrom;
+use std::fmt;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderV1 {
+    pub item: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderV2 {
+    pub item: String,
+    pub quantity: u32,
+}
+
+impl From<OrderV1> for OrderV2 {
+    fn from(v1: OrderV1) -> Self {
+        Self {
+            item: v1.item,
+            quantity: 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EmptyOrder;
+
+impl fmt::Display for EmptyOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("orders must have a positive quantity")
+    }
+}
+
+impl std::error::Error for EmptyOrder {}
+
+impl From<std::convert::Infallible> for EmptyOrder {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(error = "EmptyOrder", chain(OrderV1, OrderV2))]
+pub struct Order {
+    pub item: String,
+    pub quantity: u32,
+}
+
+impl TryFrom<OrderV2> for Order {
+    type Error = EmptyOrder;
+
+    fn try_from(v2: OrderV2) -> Result<Self, Self::Error> {
+        if v2.quantity == 0 {
+            return Err(EmptyOrder);
+        }
+        Ok(Self {
+            item: v2.item,
+            quantity: v2.quantity,
+        })
+    }
+}
+
+impl From<&Order> for OrderV2 {
+    fn from(order: &Order) -> Self {
+        Self {
+            item: order.item.clone(),
+            quantity: order.quantity,
+        }
+    }
+}
+
+/// Drive a future that never waits on I/O to completion.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn body_text<B: MessageBody>(body: B) -> String
+where
+    B::Error: fmt::Debug,
+{
+    String::from_utf8(block_on(to_bytes(body)).unwrap().to_vec()).unwrap()
+}
+
+fn extract(body: &'static str) -> Result<Order, Rejection> {
+    let (req, mut payload) = TestRequest::post()
+        .insert_header(("content-type", "application/json"))
+        .set_payload(body)
+        .to_http_parts();
+    block_on(VersionedJson::<Order>::from_request(&req, &mut payload))
+        .map(|VersionedJson(order)| order)
+}
+
+#[test]
+fn extracts_any_version() {
+    assert_eq!(
+        extract(r#"{"_version":"1","item":"tea"}"#).unwrap(),
+        Order {
+            item: "tea".to_string(),
+            quantity: 1
+        }
+    );
+    assert_eq!(
+        extract(r#"{"_version":"2","item":"tea","quantity":3}"#).unwrap(),
+        Order {
+            item: "tea".to_string(),
+            quantity: 3
+        }
+    );
+}
+
+#[test]
+fn rejects_invalid_bodies_and_failed_migrations() {
+    let rejection = extract(r#"{"_version":"7"}"#).unwrap_err();
+    assert!(matches!(rejection, Rejection::Json(_)));
+    assert_eq!(rejection.status_code(), StatusCode::BAD_REQUEST);
+
+    let rejection = extract(r#"{"_version":"2","item":"tea","quantity":0}"#).unwrap_err();
+    assert!(matches!(rejection, Rejection::Migration(_)));
+    let response = rejection.error_response();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        body_text(response.into_body()),
+        "migration failed: orders must have a positive quantity"
+    );
+}
+
+#[test]
+fn responds_with_the_latest_version() {
+    let req = TestRequest::default().to_http_request();
+    let response = VersionedJson(Order {
+        item: "tea".to_string(),
+        quantity: 2,
+    })
+    .respond_to(&req);
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body_text(response.into_body())).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"_version": "2", "item": "tea", "quantity": 2})
+    );
+}
@@ -287,3 +287,4481 @@ mod transparent_edge_cases {
         assert!(err.is_data());
     }
 }
+
+mod ordering {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+    pub struct EventV1 {
+        pub id: u32,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+    pub struct EventV2 {
+        pub id: u32,
+        pub source: String,
+    }
+
+    impl From<EventV1> for EventV2 {
+        fn from(v1: EventV1) -> Self {
+            Self {
+                id: v1.id,
+                source: "unknown".to_string(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(EventV1, EventV2),
+        ord = true,
+        derive(PartialEq, Eq)
+    )]
+    pub struct Event {
+        pub id: u32,
+        pub source: String,
+    }
+
+    impl From<EventV2> for Event {
+        fn from(v2: EventV2) -> Self {
+            Self {
+                id: v2.id,
+                source: v2.source,
+            }
+        }
+    }
+
+    impl From<&Event> for EventV2 {
+        fn from(event: &Event) -> Self {
+            Self {
+                id: event.id,
+                source: event.source.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn representations_order_by_version() {
+        let old = EventVersions::from(EventV1 { id: 1 });
+        let new = EventVersions::from(EventV2 {
+            id: 2,
+            source: "api".to_string(),
+        });
+
+        assert!(old < new);
+        assert_eq!(old.cmp(&new), std::cmp::Ordering::Less);
+        assert_eq!(old, EventVersions::from(EventV1 { id: 1 }));
+    }
+
+    #[test]
+    fn same_version_representations_compare_by_payload() {
+        let first = EventVersions::from(EventV1 { id: 1 });
+        let second = EventVersions::from(EventV1 { id: 99 });
+
+        assert_ne!(first, second);
+        assert_eq!(first.cmp(&second), std::cmp::Ordering::Less);
+
+        let set: std::collections::BTreeSet<_> = [second.clone(), first.clone()].into();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), [first, second]);
+    }
+
+    #[test]
+    fn sort_by_version_orders_oldest_first_and_is_stable() {
+        let mut batch: Vec<EventVersions> = [
+            r#"{"_version":"2","id":1,"source":"api"}"#,
+            r#"{"_version":"1","id":2}"#,
+            r#"{"_version":"2","id":3,"source":"cli"}"#,
+            r#"{"_version":"1","id":4}"#,
+        ]
+        .iter()
+        .map(|json| serde_json::from_str(json).unwrap())
+        .collect();
+
+        EventVersions::sort_by_version(&mut batch);
+
+        let versions: Vec<u32> = batch.iter().map(EventVersions::version).collect();
+        assert_eq!(versions, [1, 1, 2, 2]);
+
+        let ids: Vec<u32> = batch.into_iter().map(|rep| Event::from(rep).id).collect();
+        assert_eq!(ids, [2, 4, 1, 3]);
+    }
+}
+
+mod without_clone_or_debug {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// A DTO holding a handle that is neither `Clone` nor meaningfully `Debug`.
+    #[derive(Serialize, Deserialize)]
+    pub struct JobV1 {
+        pub name: String,
+        #[serde(skip)]
+        pub done: Option<mpsc::Receiver<()>>,
+    }
+
+    /// A DTO whose `Debug` output would leak a secret, so it deliberately has none.
+    #[derive(Serialize, Deserialize)]
+    pub struct JobV2 {
+        pub name: String,
+        pub api_token: String,
+    }
+
+    impl From<JobV1> for JobV2 {
+        fn from(v1: JobV1) -> Self {
+            // Deserialized jobs never carry a live completion handle.
+            debug_assert!(v1.done.is_none());
+            Self {
+                name: v1.name,
+                api_token: String::new(),
+            }
+        }
+    }
+
+    #[derive(Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(JobV1, JobV2),
+        clone = false,
+        debug = false,
+        transparent = true
+    )]
+    pub struct Job {
+        pub name: String,
+        pub api_token: String,
+    }
+
+    impl From<JobV2> for Job {
+        fn from(v2: JobV2) -> Self {
+            Self {
+                name: v2.name,
+                api_token: v2.api_token,
+            }
+        }
+    }
+
+    impl From<&Job> for JobV2 {
+        fn from(job: &Job) -> Self {
+            Self {
+                name: job.name.clone(),
+                api_token: job.api_token.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn non_clone_dtos_round_trip() {
+        let json_v1 = r#"{"_version":"1","name":"backup"}"#;
+        let job: Job = serde_json::from_str(json_v1).unwrap();
+        assert_eq!(job.name, "backup");
+        assert!(job.api_token.is_empty());
+
+        let rep: JobVersions = serde_json::from_str(json_v1).unwrap();
+        assert_eq!(rep.version(), 1);
+
+        let json = serde_json::to_string(&job).unwrap();
+        let rep: JobVersions = serde_json::from_str(&json).unwrap();
+        assert!(rep.is_current());
+    }
+}
+
+mod unknown_version_handler {
+    use super::*;
+    use serde_evolve::Payload;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PluginV1 {
+        pub name: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PluginV2 {
+        pub name: String,
+        pub enabled: bool,
+    }
+
+    impl From<PluginV1> for PluginV2 {
+        fn from(v1: PluginV1) -> Self {
+            Self {
+                name: v1.name,
+                enabled: true,
+            }
+        }
+    }
+
+    /// Attempt to read documents from newer writers as the latest known version, which
+    /// ignores any fields added since.
+    fn recover(tag: &str, payload: Payload) -> Result<PluginVersions, String> {
+        match tag.parse::<u32>() {
+            Ok(version) if version > PluginVersions::CURRENT => payload
+                .deserialize::<PluginV2>()
+                .map(PluginVersions::from)
+                .map_err(|err| format!("cannot recover version {version}: {err}")),
+            _ => Err(format!("unrecognised plugin version '{tag}'")),
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(PluginV1, PluginV2), on_unknown = recover)]
+    pub struct Plugin {
+        pub name: String,
+        pub enabled: bool,
+    }
+
+    impl From<PluginV2> for Plugin {
+        fn from(v2: PluginV2) -> Self {
+            Self {
+                name: v2.name,
+                enabled: v2.enabled,
+            }
+        }
+    }
+
+    impl From<&Plugin> for PluginV2 {
+        fn from(plugin: &Plugin) -> Self {
+            Self {
+                name: plugin.name.clone(),
+                enabled: plugin.enabled,
+            }
+        }
+    }
+
+    #[test]
+    fn known_versions_deserialize_normally() {
+        let rep: PluginVersions =
+            serde_json::from_str(r#"{"_version":"1","name":"lint"}"#).unwrap();
+        assert_eq!(rep.version(), 1);
+        assert_eq!(
+            Plugin::from(rep),
+            Plugin {
+                name: "lint".to_string(),
+                enabled: true
+            }
+        );
+
+        let plugin = Plugin {
+            name: "fmt".to_string(),
+            enabled: false,
+        };
+        let json = serde_json::to_string(&PluginVersions::from(&plugin)).unwrap();
+        let rep: PluginVersions = serde_json::from_str(&json).unwrap();
+        assert_eq!(Plugin::from(rep), plugin);
+    }
+
+    #[test]
+    fn handler_recovers_future_versions() {
+        let json = r#"{"_version":"3","name":"lint","enabled":false,"priority":7}"#;
+        let rep: PluginVersions = serde_json::from_str(json).unwrap();
+        assert!(rep.is_current());
+        assert!(!Plugin::from(rep).enabled);
+    }
+
+    #[test]
+    fn handler_errors_are_reported() {
+        let err = serde_json::from_str::<PluginVersions>(r#"{"_version":"beta","name":"x"}"#)
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unrecognised plugin version 'beta'")
+        );
+
+        let err =
+            serde_json::from_str::<PluginVersions>(r#"{"_version":"9","title":"x"}"#).unwrap_err();
+        assert!(err.to_string().contains("cannot recover version 9"));
+    }
+
+    #[test]
+    fn missing_tag_is_rejected() {
+        let err = serde_json::from_str::<PluginVersions>(r#"{"name":"x"}"#).unwrap_err();
+        assert!(err.to_string().contains("missing field `_version`"));
+    }
+}
+
+mod custom_tag {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SettingsV1 {
+        pub theme: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SettingsV2 {
+        pub theme: String,
+        pub font_size: u8,
+    }
+
+    impl From<SettingsV1> for SettingsV2 {
+        fn from(v1: SettingsV1) -> Self {
+            Self {
+                theme: v1.theme,
+                font_size: 12,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(SettingsV1, SettingsV2),
+        tag = "schema_version",
+        transparent = true
+    )]
+    pub struct Settings {
+        pub theme: String,
+        pub font_size: u8,
+    }
+
+    impl From<SettingsV2> for Settings {
+        fn from(v2: SettingsV2) -> Self {
+            Self {
+                theme: v2.theme,
+                font_size: v2.font_size,
+            }
+        }
+    }
+
+    impl From<&Settings> for SettingsV2 {
+        fn from(settings: &Settings) -> Self {
+            Self {
+                theme: settings.theme.clone(),
+                font_size: settings.font_size,
+            }
+        }
+    }
+
+    #[test]
+    fn reads_and_writes_custom_tag_field() {
+        let json_v1 = r#"{"schema_version":"1","theme":"dark"}"#;
+        let settings: Settings = serde_json::from_str(json_v1).unwrap();
+        assert_eq!(settings.font_size, 12);
+
+        let value = serde_json::to_value(&settings).unwrap();
+        assert_eq!(value["schema_version"], "2");
+        assert!(value.get("_version").is_none());
+
+        let err =
+            serde_json::from_str::<Settings>(r#"{"_version":"1","theme":"dark"}"#).unwrap_err();
+        assert!(err.to_string().contains("schema_version"));
+    }
+}
+
+mod untagged_legacy {
+    use super::*;
+
+    /// Written before versioning existed.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct ContactV1 {
+        pub name: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct ContactV2 {
+        pub name: String,
+        pub phone: String,
+    }
+
+    impl From<ContactV1> for ContactV2 {
+        fn from(v1: ContactV1) -> Self {
+            Self {
+                name: v1.name,
+                phone: String::new(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(ContactV1, ContactV2),
+        tag_style = "untagged"
+    )]
+    pub struct Contact {
+        pub name: String,
+        pub phone: String,
+    }
+
+    impl From<ContactV2> for Contact {
+        fn from(v2: ContactV2) -> Self {
+            Self {
+                name: v2.name,
+                phone: v2.phone,
+            }
+        }
+    }
+
+    impl From<&Contact> for ContactV2 {
+        fn from(contact: &Contact) -> Self {
+            Self {
+                name: contact.name.clone(),
+                phone: contact.phone.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn untagged_payloads_are_matched_newest_first() {
+        let rep: ContactVersions = serde_json::from_str(r#"{"name":"Ann"}"#).unwrap();
+        assert_eq!(rep.version(), 1);
+        assert_eq!(Contact::from(rep).phone, "");
+
+        let rep: ContactVersions = serde_json::from_str(r#"{"name":"Bob","phone":"555"}"#).unwrap();
+        assert_eq!(rep.version(), 2);
+
+        let contact = Contact {
+            name: "Cy".to_string(),
+            phone: "123".to_string(),
+        };
+        let json = serde_json::to_string(&ContactVersions::from(&contact)).unwrap();
+        assert_eq!(json, r#"{"name":"Cy","phone":"123"}"#);
+    }
+
+    #[test]
+    fn unmatched_payloads_are_rejected() {
+        assert!(serde_json::from_str::<ContactVersions>(r#"{"phone":"555"}"#).is_err());
+    }
+}
+
+mod adjacent_tagging {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ReadingV1 {
+        pub celsius: f64,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ReadingV2 {
+        pub celsius: f64,
+        pub sensor: String,
+    }
+
+    impl From<ReadingV1> for ReadingV2 {
+        fn from(v1: ReadingV1) -> Self {
+            Self {
+                celsius: v1.celsius,
+                sensor: "default".to_string(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(ReadingV1, ReadingV2),
+        tag_style = "adjacent"
+    )]
+    pub struct Reading {
+        pub celsius: f64,
+        pub sensor: String,
+    }
+
+    impl From<ReadingV2> for Reading {
+        fn from(v2: ReadingV2) -> Self {
+            Self {
+                celsius: v2.celsius,
+                sensor: v2.sensor,
+            }
+        }
+    }
+
+    impl From<&Reading> for ReadingV2 {
+        fn from(reading: &Reading) -> Self {
+            Self {
+                celsius: reading.celsius,
+                sensor: reading.sensor.clone(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(ReadingV1, ReadingV2),
+        rep = CustomReadingEnvelope,
+        tag_style = "adjacent",
+        tag = "v",
+        content = "body",
+        on_unknown = reject
+    )]
+    pub struct CustomReading {
+        pub celsius: f64,
+        pub sensor: String,
+    }
+
+    fn reject(tag: &str, _payload: serde_evolve::Payload) -> Result<CustomReadingEnvelope, String> {
+        Err(format!("unsupported reading version {tag}"))
+    }
+
+    impl From<ReadingV2> for CustomReading {
+        fn from(v2: ReadingV2) -> Self {
+            Self {
+                celsius: v2.celsius,
+                sensor: v2.sensor,
+            }
+        }
+    }
+
+    impl From<&CustomReading> for ReadingV2 {
+        fn from(reading: &CustomReading) -> Self {
+            Self {
+                celsius: reading.celsius,
+                sensor: reading.sensor.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn payload_is_nested_under_content_field() {
+        let reading = Reading {
+            celsius: 21.5,
+            sensor: "attic".to_string(),
+        };
+        let value = serde_json::to_value(ReadingVersions::from(&reading)).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"_version": "2", "data": {"celsius": 21.5, "sensor": "attic"}})
+        );
+
+        let rep: ReadingVersions =
+            serde_json::from_str(r#"{"_version":"1","data":{"celsius":3.0}}"#).unwrap();
+        assert_eq!(rep.version(), 1);
+        assert_eq!(Reading::from(rep).sensor, "default");
+    }
+
+    #[test]
+    fn custom_field_names_work_with_buffered_deserialization() {
+        let rep: CustomReadingEnvelope =
+            serde_json::from_str(r#"{"body":{"celsius":3.0},"v":"1"}"#).unwrap();
+        assert_eq!(CustomReading::from(rep).sensor, "default");
+
+        let err =
+            serde_json::from_str::<CustomReadingEnvelope>(r#"{"v":"7","body":{}}"#).unwrap_err();
+        assert!(err.to_string().contains("unsupported reading version 7"));
+
+        let err = serde_json::from_str::<CustomReadingEnvelope>(r#"{"v":"1"}"#).unwrap_err();
+        assert!(err.to_string().contains("missing field `body`"));
+    }
+
+    #[test]
+    fn content_may_precede_the_tag() {
+        let rep: ReadingVersions =
+            serde_json::from_str(r#"{"data":{"celsius":3.0},"note":"x","_version":"1"}"#).unwrap();
+        assert_eq!(rep.version(), 1);
+
+        let err =
+            serde_json::from_str::<ReadingVersions>(r#"{"_version":"7","data":{}}"#).unwrap_err();
+        assert!(err.to_string().contains("unknown variant `7`"));
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn round_trips_through_non_self_describing_formats() {
+        use serde_evolve::format::{Format, Postcard};
+
+        let reading = Reading {
+            celsius: 21.5,
+            sensor: "attic".to_string(),
+        };
+        let bytes = Postcard.encode(&ReadingVersions::from(&reading)).unwrap();
+        let rep: ReadingVersions = Postcard.decode(&bytes).unwrap();
+        assert!(rep.is_current());
+        assert_eq!(Reading::from(rep), reading);
+
+        let bytes = Postcard
+            .encode(&ReadingVersions::from(ReadingV1 { celsius: 3.0 }))
+            .unwrap();
+        let rep: ReadingVersions = Postcard.decode(&bytes).unwrap();
+        assert_eq!(rep.version(), 1);
+        assert_eq!(Reading::from(rep).sensor, "default");
+    }
+}
+
+mod default_version {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct OrderV1 {
+        pub item: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct OrderV2 {
+        pub item: String,
+        pub quantity: u32,
+    }
+
+    impl From<OrderV1> for OrderV2 {
+        fn from(v1: OrderV1) -> Self {
+            Self {
+                item: v1.item,
+                quantity: 1,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(OrderV1, OrderV2),
+        default_version = 1,
+        transparent = true
+    )]
+    pub struct Order {
+        pub item: String,
+        pub quantity: u32,
+    }
+
+    impl From<OrderV2> for Order {
+        fn from(v2: OrderV2) -> Self {
+            Self {
+                item: v2.item,
+                quantity: v2.quantity,
+            }
+        }
+    }
+
+    impl From<&Order> for OrderV2 {
+        fn from(order: &Order) -> Self {
+            Self {
+                item: order.item.clone(),
+                quantity: order.quantity,
+            }
+        }
+    }
+
+    #[test]
+    fn untagged_documents_are_read_as_default_version() {
+        let order: Order = serde_json::from_str(r#"{"item":"widget"}"#).unwrap();
+        assert_eq!(order.quantity, 1);
+
+        let rep: OrderVersions = serde_json::from_str(r#"{"item":"widget"}"#).unwrap();
+        assert_eq!(rep.version(), 1);
+    }
+
+    #[test]
+    fn tagged_documents_are_unaffected() {
+        let order: Order =
+            serde_json::from_str(r#"{"_version":"2","item":"widget","quantity":5}"#).unwrap();
+        assert_eq!(order.quantity, 5);
+
+        let json = serde_json::to_string(&order).unwrap();
+        assert!(json.contains(r#""_version":"2""#));
+
+        let err = serde_json::from_str::<Order>(r#"{"_version":"3","item":"widget"}"#).unwrap_err();
+        assert!(err.to_string().contains("unknown variant `3`"));
+    }
+}
+
+mod enum_domain {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(tag = "kind")]
+    pub enum CommandV1 {
+        Start,
+        Stop,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(tag = "kind")]
+    pub enum CommandV2 {
+        Start { delay_secs: u32 },
+        Stop { force: bool },
+    }
+
+    impl From<CommandV1> for CommandV2 {
+        fn from(v1: CommandV1) -> Self {
+            match v1 {
+                CommandV1::Start => Self::Start { delay_secs: 0 },
+                CommandV1::Stop => Self::Stop { force: false },
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(CommandV1, CommandV2), transparent = true)]
+    pub enum Command {
+        Start { delay_secs: u32 },
+        Stop { force: bool },
+    }
+
+    impl From<CommandV2> for Command {
+        fn from(v2: CommandV2) -> Self {
+            match v2 {
+                CommandV2::Start { delay_secs } => Self::Start { delay_secs },
+                CommandV2::Stop { force } => Self::Stop { force },
+            }
+        }
+    }
+
+    impl From<&Command> for CommandV2 {
+        fn from(command: &Command) -> Self {
+            match *command {
+                Command::Start { delay_secs } => Self::Start { delay_secs },
+                Command::Stop { force } => Self::Stop { force },
+            }
+        }
+    }
+
+    #[test]
+    fn enum_domain_types_migrate_and_round_trip() {
+        let command: Command = serde_json::from_str(r#"{"_version":"1","kind":"Stop"}"#).unwrap();
+        assert_eq!(command, Command::Stop { force: false });
+
+        let rep: CommandVersions =
+            serde_json::from_str(r#"{"_version":"1","kind":"Start"}"#).unwrap();
+        assert_eq!(Command::from(rep), Command::Start { delay_secs: 0 });
+
+        let command = Command::Start { delay_secs: 5 };
+        let json = serde_json::to_string(&command).unwrap();
+        assert_eq!(json, r#"{"_version":"2","kind":"Start","delay_secs":5}"#);
+        assert_eq!(serde_json::from_str::<Command>(&json).unwrap(), command);
+    }
+}
+
+mod generic_domain {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+    pub struct WrapperV1<T> {
+        pub value: T,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+    pub struct WrapperV2<T> {
+        pub value: T,
+        pub label: String,
+    }
+
+    impl<T> From<WrapperV1<T>> for WrapperV2<T> {
+        fn from(v1: WrapperV1<T>) -> Self {
+            Self {
+                value: v1.value,
+                label: String::new(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(WrapperV1<T>, WrapperV2<T>),
+        transparent = true,
+        ord = true,
+        derive(PartialEq, Eq)
+    )]
+    pub struct Wrapper<T>
+    where
+        T: Clone,
+    {
+        pub value: T,
+        pub label: String,
+    }
+
+    impl<T: Clone> From<WrapperV2<T>> for Wrapper<T> {
+        fn from(v2: WrapperV2<T>) -> Self {
+            Self {
+                value: v2.value,
+                label: v2.label,
+            }
+        }
+    }
+
+    impl<T: Clone> From<&Wrapper<T>> for WrapperV2<T> {
+        fn from(wrapper: &Wrapper<T>) -> Self {
+            Self {
+                value: wrapper.value.clone(),
+                label: wrapper.label.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn generic_domain_types_migrate_and_round_trip() {
+        let wrapper: Wrapper<u32> = serde_json::from_str(r#"{"_version":"1","value":7}"#).unwrap();
+        assert_eq!(
+            wrapper,
+            Wrapper {
+                value: 7,
+                label: String::new()
+            }
+        );
+
+        let rep: WrapperVersions<Vec<String>> =
+            serde_json::from_str(r#"{"_version":"1","value":["a"]}"#).unwrap();
+        assert_eq!(rep.version(), 1);
+        assert_eq!(Wrapper::from(rep).value, vec!["a".to_string()]);
+
+        let wrapper = Wrapper {
+            value: true,
+            label: "flag".to_string(),
+        };
+        let json = serde_json::to_string(&wrapper).unwrap();
+        assert_eq!(json, r#"{"_version":"2","value":true,"label":"flag"}"#);
+        assert_eq!(
+            serde_json::from_str::<Wrapper<bool>>(&json).unwrap(),
+            wrapper
+        );
+    }
+}
+
+mod per_step_errors {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::fmt;
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct EmptyName;
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct NegativeAge(i64);
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum MemberError {
+        EmptyName,
+        NegativeAge(i64),
+    }
+
+    impl From<EmptyName> for MemberError {
+        fn from(_: EmptyName) -> Self {
+            Self::EmptyName
+        }
+    }
+
+    impl From<NegativeAge> for MemberError {
+        fn from(err: NegativeAge) -> Self {
+            Self::NegativeAge(err.0)
+        }
+    }
+
+    impl fmt::Display for MemberError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::EmptyName => f.write_str("name must not be empty"),
+                Self::NegativeAge(age) => write!(f, "age {age} is negative"),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct MemberV1 {
+        pub name: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct MemberV2 {
+        pub name: String,
+        pub age: i64,
+    }
+
+    impl TryFrom<MemberV1> for MemberV2 {
+        type Error = EmptyName;
+
+        fn try_from(v1: MemberV1) -> Result<Self, Self::Error> {
+            if v1.name.is_empty() {
+                return Err(EmptyName);
+            }
+            Ok(Self {
+                name: v1.name,
+                age: 0,
+            })
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(error = MemberError, chain(MemberV1, MemberV2))]
+    pub struct Member {
+        pub name: String,
+        pub age: u32,
+    }
+
+    impl TryFrom<MemberV2> for Member {
+        type Error = NegativeAge;
+
+        fn try_from(v2: MemberV2) -> Result<Self, Self::Error> {
+            let age = u32::try_from(v2.age).map_err(|_| NegativeAge(v2.age))?;
+            Ok(Self { name: v2.name, age })
+        }
+    }
+
+    impl From<&Member> for MemberV2 {
+        fn from(member: &Member) -> Self {
+            Self {
+                name: member.name.clone(),
+                age: i64::from(member.age),
+            }
+        }
+    }
+
+    #[test]
+    fn each_step_may_use_its_own_error_type() {
+        let rep: MemberVersions = serde_json::from_str(r#"{"_version":"1","name":""}"#).unwrap();
+        assert_eq!(Member::try_from(rep), Err(MemberError::EmptyName));
+
+        let rep: MemberVersions =
+            serde_json::from_str(r#"{"_version":"2","name":"Ann","age":-3}"#).unwrap();
+        assert_eq!(Member::try_from(rep), Err(MemberError::NegativeAge(-3)));
+
+        let rep: MemberVersions = serde_json::from_str(r#"{"_version":"1","name":"Ann"}"#).unwrap();
+        assert_eq!(
+            Member::try_from(rep),
+            Ok(Member {
+                name: "Ann".to_string(),
+                age: 0
+            })
+        );
+    }
+}
+
+mod downgrade {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct SettingsV1 {
+        pub theme: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct SettingsV2 {
+        pub theme: String,
+        pub font_size: u8,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct SettingsV3 {
+        pub theme: String,
+        pub font_size: u8,
+        pub high_contrast: bool,
+    }
+
+    impl From<SettingsV1> for SettingsV2 {
+        fn from(v1: SettingsV1) -> Self {
+            Self {
+                theme: v1.theme,
+                font_size: 12,
+            }
+        }
+    }
+
+    impl From<SettingsV2> for SettingsV3 {
+        fn from(v2: SettingsV2) -> Self {
+            Self {
+                theme: v2.theme,
+                font_size: v2.font_size,
+                high_contrast: false,
+            }
+        }
+    }
+
+    impl From<SettingsV3> for SettingsV2 {
+        fn from(v3: SettingsV3) -> Self {
+            Self {
+                theme: v3.theme,
+                font_size: v3.font_size,
+            }
+        }
+    }
+
+    impl From<SettingsV2> for SettingsV1 {
+        fn from(v2: SettingsV2) -> Self {
+            Self { theme: v2.theme }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(SettingsV1, SettingsV2, SettingsV3),
+        downgrade = true,
+        derive(PartialEq, Eq)
+    )]
+    pub struct Settings {
+        pub theme: String,
+        pub font_size: u8,
+        pub high_contrast: bool,
+    }
+
+    impl From<SettingsV3> for Settings {
+        fn from(v3: SettingsV3) -> Self {
+            Self {
+                theme: v3.theme,
+                font_size: v3.font_size,
+                high_contrast: v3.high_contrast,
+            }
+        }
+    }
+
+    impl From<&Settings> for SettingsV3 {
+        fn from(settings: &Settings) -> Self {
+            Self {
+                theme: settings.theme.clone(),
+                font_size: settings.font_size,
+                high_contrast: settings.high_contrast,
+            }
+        }
+    }
+
+    #[test]
+    fn domain_converts_down_to_older_versions() {
+        let settings = Settings {
+            theme: "dark".to_string(),
+            font_size: 14,
+            high_contrast: true,
+        };
+
+        assert_eq!(
+            SettingsV2::from(&settings),
+            SettingsV2 {
+                theme: "dark".to_string(),
+                font_size: 14,
+            }
+        );
+        assert_eq!(
+            SettingsV1::from(&settings),
+            SettingsV1 {
+                theme: "dark".to_string(),
+            }
+        );
+
+        let rep = SettingsVersions::from(SettingsV2::from(&settings));
+        let json = serde_json::to_string(&rep).unwrap();
+        assert_eq!(json, r#"{"_version":"2","theme":"dark","font_size":14}"#);
+    }
+
+    #[test]
+    fn at_version_selects_the_wire_version() {
+        let settings = Settings {
+            theme: "light".to_string(),
+            font_size: 10,
+            high_contrast: false,
+        };
+
+        let rep = SettingsVersions::at_version(&settings, 1).unwrap();
+        assert_eq!(rep.version(), 1);
+        assert_eq!(
+            serde_json::to_string(&rep).unwrap(),
+            r#"{"_version":"1","theme":"light"}"#
+        );
+        assert!(
+            SettingsVersions::at_version(&settings, 3)
+                .unwrap()
+                .is_current()
+        );
+
+        let err = SettingsVersions::at_version(&settings, 4).unwrap_err();
+        assert_eq!(
+            err,
+            serde_evolve::UnsupportedVersion {
+                found: 4,
+                min: 1,
+                max: 3
+            }
+        );
+        assert_eq!(err.to_string(), "unsupported version 4, expected 1..=3");
+    }
+
+    #[test]
+    fn downgrade_to_only_steps_down() {
+        let v3 = SettingsVersions::from(SettingsV3 {
+            theme: "dark".to_string(),
+            font_size: 14,
+            high_contrast: true,
+        });
+        let v1 = v3.downgrade_to(1).unwrap();
+        assert_eq!(
+            v1,
+            SettingsVersions::from(SettingsV1 {
+                theme: "dark".to_string(),
+            })
+        );
+
+        let err = v1.downgrade_to(2).unwrap_err();
+        assert_eq!((err.found, err.max), (2, 1));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn pinned_versions_serialize_through_the_downgrade_chain() {
+        let settings = Settings {
+            theme: "dark".to_string(),
+            font_size: 14,
+            high_contrast: true,
+        };
+        assert_eq!(
+            serde_evolve::pinned::to_version_string(&settings, 2).unwrap(),
+            r#"{"_version":"2","theme":"dark","font_size":14}"#
+        );
+        assert_eq!(
+            serde_evolve::pinned::encode(&settings, 1, &serde_evolve::format::Json::default())
+                .unwrap(),
+            br#"{"_version":"1","theme":"dark"}"#
+        );
+        assert!(matches!(
+            serde_evolve::pinned::to_version_string(&settings, 4),
+            Err(serde_evolve::pinned::Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn convert_to_steps_between_any_versions() {
+        let v1 = SettingsVersions::from(SettingsV1 {
+            theme: "dark".to_string(),
+        });
+
+        let v3 = v1.convert_to(3).unwrap();
+        assert_eq!(
+            v3,
+            SettingsVersions::from(SettingsV3 {
+                theme: "dark".to_string(),
+                font_size: 12,
+                high_contrast: false,
+            })
+        );
+        assert_eq!(
+            v3.clone().convert_to(2).unwrap(),
+            SettingsVersions::from(SettingsV2 {
+                theme: "dark".to_string(),
+                font_size: 12,
+            })
+        );
+        assert_eq!(v3.clone().convert_to(3).unwrap(), v3);
+        assert_eq!(v3.clone().downgrade_once().version(), 2);
+
+        let err = v3.convert_to(4).unwrap_err();
+        assert_eq!((err.found, err.min, err.max), (4, 1, 3));
+    }
+}
+
+mod deprecated_versions {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct TokenV1 {
+        pub secret: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct TokenV2 {
+        pub secret: String,
+        pub scopes: Vec<String>,
+    }
+
+    impl From<TokenV1> for TokenV2 {
+        fn from(v1: TokenV1) -> Self {
+            Self {
+                secret: v1.secret,
+                scopes: Vec::new(),
+            }
+        }
+    }
+
+    macro_rules! token_domain {
+        ($name:ident, $($policy:tt)*) => {
+            #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+            #[versioned(mode = "infallible", chain(TokenV1 deprecated, TokenV2) $($policy)*)]
+            pub struct $name {
+                pub secret: String,
+                pub scopes: Vec<String>,
+            }
+
+            impl From<TokenV2> for $name {
+                fn from(v2: TokenV2) -> Self {
+                    Self {
+                        secret: v2.secret,
+                        scopes: v2.scopes,
+                    }
+                }
+            }
+
+            impl From<&$name> for TokenV2 {
+                fn from(token: &$name) -> Self {
+                    Self {
+                        secret: token.secret.clone(),
+                        scopes: token.scopes.clone(),
+                    }
+                }
+            }
+        };
+    }
+
+    token_domain!(WarnToken,);
+    token_domain!(StrictToken, , on_deprecated = "error");
+    token_domain!(LenientToken, , on_deprecated = "accept");
+
+    const V1: &str = r#"{"_version":"1","secret":"abc"}"#;
+    const V2: &str = r#"{"_version":"2","secret":"abc","scopes":["read"]}"#;
+
+    #[test]
+    fn warn_and_accept_policies_read_deprecated_versions() {
+        let rep: WarnTokenVersions = serde_json::from_str(V1).unwrap();
+        assert_eq!(WarnToken::from(rep).secret, "abc");
+
+        let rep: LenientTokenVersions = serde_json::from_str(V1).unwrap();
+        assert_eq!(rep.version(), 1);
+    }
+
+    #[test]
+    fn error_policy_rejects_deprecated_versions() {
+        let err = serde_json::from_str::<StrictTokenVersions>(V1).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("version 1 of StrictToken is deprecated")
+        );
+
+        let rep: StrictTokenVersions = serde_json::from_str(V2).unwrap();
+        assert!(rep.is_current());
+    }
+}
+
+mod async_migrations {
+    use super::*;
+    use serde_evolve::TryFromAsync;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    /// Drive a future that never waits on I/O to completion.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct RemapError(u32);
+
+    impl From<std::convert::Infallible> for RemapError {
+        fn from(never: std::convert::Infallible) -> Self {
+            match never {}
+        }
+    }
+
+    /// Stands in for a remote ID-remapping service.
+    fn remap(legacy_id: u32) -> std::future::Ready<Result<String, RemapError>> {
+        std::future::ready(if legacy_id == 0 {
+            Err(RemapError(legacy_id))
+        } else {
+            Ok(format!("acct-{legacy_id}"))
+        })
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct AccountV1 {
+        pub legacy_id: u32,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct AccountV2 {
+        pub id: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct AccountV3 {
+        pub id: String,
+        pub active: bool,
+    }
+
+    impl TryFromAsync<AccountV1> for AccountV2 {
+        type Error = RemapError;
+
+        async fn try_from_async(v1: AccountV1) -> Result<Self, Self::Error> {
+            Ok(Self {
+                id: remap(v1.legacy_id).await?,
+            })
+        }
+    }
+
+    impl From<AccountV2> for AccountV3 {
+        fn from(v2: AccountV2) -> Self {
+            Self {
+                id: v2.id,
+                active: true,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(error = RemapError, chain(AccountV1, AccountV2 async, AccountV3))]
+    pub struct Account {
+        pub id: String,
+        pub active: bool,
+    }
+
+    impl From<AccountV3> for Account {
+        fn from(v3: AccountV3) -> Self {
+            Self {
+                id: v3.id,
+                active: v3.active,
+            }
+        }
+    }
+
+    impl From<&Account> for AccountV3 {
+        fn from(account: &Account) -> Self {
+            Self {
+                id: account.id.clone(),
+                active: account.active,
+            }
+        }
+    }
+
+    #[test]
+    fn async_steps_are_awaited() {
+        let rep: AccountVersions =
+            serde_json::from_str(r#"{"_version":"1","legacy_id":42}"#).unwrap();
+        let account = block_on(Account::from_rep(rep)).unwrap();
+        assert_eq!(
+            account,
+            Account {
+                id: "acct-42".to_string(),
+                active: true
+            }
+        );
+
+        let rep: AccountVersions =
+            serde_json::from_str(r#"{"_version":"1","legacy_id":0}"#).unwrap();
+        assert_eq!(block_on(Account::from_rep(rep)).unwrap_err(), RemapError(0));
+
+        let rep = AccountVersions::from(&account);
+        assert_eq!(block_on(Account::from_rep(rep)).unwrap(), account);
+    }
+}
+
+mod manual_chain {
+    use super::*;
+    use serde_evolve::Payload;
+    use serde_evolve::migrate::{Chain, Error};
+
+    // Stand-ins for generated types that cannot carry the derive.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ConfigV1 {
+        pub port: u16,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ConfigV2 {
+        pub port: u16,
+        pub host: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Config {
+        pub address: String,
+    }
+
+    impl From<ConfigV1> for ConfigV2 {
+        fn from(v1: ConfigV1) -> Self {
+            Self {
+                port: v1.port,
+                host: "localhost".to_string(),
+            }
+        }
+    }
+
+    impl TryFrom<ConfigV2> for Config {
+        type Error = String;
+
+        fn try_from(v2: ConfigV2) -> Result<Self, Self::Error> {
+            if v2.port == 0 {
+                return Err("port must be non-zero".to_string());
+            }
+            Ok(Self {
+                address: format!("{}:{}", v2.host, v2.port),
+            })
+        }
+    }
+
+    fn payload(json: &str) -> Payload {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn chains_can_be_assembled_without_the_derive() {
+        let chain = Chain::<ConfigV1>::start()
+            .then::<ConfigV2>()
+            .finish::<Config>();
+        assert_eq!(chain.current(), 2);
+
+        let config = chain.migrate_value(1, payload(r#"{"port":80}"#)).unwrap();
+        assert_eq!(config.address, "localhost:80");
+
+        let config = chain
+            .migrate_value(2, payload(r#"{"port":8080,"host":"example.com"}"#))
+            .unwrap();
+        assert_eq!(config.address, "example.com:8080");
+
+        let mut de = serde_json::Deserializer::from_str(r#"{"_version":"1","port":443}"#);
+        let config = chain.deserialize("_version", &mut de).unwrap();
+        assert_eq!(config.address, "localhost:443");
+    }
+
+    #[test]
+    fn chains_can_keep_their_own_tags() {
+        let chain = Chain::<ConfigV1>::start()
+            .tagged("v1alpha1")
+            .then::<ConfigV2>()
+            .finish::<Config>();
+        assert_eq!(chain.version_of_tag("v1alpha1"), Some(1));
+        assert_eq!(chain.version_of_tag("1"), None);
+        assert_eq!(chain.version_of_tag("2"), Some(2));
+
+        let mut de = serde_json::Deserializer::from_str(r#"{"_version":"v1alpha1","port":443}"#);
+        let config = chain.deserialize("_version", &mut de).unwrap();
+        assert_eq!(config.address, "localhost:443");
+
+        let mut de = serde_json::Deserializer::from_str(r#"{"_version":"1","port":443}"#);
+        let err = chain.deserialize("_version", &mut de).unwrap_err();
+        assert!(err.to_string().contains("invalid version tag `1`"));
+    }
+
+    #[test]
+    fn chain_errors_are_typed() {
+        let chain = Chain::<ConfigV1>::start()
+            .then::<ConfigV2>()
+            .finish::<Config>();
+
+        assert!(matches!(
+            chain.migrate_value(3, payload("{}")),
+            Err(Error::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            chain.migrate_value(1, payload(r#"{"port":"x"}"#)),
+            Err(Error::Payload(_))
+        ));
+        let err = chain
+            .migrate_value(1, payload(r#"{"port":0}"#))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "migration failed: port must be non-zero");
+    }
+
+    #[test]
+    fn chain_steps_can_be_closures() {
+        let default_host = "plugin.local".to_string();
+        let chain = Chain::<ConfigV1>::start()
+            .then_with(move |v1: ConfigV1| {
+                Ok::<_, std::convert::Infallible>(ConfigV2 {
+                    port: v1.port,
+                    host: default_host.clone(),
+                })
+            })
+            .finish_with(|v2: ConfigV2| {
+                if v2.port == 0 {
+                    return Err("port must be non-zero");
+                }
+                Ok(Config {
+                    address: format!("{}:{}", v2.host, v2.port),
+                })
+            });
+        assert_eq!(chain.current(), 2);
+
+        let config = chain.migrate_value(1, payload(r#"{"port":80}"#)).unwrap();
+        assert_eq!(config.address, "plugin.local:80");
+
+        let err = chain
+            .migrate_value(2, payload(r#"{"port":0,"host":"x"}"#))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "migration failed: port must be non-zero");
+    }
+}
+
+mod generated_tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct LampV1 {
+        pub on: bool,
+    }
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct LampV2 {
+        pub brightness: u8,
+    }
+
+    impl From<LampV1> for LampV2 {
+        fn from(v1: LampV1) -> Self {
+            Self {
+                brightness: if v1.on { 100 } else { 0 },
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Versioned)]
+    #[versioned(mode = "infallible", chain(LampV1, LampV2), generate_tests = true)]
+    pub struct Lamp {
+        pub brightness: u8,
+    }
+
+    impl From<LampV2> for Lamp {
+        fn from(v2: LampV2) -> Self {
+            Self {
+                brightness: v2.brightness,
+            }
+        }
+    }
+
+    impl From<&Lamp> for LampV2 {
+        fn from(lamp: &Lamp) -> Self {
+            Self {
+                brightness: lamp.brightness,
+            }
+        }
+    }
+
+    // Only the latest version has a `Default` impl, and its default is rejected by the
+    // domain type; the generated tests must still compile and pass.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PortV1 {
+        pub number: u16,
+    }
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    pub struct PortV2 {
+        pub number: u16,
+        pub protocol: String,
+    }
+
+    impl TryFrom<PortV1> for PortV2 {
+        type Error = String;
+
+        fn try_from(v1: PortV1) -> Result<Self, Self::Error> {
+            Ok(Self {
+                number: v1.number,
+                protocol: "tcp".to_string(),
+            })
+        }
+    }
+
+    #[derive(Clone, Debug, Versioned)]
+    #[versioned(error = "String", chain(PortV1, PortV2), generate_tests = true)]
+    pub struct Port {
+        pub number: u16,
+        pub protocol: String,
+    }
+
+    impl TryFrom<PortV2> for Port {
+        type Error = String;
+
+        fn try_from(v2: PortV2) -> Result<Self, Self::Error> {
+            if v2.number == 0 {
+                return Err("port must be non-zero".to_string());
+            }
+            Ok(Self {
+                number: v2.number,
+                protocol: v2.protocol,
+            })
+        }
+    }
+
+    impl From<&Port> for PortV2 {
+        fn from(port: &Port) -> Self {
+            Self {
+                number: port.number,
+                protocol: port.protocol.clone(),
+            }
+        }
+    }
+}
+
+mod borrowed_versions {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct LineV1<'a> {
+        pub text: &'a str,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct LineV2<'a> {
+        pub text: &'a str,
+        pub level: u8,
+    }
+
+    impl<'a> From<LineV1<'a>> for LineV2<'a> {
+        fn from(v1: LineV1<'a>) -> Self {
+            Self {
+                text: v1.text,
+                level: 0,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(LineV1<'a>, LineV2<'a>), transparent = true)]
+    pub struct Line<'a> {
+        pub text: &'a str,
+        pub level: u8,
+    }
+
+    impl<'a> From<LineV2<'a>> for Line<'a> {
+        fn from(v2: LineV2<'a>) -> Self {
+            Self {
+                text: v2.text,
+                level: v2.level,
+            }
+        }
+    }
+
+    impl<'a> From<&Line<'a>> for LineV2<'a> {
+        fn from(line: &Line<'a>) -> Self {
+            Self {
+                text: line.text,
+                level: line.level,
+            }
+        }
+    }
+
+    #[test]
+    fn borrows_from_the_input() {
+        let input = String::from(r#"{"_version":"1","text":"hello"}"#);
+        let rep: LineVersions<'_> = serde_json::from_str(&input).unwrap();
+        let line = Line::from(rep);
+        assert_eq!(line.text, "hello");
+        let offset = input.find("hello").unwrap();
+        assert!(std::ptr::eq(line.text.as_ptr(), input[offset..].as_ptr()));
+
+        let json = serde_json::to_string(&line).unwrap();
+        assert_eq!(json, r#"{"_version":"2","text":"hello","level":0}"#);
+        let line: Line<'_> = serde_json::from_str(&json).unwrap();
+        assert_eq!(line.text, "hello");
+    }
+}
+
+mod latest_ref {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct NoteV1 {
+        pub body: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct NoteV2 {
+        pub body: String,
+        pub tags: Vec<String>,
+    }
+
+    #[derive(Serialize)]
+    pub struct NoteV2Ref<'a> {
+        pub body: &'a str,
+        pub tags: &'a [String],
+    }
+
+    impl From<NoteV1> for NoteV2 {
+        fn from(v1: NoteV1) -> Self {
+            Self {
+                body: v1.body,
+                tags: Vec::new(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(NoteV1, NoteV2),
+        transparent = true,
+        latest_ref = NoteV2Ref
+    )]
+    pub struct Note {
+        pub body: String,
+        pub tags: Vec<String>,
+    }
+
+    impl From<NoteV2> for Note {
+        fn from(v2: NoteV2) -> Self {
+            Self {
+                body: v2.body,
+                tags: v2.tags,
+            }
+        }
+    }
+
+    impl From<&Note> for NoteV2 {
+        fn from(note: &Note) -> Self {
+            Self {
+                body: note.body.clone(),
+                tags: note.tags.clone(),
+            }
+        }
+    }
+
+    impl<'a> From<&'a Note> for NoteV2Ref<'a> {
+        fn from(note: &'a Note) -> Self {
+            Self {
+                body: &note.body,
+                tags: &note.tags,
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Wrapper<'a> {
+        #[serde(serialize_with = "NoteVersions::serialize_latest")]
+        note: &'a Note,
+    }
+
+    fn note() -> Note {
+        Note {
+            body: "hello".to_string(),
+            tags: vec!["greeting".to_string()],
+        }
+    }
+
+    #[test]
+    fn matches_the_owned_representation() {
+        let note = note();
+        let borrowed = serde_json::to_string(&note).unwrap();
+        let owned = serde_json::to_string(&NoteVersions::from(&note)).unwrap();
+        assert_eq!(borrowed, owned);
+        assert_eq!(
+            borrowed,
+            r#"{"_version":"2","body":"hello","tags":["greeting"]}"#
+        );
+
+        let decoded: Note = serde_json::from_str(&borrowed).unwrap();
+        assert_eq!(decoded, note);
+    }
+
+    #[test]
+    fn works_with_serialize_with() {
+        let note = note();
+        let json = serde_json::to_value(Wrapper { note: &note }).unwrap();
+        assert_eq!(json["note"]["_version"], "2");
+        assert_eq!(json["note"]["body"], "hello");
+    }
+}
+
+mod step_context {
+    use super::*;
+    use serde_evolve::StepError;
+    use std::convert::TryFrom;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PriceV1 {
+        pub amount: f64,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PriceV2 {
+        pub cents: i64,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PriceV3 {
+        pub cents: u64,
+    }
+
+    impl TryFrom<PriceV1> for PriceV2 {
+        type Error = String;
+
+        fn try_from(v1: PriceV1) -> Result<Self, Self::Error> {
+            if !v1.amount.is_finite() {
+                return Err("amount must be finite".to_string());
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            Ok(Self {
+                cents: (v1.amount * 100.0).round() as i64,
+            })
+        }
+    }
+
+    impl TryFrom<PriceV2> for PriceV3 {
+        type Error = String;
+
+        fn try_from(v2: PriceV2) -> Result<Self, Self::Error> {
+            u64::try_from(v2.cents)
+                .map(|cents| Self { cents })
+                .map_err(|_| format!("{} cents is negative", v2.cents))
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(error = String, step_context = true, chain(PriceV1, PriceV2, PriceV3))]
+    pub struct Price {
+        pub cents: u64,
+    }
+
+    impl TryFrom<PriceV3> for Price {
+        type Error = String;
+
+        fn try_from(v3: PriceV3) -> Result<Self, Self::Error> {
+            if v3.cents == 0 {
+                return Err("price must not be zero".to_string());
+            }
+            Ok(Self { cents: v3.cents })
+        }
+    }
+
+    impl From<&Price> for PriceV3 {
+        fn from(price: &Price) -> Self {
+            Self { cents: price.cents }
+        }
+    }
+
+    #[test]
+    fn errors_name_the_failing_step() {
+        let err = Price::try_from(PriceVersions::V1(PriceV1 { amount: f64::NAN })).unwrap_err();
+        assert_eq!(
+            err,
+            StepError::new("Price", 1, Some(2), "amount must be finite".to_string())
+        );
+
+        let err = Price::try_from(PriceVersions::V1(PriceV1 { amount: -1.0 })).unwrap_err();
+        assert_eq!((err.from, err.to), (2, Some(3)));
+        assert_eq!(
+            err.to_string(),
+            "migrating Price from V2 to V3 (read as V1): -100 cents is negative"
+        );
+
+        let err = Price::try_from(PriceVersions::V2(PriceV2 { cents: 0 })).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "converting Price from V3 (read as V2): price must not be zero"
+        );
+        assert_eq!(err.into_inner(), "price must not be zero");
+    }
+
+    #[test]
+    fn accessors_report_step_context() {
+        let rep = PriceVersions::V2(PriceV2 { cents: -5 });
+        assert_eq!(rep.clone().upgrade_once().unwrap_err().from, 2);
+        assert_eq!(rep.into_latest().unwrap_err().to, Some(3));
+
+        let price = Price::try_from(PriceVersions::V1(PriceV1 { amount: 1.5 })).unwrap();
+        assert_eq!(price, Price { cents: 150 });
+    }
+}
+
+mod migration_errors {
+    use super::*;
+    use serde_evolve::{ConvertError, MigrationStep};
+    use std::convert::TryFrom;
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum QuotaError {
+        Unlimited,
+        TooLarge(u64),
+        Empty,
+    }
+
+    impl std::fmt::Display for QuotaError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::Unlimited => f.write_str("unlimited quotas are no longer supported"),
+                Self::TooLarge(bytes) => write!(f, "{bytes} bytes exceeds the maximum"),
+                Self::Empty => f.write_str("quota must not be empty"),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct QuotaV1 {
+        pub megabytes: Option<u64>,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct QuotaV2 {
+        pub bytes: u64,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct QuotaV3 {
+        pub bytes: u32,
+    }
+
+    impl TryFrom<QuotaV1> for QuotaV2 {
+        type Error = QuotaError;
+
+        fn try_from(v1: QuotaV1) -> Result<Self, Self::Error> {
+            let megabytes = v1.megabytes.ok_or(QuotaError::Unlimited)?;
+            Ok(Self {
+                bytes: megabytes * 1_000_000,
+            })
+        }
+    }
+
+    impl TryFrom<QuotaV2> for QuotaV3 {
+        type Error = QuotaError;
+
+        fn try_from(v2: QuotaV2) -> Result<Self, Self::Error> {
+            u32::try_from(v2.bytes)
+                .map(|bytes| Self { bytes })
+                .map_err(|_| QuotaError::TooLarge(v2.bytes))
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        error = QuotaError,
+        step_context = true,
+        downgrade = true,
+        chain(QuotaV1, QuotaV2, QuotaV3)
+    )]
+    pub struct Quota {
+        pub bytes: u32,
+    }
+
+    impl TryFrom<QuotaV3> for Quota {
+        type Error = QuotaError;
+
+        fn try_from(v3: QuotaV3) -> Result<Self, Self::Error> {
+            if v3.bytes == 0 {
+                return Err(QuotaError::Empty);
+            }
+            Ok(Self { bytes: v3.bytes })
+        }
+    }
+
+    impl From<QuotaV3> for QuotaV2 {
+        fn from(v3: QuotaV3) -> Self {
+            Self {
+                bytes: u64::from(v3.bytes),
+            }
+        }
+    }
+
+    impl From<QuotaV2> for QuotaV1 {
+        fn from(v2: QuotaV2) -> Self {
+            Self {
+                megabytes: Some(v2.bytes / 1_000_000),
+            }
+        }
+    }
+
+    impl From<&Quota> for QuotaV3 {
+        fn from(quota: &Quota) -> Self {
+            Self { bytes: quota.bytes }
+        }
+    }
+
+    #[test]
+    fn errors_record_the_source_version_and_failed_step() {
+        let rep = QuotaVersions::V1(QuotaV1 {
+            megabytes: Some(5_000),
+        });
+        let err = Quota::try_from(rep).unwrap_err();
+        assert_eq!(err.source_version, 1);
+        assert_eq!(
+            err.failed_step(),
+            MigrationStep {
+                from: 2,
+                to: Some(3)
+            }
+        );
+        assert_eq!(*err, QuotaError::TooLarge(5_000_000_000));
+        assert_eq!(
+            err.to_string(),
+            "migrating Quota from V2 to V3 (read as V1): 5000000000 bytes exceeds the maximum"
+        );
+
+        let err = Quota::try_from(QuotaVersions::V1(QuotaV1 { megabytes: None })).unwrap_err();
+        assert!(matches!(*err, QuotaError::Unlimited));
+        assert_eq!(err.failed_step().to, Some(2));
+        assert_eq!(err.into_inner(), QuotaError::Unlimited);
+
+        let err = Quota::try_from(QuotaVersions::V2(QuotaV2 { bytes: 0 })).unwrap_err();
+        assert_eq!(
+            (err.source_version, err.failed_step()),
+            (2, MigrationStep { from: 3, to: None })
+        );
+        assert_eq!(
+            err.to_string(),
+            "converting Quota from V3 (read as V2): quota must not be empty"
+        );
+    }
+
+    #[test]
+    fn conversions_report_the_version_they_started_from() {
+        let rep = QuotaVersions::V1(QuotaV1 {
+            megabytes: Some(5_000),
+        });
+        let Err(ConvertError::Step(err)) = rep.convert_to(3) else {
+            panic!("expected a failed step");
+        };
+        assert_eq!(err.source_version, 1);
+        assert_eq!(err.failed_step().from, 2);
+
+        let rep = QuotaVersions::V2(QuotaV2 { bytes: 1 << 40 });
+        assert_eq!(rep.upgrade_once().unwrap_err().source_version, 2);
+    }
+}
+
+mod infallible_entries {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SensorV1 {
+        pub reading: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SensorV2 {
+        pub reading: f64,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SensorV3 {
+        pub reading: f64,
+        pub unit: String,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct InvalidReading(String);
+
+    impl TryFrom<SensorV1> for SensorV2 {
+        type Error = InvalidReading;
+
+        fn try_from(v1: SensorV1) -> Result<Self, Self::Error> {
+            v1.reading
+                .parse()
+                .map(|reading| Self { reading })
+                .map_err(|_| InvalidReading(v1.reading))
+        }
+    }
+
+    // No `From<Infallible>` for `InvalidReading` is needed: the step is marked `infallible`.
+    impl From<SensorV2> for SensorV3 {
+        fn from(v2: SensorV2) -> Self {
+            Self {
+                reading: v2.reading,
+                unit: "celsius".to_string(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Versioned)]
+    #[versioned(error = InvalidReading, chain(SensorV1, SensorV2, SensorV3 infallible))]
+    pub struct Sensor {
+        pub reading: f64,
+        pub unit: String,
+    }
+
+    impl TryFrom<SensorV3> for Sensor {
+        type Error = InvalidReading;
+
+        fn try_from(v3: SensorV3) -> Result<Self, Self::Error> {
+            Ok(Self {
+                reading: v3.reading,
+                unit: v3.unit,
+            })
+        }
+    }
+
+    impl From<&Sensor> for SensorV3 {
+        fn from(sensor: &Sensor) -> Self {
+            Self {
+                reading: sensor.reading,
+                unit: sensor.unit.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn infallible_steps_use_from_within_fallible_chains() {
+        let rep: SensorVersions =
+            serde_json::from_str(r#"{"_version":"1","reading":"21.5"}"#).unwrap();
+        assert_eq!(
+            Sensor::try_from(rep),
+            Ok(Sensor {
+                reading: 21.5,
+                unit: "celsius".to_string()
+            })
+        );
+
+        let rep: SensorVersions =
+            serde_json::from_str(r#"{"_version":"1","reading":"warm"}"#).unwrap();
+        assert_eq!(
+            Sensor::try_from(rep),
+            Err(InvalidReading("warm".to_string()))
+        );
+
+        let rep = SensorVersions::V2(SensorV2 { reading: 3.0 });
+        assert!(rep.upgrade_once().unwrap().is_current());
+    }
+}
+
+mod retired_versions {
+    use super::*;
+    use serde_evolve::UnsupportedVersion;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct TicketV3 {
+        pub title: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct TicketV4 {
+        pub title: String,
+        pub priority: u8,
+    }
+
+    impl From<TicketV3> for TicketV4 {
+        fn from(v3: TicketV3) -> Self {
+            Self {
+                title: v3.title,
+                priority: 0,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(TicketV3, TicketV4), min_supported = 3)]
+    pub struct Ticket {
+        pub title: String,
+        pub priority: u8,
+    }
+
+    impl From<TicketV4> for Ticket {
+        fn from(v4: TicketV4) -> Self {
+            Self {
+                title: v4.title,
+                priority: v4.priority,
+            }
+        }
+    }
+
+    impl From<&Ticket> for TicketV4 {
+        fn from(ticket: &Ticket) -> Self {
+            Self {
+                title: ticket.title.clone(),
+                priority: ticket.priority,
+            }
+        }
+    }
+
+    #[test]
+    fn versions_are_numbered_from_min_supported() {
+        assert_eq!(TicketVersions::MIN_SUPPORTED, 3);
+        assert_eq!(TicketVersions::CURRENT, 4);
+
+        let rep: TicketVersions =
+            serde_json::from_str(r#"{"_version":"3","title":"Fix"}"#).unwrap();
+        assert!(matches!(rep, TicketVersions::V3(_)));
+        assert_eq!(rep.steps_behind(), 1);
+        assert_eq!(
+            Ticket::from(rep),
+            Ticket {
+                title: "Fix".to_string(),
+                priority: 0
+            }
+        );
+
+        let json = serde_json::to_value(TicketVersions::from(&Ticket {
+            title: "Ship".to_string(),
+            priority: 2,
+        }))
+        .unwrap();
+        assert_eq!(json["_version"], "4");
+    }
+
+    #[test]
+    fn retired_versions_are_rejected_as_unsupported() {
+        let err = serde_json::from_str::<TicketVersions>(r#"{"_version":"2","title":"Old"}"#)
+            .unwrap_err();
+        let expected = UnsupportedVersion {
+            found: 2,
+            min: 3,
+            max: 4,
+        };
+        assert!(err.to_string().starts_with(&expected.to_string()));
+
+        let err = serde_json::from_str::<TicketVersions>(r#"{"_version":"5","title":"New"}"#)
+            .unwrap_err();
+        let expected = UnsupportedVersion {
+            found: 5,
+            min: 3,
+            max: 4,
+        };
+        assert!(err.to_string().starts_with(&expected.to_string()));
+    }
+
+    #[test]
+    fn unsupported_versions_can_be_matched() {
+        let err = serde_evolve::read_json::<TicketVersions>(r#"{"_version":"2","title":"Old"}"#)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            serde_evolve::VersionedError::UnsupportedVersion(UnsupportedVersion {
+                found: 2,
+                min: 3,
+                max: 4,
+            })
+        );
+
+        let err = serde_evolve::read_json::<TicketVersions>(r#"{"_version":"9","title":"New"}"#)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            serde_evolve::VersionedError::UnsupportedVersion(UnsupportedVersion { found: 9, .. })
+        ));
+    }
+}
+
+mod forwarded_derives {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    pub struct LabelV1 {
+        pub text: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    pub struct LabelV2 {
+        pub text: String,
+        pub colour: String,
+    }
+
+    impl From<LabelV1> for LabelV2 {
+        fn from(v1: LabelV1) -> Self {
+            Self {
+                text: v1.text,
+                colour: "grey".to_string(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(LabelV1, LabelV2),
+        derive(PartialEq, Eq, Hash),
+        rep_attrs(#[doc = "Every stored version of a label."] #[must_use])
+    )]
+    pub struct Label {
+        pub text: String,
+        pub colour: String,
+    }
+
+    impl From<LabelV2> for Label {
+        fn from(v2: LabelV2) -> Self {
+            Self {
+                text: v2.text,
+                colour: v2.colour,
+            }
+        }
+    }
+
+    impl From<&Label> for LabelV2 {
+        fn from(label: &Label) -> Self {
+            Self {
+                text: label.text.clone(),
+                colour: label.colour.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn representations_use_forwarded_derives() {
+        let v1 = LabelVersions::V1(LabelV1 {
+            text: "todo".to_string(),
+        });
+        assert_eq!(v1, v1.clone());
+        assert_ne!(v1, LabelVersions::V2(v1.clone().into_latest()));
+
+        let reps: HashSet<_> = [v1.clone(), v1].into_iter().collect();
+        assert_eq!(reps.len(), 1);
+    }
+}
+
+mod tag_aliases {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct NoteV1 {
+        pub body: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct NoteV2 {
+        pub body: String,
+        pub pinned: bool,
+    }
+
+    impl From<NoteV1> for NoteV2 {
+        fn from(v1: NoteV1) -> Self {
+            Self {
+                body: v1.body,
+                pinned: false,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(NoteV1 alias("v1", "1.0"), NoteV2 alias("v2"))
+    )]
+    pub struct Note {
+        pub body: String,
+        pub pinned: bool,
+    }
+
+    impl From<NoteV2> for Note {
+        fn from(v2: NoteV2) -> Self {
+            Self {
+                body: v2.body,
+                pinned: v2.pinned,
+            }
+        }
+    }
+
+    impl From<&Note> for NoteV2 {
+        fn from(note: &Note) -> Self {
+            Self {
+                body: note.body.clone(),
+                pinned: note.pinned,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(NoteV1 alias("v1"), NoteV2),
+        default_version = 1
+    )]
+    pub struct DefaultedNote {
+        pub body: String,
+        pub pinned: bool,
+    }
+
+    impl From<NoteV2> for DefaultedNote {
+        fn from(v2: NoteV2) -> Self {
+            Self {
+                body: v2.body,
+                pinned: v2.pinned,
+            }
+        }
+    }
+
+    impl From<&DefaultedNote> for NoteV2 {
+        fn from(note: &DefaultedNote) -> Self {
+            Self {
+                body: note.body.clone(),
+                pinned: note.pinned,
+            }
+        }
+    }
+
+    #[test]
+    fn every_spelling_reads_the_same_version() {
+        for tag in ["1", "v1", "1.0"] {
+            let json = format!(r#"{{"_version":"{tag}","body":"hello"}}"#);
+            let rep: NoteVersions = serde_json::from_str(&json).unwrap();
+            assert_eq!(rep.version(), 1);
+        }
+
+        let rep: NoteVersions =
+            serde_json::from_str(r#"{"_version":"v2","body":"hello","pinned":true}"#).unwrap();
+        assert_eq!(rep.version(), 2);
+    }
+
+    #[test]
+    fn serialization_writes_the_canonical_tag() {
+        let note = Note {
+            body: "hello".to_string(),
+            pinned: true,
+        };
+        let json = serde_json::to_value(NoteVersions::from(&note)).unwrap();
+        assert_eq!(json["_version"], "2");
+    }
+
+    #[test]
+    fn aliases_are_recognised_when_buffering() {
+        let rep: DefaultedNoteVersions =
+            serde_json::from_str(r#"{"_version":"v1","body":"hello"}"#).unwrap();
+        assert_eq!(rep.version(), 1);
+
+        let rep: DefaultedNoteVersions = serde_json::from_str(r#"{"body":"hello"}"#).unwrap();
+        assert_eq!(rep.version(), 1);
+    }
+}
+
+mod gated_entries {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SensorV1 {
+        pub reading: i32,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SensorV2 {
+        pub reading: i64,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SensorV3 {
+        pub reading: i64,
+        pub unit: String,
+    }
+
+    impl From<SensorV1> for SensorV2 {
+        fn from(v1: SensorV1) -> Self {
+            Self {
+                reading: v1.reading.into(),
+            }
+        }
+    }
+
+    impl From<SensorV2> for SensorV3 {
+        fn from(v2: SensorV2) -> Self {
+            Self {
+                reading: v2.reading,
+                unit: "celsius".to_string(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(SensorV1 cfg(any()), SensorV2 cfg(all()), SensorV3)
+    )]
+    pub struct Sensor {
+        pub reading: i64,
+        pub unit: String,
+    }
+
+    impl From<SensorV3> for Sensor {
+        fn from(v3: SensorV3) -> Self {
+            Self {
+                reading: v3.reading,
+                unit: v3.unit,
+            }
+        }
+    }
+
+    impl From<&Sensor> for SensorV3 {
+        fn from(sensor: &Sensor) -> Self {
+            Self {
+                reading: sensor.reading,
+                unit: sensor.unit.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn compiled_out_versions_are_not_recognised() {
+        let err =
+            serde_json::from_str::<SensorVersions>(r#"{"_version":"1","reading":3}"#).unwrap_err();
+        assert!(err.to_string().contains("unknown variant `1`"));
+    }
+
+    #[test]
+    fn enabled_versions_still_migrate() {
+        let rep: SensorVersions = serde_json::from_str(r#"{"_version":"2","reading":3}"#).unwrap();
+        assert_eq!(rep.version(), 2);
+        assert_eq!(
+            Sensor::from(rep),
+            Sensor {
+                reading: 3,
+                unit: "celsius".to_string(),
+            }
+        );
+        assert_eq!(SensorVersions::CURRENT, 3);
+    }
+
+    #[test]
+    fn supported_versions_omit_compiled_out_entries() {
+        assert_eq!(SensorVersions::SUPPORTED, &[2, 3]);
+        assert!(!SensorVersions::supports_version(1));
+        assert!(SensorVersions::supports_version(2));
+        assert!(!SensorVersions::supports_version(4));
+        assert_eq!(SensorVersions::negotiate(1, 1), None);
+        assert_eq!(SensorVersions::negotiate(1, 2), Some(2));
+    }
+}
+
+mod tag_overrides {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PlanV1 {
+        pub seats: u32,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PlanV2 {
+        pub seats: u32,
+        pub tier: String,
+    }
+
+    impl From<PlanV1> for PlanV2 {
+        fn from(v1: PlanV1) -> Self {
+            Self {
+                seats: v1.seats,
+                tier: "basic".to_string(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(PlanV1 as "initial" alias("v1"), PlanV2 as "2023-06")
+    )]
+    pub struct Plan {
+        pub seats: u32,
+        pub tier: String,
+    }
+
+    impl From<PlanV2> for Plan {
+        fn from(v2: PlanV2) -> Self {
+            Self {
+                seats: v2.seats,
+                tier: v2.tier,
+            }
+        }
+    }
+
+    impl From<&Plan> for PlanV2 {
+        fn from(plan: &Plan) -> Self {
+            Self {
+                seats: plan.seats,
+                tier: plan.tier.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn historical_tags_are_read_and_written() {
+        let rep: PlanVersions =
+            serde_json::from_str(r#"{"_version":"initial","seats":5}"#).unwrap();
+        assert_eq!(rep.version(), 1);
+        let rep: PlanVersions = serde_json::from_str(r#"{"_version":"v1","seats":5}"#).unwrap();
+        assert_eq!(rep.version(), 1);
+
+        let plan = Plan::from(rep);
+        let json = serde_json::to_value(PlanVersions::from(&plan)).unwrap();
+        assert_eq!(json["_version"], "2023-06");
+        assert_eq!(PlanVersions::CURRENT, 2);
+    }
+
+    #[test]
+    fn positional_tags_are_replaced() {
+        assert!(serde_json::from_str::<PlanVersions>(r#"{"_version":"1","seats":5}"#).is_err());
+    }
+
+    #[test]
+    fn the_version_table_lists_types_and_tags() {
+        let infos: Vec<_> = PlanVersions::VERSIONS
+            .iter()
+            .map(|info| (info.version, info.type_name, info.tag))
+            .collect();
+        assert_eq!(infos, [(1, "PlanV1", "initial"), (2, "PlanV2", "2023-06")]);
+        assert!(!PlanVersions::VERSIONS[0].deprecated);
+    }
+
+    #[test]
+    fn tags_map_to_version_numbers() {
+        use serde_evolve::Representation;
+
+        assert_eq!(PlanVersions::version_of_tag("2023-06"), Some(2));
+        assert_eq!(PlanVersions::version_of_tag("1"), None);
+        assert_eq!(PlanVersions::tag_of_version(1), Some("initial"));
+        assert_eq!(PlanVersions::tag_of_version(3), None);
+    }
+}
+
+mod discovered_chain {
+    use super::*;
+
+    #[serde_evolve::versioned_module]
+    pub mod ticket {
+        use super::*;
+
+        #[derive(Clone, Debug, Serialize, Deserialize)]
+        pub struct TicketV1 {
+            pub title: String,
+        }
+
+        #[derive(Clone, Debug, Serialize, Deserialize)]
+        pub struct TicketV2 {
+            pub title: String,
+            pub open: bool,
+        }
+
+        impl From<TicketV1> for TicketV2 {
+            fn from(v1: TicketV1) -> Self {
+                Self {
+                    title: v1.title,
+                    open: true,
+                }
+            }
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+        #[versioned(mode = "infallible")]
+        pub struct Ticket {
+            pub title: String,
+            pub open: bool,
+        }
+
+        impl From<TicketV2> for Ticket {
+            fn from(v2: TicketV2) -> Self {
+                Self {
+                    title: v2.title,
+                    open: v2.open,
+                }
+            }
+        }
+
+        impl From<&Ticket> for TicketV2 {
+            fn from(ticket: &Ticket) -> Self {
+                Self {
+                    title: ticket.title.clone(),
+                    open: ticket.open,
+                }
+            }
+        }
+    }
+
+    use ticket::{Ticket, TicketVersions};
+
+    #[test]
+    fn module_versions_form_the_chain() {
+        assert_eq!(TicketVersions::CURRENT, 2);
+
+        let rep: TicketVersions =
+            serde_json::from_str(r#"{"_version":"1","title":"bug"}"#).unwrap();
+        assert_eq!(
+            Ticket::from(rep),
+            Ticket {
+                title: "bug".to_string(),
+                open: true,
+            }
+        );
+    }
+}
+
+mod evolve_derive {
+    use super::*;
+    use serde_evolve::Evolve;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ProfileV1 {
+        pub title: String,
+        pub age: u32,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Evolve)]
+    #[evolve(from = ProfileV1)]
+    pub struct ProfileV2 {
+        #[evolve(rename_from = "title")]
+        pub name: String,
+        pub age: u32,
+        #[evolve(default)]
+        pub tags: Vec<String>,
+        #[evolve(default = "String::from(\"en\")")]
+        pub locale: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(ProfileV1, ProfileV2))]
+    pub struct Profile {
+        pub inner: ProfileV2,
+    }
+
+    impl From<ProfileV2> for Profile {
+        fn from(inner: ProfileV2) -> Self {
+            Self { inner }
+        }
+    }
+
+    impl From<&Profile> for ProfileV2 {
+        fn from(profile: &Profile) -> Self {
+            profile.inner.clone()
+        }
+    }
+
+    #[test]
+    fn derived_steps_rename_and_add_fields() {
+        let rep: ProfileVersions =
+            serde_json::from_str(r#"{"_version":"1","title":"Ada","age":36}"#).unwrap();
+        assert_eq!(
+            Profile::from(rep).inner,
+            ProfileV2 {
+                name: "Ada".to_string(),
+                age: 36,
+                tags: Vec::new(),
+                locale: "en".to_string(),
+            }
+        );
+    }
+}
+
+mod migrate_dsl {
+    use super::*;
+
+    serde_evolve::migrate! {
+        #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+        pub struct MemberV1 {
+            pub name: String,
+            pub legacy_flag: bool,
+        }
+
+        MemberV1 -> MemberV2 {
+            rename name => full_name;
+            add email: Option<String> = None;
+            drop legacy_flag;
+        }
+
+        MemberV2 -> MemberV3 {
+            add tags: Vec<String>;
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(MemberV1, MemberV2, MemberV3))]
+    pub struct Member {
+        pub inner: MemberV3,
+    }
+
+    impl From<MemberV3> for Member {
+        fn from(inner: MemberV3) -> Self {
+            Self { inner }
+        }
+    }
+
+    impl From<&Member> for MemberV3 {
+        fn from(member: &Member) -> Self {
+            member.inner.clone()
+        }
+    }
+
+    #[test]
+    fn declared_steps_rename_add_and_drop_fields() {
+        let rep: MemberVersions =
+            serde_json::from_str(r#"{"_version":"1","name":"Ada","legacy_flag":true}"#).unwrap();
+        assert_eq!(
+            Member::from(rep).inner,
+            MemberV3 {
+                full_name: "Ada".to_string(),
+                email: None,
+                tags: Vec::new(),
+            }
+        );
+    }
+}
+
+mod auto_migrate {
+    use super::*;
+
+    #[serde_evolve::versioned_module(auto_migrate)]
+    pub mod contact {
+        use super::*;
+
+        #[derive(Clone, Debug, Serialize, Deserialize)]
+        pub struct ContactV1 {
+            pub name: String,
+        }
+
+        #[derive(Clone, Debug, Serialize, Deserialize)]
+        pub struct ContactV2 {
+            pub name: String,
+            pub email: Option<String>,
+            pub starred: bool,
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+        #[versioned(mode = "infallible")]
+        pub struct Contact {
+            pub name: String,
+            pub email: Option<String>,
+        }
+
+        impl From<ContactV2> for Contact {
+            fn from(v2: ContactV2) -> Self {
+                Self {
+                    name: v2.name,
+                    email: v2.email,
+                }
+            }
+        }
+
+        impl From<&Contact> for ContactV2 {
+            fn from(contact: &Contact) -> Self {
+                Self {
+                    name: contact.name.clone(),
+                    email: contact.email.clone(),
+                    starred: false,
+                }
+            }
+        }
+    }
+
+    use contact::{Contact, ContactV2, ContactVersions};
+
+    #[test]
+    fn added_fields_take_their_defaults() {
+        let rep: ContactVersions =
+            serde_json::from_str(r#"{"_version":"1","name":"Ada"}"#).unwrap();
+        let v2: ContactV2 = rep.into_latest();
+        assert_eq!((v2.email, v2.starred), (None, false));
+        assert_eq!(
+            Contact::from(ContactVersions::from(contact::ContactV1 {
+                name: "Ada".to_string(),
+            })),
+            Contact {
+                name: "Ada".to_string(),
+                email: None,
+            }
+        );
+    }
+}
+
+mod self_latest {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BookmarkV1 {
+        pub url: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Versioned)]
+    #[versioned(
+        error = String,
+        chain(BookmarkV1),
+        self_latest = true,
+        downgrade = true
+    )]
+    pub struct Bookmark {
+        pub url: String,
+        pub title: String,
+    }
+
+    impl TryFrom<BookmarkV1> for Bookmark {
+        type Error = String;
+
+        fn try_from(v1: BookmarkV1) -> Result<Self, Self::Error> {
+            if v1.url.is_empty() {
+                return Err("empty url".to_string());
+            }
+            Ok(Self {
+                title: v1.url.clone(),
+                url: v1.url,
+            })
+        }
+    }
+
+    impl From<Bookmark> for BookmarkV1 {
+        fn from(bookmark: Bookmark) -> Self {
+            Self { url: bookmark.url }
+        }
+    }
+
+    fn sample() -> Bookmark {
+        Bookmark {
+            url: "https://example.com".to_string(),
+            title: "Example".to_string(),
+        }
+    }
+
+    #[test]
+    fn the_domain_type_is_the_latest_version() {
+        let bookmark = sample();
+        let rep = BookmarkVersions::from(&bookmark);
+        assert_eq!(rep.version(), 2);
+        assert!(matches!(&rep, BookmarkVersions::V2(latest) if *latest == bookmark));
+
+        let json = serde_json::to_value(&rep).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "_version": "2",
+                "url": "https://example.com",
+                "title": "Example",
+            })
+        );
+        let decoded: BookmarkVersions = serde_json::from_value(json).unwrap();
+        assert_eq!(Bookmark::try_from(decoded).unwrap(), bookmark);
+    }
+
+    #[test]
+    fn older_versions_migrate_into_the_domain_type() {
+        let rep: BookmarkVersions =
+            serde_json::from_str(r#"{"_version":"1","url":"https://a.example"}"#).unwrap();
+        assert_eq!(
+            rep.clone().into_latest().unwrap().title,
+            "https://a.example"
+        );
+        assert_eq!(
+            Bookmark::try_from(rep).unwrap().url,
+            "https://a.example".to_string()
+        );
+    }
+
+    #[test]
+    fn serialization_borrows_the_domain_value() {
+        let mut serializer = serde_json::Serializer::new(Vec::new());
+        BookmarkVersions::serialize_latest(&sample(), &mut serializer).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&serializer.into_inner()).unwrap();
+        assert_eq!(json["_version"], "2");
+        assert_eq!(json["title"], "Example");
+    }
+
+    #[test]
+    fn earlier_versions_can_still_be_written() {
+        let rep = BookmarkVersions::at_version(&sample(), 1).unwrap();
+        assert_eq!(rep.version(), 1);
+    }
+
+    #[test]
+    fn convert_to_reports_failed_steps() {
+        let rep = BookmarkVersions::from(&sample()).convert_to(1).unwrap();
+        assert_eq!(rep.version(), 1);
+        assert_eq!(rep.convert_to(2).unwrap().version(), 2);
+
+        let empty = BookmarkVersions::from(BookmarkV1 { url: String::new() });
+        assert_eq!(
+            empty.convert_to(2).unwrap_err(),
+            serde_evolve::ConvertError::Step("empty url".to_string())
+        );
+    }
+}
+
+mod generated_latest {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    pub struct InvoiceV1 {
+        pub total: u32,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(InvoiceV1),
+        generate_latest = true,
+        derive(PartialEq, Eq, Hash)
+    )]
+    pub struct Invoice {
+        pub total: u64,
+        /// ISO 4217 currency code.
+        pub currency: String,
+    }
+
+    impl From<InvoiceV1> for InvoiceV2 {
+        fn from(v1: InvoiceV1) -> Self {
+            Self {
+                total: v1.total.into(),
+                currency: "EUR".to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn the_latest_dto_mirrors_the_domain_type() {
+        let invoice = Invoice {
+            total: 12,
+            currency: "GBP".to_string(),
+        };
+        let json = serde_json::to_value(InvoiceVersions::from(&invoice)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"_version": "2", "total": 12, "currency": "GBP"})
+        );
+
+        let rep: InvoiceVersions = serde_json::from_value(json).unwrap();
+        assert_eq!(Invoice::from(rep), invoice);
+    }
+
+    #[test]
+    fn extra_derives_apply_to_the_generated_dto() {
+        let invoice = Invoice {
+            total: 1,
+            currency: "EUR".to_string(),
+        };
+        let reps: std::collections::HashSet<_> = [
+            InvoiceVersions::from(&invoice),
+            InvoiceVersions::from(InvoiceV1 { total: 1 }),
+            InvoiceVersions::from(invoice),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(reps.len(), 2);
+    }
+
+    #[test]
+    fn owned_values_convert_without_cloning() {
+        let invoice = Invoice {
+            total: 7,
+            currency: "USD".to_string(),
+        };
+        let rep = InvoiceVersions::from(invoice);
+        let latest: InvoiceV2 = rep.into_latest();
+        assert_eq!(latest.currency, "USD");
+    }
+
+    #[test]
+    fn historical_versions_migrate_through_the_generated_dto() {
+        let rep: InvoiceVersions = serde_json::from_str(r#"{"_version":"1","total":5}"#).unwrap();
+        let latest: InvoiceV2 = rep.clone().into_latest();
+        assert_eq!(latest.currency, "EUR");
+        assert_eq!(
+            Invoice::from(rep),
+            Invoice {
+                total: 5,
+                currency: "EUR".to_string(),
+            }
+        );
+    }
+}
+
+mod negotiation {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PingV2 {
+        pub id: u32,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PingV3 {
+        pub id: u64,
+    }
+
+    impl From<PingV2> for PingV3 {
+        fn from(v2: PingV2) -> Self {
+            Self { id: v2.id.into() }
+        }
+    }
+
+    #[derive(Clone, Debug, Versioned)]
+    #[versioned(mode = "infallible", chain(PingV2, PingV3), min_supported = 2)]
+    pub struct Ping {
+        pub id: u64,
+    }
+
+    impl From<PingV3> for Ping {
+        fn from(v3: PingV3) -> Self {
+            Self { id: v3.id }
+        }
+    }
+
+    impl From<&Ping> for PingV3 {
+        fn from(ping: &Ping) -> Self {
+            Self { id: ping.id }
+        }
+    }
+
+    #[test]
+    fn picks_the_newest_shared_version() {
+        assert_eq!(PingVersions::negotiate(1, 5), Some(3));
+        assert_eq!(PingVersions::negotiate(1, 2), Some(2));
+        assert_eq!(PingVersions::negotiate(3, 3), Some(3));
+    }
+
+    #[test]
+    fn rejects_disjoint_ranges() {
+        assert_eq!(PingVersions::negotiate(1, 1), None);
+        assert_eq!(PingVersions::negotiate(4, 6), None);
+        assert_eq!(PingVersions::negotiate(3, 2), None);
+    }
+
+    #[test]
+    fn is_usable_in_const_context() {
+        const AGREED: Option<u32> = PingVersions::negotiate(2, 2);
+        assert_eq!(AGREED, Some(2));
+    }
+}
+
+mod nested_versioned {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct AddressV1 {
+        pub line: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct AddressV2 {
+        pub line: String,
+        pub postcode: Option<String>,
+    }
+
+    impl From<AddressV1> for AddressV2 {
+        fn from(v1: AddressV1) -> Self {
+            Self {
+                line: v1.line,
+                postcode: None,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(AddressV1, AddressV2), transparent = true)]
+    pub struct Address {
+        pub line: String,
+        pub postcode: Option<String>,
+    }
+
+    impl From<AddressV2> for Address {
+        fn from(v2: AddressV2) -> Self {
+            Self {
+                line: v2.line,
+                postcode: v2.postcode,
+            }
+        }
+    }
+
+    impl From<&Address> for AddressV2 {
+        fn from(address: &Address) -> Self {
+            Self {
+                line: address.line.clone(),
+                postcode: address.postcode.clone(),
+            }
+        }
+    }
+
+    // The outer DTOs hold the inner *domain* type, which (being transparent) reads any
+    // version of its own chain and always writes the latest.
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct CustomerV1 {
+        pub name: String,
+        pub address: Address,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct CustomerV2 {
+        pub name: String,
+        pub addresses: Vec<Address>,
+    }
+
+    impl From<CustomerV1> for CustomerV2 {
+        fn from(v1: CustomerV1) -> Self {
+            Self {
+                name: v1.name,
+                addresses: vec![v1.address],
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(CustomerV1, CustomerV2), transparent = true)]
+    pub struct Customer {
+        pub name: String,
+        pub addresses: Vec<Address>,
+    }
+
+    impl From<CustomerV2> for Customer {
+        fn from(v2: CustomerV2) -> Self {
+            Self {
+                name: v2.name,
+                addresses: v2.addresses,
+            }
+        }
+    }
+
+    impl From<&Customer> for CustomerV2 {
+        fn from(customer: &Customer) -> Self {
+            Self {
+                name: customer.name.clone(),
+                addresses: customer.addresses.clone(),
+            }
+        }
+    }
+
+    // A flattened versioned type shares its parent's object, so it needs a tag of its own.
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(AddressV1, AddressV2),
+        transparent = true,
+        tag = "address_version"
+    )]
+    pub struct InlineAddress {
+        pub line: String,
+        pub postcode: Option<String>,
+    }
+
+    impl From<AddressV2> for InlineAddress {
+        fn from(v2: AddressV2) -> Self {
+            Self {
+                line: v2.line,
+                postcode: v2.postcode,
+            }
+        }
+    }
+
+    impl From<&InlineAddress> for AddressV2 {
+        fn from(address: &InlineAddress) -> Self {
+            Self {
+                line: address.line.clone(),
+                postcode: address.postcode.clone(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct BranchV1 {
+        pub name: String,
+        #[serde(flatten)]
+        pub address: InlineAddress,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(BranchV1), transparent = true)]
+    pub struct Branch {
+        pub name: String,
+        pub address: InlineAddress,
+    }
+
+    impl From<BranchV1> for Branch {
+        fn from(v1: BranchV1) -> Self {
+            Self {
+                name: v1.name,
+                address: v1.address,
+            }
+        }
+    }
+
+    impl From<&Branch> for BranchV1 {
+        fn from(branch: &Branch) -> Self {
+            Self {
+                name: branch.name.clone(),
+                address: branch.address.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn inner_versions_migrate_independently_of_the_outer_version() {
+        let json = r#"{
+            "_version": "1",
+            "name": "Ada",
+            "address": {"_version": "1", "line": "1 Analytical Way"}
+        }"#;
+        let customer: Customer = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            customer.addresses,
+            [Address {
+                line: "1 Analytical Way".to_string(),
+                postcode: None,
+            }]
+        );
+
+        let json = r#"{
+            "_version": "2",
+            "name": "Ada",
+            "addresses": [
+                {"_version": "1", "line": "old"},
+                {"_version": "2", "line": "new", "postcode": "AB1"}
+            ]
+        }"#;
+        let customer: Customer = serde_json::from_str(json).unwrap();
+        assert_eq!(customer.addresses[0].postcode, None);
+        assert_eq!(customer.addresses[1].postcode.as_deref(), Some("AB1"));
+    }
+
+    #[test]
+    fn every_level_is_written_at_its_latest_version() {
+        let customer = Customer {
+            name: "Ada".to_string(),
+            addresses: vec![Address {
+                line: "1 Analytical Way".to_string(),
+                postcode: None,
+            }],
+        };
+        let json = serde_json::to_value(&customer).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "_version": "2",
+                "name": "Ada",
+                "addresses": [{"_version": "2", "line": "1 Analytical Way", "postcode": null}],
+            })
+        );
+        assert_eq!(serde_json::from_value::<Customer>(json).unwrap(), customer);
+    }
+
+    #[test]
+    fn flattened_types_use_their_own_tag() {
+        let json = r#"{"_version":"1","name":"Central","address_version":"1","line":"High St"}"#;
+        let branch: Branch = serde_json::from_str(json).unwrap();
+        assert_eq!(branch.address.line, "High St");
+
+        let json = serde_json::to_value(&branch).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "_version": "1",
+                "name": "Central",
+                "address_version": "2",
+                "line": "High St",
+                "postcode": null,
+            })
+        );
+    }
+}
+
+mod strict_fields {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ReceiptV1 {
+        #[serde(alias = "sum")]
+        pub amount: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub note: Option<String>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(ReceiptV1),
+        generate_latest = true,
+        deny_unknown_fields = true
+    )]
+    pub struct Receipt {
+        pub amount: u64,
+        pub note: Option<String>,
+    }
+
+    impl From<ReceiptV1> for ReceiptV2 {
+        fn from(v1: ReceiptV1) -> Self {
+            Self {
+                amount: v1.amount.into(),
+                note: v1.note,
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_undeclared_keys() {
+        let err = serde_json::from_str::<ReceiptVersions>(
+            r#"{"_version":"1","amount":3,"currency":"EUR"}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown field `currency`"));
+
+        let err = serde_json::from_str::<ReceiptVersions>(
+            r#"{"_version":"2","amount":3,"note":null,"extra":true}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown field `extra`"));
+    }
+
+    #[test]
+    fn accepts_aliases_and_skipped_fields() {
+        let rep: ReceiptVersions =
+            serde_json::from_str(r#"{"_version":"1","sum":3,"note":null}"#).unwrap();
+        assert_eq!(
+            Receipt::from(rep),
+            Receipt {
+                amount: 3,
+                note: None,
+            }
+        );
+    }
+}
+
+mod fallible_serialization {
+    use super::*;
+    use std::fmt;
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct RedactionError(&'static str);
+
+    impl fmt::Display for RedactionError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "cannot redact: {}", self.0)
+        }
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SecretV1 {
+        pub label: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SecretV2 {
+        pub label: String,
+        pub hint: String,
+    }
+
+    impl From<SecretV1> for SecretV2 {
+        fn from(v1: SecretV1) -> Self {
+            Self {
+                label: v1.label,
+                hint: String::new(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(SecretV1, SecretV2),
+        transparent = true,
+        serialize_error = RedactionError
+    )]
+    pub struct Secret {
+        pub label: String,
+        pub value: String,
+    }
+
+    impl From<SecretV2> for Secret {
+        fn from(v2: SecretV2) -> Self {
+            Self {
+                label: v2.label,
+                value: String::new(),
+            }
+        }
+    }
+
+    impl TryFrom<&Secret> for SecretV2 {
+        type Error = RedactionError;
+
+        fn try_from(secret: &Secret) -> Result<Self, Self::Error> {
+            if secret.value.len() < 4 {
+                return Err(RedactionError("value too short to hint"));
+            }
+            Ok(Self {
+                label: secret.label.clone(),
+                hint: secret.value[..2].to_string(),
+            })
+        }
+    }
+
+    #[test]
+    fn converts_valid_values() {
+        let secret = Secret {
+            label: "db".to_string(),
+            value: "hunter2".to_string(),
+        };
+        let rep = SecretVersions::try_from(&secret).unwrap();
+        assert!(rep.is_current());
+
+        assert_eq!(
+            serde_json::to_value(&secret).unwrap(),
+            serde_json::json!({"_version": "2", "label": "db", "hint": "hu"})
+        );
+    }
+
+    #[test]
+    fn surfaces_conversion_errors_from_serialize() {
+        let secret = Secret {
+            label: "db".to_string(),
+            value: "pw".to_string(),
+        };
+        assert_eq!(
+            SecretVersions::try_from(&secret).unwrap_err(),
+            RedactionError("value too short to hint")
+        );
+
+        let err = serde_json::to_string(&secret).unwrap_err();
+        assert_eq!(err.to_string(), "cannot redact: value too short to hint");
+    }
+}
+
+mod owned_conversion {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BlobV1 {
+        pub bytes: Vec<u8>,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BlobV2 {
+        pub bytes: Vec<u8>,
+        pub mime: String,
+    }
+
+    impl From<BlobV1> for BlobV2 {
+        fn from(v1: BlobV1) -> Self {
+            Self {
+                bytes: v1.bytes,
+                mime: "application/octet-stream".to_string(),
+            }
+        }
+    }
+
+    // Deliberately not `Clone`.
+    #[derive(Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(BlobV1, BlobV2), from_owned = true)]
+    pub struct Blob {
+        pub bytes: Vec<u8>,
+        pub mime: String,
+    }
+
+    impl From<BlobV2> for Blob {
+        fn from(v2: BlobV2) -> Self {
+            Self {
+                bytes: v2.bytes,
+                mime: v2.mime,
+            }
+        }
+    }
+
+    impl From<&Blob> for BlobV2 {
+        fn from(blob: &Blob) -> Self {
+            Self {
+                bytes: blob.bytes.clone(),
+                mime: blob.mime.clone(),
+            }
+        }
+    }
+
+    impl From<Blob> for BlobV2 {
+        fn from(blob: Blob) -> Self {
+            Self {
+                bytes: blob.bytes,
+                mime: blob.mime,
+            }
+        }
+    }
+
+    #[test]
+    fn moves_owned_values_into_the_latest_version() {
+        let blob = Blob {
+            bytes: vec![1, 2, 3],
+            mime: "image/png".to_string(),
+        };
+        let ptr = blob.bytes.as_ptr();
+
+        let BlobVersions::V2(latest) = BlobVersions::from(blob) else {
+            panic!("expected the latest version");
+        };
+        assert_eq!(latest.bytes.as_ptr(), ptr);
+        assert_eq!(
+            serde_json::to_value(BlobVersions::from(latest)).unwrap(),
+            serde_json::json!({"_version": "2", "bytes": [1, 2, 3], "mime": "image/png"})
+        );
+    }
+}
+
+mod crate_path {
+    use super::*;
+
+    pub mod facade {
+        pub use serde_evolve as evolve;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct TagV1 {
+        pub name: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct TagV2 {
+        pub name: String,
+        pub colour: String,
+    }
+
+    impl From<TagV1> for TagV2 {
+        fn from(v1: TagV1) -> Self {
+            Self {
+                name: v1.name,
+                colour: "grey".to_string(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(TagV1, TagV2),
+        transparent = true,
+        on_migrate = record,
+        crate = "crate::crate_path::facade::evolve"
+    )]
+    pub struct Tag {
+        pub name: String,
+        pub colour: String,
+    }
+
+    const fn record(_: &facade::evolve::hook::MigrationEvent) {}
+
+    impl From<TagV2> for Tag {
+        fn from(v2: TagV2) -> Self {
+            Self {
+                name: v2.name,
+                colour: v2.colour,
+            }
+        }
+    }
+
+    impl From<&Tag> for TagV2 {
+        fn from(tag: &Tag) -> Self {
+            Self {
+                name: tag.name.clone(),
+                colour: tag.colour.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn generated_code_resolves_through_the_facade() {
+        use facade::evolve::Representation;
+
+        let tag: Tag = serde_json::from_str(r#"{"_version":"1","name":"rust"}"#).unwrap();
+        assert_eq!(tag.colour, "grey");
+        assert_eq!(<TagVersions as Representation>::CURRENT, 2);
+    }
+}
+
+mod tuple_domains {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct EmailV1 {
+        pub address: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct EmailV2 {
+        pub local: String,
+        pub domain: String,
+    }
+
+    impl From<EmailV1> for EmailV2 {
+        fn from(v1: EmailV1) -> Self {
+            let (local, domain) = v1.address.split_once('@').unwrap_or((&v1.address, ""));
+            Self {
+                local: local.to_string(),
+                domain: domain.to_string(),
+            }
+        }
+    }
+
+    /// A newtype around a rich inner type.
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(EmailV1, EmailV2), transparent = true)]
+    pub struct Email(pub (String, String));
+
+    impl From<EmailV2> for Email {
+        fn from(v2: EmailV2) -> Self {
+            Self((v2.local, v2.domain))
+        }
+    }
+
+    impl From<&Email> for EmailV2 {
+        fn from(email: &Email) -> Self {
+            Self {
+                local: email.0.0.clone(),
+                domain: email.0.1.clone(),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct PointV1(pub i32, pub i32);
+
+    /// A tuple struct whose latest version is generated from its fields.
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(PointV1),
+        generate_latest = true,
+        transparent = true,
+        tag_style = "adjacent"
+    )]
+    pub struct Point(pub i64, pub i64, #[serde(default)] pub i64);
+
+    impl From<PointV1> for PointV2 {
+        fn from(v1: PointV1) -> Self {
+            Self(v1.0.into(), v1.1.into(), 0)
+        }
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HeartbeatV1 {}
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct HeartbeatV2;
+
+    impl From<HeartbeatV1> for HeartbeatV2 {
+        fn from(_: HeartbeatV1) -> Self {
+            Self
+        }
+    }
+
+    /// A unit struct, carrying nothing but its version.
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(HeartbeatV1, HeartbeatV2),
+        transparent = true
+    )]
+    pub struct Heartbeat;
+
+    impl From<HeartbeatV2> for Heartbeat {
+        fn from(_: HeartbeatV2) -> Self {
+            Self
+        }
+    }
+
+    impl From<&Heartbeat> for HeartbeatV2 {
+        fn from(_: &Heartbeat) -> Self {
+            Self
+        }
+    }
+
+    #[test]
+    fn newtype_domains_round_trip() {
+        let email: Email =
+            serde_json::from_str(r#"{"_version":"1","address":"ada@example.com"}"#).unwrap();
+        assert_eq!(email, Email(("ada".to_string(), "example.com".to_string())));
+        assert_eq!(
+            serde_json::to_value(&email).unwrap(),
+            serde_json::json!({"_version": "2", "local": "ada", "domain": "example.com"})
+        );
+    }
+
+    #[test]
+    fn tuple_domains_generate_their_latest_version() {
+        let point: Point = serde_json::from_str(r#"{"_version":"1","data":[1,2]}"#).unwrap();
+        assert_eq!(point, Point(1, 2, 0));
+
+        let point = Point(3, 4, 5);
+        assert_eq!(
+            serde_json::to_value(&point).unwrap(),
+            serde_json::json!({"_version": "2", "data": [3, 4, 5]})
+        );
+        let point: Point = serde_json::from_str(r#"{"_version":"2","data":[3,4]}"#).unwrap();
+        assert_eq!(point, Point(3, 4, 0));
+        assert_eq!(Point::from(PointVersions::from(point.clone())), point);
+    }
+
+    #[test]
+    fn unit_domains_round_trip() {
+        let heartbeat: Heartbeat = serde_json::from_str(r#"{"_version":"1"}"#).unwrap();
+        assert_eq!(heartbeat, Heartbeat);
+        assert_eq!(
+            serde_json::to_string(&heartbeat).unwrap(),
+            r#"{"_version":"2"}"#
+        );
+    }
+}
+
+mod boxed_variants {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct ReportV1 {
+        pub title: String,
+        pub rows: [u64; 16],
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct ReportV2 {
+        pub title: String,
+        pub rows: [u64; 16],
+        pub footer: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct ReportV3 {
+        pub title: String,
+        pub rows: [u64; 16],
+        pub footer: String,
+        pub pages: u32,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct TooLong;
+
+    impl std::fmt::Display for TooLong {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("title too long")
+        }
+    }
+
+    impl From<ReportV1> for ReportV2 {
+        fn from(v1: ReportV1) -> Self {
+            Self {
+                title: v1.title,
+                rows: v1.rows,
+                footer: String::new(),
+            }
+        }
+    }
+
+    impl From<ReportV2> for ReportV1 {
+        fn from(v2: ReportV2) -> Self {
+            Self {
+                title: v2.title,
+                rows: v2.rows,
+            }
+        }
+    }
+
+    impl TryFrom<ReportV2> for ReportV3 {
+        type Error = TooLong;
+
+        fn try_from(v2: ReportV2) -> Result<Self, Self::Error> {
+            if v2.title.len() > 8 {
+                return Err(TooLong);
+            }
+            Ok(Self {
+                title: v2.title,
+                rows: v2.rows,
+                footer: v2.footer,
+                pages: 1,
+            })
+        }
+    }
+
+    impl From<ReportV3> for ReportV2 {
+        fn from(v3: ReportV3) -> Self {
+            Self {
+                title: v3.title,
+                rows: v3.rows,
+                footer: v3.footer,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        error = TooLong,
+        chain(ReportV1, ReportV2 infallible, ReportV3),
+        transparent = true,
+        downgrade = true,
+        derive(PartialEq, Eq),
+        boxed
+    )]
+    pub struct Report {
+        pub title: String,
+        pub rows: [u64; 16],
+        pub pages: u32,
+    }
+
+    impl TryFrom<ReportV3> for Report {
+        type Error = TooLong;
+
+        fn try_from(v3: ReportV3) -> Result<Self, Self::Error> {
+            Ok(Self {
+                title: v3.title,
+                rows: v3.rows,
+                pages: v3.pages,
+            })
+        }
+    }
+
+    impl From<&Report> for ReportV3 {
+        fn from(report: &Report) -> Self {
+            Self {
+                title: report.title.clone(),
+                rows: report.rows,
+                footer: String::new(),
+                pages: report.pages,
+            }
+        }
+    }
+
+    fn v1(title: &str) -> ReportV1 {
+        ReportV1 {
+            title: title.to_string(),
+            rows: [7; 16],
+        }
+    }
+
+    #[test]
+    fn the_enum_is_pointer_sized() {
+        assert_eq!(
+            std::mem::size_of::<ReportVersions>(),
+            std::mem::size_of::<Box<ReportV3>>() * 2
+        );
+        assert!(std::mem::size_of::<ReportVersions>() < std::mem::size_of::<ReportV3>());
+    }
+
+    #[test]
+    fn migrates_boxed_values() {
+        let rep = ReportVersions::from(v1("weekly"));
+        let rep = rep.upgrade_once().unwrap();
+        assert_eq!(rep.version(), 2);
+        assert_eq!(rep.clone().into_latest().unwrap().pages, 1);
+
+        let report = Report::try_from(rep).unwrap();
+        assert_eq!(report.title, "weekly");
+        assert_eq!(
+            Report::try_from(ReportVersions::from(v1("quarterly"))).unwrap_err(),
+            TooLong
+        );
+    }
+
+    #[test]
+    fn serializes_through_the_box() {
+        let json = serde_json::to_value(ReportVersions::from(v1("weekly"))).unwrap();
+        assert_eq!(json["_version"], "1");
+        let rep: ReportVersions = serde_json::from_value(json).unwrap();
+        assert_eq!(rep, ReportVersions::from(v1("weekly")));
+
+        let report: Report = serde_json::from_str(
+            &serde_json::to_string(&ReportVersions::from(v1("daily"))).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(serde_json::to_value(&report).unwrap()["_version"], "3");
+        assert_eq!(
+            ReportVersions::at_version(&report, 1).unwrap(),
+            ReportVersions::from(v1("daily"))
+        );
+    }
+}
+
+mod coded_entries {
+    use super::*;
+    use serde_evolve::codec::{Codec, Coded};
+    use serde_json::json;
+
+    /// A legacy message type without serde support, encoded by hand as `id:label`.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct TicketMessage {
+        pub id: u32,
+        pub label: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Encoded {
+        pub message: String,
+    }
+
+    pub struct Legacy;
+
+    impl Codec<TicketMessage> for Legacy {
+        type Wire = Encoded;
+        type Error = String;
+
+        fn to_wire(value: &TicketMessage) -> Encoded {
+            Encoded {
+                message: format!("{}:{}", value.id, value.label),
+            }
+        }
+
+        fn from_wire(wire: Encoded) -> Result<TicketMessage, String> {
+            let (id, label) = wire
+                .message
+                .split_once(':')
+                .ok_or_else(|| format!("malformed message '{}'", wire.message))?;
+            Ok(TicketMessage {
+                id: id.parse().map_err(|_| format!("malformed id '{id}'"))?,
+                label: label.to_string(),
+            })
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct TicketV2 {
+        pub id: u32,
+        pub title: String,
+    }
+
+    impl From<TicketMessage> for TicketV2 {
+        fn from(message: TicketMessage) -> Self {
+            Self {
+                id: message.id,
+                title: message.label,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(TicketMessage codec(Legacy), TicketV2),
+        derive(PartialEq, Eq)
+    )]
+    pub struct Ticket {
+        pub id: u32,
+        pub title: String,
+    }
+
+    impl From<TicketV2> for Ticket {
+        fn from(v2: TicketV2) -> Self {
+            Self {
+                id: v2.id,
+                title: v2.title,
+            }
+        }
+    }
+
+    impl From<&Ticket> for TicketV2 {
+        fn from(ticket: &Ticket) -> Self {
+            Self {
+                id: ticket.id,
+                title: ticket.title.clone(),
+            }
+        }
+    }
+
+    fn message() -> TicketMessage {
+        TicketMessage {
+            id: 7,
+            label: "printer on fire".to_string(),
+        }
+    }
+
+    #[test]
+    fn serializes_through_the_codec() {
+        let rep = TicketVersions::from(Coded::from(message()));
+        let json = serde_json::to_value(&rep).unwrap();
+        assert_eq!(
+            json,
+            json!({"_version": "1", "message": "7:printer on fire"})
+        );
+
+        let decoded: TicketVersions = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, rep);
+        let TicketVersions::V1(coded) = decoded else {
+            panic!("expected version 1");
+        };
+        assert_eq!(coded.label, "printer on fire");
+    }
+
+    #[test]
+    fn migrates_the_unwrapped_value() {
+        let ticket: Ticket = serde_json::from_value::<TicketVersions>(
+            json!({"_version": "1", "message": "7:printer on fire"}),
+        )
+        .map(Ticket::from)
+        .unwrap();
+        assert_eq!(ticket.title, "printer on fire");
+
+        let rep = TicketVersions::from(Coded::from(message()));
+        assert_eq!(rep.into_latest().id, 7);
+        assert_eq!(TicketVersions::VERSIONS[0].type_name, "TicketMessage");
+    }
+
+    #[test]
+    fn reports_codec_errors() {
+        let err =
+            serde_json::from_value::<TicketVersions>(json!({"_version": "1", "message": "7"}))
+                .unwrap_err();
+        assert!(err.to_string().contains("malformed message '7'"));
+    }
+}
+
+mod remote_entries {
+    use super::*;
+    use serde_evolve::codec::Remote;
+    use serde_json::json;
+
+    /// Stands in for another crate, whose type implements no serde traits.
+    pub mod vendor {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct Endpoint {
+            pub host: String,
+            pub port: u16,
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "vendor::Endpoint")]
+    pub struct EndpointDef {
+        pub host: String,
+        pub port: u16,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct EndpointV2 {
+        pub url: String,
+    }
+
+    impl From<vendor::Endpoint> for EndpointV2 {
+        fn from(v1: vendor::Endpoint) -> Self {
+            Self {
+                url: format!("http://{}:{}", v1.host, v1.port),
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        chain(vendor::Endpoint remote(EndpointDef), EndpointV2),
+        derive(PartialEq, Eq)
+    )]
+    pub struct Endpoint {
+        pub url: String,
+    }
+
+    impl From<EndpointV2> for Endpoint {
+        fn from(v2: EndpointV2) -> Self {
+            Self { url: v2.url }
+        }
+    }
+
+    impl From<&Endpoint> for EndpointV2 {
+        fn from(endpoint: &Endpoint) -> Self {
+            Self {
+                url: endpoint.url.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn serializes_through_the_remote_definition() {
+        let rep = EndpointVersions::from(Remote::from(vendor::Endpoint {
+            host: "localhost".to_string(),
+            port: 8080,
+        }));
+        let json = serde_json::to_value(&rep).unwrap();
+        assert_eq!(
+            json,
+            json!({"_version": "1", "host": "localhost", "port": 8080})
+        );
+
+        let decoded: EndpointVersions = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, rep);
+        assert_eq!(EndpointVersions::VERSIONS[0].type_name, "vendor::Endpoint");
+    }
+
+    #[test]
+    fn migrates_the_foreign_value() {
+        let endpoint: Endpoint = serde_json::from_value::<EndpointVersions>(
+            json!({"_version": "1", "host": "example.com", "port": 443}),
+        )
+        .map(Endpoint::from)
+        .unwrap();
+        assert_eq!(endpoint.url, "http://example.com:443");
+
+        let err = serde_json::from_value::<EndpointVersions>(
+            json!({"_version": "1", "host": "example.com"}),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("missing field `port`"));
+    }
+}
+
+mod strict {
+    use super::*;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct InvoiceV1 {
+        pub number: u64,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct InvoiceV2 {
+        pub number: u64,
+        pub total_cents: u64,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(InvoiceV1, InvoiceV2), strict = true)]
+    pub struct Invoice {
+        pub total_cents: u64,
+        pub number: u64,
+    }
+
+    impl From<InvoiceV1> for InvoiceV2 {
+        fn from(v1: InvoiceV1) -> Self {
+            Self {
+                number: v1.number,
+                total_cents: 0,
+            }
+        }
+    }
+
+    impl From<InvoiceV2> for Invoice {
+        fn from(v2: InvoiceV2) -> Self {
+            Self {
+                total_cents: v2.total_cents,
+                number: v2.number,
+            }
+        }
+    }
+
+    impl From<&Invoice> for InvoiceV2 {
+        fn from(invoice: &Invoice) -> Self {
+            Self {
+                number: invoice.number,
+                total_cents: invoice.total_cents,
+            }
+        }
+    }
+
+    #[test]
+    fn matching_latest_version_compiles() {
+        let rep: InvoiceVersions = serde_json::from_str(r#"{"_version":"1","number":7}"#).unwrap();
+        assert_eq!(
+            Invoice::from(rep),
+            Invoice {
+                total_cents: 0,
+                number: 7,
+            }
+        );
+    }
+}
+
+mod dated {
+    use super::*;
+    use serde_evolve::VersionDate;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ReportV1 {
+        pub title: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct ReportV2 {
+        pub title: String,
+        pub pages: u32,
+    }
+
+    impl From<ReportV1> for ReportV2 {
+        fn from(v1: ReportV1) -> Self {
+            Self {
+                title: v1.title,
+                pages: 0,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        dated = true,
+        chain(ReportV1 as "2024-03", ReportV2 as "2025-01-15")
+    )]
+    pub struct Report {
+        pub title: String,
+        pub pages: u32,
+    }
+
+    impl From<ReportV2> for Report {
+        fn from(v2: ReportV2) -> Self {
+            Self {
+                title: v2.title,
+                pages: v2.pages,
+            }
+        }
+    }
+
+    impl From<&Report> for ReportV2 {
+        fn from(report: &Report) -> Self {
+            Self {
+                title: report.title.clone(),
+                pages: report.pages,
+            }
+        }
+    }
+
+    /// A dated chain whose latest version is the domain type itself.
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Versioned)]
+    #[versioned(
+        mode = "infallible",
+        dated = true,
+        chain(ReportV1 as "2024-03"),
+        self_latest = true,
+        latest_date = "2025-01-15"
+    )]
+    pub struct Summary {
+        pub title: String,
+    }
+
+    impl From<ReportV1> for Summary {
+        fn from(v1: ReportV1) -> Self {
+            Self { title: v1.title }
+        }
+    }
+
+    fn date(s: &str) -> VersionDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn versions_are_dated_by_their_tags() {
+        let rep: ReportVersions =
+            serde_json::from_str(r#"{"_version":"2024-03","title":"Q1"}"#).unwrap();
+        assert_eq!(rep.version(), 1);
+        assert_eq!(rep.date(), date("2024-03-01"));
+        assert_eq!(rep.date().to_string(), "2024-03-01");
+        assert!(rep.is_older_than(date("2025-01-01")));
+        assert!(!rep.is_older_than(date("2024-03-01")));
+
+        assert_eq!(
+            ReportVersions::date_of_version(2),
+            VersionDate::new(2025, 1, 15)
+        );
+        assert_eq!(ReportVersions::date_of_version(3), None);
+    }
+
+    #[test]
+    fn implicit_latest_versions_are_dated_by_latest_date() {
+        let summary = Summary {
+            title: "Q1".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(SummaryVersions::from(&summary)).unwrap(),
+            serde_json::json!({"_version": "2025-01-15", "title": "Q1"})
+        );
+
+        let rep: SummaryVersions =
+            serde_json::from_str(r#"{"_version":"2024-03","title":"Q1"}"#).unwrap();
+        assert!(rep.is_older_than(date("2025-01-01")));
+        assert_eq!(Summary::from(rep), summary);
+        assert_eq!(
+            SummaryVersions::date_of_version(2),
+            VersionDate::new(2025, 1, 15)
+        );
+    }
+
+    #[test]
+    fn version_dates_are_validated() {
+        assert!("2024-02-29".parse::<VersionDate>().is_ok());
+        assert_eq!(
+            "2023-02-29".parse::<VersionDate>().unwrap_err().to_string(),
+            "invalid version date '2023-02-29', expected YYYY-MM-DD or YYYY-MM"
+        );
+        assert!("2024-3-01".parse::<VersionDate>().is_err());
+        assert!("2024-03-01-01".parse::<VersionDate>().is_err());
+        assert!(date("2024-12-31").is_before(date("2025-01-01")));
+    }
+}
+
+mod branching {
+    use super::*;
+    use serde_evolve::Representation;
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct NoteV1 {
+        pub text: String,
+    }
+
+    /// Written by the desktop app.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct NoteV2 {
+        pub text: String,
+        pub pinned: bool,
+    }
+
+    /// Written by the mobile app, in place of `NoteV2`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct MobileNoteV2 {
+        pub body: String,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct NoteV3 {
+        pub text: String,
+        pub pinned: bool,
+        pub source: String,
+    }
+
+    impl From<NoteV1> for NoteV2 {
+        fn from(v1: NoteV1) -> Self {
+            Self {
+                text: v1.text,
+                pinned: false,
+            }
+        }
+    }
+
+    impl TryFrom<NoteV2> for NoteV3 {
+        type Error = String;
+
+        fn try_from(v2: NoteV2) -> Result<Self, Self::Error> {
+            Ok(Self {
+                text: v2.text,
+                pinned: v2.pinned,
+                source: "desktop".to_string(),
+            })
+        }
+    }
+
+    impl TryFrom<MobileNoteV2> for NoteV3 {
+        type Error = String;
+
+        fn try_from(mobile: MobileNoteV2) -> Result<Self, Self::Error> {
+            if mobile.body.is_empty() {
+                return Err("empty note".to_string());
+            }
+            Ok(Self {
+                text: mobile.body,
+                pinned: false,
+                source: "mobile".to_string(),
+            })
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(
+        error = String,
+        step_context = true,
+        chain(NoteV1, NoteV2 infallible, MobileNoteV2 as "2-mobile" branch, NoteV3)
+    )]
+    pub struct Note {
+        pub text: String,
+        pub source: String,
+    }
+
+    impl TryFrom<NoteV3> for Note {
+        type Error = String;
+
+        fn try_from(v3: NoteV3) -> Result<Self, Self::Error> {
+            Ok(Self {
+                text: v3.text,
+                source: v3.source,
+            })
+        }
+    }
+
+    impl From<&Note> for NoteV3 {
+        fn from(note: &Note) -> Self {
+            Self {
+                text: note.text.clone(),
+                pinned: false,
+                source: note.source.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn the_tag_selects_the_branch() {
+        let rep: NoteVersions =
+            serde_json::from_str(r#"{"_version":"2-mobile","body":"hi"}"#).unwrap();
+        assert!(matches!(rep, NoteVersions::MobileNoteV2(_)));
+        assert_eq!(rep.version(), 2);
+        assert_eq!(Note::try_from(rep).unwrap().source, "mobile");
+
+        let rep: NoteVersions =
+            serde_json::from_str(r#"{"_version":"2","text":"hi","pinned":true}"#).unwrap();
+        assert_eq!(Note::try_from(rep).unwrap().source, "desktop");
+
+        let rep: NoteVersions = serde_json::from_str(r#"{"_version":"1","text":"hi"}"#).unwrap();
+        assert!(matches!(rep.upgrade_once().unwrap(), NoteVersions::V2(_)));
+    }
+
+    #[test]
+    fn branches_migrate_into_the_next_version() {
+        let rep = NoteVersions::MobileNoteV2(MobileNoteV2 {
+            body: String::new(),
+        });
+        let err = Note::try_from(rep).unwrap_err();
+        assert_eq!((err.from, err.to), (2, Some(3)));
+        assert_eq!(err.error, "empty note");
+
+        let rep = NoteVersions::MobileNoteV2(MobileNoteV2 {
+            body: "hi".to_string(),
+        });
+        assert!(rep.upgrade_once().unwrap().is_current());
+    }
+
+    #[test]
+    fn version_numbers_read_the_main_line() {
+        assert_eq!(NoteVersions::SUPPORTED, &[1, 2, 3]);
+        assert_eq!(NoteVersions::CURRENT, 3);
+        assert_eq!(NoteVersions::version_of_tag("2-mobile"), Some(2));
+        assert_eq!(NoteVersions::tag_of_version(2), Some("2"));
+
+        let payload: serde_evolve::Payload =
+            serde_json::from_str(r#"{"text":"hi","pinned":false}"#).unwrap();
+        let rep = NoteVersions::from_payload(2, payload).unwrap().unwrap();
+        assert!(matches!(rep, NoteVersions::V2(_)));
+    }
+}
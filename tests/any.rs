@@ -0,0 +1,185 @@
+//! Tests for type-erased representations and type-tagged documents
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_evolve::any::AnyVersioned;
+use serde_evolve::registry::{Error, Registry};
+use serde_json::json;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountV1 {
+    pub owner: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountV2 {
+    pub owner: String,
+    pub active: bool,
+}
+
+impl From<AccountV1> for AccountV2 {
+    fn from(v1: AccountV1) -> Self {
+        Self {
+            owner: v1.owner,
+            active: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(mode = "infallible", chain(AccountV1, AccountV2), any = true)]
+pub struct Account {
+    pub owner: String,
+    pub active: bool,
+}
+
+impl From<AccountV2> for Account {
+    fn from(v2: AccountV2) -> Self {
+        Self {
+            owner: v2.owner,
+            active: v2.active,
+        }
+    }
+}
+
+impl From<&Account> for AccountV2 {
+    fn from(account: &Account) -> Self {
+        Self {
+            owner: account.owner.clone(),
+            active: account.active,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoteV1 {
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(mode = "infallible", chain(NoteV1), any = true)]
+pub struct Note {
+    pub text: String,
+}
+
+impl From<NoteV1> for Note {
+    fn from(v1: NoteV1) -> Self {
+        Self { text: v1.text }
+    }
+}
+
+impl From<&Note> for NoteV1 {
+    fn from(note: &Note) -> Self {
+        Self {
+            text: note.text.clone(),
+        }
+    }
+}
+
+fn documents() -> Vec<Box<dyn AnyVersioned>> {
+    vec![
+        Box::new(AccountVersions::V1(AccountV1 {
+            owner: "ada".to_string(),
+        })),
+        Box::new(NoteVersions::V1(NoteV1 {
+            text: "hello".to_string(),
+        })),
+    ]
+}
+
+fn registry() -> Registry {
+    let mut registry = Registry::new();
+    registry
+        .register::<AccountVersions, Account>("Account")
+        .register::<NoteVersions, Note>("Note");
+    registry
+}
+
+#[test]
+fn migrates_mixed_types_through_trait_objects() {
+    let documents = documents();
+
+    let summary: Vec<_> = documents
+        .iter()
+        .map(|document| {
+            (
+                document.type_name(),
+                document.version(),
+                document.current_version(),
+                document.is_current(),
+            )
+        })
+        .collect();
+    assert_eq!(summary, [("Account", 1, 2, false), ("Note", 1, 1, true)]);
+
+    assert_eq!(
+        documents[0].to_json().unwrap(),
+        json!({"_version": "1", "owner": "ada"})
+    );
+    assert_eq!(
+        documents[0].migrate_to_latest_json().unwrap(),
+        json!({"_version": "2", "owner": "ada", "active": true})
+    );
+    assert_eq!(
+        documents[1].migrate_to_latest_json().unwrap(),
+        json!({"_version": "1", "text": "hello"})
+    );
+
+    assert!(documents[0].downcast_ref::<NoteVersions>().is_none());
+    let Some(AccountVersions::V1(account)) = documents[0].downcast_ref::<AccountVersions>() else {
+        panic!("expected a version 1 account");
+    };
+    assert_eq!(account.owner, "ada");
+}
+
+#[test]
+fn type_tagged_documents_round_trip_through_the_registry() {
+    let registry = registry();
+
+    let rows: Vec<_> = documents()
+        .iter()
+        .map(|document| document.to_typed_json().unwrap())
+        .collect();
+    assert_eq!(
+        rows[0],
+        json!({"_type": "Account", "_version": "1", "owner": "ada"})
+    );
+
+    let decoded: Vec<_> = rows
+        .iter()
+        .map(|row| registry.deserialize_any_versioned(row.clone()).unwrap())
+        .collect();
+    assert_eq!(decoded[0].type_name(), "Account");
+    assert_eq!(decoded[0].version(), 1);
+    assert!(decoded[1].downcast_ref::<NoteVersions>().is_some());
+
+    let account: Account = registry.deserialize_versioned(rows[0].clone()).unwrap();
+    assert_eq!(account.owner, "ada");
+    assert!(account.active);
+}
+
+#[test]
+fn reports_untyped_and_mismatched_documents() {
+    let registry = registry();
+
+    let err = registry
+        .deserialize_any_versioned(json!({"_version": "1", "text": "hello"}))
+        .unwrap_err();
+    assert!(matches!(err, Error::Json(_)));
+
+    let err = registry
+        .deserialize_any_versioned(json!({"_type": "Order", "_version": "1"}))
+        .unwrap_err();
+    assert!(matches!(err, Error::UnknownType(ref name) if name == "Order"));
+
+    let err = registry
+        .deserialize_versioned::<Account>(json!({"_type": "Note", "_version": "1", "text": "hi"}))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "document is a 'Note', not the requested type"
+    );
+}
@@ -0,0 +1,275 @@
+//! Tests for Avro schema export
+
+#![cfg(feature = "schemars")]
+#![allow(missing_docs)]
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_evolve::avro::{self, VERSION_ATTRIBUTE};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProductV1 {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub enum Status {
+    Active,
+    Retired,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Dimensions {
+    pub width: f32,
+    pub height: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProductV2 {
+    pub name: String,
+    pub price: u64,
+    pub stock: i32,
+    pub status: Status,
+    pub size: Option<Dimensions>,
+    pub tags: Vec<String>,
+    pub labels: BTreeMap<String, bool>,
+}
+
+impl From<ProductV1> for ProductV2 {
+    fn from(v1: ProductV1) -> Self {
+        Self {
+            name: v1.name,
+            price: 0,
+            stock: 0,
+            status: Status::Active,
+            size: None,
+            tags: Vec::new(),
+            labels: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(mode = "infallible", chain(ProductV1, ProductV2), schema = true)]
+pub struct Product {
+    pub inner: ProductV2,
+}
+
+impl From<ProductV2> for Product {
+    fn from(inner: ProductV2) -> Self {
+        Self { inner }
+    }
+}
+
+impl From<&Product> for ProductV2 {
+    fn from(product: &Product) -> Self {
+        product.inner.clone()
+    }
+}
+
+#[test]
+fn avro_schema_for_names_each_record_after_its_dto() {
+    let v1 = ProductVersions::avro_schema_for(1).unwrap().unwrap();
+    assert_eq!(
+        v1,
+        json!({
+            "type": "record",
+            "name": "ProductV1",
+            VERSION_ATTRIBUTE: 1,
+            "fields": [{ "name": "name", "type": "string" }],
+        })
+    );
+    assert!(ProductVersions::avro_schema_for(3).is_none());
+}
+
+#[test]
+fn avro_schema_for_translates_nested_types() {
+    let v2 = ProductVersions::avro_schema_for(2).unwrap().unwrap();
+    assert_eq!(v2["name"], "ProductV2");
+    assert_eq!(v2[VERSION_ATTRIBUTE], 2);
+
+    let fields: BTreeMap<_, _> = v2["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| (field["name"].as_str().unwrap(), field.clone()))
+        .collect();
+    assert_eq!(fields["price"]["type"], "long");
+    assert_eq!(fields["stock"]["type"], "int");
+    assert_eq!(
+        fields["status"]["type"],
+        json!({ "type": "enum", "name": "Status", "symbols": ["Active", "Retired"] })
+    );
+    assert_eq!(
+        fields["size"],
+        json!({
+            "name": "size",
+            "type": ["null", {
+                "type": "record",
+                "name": "Dimensions",
+                "fields": [
+                    { "name": "height", "type": "double" },
+                    { "name": "width", "type": "float" },
+                ],
+            }],
+            "default": null,
+        })
+    );
+    assert_eq!(
+        fields["tags"]["type"],
+        json!({ "type": "array", "items": "string" })
+    );
+    assert_eq!(
+        fields["labels"]["type"],
+        json!({ "type": "map", "values": "boolean" })
+    );
+}
+
+#[test]
+fn from_json_schema_rejects_invalid_names() {
+    let schema = schemars::json_schema!({
+        "type": "object",
+        "properties": { "first-name": { "type": "string" } },
+    });
+    let err = avro::from_json_schema(&schema, "Person", 1).unwrap_err();
+    assert_eq!(err.path, "Person.first-name");
+    assert_eq!(
+        err.to_string(),
+        "no Avro schema for 'Person.first-name': 'first-name' is not a valid Avro name"
+    );
+}
+
+#[test]
+fn record_name_drops_paths_and_type_arguments() {
+    assert_eq!(avro::record_name("ProductV2"), "ProductV2");
+    assert_eq!(avro::record_name("crate :: v2 :: ProductV2"), "ProductV2");
+    assert_eq!(avro::record_name("Page < crate :: Item >"), "Page");
+}
+
+#[cfg(feature = "avro")]
+mod containers {
+    use super::*;
+    use serde_evolve::avro::ContainerError;
+
+    fn product_v2() -> ProductV2 {
+        ProductV2 {
+            name: "lamp".to_string(),
+            price: 1200,
+            stock: -1,
+            status: Status::Retired,
+            size: Some(Dimensions {
+                width: 0.5,
+                height: 1.25,
+            }),
+            tags: vec!["desk".to_string()],
+            labels: BTreeMap::from([("fragile".to_string(), true)]),
+        }
+    }
+
+    #[test]
+    fn containers_carry_their_version_and_migrate_on_read() {
+        let schema = ProductVersions::avro_schema_for(1).unwrap().unwrap();
+        let reps = [
+            ProductVersions::V1(ProductV1 {
+                name: "pen".to_string(),
+            }),
+            ProductVersions::V1(ProductV1 {
+                name: "ink".to_string(),
+            }),
+        ];
+        let bytes = avro::write_container(&schema, &reps).unwrap();
+
+        let header = apache_avro::Reader::new(bytes.as_slice()).unwrap();
+        assert_eq!(header.user_metadata()[VERSION_ATTRIBUTE], b"1");
+
+        let products: Vec<_> = avro::read_container::<ProductVersions>(&bytes)
+            .unwrap()
+            .into_iter()
+            .map(Product::from)
+            .collect();
+        let names: Vec<_> = products
+            .iter()
+            .map(|product| product.inner.name.as_str())
+            .collect();
+        assert_eq!(names, ["pen", "ink"]);
+        assert!(matches!(products[0].inner.status, Status::Active));
+    }
+
+    #[test]
+    fn containers_round_trip_every_field() {
+        let schema = ProductVersions::avro_schema_for(2).unwrap().unwrap();
+        let product = Product {
+            inner: product_v2(),
+        };
+        let bytes = avro::write_container(&schema, [&ProductVersions::from(&product)]).unwrap();
+
+        let read = avro::read_container::<ProductVersions>(&bytes).unwrap();
+        let [ProductVersions::V2(v2)] = read.as_slice() else {
+            panic!("expected one V2 record, got {read:?}");
+        };
+        assert_eq!(
+            serde_json::to_value(v2).unwrap(),
+            serde_json::to_value(product_v2()).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_values_at_another_version() {
+        let schema = ProductVersions::avro_schema_for(2).unwrap().unwrap();
+        let rep = ProductVersions::V1(ProductV1 {
+            name: "pen".to_string(),
+        });
+        assert!(matches!(
+            avro::write_container(&schema, [&rep]),
+            Err(ContainerError::VersionMismatch {
+                schema: 2,
+                found: 1
+            })
+        ));
+
+        let mut unversioned = schema;
+        unversioned
+            .as_object_mut()
+            .unwrap()
+            .remove(VERSION_ATTRIBUTE);
+        assert!(matches!(
+            avro::write_container::<ProductVersions>(&unversioned, []),
+            Err(ContainerError::MissingVersion)
+        ));
+    }
+
+    #[test]
+    fn rejects_containers_without_a_supported_version() {
+        let schema = ProductVersions::avro_schema_for(1).unwrap().unwrap();
+        let parsed = apache_avro::Schema::parse(&schema).unwrap();
+        let mut writer = apache_avro::Writer::new(&parsed, Vec::new());
+        writer
+            .append_ser(ProductV1 {
+                name: "pen".to_string(),
+            })
+            .unwrap();
+        let bytes = writer.into_inner().unwrap();
+        assert!(matches!(
+            avro::read_container::<ProductVersions>(&bytes),
+            Err(ContainerError::MissingVersion)
+        ));
+
+        let mut writer = apache_avro::Writer::new(&parsed, Vec::new());
+        writer
+            .add_user_metadata(VERSION_ATTRIBUTE.to_owned(), "9")
+            .unwrap();
+        let bytes = writer.into_inner().unwrap();
+        let err = avro::read_container::<ProductVersions>(&bytes).unwrap_err();
+        assert!(matches!(
+            &err,
+            ContainerError::UnsupportedVersion(err) if err.found == 9
+        ));
+        assert!(matches!(
+            serde_evolve::VersionedError::from(err),
+            serde_evolve::VersionedError::UnsupportedVersion(_)
+        ));
+    }
+}
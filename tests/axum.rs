@@ -0,0 +1,164 @@
+//! Tests for the axum extractor and response
+
+#![cfg(feature = "axum")]
+#![allow(missing_docs)]
+
+use axum::body::{Body, to_bytes};
+use axum::extract::{FromRequest, Request};
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_evolve::axum::{Rejection, VersionedJson};
+use std::convert::TryFrom;
+use std::fmt;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderV1 {
+    pub item: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderV2 {
+    pub item: String,
+    pub quantity: u32,
+}
+
+impl From<OrderV1> for OrderV2 {
+    fn from(v1: OrderV1) -> Self {
+        Self {
+            item: v1.item,
+            quantity: 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EmptyOrder;
+
+impl fmt::Display for EmptyOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("orders must have a positive quantity")
+    }
+}
+
+impl std::error::Error for EmptyOrder {}
+
+impl From<std::convert::Infallible> for EmptyOrder {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(error = "EmptyOrder", chain(OrderV1, OrderV2))]
+pub struct Order {
+    pub item: String,
+    pub quantity: u32,
+}
+
+impl TryFrom<OrderV2> for Order {
+    type Error = EmptyOrder;
+
+    fn try_from(v2: OrderV2) -> Result<Self, Self::Error> {
+        if v2.quantity == 0 {
+            return Err(EmptyOrder);
+        }
+        Ok(Self {
+            item: v2.item,
+            quantity: v2.quantity,
+        })
+    }
+}
+
+impl From<&Order> for OrderV2 {
+    fn from(order: &Order) -> Self {
+        Self {
+            item: order.item.clone(),
+            quantity: order.quantity,
+        }
+    }
+}
+
+/// Drive a future that never waits on I/O to completion.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn json_request(body: &'static str) -> Request {
+    Request::builder()
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn extract(body: &'static str) -> Result<Order, Rejection> {
+    block_on(VersionedJson::<Order>::from_request(
+        json_request(body),
+        &(),
+    ))
+    .map(|VersionedJson(order)| order)
+}
+
+#[test]
+fn extracts_any_version() {
+    assert_eq!(
+        extract(r#"{"_version":"1","item":"tea"}"#).unwrap(),
+        Order {
+            item: "tea".to_string(),
+            quantity: 1
+        }
+    );
+    assert_eq!(
+        extract(r#"{"_version":"2","item":"tea","quantity":3}"#).unwrap(),
+        Order {
+            item: "tea".to_string(),
+            quantity: 3
+        }
+    );
+}
+
+#[test]
+fn rejects_invalid_bodies_and_failed_migrations() {
+    let rejection = extract(r#"{"_version":"7"}"#).unwrap_err();
+    assert!(matches!(rejection, Rejection::Json(_)));
+    assert_eq!(
+        rejection.into_response().status(),
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    let rejection = extract(r#"{"_version":"2","item":"tea","quantity":0}"#).unwrap_err();
+    assert!(matches!(rejection, Rejection::Migration(_)));
+    let response = rejection.into_response();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = block_on(to_bytes(response.into_body(), usize::MAX)).unwrap();
+    assert_eq!(
+        body,
+        "migration failed: orders must have a positive quantity"
+    );
+}
+
+#[test]
+fn responds_with_the_latest_version() {
+    let response = VersionedJson(Order {
+        item: "tea".to_string(),
+        quantity: 2,
+    })
+    .into_response();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = block_on(to_bytes(response.into_body(), usize::MAX)).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"_version": "2", "item": "tea", "quantity": 2})
+    );
+}
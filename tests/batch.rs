@@ -0,0 +1,278 @@
+//! Tests for element-wise migration of collections
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_evolve::batch::{self, ColumnError, ElementError, VersionedVec};
+use serde_evolve::format::Json;
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventV1 {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventV2 {
+    pub name: String,
+    pub count: u32,
+}
+
+impl From<EventV1> for EventV2 {
+    fn from(v1: EventV1) -> Self {
+        Self {
+            name: v1.name,
+            count: 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EmptyName;
+
+impl fmt::Display for EmptyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("event name must not be empty")
+    }
+}
+
+impl std::error::Error for EmptyName {}
+
+impl From<std::convert::Infallible> for EmptyName {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(error = "EmptyName", chain(EventV1, EventV2))]
+pub struct Event {
+    pub name: String,
+    pub count: u32,
+}
+
+impl TryFrom<EventV2> for Event {
+    type Error = EmptyName;
+
+    fn try_from(v2: EventV2) -> Result<Self, Self::Error> {
+        if v2.name.is_empty() {
+            return Err(EmptyName);
+        }
+        Ok(Self {
+            name: v2.name,
+            count: v2.count,
+        })
+    }
+}
+
+impl From<&Event> for EventV2 {
+    fn from(event: &Event) -> Self {
+        Self {
+            name: event.name.clone(),
+            count: event.count,
+        }
+    }
+}
+
+#[test]
+fn migrates_each_element_and_indexes_failures() {
+    let json = r#"[
+        {"_version":"1","name":"login"},
+        {"_version":"2","name":"","count":2},
+        {"_version":"7","name":"future"},
+        {"_version":"2","name":"logout","count":3}
+    ]"#;
+
+    let batch: VersionedVec<Event> = serde_json::from_str(json).unwrap();
+    let names: Vec<_> = batch
+        .items
+        .iter()
+        .map(|event| event.name.as_str())
+        .collect();
+    assert_eq!(names, ["login", "logout"]);
+    assert_eq!(batch.items[0].count, 1);
+
+    assert!(!batch.is_complete());
+    let errors: Vec<_> = batch.errors.iter().map(|(index, _)| *index).collect();
+    assert_eq!(errors, [1, 2]);
+    assert!(matches!(batch.errors[0].1, ElementError::Migration(_)));
+    assert_eq!(
+        batch.errors[0].1.to_string(),
+        "migration failed: event name must not be empty"
+    );
+    assert!(matches!(batch.errors[1].1, ElementError::Payload(_)));
+}
+
+#[test]
+fn rejects_input_that_is_not_a_sequence() {
+    assert!(serde_json::from_str::<VersionedVec<Event>>(r#"{"_version":"1"}"#).is_err());
+}
+
+#[test]
+fn converts_into_a_result() {
+    let batch: VersionedVec<Event> =
+        serde_json::from_str(r#"[{"_version":"1","name":"login"}]"#).unwrap();
+    let events = batch.into_result().unwrap();
+    assert_eq!(events.len(), 1);
+
+    let batch: VersionedVec<Event> =
+        serde_json::from_str(r#"[{"_version":"1","name":""}]"#).unwrap();
+    let errors = batch.into_result().unwrap_err();
+    assert_eq!(errors[0].0, 0);
+}
+
+#[test]
+fn serializes_items_at_the_current_version() {
+    let batch: VersionedVec<Event> =
+        serde_json::from_str(r#"[{"_version":"1","name":"login"}, 5]"#).unwrap();
+    assert_eq!(
+        serde_json::to_value(&batch).unwrap(),
+        serde_json::json!([{"_version": "2", "name": "login", "count": 1}])
+    );
+}
+
+#[test]
+fn lossy_migration_keeps_the_values_that_migrate() {
+    let reps = vec![
+        EventVersions::from(EventV1 {
+            name: "login".to_string(),
+        }),
+        EventVersions::from(EventV1 {
+            name: String::new(),
+        }),
+        EventVersions::from(EventV2 {
+            name: "logout".to_string(),
+            count: 2,
+        }),
+    ];
+
+    let (events, failures) = batch::migrate_all_lossy::<Event, _>(reps);
+    let names: Vec<_> = events.iter().map(|event| event.name.as_str()).collect();
+    assert_eq!(names, ["login", "logout"]);
+
+    assert_eq!(failures.len(), 1);
+    assert_eq!((failures[0].index, failures[0].version), (1, 1));
+    assert_eq!(
+        failures[0].to_string(),
+        "record 1 at version 1 failed to migrate: event name must not be empty"
+    );
+}
+
+#[test]
+fn migrates_a_binary_column_row_by_row() {
+    let rows: Vec<Option<&[u8]>> = vec![
+        Some(br#"{"_version":"1","name":"login"}"#),
+        None,
+        Some(br#"{"_version":"2","name":"","count":2}"#),
+        Some(b"not json"),
+        Some(br#"{"_version":"2","name":"logout","count":3}"#),
+    ];
+
+    let column = batch::migrate_column::<Event, _, _, _>(&Json::default(), rows);
+    let values: Vec<_> = column
+        .values
+        .iter()
+        .map(|value| {
+            value
+                .as_deref()
+                .map(|bytes| std::str::from_utf8(bytes).unwrap())
+        })
+        .collect();
+    assert_eq!(
+        values,
+        [
+            Some(r#"{"_version":"2","name":"login","count":1}"#),
+            None,
+            None,
+            None,
+            Some(r#"{"_version":"2","name":"logout","count":3}"#),
+        ]
+    );
+
+    assert!(!column.is_complete());
+    let errors: Vec<_> = column.errors.iter().map(|(index, _)| *index).collect();
+    assert_eq!(errors, [2, 3]);
+    assert!(matches!(column.errors[0].1, ColumnError::Migration(_)));
+    assert!(matches!(column.errors[1].1, ColumnError::Format(_)));
+}
+
+#[cfg(feature = "arrow")]
+mod arrow {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::{ArrayRef, BinaryArray, Int64Array, LargeBinaryArray, RecordBatch};
+    use arrow_schema::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    fn export(payload: ArrayRef) -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("payload", payload.data_type().clone(), false),
+        ]);
+        let ids = Arc::new(Int64Array::from(vec![1, 2, 3]));
+        RecordBatch::try_new(Arc::new(schema), vec![ids, payload]).unwrap()
+    }
+
+    const ROWS: [&[u8]; 3] = [
+        br#"{"_version":"1","name":"login"}"#,
+        br#"{"_version":"2","name":"","count":2}"#,
+        br#"{"_version":"2","name":"logout","count":3}"#,
+    ];
+
+    #[test]
+    fn migrates_a_column_of_a_record_batch() {
+        let batch = export(Arc::new(BinaryArray::from_iter_values(ROWS)));
+
+        let migrated =
+            batch::migrate_record_batch::<Event, _>(&Json::default(), &batch, "payload").unwrap();
+        let rows: Vec<_> = migrated.batch["payload"]
+            .as_binary::<i32>()
+            .iter()
+            .map(|row| row.map(|bytes| std::str::from_utf8(bytes).unwrap()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                Some(r#"{"_version":"2","name":"login","count":1}"#),
+                None,
+                Some(r#"{"_version":"2","name":"logout","count":3}"#),
+            ]
+        );
+        assert_eq!(migrated.batch.column(0), batch.column(0));
+        assert!(migrated.batch.schema().field(1).is_nullable());
+        assert!(!migrated.is_complete());
+        assert_eq!(migrated.errors.len(), 1);
+        assert_eq!(migrated.errors[0].0, 1);
+    }
+
+    #[test]
+    fn migrates_large_binary_columns() {
+        let batch = export(Arc::new(LargeBinaryArray::from_iter_values([
+            ROWS[0], ROWS[2], ROWS[0],
+        ])));
+
+        let migrated =
+            batch::migrate_record_batch::<Event, _>(&Json::default(), &batch, "payload").unwrap();
+        assert!(migrated.is_complete());
+        assert_eq!(migrated.batch.schema(), batch.schema());
+        assert_eq!(
+            migrated.batch["payload"].as_binary::<i64>().value(0),
+            br#"{"_version":"2","name":"login","count":1}"#
+        );
+    }
+
+    #[test]
+    fn rejects_missing_and_non_binary_columns() {
+        let batch = export(Arc::new(BinaryArray::from_iter_values(ROWS)));
+        let json = Json::default();
+        assert!(batch::migrate_record_batch::<Event, _>(&json, &batch, "missing").is_err());
+        let err = batch::migrate_record_batch::<Event, _>(&json, &batch, "id").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid argument error: column 'id' is Int64, not binary"
+        );
+    }
+}
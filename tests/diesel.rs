@@ -0,0 +1,132 @@
+//! Tests for the diesel `jsonb` integration
+
+#![cfg(feature = "diesel")]
+#![allow(missing_docs)]
+
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgMetadataLookup, PgTypeMetadata, PgValue};
+use diesel::query_builder::BindCollector;
+use diesel::query_builder::bind_collector::RawBytesBindCollector;
+use diesel::sql_types::Jsonb;
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use std::num::NonZeroU32;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileV1 {
+    pub handle: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileV2 {
+    pub handle: String,
+    pub verified: bool,
+}
+
+impl From<ProfileV1> for ProfileV2 {
+    fn from(v1: ProfileV1) -> Self {
+        Self {
+            handle: v1.handle,
+            verified: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(
+    mode = "infallible",
+    chain(ProfileV1, ProfileV2),
+    transparent = true,
+    diesel = true
+)]
+pub struct Profile {
+    pub handle: String,
+    pub verified: bool,
+}
+
+impl From<ProfileV2> for Profile {
+    fn from(v2: ProfileV2) -> Self {
+        Self {
+            handle: v2.handle,
+            verified: v2.verified,
+        }
+    }
+}
+
+impl From<&Profile> for ProfileV2 {
+    fn from(profile: &Profile) -> Self {
+        Self {
+            handle: profile.handle.clone(),
+            verified: profile.verified,
+        }
+    }
+}
+
+// `jsonb` has a fixed OID, so no lookups are performed.
+struct NoLookup;
+
+impl PgMetadataLookup for NoLookup {
+    fn lookup_type(&mut self, type_name: &str, _schema: Option<&str>) -> PgTypeMetadata {
+        unreachable!("unexpected lookup of {type_name}")
+    }
+}
+
+const JSONB_OID: NonZeroU32 = NonZeroU32::new(3802).unwrap();
+
+fn jsonb(json: &str) -> Vec<u8> {
+    let mut bytes = vec![1];
+    bytes.extend_from_slice(json.as_bytes());
+    bytes
+}
+
+fn to_sql<T: diesel::serialize::ToSql<Jsonb, Pg>>(value: &T) -> serde_json::Value {
+    let mut collector = RawBytesBindCollector::<Pg>::new();
+    collector
+        .push_bound_value::<Jsonb, T>(value, &mut NoLookup)
+        .unwrap();
+    let bytes = collector.binds[0].as_deref().unwrap();
+    assert_eq!(bytes[0], 1);
+    serde_json::from_slice(&bytes[1..]).unwrap()
+}
+
+#[test]
+fn decodes_old_rows_into_the_representation() {
+    let bytes = jsonb(r#"{"_version":"1","handle":"ada"}"#);
+    let rep = <ProfileVersions as FromSql<Jsonb, Pg>>::from_sql(PgValue::new(&bytes, &JSONB_OID))
+        .unwrap();
+    assert!(matches!(rep, ProfileVersions::V1(_)));
+}
+
+#[test]
+fn decodes_old_rows_into_transparent_domain_types() {
+    let bytes = jsonb(r#"{"_version":"1","handle":"ada"}"#);
+    let profile =
+        <Profile as FromSql<Jsonb, Pg>>::from_sql(PgValue::new(&bytes, &JSONB_OID)).unwrap();
+    assert_eq!(
+        profile,
+        Profile {
+            handle: "ada".to_string(),
+            verified: false
+        }
+    );
+}
+
+#[test]
+fn rejects_text_encoded_values() {
+    let bytes = br#"{"_version":"1","handle":"ada"}"#;
+    let err =
+        <Profile as FromSql<Jsonb, Pg>>::from_sql(PgValue::new(bytes, &JSONB_OID)).unwrap_err();
+    assert_eq!(err.to_string(), "unsupported jsonb encoding version");
+}
+
+#[test]
+fn encodes_the_latest_version() {
+    let profile = Profile {
+        handle: "ada".to_string(),
+        verified: true,
+    };
+    let expected = serde_json::json!({"_version": "2", "handle": "ada", "verified": true});
+
+    assert_eq!(to_sql(&profile), expected);
+    assert_eq!(to_sql(&ProfileVersions::from(&profile)), expected);
+}
@@ -0,0 +1,216 @@
+//! Tests for the binary envelope codec
+
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::{Representation, Versioned, envelope};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampleV1 {
+    pub value: u16,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampleV2 {
+    pub value: u32,
+    pub unit: String,
+}
+
+impl From<SampleV1> for SampleV2 {
+    fn from(v1: SampleV1) -> Self {
+        Self {
+            value: u32::from(v1.value),
+            unit: "ms".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(mode = "infallible", chain(SampleV1, SampleV2))]
+pub struct Sample {
+    pub value: u32,
+    pub unit: String,
+}
+
+impl From<SampleV2> for Sample {
+    fn from(v2: SampleV2) -> Self {
+        Self {
+            value: v2.value,
+            unit: v2.unit,
+        }
+    }
+}
+
+impl From<&Sample> for SampleV2 {
+    fn from(sample: &Sample) -> Self {
+        Self {
+            value: sample.value,
+            unit: sample.unit.clone(),
+        }
+    }
+}
+
+#[test]
+fn representation_trait_reports_versions() {
+    let rep = SampleVersions::from(SampleV1 { value: 3 });
+    assert_eq!(Representation::version(&rep), 1);
+    assert_eq!(<SampleVersions as Representation>::CURRENT, 2);
+}
+
+#[test]
+fn header_is_validated() {
+    assert!(matches!(
+        envelope::read_header::<()>(b"SEVO"),
+        Err(envelope::Error::Truncated)
+    ));
+    assert!(matches!(
+        envelope::read_header::<()>(b"NOPE\x01\0\0\0"),
+        Err(envelope::Error::BadMagic)
+    ));
+    let (version, payload) = envelope::read_header::<()>(b"SEVO\x02\0\0\0rest").unwrap();
+    assert_eq!((version, payload), (2, &b"rest"[..]));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn envelope_round_trips_with_json() {
+    use serde_evolve::format::Json;
+
+    let rep = SampleVersions::from(SampleV1 { value: 3 });
+    let bytes = envelope::encode(&rep, &Json::default()).unwrap();
+    assert_eq!(&bytes[..8], b"SEVO\x01\0\0\0");
+    assert_eq!(&bytes[8..], br#"{"value":3}"#);
+
+    let decoded: SampleVersions = envelope::decode(&bytes, &Json::default()).unwrap();
+    assert_eq!(
+        Sample::from(decoded),
+        Sample {
+            value: 3,
+            unit: "ms".to_string()
+        }
+    );
+}
+
+#[cfg(all(feature = "checksum", feature = "json"))]
+#[test]
+fn checked_envelope_detects_corruption() {
+    use serde_evolve::format::Json;
+
+    let rep = SampleVersions::from(SampleV1 { value: 3 });
+    let bytes = envelope::encode_checked(&rep, &Json::default()).unwrap();
+    assert_eq!(&bytes[..8], b"SEVK\x01\0\0\0");
+    assert_eq!(&bytes[12..], br#"{"value":3}"#);
+
+    let decoded: SampleVersions = envelope::decode(&bytes, &Json::default()).unwrap();
+    assert_eq!(decoded.version(), 1);
+
+    let mut corrupt = bytes;
+    corrupt[21] = b'4';
+    let err = envelope::decode::<SampleVersions, _>(&corrupt, &Json::default()).unwrap_err();
+    assert!(matches!(err, envelope::Error::ChecksumMismatch { .. }));
+    assert!(err.to_string().starts_with("envelope payload is corrupt"));
+
+    assert!(matches!(
+        envelope::read_header::<()>(b"SEVK\x01\0\0\0\0\0"),
+        Err(envelope::Error::Truncated)
+    ));
+}
+
+/// Stands in for a compressor: reverses the payload bytes.
+#[cfg(feature = "json")]
+struct Reversed;
+
+#[cfg(feature = "json")]
+impl envelope::Transform for Reversed {
+    type Error = std::io::Error;
+
+    fn apply(&self, mut payload: Vec<u8>) -> Result<Vec<u8>, Self::Error> {
+        payload.reverse();
+        Ok(payload)
+    }
+
+    fn reverse(&self, payload: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        if payload.first() != Some(&b'}') {
+            return Err(std::io::Error::other("not a reversed document"));
+        }
+        Ok(payload.iter().rev().copied().collect())
+    }
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn transformed_payloads_keep_a_readable_header() {
+    use serde_evolve::format::Json;
+
+    let format = envelope::Transformed::new(Json::default(), Reversed);
+    let rep = SampleVersions::from(SampleV1 { value: 3 });
+    let bytes = envelope::encode(&rep, &format).unwrap();
+    assert_eq!(&bytes[..8], b"SEVO\x01\0\0\0");
+    assert_eq!(&bytes[8..], br#"}3:"eulav"{"#);
+
+    let decoded: SampleVersions = envelope::decode(&bytes, &format).unwrap();
+    assert_eq!(decoded.version(), 1);
+
+    let plain = envelope::encode(&rep, &Json::default()).unwrap();
+    let err = envelope::decode::<SampleVersions, _>(&plain, &format).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "format error: payload transform failed: not a reversed document"
+    );
+}
+
+#[cfg(feature = "postcard")]
+#[test]
+fn envelope_supports_non_self_describing_formats() {
+    use serde_evolve::format::Postcard;
+
+    let sample = Sample {
+        value: 70_000,
+        unit: "us".to_string(),
+    };
+    let bytes = envelope::encode(&SampleVersions::from(&sample), &Postcard).unwrap();
+    let decoded: SampleVersions = envelope::decode(&bytes, &Postcard).unwrap();
+    assert!(decoded.is_current());
+    assert_eq!(Sample::from(decoded), sample);
+
+    let mut future = bytes;
+    future[4] = 9;
+    let err = envelope::decode::<SampleVersions, _>(&future, &Postcard).unwrap_err();
+    assert_eq!(err.to_string(), "unsupported version 9, expected 1..=2");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn structured_envelope_keeps_the_version_outside_the_payload() {
+    use serde_evolve::Envelope;
+
+    let sample = Sample {
+        value: 5,
+        unit: "s".to_string(),
+    };
+    let envelope = Envelope::wrap(&sample).unwrap();
+    assert_eq!(
+        serde_json::to_value(&envelope).unwrap(),
+        serde_json::json!({"version": 2, "payload": {"value": 5, "unit": "s"}})
+    );
+    assert_eq!(envelope.open::<Sample>().unwrap(), sample);
+
+    let old: Envelope = serde_json::from_str(r#"{"version":1,"payload":{"value":7}}"#).unwrap();
+    assert_eq!(
+        old.open::<Sample>().unwrap(),
+        Sample {
+            value: 7,
+            unit: "ms".to_string(),
+        }
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn structured_envelope_reports_unknown_versions() {
+    use serde_evolve::Envelope;
+
+    let future: Envelope = serde_json::from_str(r#"{"version":9,"payload":{}}"#).unwrap();
+    let err = future.open::<Sample>().unwrap_err();
+    assert_eq!(err.to_string(), "unsupported version 9, expected 1..=2");
+}
@@ -0,0 +1,222 @@
+//! Tests for structured errors
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::payload::read_payload;
+use serde_evolve::{
+    ConvertError, MigrationStep, Payload, StepError, UnsupportedVersion, Versioned, VersionedError,
+    read_json, read_json_slice,
+};
+use serde_json::json;
+use std::convert::TryFrom;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitV1 {
+    pub max: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitV2 {
+    pub max: u32,
+}
+
+impl TryFrom<LimitV1> for LimitV2 {
+    type Error = String;
+
+    fn try_from(v1: LimitV1) -> Result<Self, Self::Error> {
+        u32::try_from(v1.max)
+            .map(|max| Self { max })
+            .map_err(|_| format!("limit {} is out of range", v1.max))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(
+    error = String,
+    step_context = true,
+    chain(LimitV1, LimitV2 as "two"),
+    derive(PartialEq, Eq)
+)]
+pub struct Limit {
+    pub max: u32,
+}
+
+impl TryFrom<LimitV2> for Limit {
+    type Error = String;
+
+    fn try_from(v2: LimitV2) -> Result<Self, Self::Error> {
+        Ok(Self { max: v2.max })
+    }
+}
+
+impl From<&Limit> for LimitV2 {
+    fn from(limit: &Limit) -> Self {
+        Self { max: limit.max }
+    }
+}
+
+fn payload(value: &serde_json::Value) -> Payload {
+    Payload::from_serialize(value).unwrap()
+}
+
+#[test]
+fn reads_payloads_by_tag() {
+    let rep: LimitVersions = read_payload("two", payload(&json!({"max": 5}))).unwrap();
+    assert_eq!(rep, LimitVersions::V2(LimitV2 { max: 5 }));
+
+    let err = read_payload::<LimitVersions>("three", payload(&json!({"max": 5}))).unwrap_err();
+    assert_eq!(
+        err,
+        VersionedError::UnknownTag {
+            tag: "three".to_string()
+        }
+    );
+
+    let err = read_payload::<LimitVersions>("9", payload(&json!({"max": 5}))).unwrap_err();
+    assert_eq!(
+        err,
+        VersionedError::UnsupportedVersion(UnsupportedVersion {
+            found: 9,
+            min: 1,
+            max: 2
+        })
+    );
+
+    let err = read_payload::<LimitVersions>("1", payload(&json!({"max": "x"}))).unwrap_err();
+    assert!(matches!(
+        err,
+        VersionedError::Payload {
+            version: Some(1),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn reads_documents_by_category() {
+    let rep: LimitVersions = read_json(r#"{"_version":"two","max":5}"#).unwrap();
+    assert_eq!(rep, LimitVersions::V2(LimitV2 { max: 5 }));
+
+    let err = read_json::<LimitVersions>(r#"{"max":5,"_version":"three"}"#).unwrap_err();
+    assert_eq!(
+        err,
+        VersionedError::UnknownTag {
+            tag: "three".to_string()
+        }
+    );
+
+    let err = read_json_slice::<LimitVersions>(br#"{"_version":"9","max":5}"#).unwrap_err();
+    assert!(matches!(
+        err,
+        VersionedError::UnsupportedVersion(UnsupportedVersion { found: 9, .. })
+    ));
+
+    let err = read_json::<LimitVersions>(r#"{"_version":"1","max":"x"}"#).unwrap_err();
+    assert!(matches!(
+        err,
+        VersionedError::Payload {
+            version: Some(1),
+            ..
+        }
+    ));
+
+    let err = read_json::<LimitVersions>(r#"{"max":5}"#).unwrap_err();
+    assert_eq!(
+        err,
+        VersionedError::Payload {
+            version: None,
+            message: "missing field `_version` at line 1 column 9".to_string(),
+        }
+    );
+}
+
+#[test]
+fn migration_failures_keep_their_context() {
+    let err = Limit::try_from(LimitVersions::V1(LimitV1 { max: -1 })).unwrap_err();
+    let err = VersionedError::from(err);
+    assert_eq!(
+        err,
+        VersionedError::Migration {
+            source_version: Some(1),
+            step: Some(MigrationStep {
+                from: 1,
+                to: Some(2)
+            }),
+            message: "limit -1 is out of range".to_string(),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "migration failed reading V1 at V1 -> V2: limit -1 is out of range"
+    );
+
+    let err = LimitVersions::V1(LimitV1 { max: -1 })
+        .upgrade_once()
+        .map_err(ConvertError::Step)
+        .unwrap_err();
+    assert!(matches!(
+        VersionedError::from(err),
+        VersionedError::Migration {
+            source_version: Some(1),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn serializes_by_category() {
+    let err = VersionedError::UnknownTag {
+        tag: "three".to_string(),
+    };
+    let value = serde_json::to_value(&err).unwrap();
+    assert_eq!(value, json!({"kind": "unknown_tag", "tag": "three"}));
+    assert_eq!(
+        serde_json::from_value::<VersionedError>(value).unwrap(),
+        err
+    );
+
+    let err = VersionedError::from(ConvertError::<StepError<String>>::Unsupported(
+        UnsupportedVersion {
+            found: 0,
+            min: 1,
+            max: 2,
+        },
+    ));
+    assert_eq!(
+        serde_json::to_value(&err).unwrap(),
+        json!({"kind": "unsupported_version", "found": 0, "min": 1, "max": 2})
+    );
+}
+
+#[test]
+fn converts_the_errors_of_every_module() {
+    let format = serde_evolve::format::Json::default();
+    let err = serde_evolve::envelope::decode::<LimitVersions, _>(b"SEV", &format).unwrap_err();
+    assert_eq!(VersionedError::from(err), VersionedError::Truncated);
+    let err = serde_evolve::envelope::decode::<LimitVersions, _>(b"NOPE\0\0\0\x01{}", &format)
+        .unwrap_err();
+    assert_eq!(VersionedError::from(err), VersionedError::BadMagic);
+
+    let dir = tempfile::tempdir().unwrap();
+    let err = serde_evolve::fs::read::<LimitVersions, _>(dir.path().join("missing.json"), &format)
+        .unwrap_err();
+    assert!(matches!(
+        VersionedError::from(err),
+        VersionedError::Io { .. }
+    ));
+
+    let err = serde_evolve::registry::Error::UnknownType("invoice".to_string());
+    let err = VersionedError::from(err);
+    assert_eq!(
+        serde_json::to_value(&err).unwrap(),
+        json!({"kind": "unknown_type", "name": "invoice"})
+    );
+
+    let err = serde_evolve::sniff::Error::Unrecognised;
+    assert!(matches!(
+        VersionedError::from(err),
+        VersionedError::Payload { version: None, .. }
+    ));
+}
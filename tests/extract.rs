@@ -0,0 +1,187 @@
+//! Tests for extracting a single field across versions
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::extract::Field;
+use serde_evolve::{Versioned, VersionedError};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountV1 {
+    pub user_id: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountV2 {
+    pub user_id: u64,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Owner {
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountV3 {
+    pub owners: Vec<Owner>,
+    pub name: String,
+}
+
+impl From<AccountV1> for AccountV2 {
+    fn from(v1: AccountV1) -> Self {
+        Self {
+            user_id: v1.user_id,
+            name: String::new(),
+        }
+    }
+}
+
+impl From<AccountV2> for AccountV3 {
+    fn from(v2: AccountV2) -> Self {
+        Self {
+            owners: vec![Owner { id: v2.user_id }],
+            name: v2.name,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(mode = "infallible", chain(AccountV1, AccountV2, AccountV3))]
+pub struct Account {
+    pub owners: Vec<Owner>,
+    pub name: String,
+}
+
+impl From<AccountV3> for Account {
+    fn from(v3: AccountV3) -> Self {
+        Self {
+            owners: v3.owners,
+            name: v3.name,
+        }
+    }
+}
+
+impl From<&Account> for AccountV3 {
+    fn from(account: &Account) -> Self {
+        Self {
+            owners: account.owners.clone(),
+            name: account.name.clone(),
+        }
+    }
+}
+
+fn owner_id() -> Field<u64> {
+    Field::new().since(1, "/user_id").since(3, "/owners/0/id")
+}
+
+#[test]
+fn extracts_the_field_of_each_version() {
+    let id = owner_id();
+    assert_eq!(
+        id.extract::<AccountVersions>(r#"{"_version": "1", "user_id": 7}"#),
+        Ok(7)
+    );
+    assert_eq!(
+        id.extract::<AccountVersions>(r#"{"name": "ada", "user_id": 8, "_version": "2"}"#),
+        Ok(8)
+    );
+    assert_eq!(
+        id.extract::<AccountVersions>(
+            r#"{"_version": "3", "name": "ada", "owners": [{"id": 9}, {"id": 10}]}"#
+        ),
+        Ok(9)
+    );
+}
+
+#[test]
+fn inherits_locations_from_older_versions() {
+    let id = owner_id();
+    assert_eq!(id.pointer(0), None);
+    assert_eq!(id.pointer(2), Some("/user_id"));
+    assert_eq!(id.pointer(4), Some("/owners/0/id"));
+
+    let moved = id.since(3, "/owners/1/id");
+    assert_eq!(moved.pointer(3), Some("/owners/1/id"));
+    assert_eq!(
+        moved.extract_at(3, r#"{"owners": [{"id": 9}, {"id": 10}]}"#),
+        Ok(10)
+    );
+}
+
+#[test]
+fn reads_only_the_path_to_the_field() {
+    // Values off the path, and anything after the field, are skipped without being read.
+    let id = Field::<u64>::new().since(1, "/a~1b/c~0d");
+    assert_eq!(
+        id.extract_at(1, r#"{"x": [1, {"y": null}], "a/b": {"c~d": 5, "e": ]]]"#),
+        Ok(5)
+    );
+}
+
+#[test]
+fn reports_missing_and_mistyped_fields() {
+    let id = owner_id();
+    let err = id
+        .extract::<AccountVersions>(r#"{"_version": "3", "owners": []}"#)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        VersionedError::Payload {
+            version: Some(3),
+            message: "no value at `/owners/0/id`".to_string(),
+        }
+    );
+
+    // Elements after the path are skipped, not reported as trailing input.
+    let err = id
+        .extract::<AccountVersions>(r#"{"_version": "3", "owners": [{}, {"id": 10}]}"#)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        VersionedError::Payload {
+            version: Some(3),
+            message: "no value at `/owners/0/id`".to_string(),
+        }
+    );
+    let err = Field::<u64>::new()
+        .since(1, "/owners/first/id")
+        .extract_at(1, r#"{"owners": [{"id": 9}]}"#)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        VersionedError::Payload {
+            version: Some(1),
+            message: "no value at `/owners/first/id`".to_string(),
+        }
+    );
+
+    let err = id
+        .extract::<AccountVersions>(r#"{"_version": "1", "user_id": "seven"}"#)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        VersionedError::Payload {
+            version: Some(1),
+            ..
+        }
+    ));
+
+    let err = Field::<u64>::new()
+        .since(2, "/user_id")
+        .extract::<AccountVersions>(r#"{"_version": "1", "user_id": 7}"#)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        VersionedError::Payload {
+            version: Some(1),
+            ..
+        }
+    ));
+
+    let err = id
+        .extract::<AccountVersions>(r#"{"_version": "9", "user_id": 7}"#)
+        .unwrap_err();
+    assert!(matches!(err, VersionedError::UnsupportedVersion(_)));
+}
@@ -0,0 +1,116 @@
+//! Tests for the figment provider
+
+#![cfg(all(feature = "figment", feature = "json"))]
+#![allow(missing_docs)]
+
+use figment::Figment;
+use figment::providers::Serialized;
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_evolve::figment::{VersionedFile, extract};
+use serde_evolve::format::Json;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SettingsV1 {
+    pub host: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SettingsV2 {
+    pub host: String,
+    pub port: u16,
+}
+
+impl From<SettingsV1> for SettingsV2 {
+    fn from(v1: SettingsV1) -> Self {
+        Self {
+            host: v1.host,
+            port: 8080,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(mode = "infallible", chain(SettingsV1, SettingsV2))]
+pub struct Settings {
+    pub host: String,
+    pub port: u16,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 80,
+        }
+    }
+}
+
+impl From<SettingsV2> for Settings {
+    fn from(v2: SettingsV2) -> Self {
+        Self {
+            host: v2.host,
+            port: v2.port,
+        }
+    }
+}
+
+impl From<&Settings> for SettingsV2 {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            host: settings.host.clone(),
+            port: settings.port,
+        }
+    }
+}
+
+fn layered(file: VersionedFile<Settings, Json>) -> Figment {
+    Figment::new()
+        .merge(Serialized::defaults(SettingsVersions::from(
+            &Settings::default(),
+        )))
+        .merge(file)
+}
+
+#[test]
+fn migrates_the_file_layer_under_later_layers() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.json");
+    std::fs::write(&path, r#"{"_version":"1","host":"example.com"}"#).unwrap();
+
+    let figment = layered(VersionedFile::new(&path, Json::default()));
+    assert_eq!(
+        extract::<Settings>(&figment).unwrap(),
+        Settings {
+            host: "example.com".to_string(),
+            port: 8080
+        }
+    );
+
+    let figment = figment.merge(Serialized::default("port", 9000));
+    assert_eq!(extract::<Settings>(&figment).unwrap().port, 9000);
+}
+
+#[test]
+fn missing_files_provide_nothing_unless_required() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing.json");
+
+    let figment = layered(VersionedFile::new(&path, Json::default()));
+    assert_eq!(extract::<Settings>(&figment).unwrap(), Settings::default());
+
+    let figment = layered(VersionedFile::new(&path, Json::default()).required(true));
+    let err = extract::<Settings>(&figment).unwrap_err();
+    assert!(err.to_string().starts_with("cannot read"), "{err}");
+}
+
+#[test]
+fn reports_files_of_no_known_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("app.json");
+    std::fs::write(&path, r#"{"_version":"9","host":"example.com"}"#).unwrap();
+
+    let figment = layered(VersionedFile::new(&path, Json::default()));
+    let err = extract::<Settings>(&figment).unwrap_err();
+    assert!(err.to_string().starts_with("cannot decode"), "{err}");
+}
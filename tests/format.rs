@@ -0,0 +1,198 @@
+//! Tests for the pluggable `Format` implementations
+
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+#[allow(unused_imports)]
+use serde_evolve::Format;
+use serde_evolve::Versioned;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoteV1 {
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoteV2 {
+    pub text: String,
+    pub pinned: bool,
+}
+
+impl From<NoteV1> for NoteV2 {
+    fn from(v1: NoteV1) -> Self {
+        Self {
+            text: v1.text,
+            pinned: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(mode = "infallible", chain(NoteV1, NoteV2))]
+pub struct Note {
+    pub text: String,
+    pub pinned: bool,
+}
+
+impl From<NoteV2> for Note {
+    fn from(v2: NoteV2) -> Self {
+        Self {
+            text: v2.text,
+            pinned: v2.pinned,
+        }
+    }
+}
+
+impl From<&Note> for NoteV2 {
+    fn from(note: &Note) -> Self {
+        Self {
+            text: note.text.clone(),
+            pinned: note.pinned,
+        }
+    }
+}
+
+/// Encode a V1 document, decode it through the representation enum, and round-trip the
+/// migrated value at the latest version.
+#[cfg(any(
+    feature = "json",
+    feature = "yaml",
+    feature = "toml",
+    feature = "cbor",
+    feature = "msgpack"
+))]
+fn check_round_trip<F: Format>(format: &F) {
+    let bytes = format
+        .encode(&NoteVersions::from(NoteV1 {
+            text: "hello".to_string(),
+        }))
+        .unwrap();
+
+    let rep: NoteVersions = format.decode(&bytes).unwrap();
+    assert_eq!(rep.version(), 1);
+    let note = Note::from(rep);
+    assert_eq!(
+        note,
+        Note {
+            text: "hello".to_string(),
+            pinned: false
+        }
+    );
+
+    let bytes = format.encode(&NoteVersions::from(&note)).unwrap();
+    let rep: NoteVersions = format.decode(&bytes).unwrap();
+    assert!(rep.is_current());
+    assert_eq!(Note::from(rep), note);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_round_trip() {
+    use serde_evolve::format::Json;
+
+    check_round_trip(&Json::default());
+    check_round_trip(&Json::pretty());
+    assert_eq!(Json::default().extension(), "json");
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn yaml_round_trip() {
+    check_round_trip(&serde_evolve::format::Yaml);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml_round_trip() {
+    check_round_trip(&serde_evolve::format::Toml);
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_round_trip() {
+    check_round_trip(&serde_evolve::format::Cbor);
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn msgpack_round_trip() {
+    check_round_trip(&serde_evolve::format::MessagePack);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn formats_are_usable_by_reference() {
+    let format = serde_evolve::format::Json::default();
+    check_round_trip(&&format);
+}
+
+#[cfg(feature = "cbor")]
+mod cbor_tags {
+    use super::*;
+    use serde_evolve::format::Cbor;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(NoteV1, NoteV2), cbor_tag = 55800)]
+    pub struct TaggedNote {
+        pub text: String,
+        pub pinned: bool,
+    }
+
+    impl From<NoteV2> for TaggedNote {
+        fn from(v2: NoteV2) -> Self {
+            Self {
+                text: v2.text,
+                pinned: v2.pinned,
+            }
+        }
+    }
+
+    impl From<&TaggedNote> for NoteV2 {
+        fn from(note: &TaggedNote) -> Self {
+            Self {
+                text: note.text.clone(),
+                pinned: note.pinned,
+            }
+        }
+    }
+
+    #[test]
+    fn cbor_carries_the_version_as_a_tag() {
+        let rep = TaggedNoteVersions::from(NoteV1 {
+            text: "hi".to_string(),
+        });
+        let bytes = Cbor.encode(&rep).unwrap();
+        // Tag 55801 (major type 6, two-byte argument) wrapping a map with a single entry.
+        assert_eq!(&bytes[..4], &[0xd9, 0xd9, 0xf9, 0xa1]);
+
+        let rep: TaggedNoteVersions = Cbor.decode(&bytes).unwrap();
+        assert_eq!(rep.version(), 1);
+
+        let note = TaggedNote {
+            text: "hi".to_string(),
+            pinned: true,
+        };
+        let bytes = Cbor.encode(&TaggedNoteVersions::from(&note)).unwrap();
+        let rep: TaggedNoteVersions = Cbor.decode(&bytes).unwrap();
+        assert_eq!(TaggedNote::from(rep), note);
+
+        let untagged = Cbor
+            .encode(&NoteV1 {
+                text: "hi".to_string(),
+            })
+            .unwrap();
+        assert!(Cbor.decode::<TaggedNoteVersions>(&untagged).is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn human_readable_formats_keep_the_field_tag() {
+        let rep = TaggedNoteVersions::from(NoteV1 {
+            text: "hi".to_string(),
+        });
+        let json = serde_json::to_string(&rep).unwrap();
+        assert_eq!(json, r#"{"_version":"1","text":"hi"}"#);
+        let rep: TaggedNoteVersions = serde_json::from_str(&json).unwrap();
+        assert_eq!(rep.version(), 1);
+    }
+}
@@ -0,0 +1,209 @@
+//! Tests for the filesystem helpers
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::{Versioned, format::Json, fs};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportV1 {
+    pub rows: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportV2 {
+    pub rows: Vec<String>,
+    pub compressed: bool,
+}
+
+impl From<ExportV1> for ExportV2 {
+    fn from(v1: ExportV1) -> Self {
+        Self {
+            rows: v1.rows,
+            compressed: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(mode = "infallible", chain(ExportV1, ExportV2))]
+pub struct Export {
+    pub rows: Vec<String>,
+    pub compressed: bool,
+}
+
+impl From<ExportV2> for Export {
+    fn from(v2: ExportV2) -> Self {
+        Self {
+            rows: v2.rows,
+            compressed: v2.compressed,
+        }
+    }
+}
+
+impl From<&Export> for ExportV2 {
+    fn from(export: &Export) -> Self {
+        Self {
+            rows: export.rows.clone(),
+            compressed: export.compressed,
+        }
+    }
+}
+
+fn write_v1(dir: &tempfile::TempDir) -> std::path::PathBuf {
+    let path = dir.path().join("export.json");
+    std::fs::write(&path, r#"{"_version":"1","rows":["a","b","c"]}"#).unwrap();
+    path
+}
+
+#[test]
+fn read_decodes_any_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_v1(&dir);
+
+    let rep: ExportVersions = fs::read(&path, &Json::default()).unwrap();
+    assert_eq!(rep.version(), 1);
+    assert_eq!(Export::from(rep).rows, ["a", "b", "c"]);
+}
+
+#[test]
+fn read_reports_io_and_format_errors() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let missing = fs::read::<ExportVersions, _>(dir.path().join("missing.json"), &Json::default());
+    assert!(matches!(missing, Err(fs::Error::Io(_))));
+
+    let path = dir.path().join("garbage.json");
+    std::fs::write(&path, "not json").unwrap();
+    let garbage = fs::read::<ExportVersions, _>(&path, &Json::default());
+    assert!(matches!(garbage, Err(fs::Error::Format(_))));
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn read_mmap_decodes_mapped_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_v1(&dir);
+
+    let rep: ExportVersions = fs::read_mmap(&path, &Json::default()).unwrap();
+    assert_eq!(Export::from(rep).rows, ["a", "b", "c"]);
+
+    let mapped = fs::Mapped::open(&path).unwrap();
+    assert!(mapped.bytes().starts_with(br#"{"_version":"1""#));
+    let rep: ExportVersions = mapped.decode(&Json::default()).unwrap();
+    assert_eq!(rep.version(), 1);
+}
+
+#[test]
+fn migrate_file_rewrites_old_versions_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_v1(&dir);
+
+    assert!(fs::migrate_file::<ExportVersions, Export, _>(&path, &Json::default()).unwrap());
+    let rewritten: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(
+        rewritten,
+        serde_json::json!({"_version": "2", "rows": ["a", "b", "c"], "compressed": false})
+    );
+
+    assert!(!fs::migrate_file::<ExportVersions, Export, _>(&path, &Json::default()).unwrap());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn load_and_upgrade_backs_up_before_writing() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_v1(&dir);
+    let original = std::fs::read(&path).unwrap();
+    // A directory in the way of the backup fails the write-back before anything is written.
+    std::fs::create_dir(dir.path().join("export.json.bak")).unwrap();
+
+    let result = fs::load_and_upgrade::<ExportVersions, Export, _>(
+        &path,
+        &Json::default(),
+        fs::WriteBack::RewriteWithBackup,
+    );
+
+    assert!(matches!(result, Err(fs::Error::Io(_))));
+    assert_eq!(std::fs::read(&path).unwrap(), original);
+    assert!(!dir.path().join("export.json.migrating").exists());
+}
+
+#[test]
+fn migrate_path_walks_directories() {
+    let dir = tempfile::tempdir().unwrap();
+    let old = write_v1(&dir);
+    let nested = dir.path().join("nested");
+    std::fs::create_dir(&nested).unwrap();
+    let current = nested.join("current.json");
+    std::fs::write(&current, r#"{"_version":"2","rows":[],"compressed":true}"#).unwrap();
+    let broken = nested.join("broken.json");
+    std::fs::write(&broken, "{").unwrap();
+    std::fs::write(nested.join("notes.txt"), "not a document").unwrap();
+
+    let report =
+        fs::migrate_path::<ExportVersions, Export, _>(dir.path(), &Json::default()).unwrap();
+
+    assert_eq!(report.migrated, vec![old]);
+    assert_eq!(report.unchanged, vec![current]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, broken);
+    assert!(matches!(report.failed[0].1, fs::Error::Format(_)));
+}
+
+#[test]
+fn migrate_path_reports_missing_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    let err =
+        fs::migrate_path::<ExportVersions, Export, _>(dir.path().join("missing"), &Json::default())
+            .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn load_and_upgrade_leaves_files_alone_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_v1(&dir);
+    let original = std::fs::read(&path).unwrap();
+
+    let export: Export =
+        fs::load_and_upgrade::<ExportVersions, _, _>(&path, &Json::default(), fs::WriteBack::Never)
+            .unwrap();
+
+    assert_eq!(export.rows, vec!["a", "b", "c"]);
+    assert_eq!(std::fs::read(&path).unwrap(), original);
+}
+
+#[test]
+fn load_and_upgrade_rewrites_with_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_v1(&dir);
+    let original = std::fs::read(&path).unwrap();
+
+    let export: Export = fs::load_and_upgrade::<ExportVersions, _, _>(
+        &path,
+        &Json::default(),
+        fs::WriteBack::RewriteWithBackup,
+    )
+    .unwrap();
+
+    assert!(!export.compressed);
+    assert_eq!(
+        std::fs::read(dir.path().join("export.json.bak")).unwrap(),
+        original
+    );
+    let rep: ExportVersions = fs::read(&path, &Json::default()).unwrap();
+    assert!(rep.is_current());
+
+    // A second load finds the file current and takes no further backup.
+    std::fs::remove_file(dir.path().join("export.json.bak")).unwrap();
+    fs::load_and_upgrade::<ExportVersions, Export, _>(
+        &path,
+        &Json::default(),
+        fs::WriteBack::RewriteWithBackup,
+    )
+    .unwrap();
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
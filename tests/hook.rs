@@ -0,0 +1,88 @@
+//! Migration hooks. These live in their own test binary because the global hook is
+//! process-wide state.
+
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_evolve::hook::{self, MigrationEvent};
+use std::sync::Mutex;
+
+static LOCAL: Mutex<Vec<MigrationEvent>> = Mutex::new(Vec::new());
+
+fn record(event: &MigrationEvent) {
+    LOCAL.lock().unwrap().push(*event);
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderV1 {
+    pub id: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderV2 {
+    pub id: u64,
+    pub notes: String,
+}
+
+impl From<OrderV1> for OrderV2 {
+    fn from(v1: OrderV1) -> Self {
+        Self {
+            id: v1.id,
+            notes: String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(mode = "infallible", chain(OrderV1, OrderV2), on_migrate = record)]
+pub struct Order {
+    pub id: u64,
+    pub notes: String,
+}
+
+impl From<OrderV2> for Order {
+    fn from(v2: OrderV2) -> Self {
+        Self {
+            id: v2.id,
+            notes: v2.notes,
+        }
+    }
+}
+
+impl From<&Order> for OrderV2 {
+    fn from(order: &Order) -> Self {
+        Self {
+            id: order.id,
+            notes: order.notes.clone(),
+        }
+    }
+}
+
+#[test]
+fn hooks_observe_migrations_of_old_versions() {
+    let global = std::sync::Arc::new(Mutex::new(Vec::new()));
+    let sink = global.clone();
+    hook::set_global(move |event| sink.lock().unwrap().push(*event));
+
+    let rep: OrderVersions = serde_json::from_str(r#"{"_version":"1","id":7}"#).unwrap();
+    let order = Order::from(rep);
+    let rep: OrderVersions =
+        serde_json::from_str(r#"{"_version":"2","id":8,"notes":"n"}"#).unwrap();
+    let _ = Order::from(rep);
+    assert_eq!(order.id, 7);
+
+    let expected = vec![MigrationEvent {
+        type_name: "Order",
+        from: 1,
+        to: 2,
+    }];
+    assert_eq!(*LOCAL.lock().unwrap(), expected);
+    assert_eq!(*global.lock().unwrap(), expected);
+
+    hook::clear_global();
+    let rep: OrderVersions = serde_json::from_str(r#"{"_version":"1","id":9}"#).unwrap();
+    let _ = Order::from(rep);
+    assert_eq!(LOCAL.lock().unwrap().len(), 2);
+    assert_eq!(global.lock().unwrap().len(), 1);
+}
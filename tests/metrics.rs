@@ -0,0 +1,142 @@
+//! Tests for the migration counter of the `metrics` feature
+
+#![cfg(all(feature = "metrics", feature = "json"))]
+#![allow(missing_docs)]
+
+use metrics::{
+    Counter, CounterFn, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+};
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TicketV1 {
+    pub title: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TicketV2 {
+    pub title: String,
+    pub open: bool,
+}
+
+impl From<TicketV1> for TicketV2 {
+    fn from(v1: TicketV1) -> Self {
+        Self {
+            title: v1.title,
+            open: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(mode = "infallible", chain(TicketV1, TicketV2))]
+pub struct Ticket {
+    pub title: String,
+    pub open: bool,
+}
+
+impl From<TicketV2> for Ticket {
+    fn from(v2: TicketV2) -> Self {
+        Self {
+            title: v2.title,
+            open: v2.open,
+        }
+    }
+}
+
+impl From<&Ticket> for TicketV2 {
+    fn from(ticket: &Ticket) -> Self {
+        Self {
+            title: ticket.title.clone(),
+            open: ticket.open,
+        }
+    }
+}
+
+/// Records the counters registered with it, and their totals.
+#[derive(Default)]
+struct CountingRecorder {
+    counters: Mutex<Vec<(Key, Arc<AtomicU64>)>>,
+}
+
+struct Total(Arc<AtomicU64>);
+
+impl CounterFn for Total {
+    fn increment(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.0.fetch_max(value, Ordering::Relaxed);
+    }
+}
+
+impl CountingRecorder {
+    /// The total of each counter, rendered as `name{label=value,...}`.
+    fn totals(&self) -> Vec<(String, u64)> {
+        let mut totals = Vec::<(String, u64)>::new();
+        for (key, total) in self.counters.lock().unwrap().iter() {
+            let labels: Vec<_> = key
+                .labels()
+                .map(|label| format!("{}={}", label.key(), label.value()))
+                .collect();
+            let series = format!("{}{{{}}}", key.name(), labels.join(","));
+            let total = total.load(Ordering::Relaxed);
+            match totals.iter_mut().find(|(existing, _)| *existing == series) {
+                Some(entry) => entry.1 += total,
+                None => totals.push((series, total)),
+            }
+        }
+        totals
+    }
+}
+
+impl Recorder for CountingRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        let total = Arc::new(AtomicU64::new(0));
+        self.counters
+            .lock()
+            .unwrap()
+            .push((key.clone(), total.clone()));
+        Counter::from_arc(Arc::new(Total(total)))
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+#[test]
+fn counts_migrations_of_old_versions() {
+    let recorder = CountingRecorder::default();
+
+    metrics::with_local_recorder(&recorder, || {
+        for document in [
+            r#"{"_version":"1","title":"a"}"#,
+            r#"{"_version":"1","title":"b"}"#,
+            r#"{"_version":"2","title":"c","open":false}"#,
+        ] {
+            let rep: TicketVersions = serde_json::from_str(document).unwrap();
+            let _ = Ticket::from(rep);
+        }
+    });
+
+    assert_eq!(
+        recorder.totals(),
+        [(
+            "serde_evolve_migrations_total{type=Ticket,from_version=1}".to_string(),
+            2
+        )]
+    );
+}
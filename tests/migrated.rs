@@ -0,0 +1,156 @@
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::{Migrated, Versioned};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaskV1 {
+    pub title: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaskV2 {
+    pub title: String,
+    pub done: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaskV3 {
+    pub title: String,
+    pub done: bool,
+    pub priority: u8,
+}
+
+impl TryFrom<TaskV1> for TaskV2 {
+    type Error = String;
+
+    fn try_from(v1: TaskV1) -> Result<Self, Self::Error> {
+        if v1.title.is_empty() {
+            return Err("title must not be empty".to_string());
+        }
+        Ok(Self {
+            title: v1.title,
+            done: false,
+        })
+    }
+}
+
+impl TryFrom<TaskV2> for TaskV3 {
+    type Error = String;
+
+    fn try_from(v2: TaskV2) -> Result<Self, Self::Error> {
+        Ok(Self {
+            title: v2.title,
+            done: v2.done,
+            priority: 0,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(error = String, chain(TaskV1, TaskV2, TaskV3))]
+pub struct Task {
+    pub title: String,
+    pub done: bool,
+    pub priority: u8,
+}
+
+impl TryFrom<TaskV3> for Task {
+    type Error = String;
+
+    fn try_from(v3: TaskV3) -> Result<Self, Self::Error> {
+        Ok(Self {
+            title: v3.title,
+            done: v3.done,
+            priority: v3.priority,
+        })
+    }
+}
+
+impl From<&Task> for TaskV3 {
+    fn from(task: &Task) -> Self {
+        Self {
+            title: task.title.clone(),
+            done: task.done,
+            priority: task.priority,
+        }
+    }
+}
+
+#[test]
+fn records_the_source_version() {
+    let task: Migrated<Task> = serde_json::from_str(r#"{"_version":"1","title":"write"}"#).unwrap();
+    assert_eq!(task.source_version, 1);
+    assert!(task.migrated);
+    assert_eq!(task.steps(), 2);
+    assert_eq!(
+        task.into_inner(),
+        Task {
+            title: "write".to_string(),
+            done: false,
+            priority: 0,
+        }
+    );
+}
+
+#[test]
+fn current_documents_are_not_migrated() {
+    let task: Migrated<Task> =
+        serde_json::from_str(r#"{"_version":"3","title":"ship","done":true,"priority":2}"#)
+            .unwrap();
+    assert_eq!(task.source_version, 3);
+    assert!(!task.migrated);
+    assert_eq!(task.steps(), 0);
+    assert!(task.value.done);
+}
+
+#[test]
+fn migration_errors_fail_deserialization() {
+    let err = serde_json::from_str::<Migrated<Task>>(r#"{"_version":"1","title":""}"#).unwrap_err();
+    assert!(err.to_string().contains("title must not be empty"));
+}
+
+#[test]
+fn from_rep_records_the_version() {
+    let rep = TaskVersions::V2(TaskV2 {
+        title: "review".to_string(),
+        done: true,
+    });
+    let task = Migrated::<Task>::from_rep(rep).unwrap();
+    assert_eq!(task.source_version, 2);
+    assert_eq!(task.steps(), 1);
+}
+
+#[cfg(feature = "json")]
+mod preserved {
+    use super::*;
+    use serde_evolve::Preserved;
+
+    #[test]
+    fn keeps_the_exact_original_document() {
+        let json = r#"{ "title": "write",  "_version": "1" }"#;
+        let task: Preserved<Task> = serde_json::from_str(json).unwrap();
+        assert_eq!(task.raw.get(), json);
+        assert_eq!(task.source_version, 1);
+        assert!(task.migrated());
+        assert_eq!(task.into_inner().title, "write");
+    }
+
+    #[test]
+    fn keeps_nested_documents_verbatim() {
+        let json = r#"[{"_version":"3","title":"ship","done":true,"priority":2}]"#;
+        let tasks: Vec<Preserved<Task>> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            tasks[0].raw.get(),
+            r#"{"_version":"3","title":"ship","done":true,"priority":2}"#
+        );
+        assert!(!tasks[0].migrated());
+    }
+
+    #[test]
+    fn migration_errors_fail_deserialization() {
+        let err =
+            serde_json::from_str::<Preserved<Task>>(r#"{"_version":"1","title":""}"#).unwrap_err();
+        assert!(err.to_string().contains("title must not be empty"));
+    }
+}
@@ -0,0 +1,185 @@
+//! Tests for the Mongo filter helpers
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::{Versioned, mongo};
+use serde_json::json;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ItemV1 {
+    pub sku: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ItemV2 {
+    pub sku: String,
+    pub stock: u32,
+}
+
+impl From<ItemV1> for ItemV2 {
+    fn from(v1: ItemV1) -> Self {
+        Self {
+            sku: v1.sku,
+            stock: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(mode = "infallible", chain(ItemV1, ItemV2), tag = "schema")]
+pub struct Item {
+    pub sku: String,
+    pub stock: u32,
+}
+
+impl From<ItemV2> for Item {
+    fn from(v2: ItemV2) -> Self {
+        Self {
+            sku: v2.sku,
+            stock: v2.stock,
+        }
+    }
+}
+
+impl From<&Item> for ItemV2 {
+    fn from(item: &Item) -> Self {
+        Self {
+            sku: item.sku.clone(),
+            stock: item.stock,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(mode = "infallible", chain(ItemV1, ItemV2), tag_style = "untagged")]
+pub struct LooseItem {
+    pub sku: String,
+    pub stock: u32,
+}
+
+impl From<ItemV2> for LooseItem {
+    fn from(v2: ItemV2) -> Self {
+        Self {
+            sku: v2.sku,
+            stock: v2.stock,
+        }
+    }
+}
+
+impl From<&LooseItem> for ItemV2 {
+    fn from(item: &LooseItem) -> Self {
+        Self {
+            sku: item.sku.clone(),
+            stock: item.stock,
+        }
+    }
+}
+
+#[test]
+fn filters_on_the_version_tag() {
+    assert_eq!(
+        mongo::filter_outdated::<ItemVersions>(),
+        Some(json!({"schema": {"$ne": "2"}}))
+    );
+    assert_eq!(
+        mongo::filter_version::<ItemVersions>(1),
+        Some(json!({"schema": "1"}))
+    );
+    assert_eq!(mongo::filter_version::<ItemVersions>(7), None);
+    assert_eq!(
+        mongo::filter_versions::<ItemVersions>(&[1, 2, 7]),
+        Some(json!({"schema": {"$in": ["1", "2"]}}))
+    );
+}
+
+#[test]
+fn untagged_representations_have_no_filters() {
+    assert_eq!(mongo::filter_outdated::<LooseItemVersions>(), None);
+    assert_eq!(mongo::filter_version::<LooseItemVersions>(1), None);
+}
+
+#[cfg(feature = "bson")]
+mod documents {
+    use super::*;
+    use bson::doc;
+    use serde_evolve::mongo::documents;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+    #[versioned(mode = "infallible", chain(ItemV1, ItemV2), tag_style = "adjacent")]
+    pub struct WrappedItem {
+        pub sku: String,
+        pub stock: u32,
+    }
+
+    impl From<ItemV2> for WrappedItem {
+        fn from(v2: ItemV2) -> Self {
+            Self {
+                sku: v2.sku,
+                stock: v2.stock,
+            }
+        }
+    }
+
+    impl From<&WrappedItem> for ItemV2 {
+        fn from(item: &WrappedItem) -> Self {
+            Self {
+                sku: item.sku.clone(),
+                stock: item.stock,
+            }
+        }
+    }
+
+    #[test]
+    fn builds_filters_as_documents() {
+        assert_eq!(
+            documents::filter_outdated::<ItemVersions>(),
+            Some(doc! {"schema": {"$ne": "2"}})
+        );
+        assert_eq!(
+            documents::filter_version::<ItemVersions>(1),
+            Some(doc! {"schema": "1"})
+        );
+        assert_eq!(
+            documents::filter_versions::<ItemVersions>(&[1, 2]),
+            Some(doc! {"schema": {"$in": ["1", "2"]}})
+        );
+        assert_eq!(documents::filter_outdated::<LooseItemVersions>(), None);
+    }
+
+    #[test]
+    fn internally_tagged_representations_round_trip_through_documents() {
+        let stored = doc! {"schema": "1", "sku": "pen"};
+        let rep: ItemVersions = bson::from_document(stored).unwrap();
+        let item = Item::from(rep);
+        assert_eq!((item.sku.as_str(), item.stock), ("pen", 0));
+
+        let document = bson::to_document(&ItemVersions::from(&item)).unwrap();
+        assert_eq!(document, doc! {"schema": "2", "sku": "pen", "stock": 0_i64});
+        let rep: ItemVersions = bson::from_document(document).unwrap();
+        assert_eq!(Item::from(rep).sku, "pen");
+    }
+
+    #[test]
+    fn adjacently_tagged_representations_round_trip_through_documents() {
+        let stored = doc! {"_version": "1", "data": {"sku": "ink"}};
+        let item = WrappedItem::from(bson::from_document::<WrappedItemVersions>(stored).unwrap());
+        assert_eq!(
+            item,
+            WrappedItem {
+                sku: "ink".to_string(),
+                stock: 0
+            }
+        );
+
+        let document = bson::to_document(&WrappedItemVersions::from(&item)).unwrap();
+        assert_eq!(
+            document.get_str("_version"),
+            Ok("2"),
+            "tags are BSON strings: {document}"
+        );
+        let rep: WrappedItemVersions = bson::from_document(document).unwrap();
+        assert_eq!(WrappedItem::from(rep), item);
+    }
+}
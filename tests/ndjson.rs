@@ -0,0 +1,148 @@
+//! Tests for streaming NDJSON migration
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_evolve::ndjson::{self, LineError, Summary};
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventV1 {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventV2 {
+    pub name: String,
+    pub count: u32,
+}
+
+impl From<EventV1> for EventV2 {
+    fn from(v1: EventV1) -> Self {
+        Self {
+            name: v1.name,
+            count: 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EmptyName;
+
+impl fmt::Display for EmptyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("event name must not be empty")
+    }
+}
+
+impl std::error::Error for EmptyName {}
+
+impl From<std::convert::Infallible> for EmptyName {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(error = "EmptyName", chain(EventV1, EventV2))]
+pub struct Event {
+    pub name: String,
+    pub count: u32,
+}
+
+impl TryFrom<EventV2> for Event {
+    type Error = EmptyName;
+
+    fn try_from(v2: EventV2) -> Result<Self, Self::Error> {
+        if v2.name.is_empty() {
+            return Err(EmptyName);
+        }
+        Ok(Self {
+            name: v2.name,
+            count: v2.count,
+        })
+    }
+}
+
+impl From<&Event> for EventV2 {
+    fn from(event: &Event) -> Self {
+        Self {
+            name: event.name.clone(),
+            count: event.count,
+        }
+    }
+}
+
+#[test]
+fn migrates_every_line_to_the_current_version() {
+    let input = concat!(
+        r#"{"_version":"1","name":"login"}"#,
+        "\n",
+        r#"{"_version":"2","name":"logout","count":3}"#,
+        "\n",
+    );
+    let mut output = Vec::new();
+
+    let summary =
+        ndjson::migrate::<EventVersions, Event>(input.as_bytes(), &mut output, |line, err| {
+            panic!("line {line} failed: {err}")
+        })
+        .unwrap();
+
+    assert_eq!(
+        summary,
+        Summary {
+            written: 2,
+            failed: 0
+        }
+    );
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        concat!(
+            r#"{"_version":"2","name":"login","count":1}"#,
+            "\n",
+            r#"{"_version":"2","name":"logout","count":3}"#,
+            "\n",
+        )
+    );
+}
+
+#[test]
+fn reports_failing_lines_and_continues() {
+    let input = concat!(
+        "not json\n",
+        "\n",
+        r#"{"_version":"1","name":""}"#,
+        "\n",
+        r#"{"_version":"1","name":"login"}"#,
+    );
+    let mut output = Vec::new();
+    let mut errors = Vec::new();
+
+    let summary =
+        ndjson::migrate::<EventVersions, Event>(input.as_bytes(), &mut output, |line, err| {
+            errors.push((line, err));
+        })
+        .unwrap();
+
+    assert_eq!(
+        summary,
+        Summary {
+            written: 1,
+            failed: 2
+        }
+    );
+    assert!(matches!(errors[0], (1, LineError::Json(_))));
+    assert!(matches!(errors[1], (3, LineError::Migration(_))));
+    assert_eq!(
+        errors[1].1.to_string(),
+        "migration failed: event name must not be empty"
+    );
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "{\"_version\":\"2\",\"name\":\"login\",\"count\":1}\n"
+    );
+}
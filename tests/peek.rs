@@ -0,0 +1,180 @@
+//! Tests for peeking at document versions
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::{
+    Versioned, VersionedError, peek_version, peek_version_reader, peek_version_slice,
+};
+use std::io::{Cursor, Read};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventV1 {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventV2 {
+    pub name: String,
+    pub attendees: Vec<String>,
+}
+
+impl From<EventV1> for EventV2 {
+    fn from(v1: EventV1) -> Self {
+        Self {
+            name: v1.name,
+            attendees: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(mode = "infallible", chain(EventV1, EventV2 as "2024"))]
+pub struct Event {
+    pub name: String,
+    pub attendees: Vec<String>,
+}
+
+impl From<EventV2> for Event {
+    fn from(v2: EventV2) -> Self {
+        Self {
+            name: v2.name,
+            attendees: v2.attendees,
+        }
+    }
+}
+
+impl From<&Event> for EventV2 {
+    fn from(event: &Event) -> Self {
+        Self {
+            name: event.name.clone(),
+            attendees: event.attendees.clone(),
+        }
+    }
+}
+
+#[test]
+fn reads_the_tag_wherever_it_is() {
+    assert_eq!(
+        peek_version::<EventVersions>(r#"{"_version":"1","name":"launch"}"#),
+        Ok(1)
+    );
+    assert_eq!(
+        peek_version::<EventVersions>(
+            r#"{"name":"launch","attendees":["ada",{"nested":{"_version":"1"}}],"_version":"2024"}"#
+        ),
+        Ok(2)
+    );
+    assert_eq!(
+        peek_version_slice::<EventVersions>(br#"{"_version":"1"}"#),
+        Ok(1)
+    );
+}
+
+#[test]
+fn stops_at_the_tag() {
+    // The payload after the tag is never parsed, so it may even be truncated.
+    assert_eq!(
+        peek_version::<EventVersions>(r#"{"_version":"2024","attendees":["ada", "#),
+        Ok(2)
+    );
+}
+
+#[test]
+fn reports_missing_and_unknown_tags() {
+    assert_eq!(
+        peek_version::<EventVersions>(r#"{"_version":"draft"}"#),
+        Err(VersionedError::UnknownTag {
+            tag: "draft".to_string()
+        })
+    );
+    assert!(matches!(
+        peek_version::<EventVersions>(r#"{"_version":"3"}"#),
+        Err(VersionedError::UnsupportedVersion(_))
+    ));
+    assert_eq!(
+        peek_version::<EventVersions>(r#"{"name":"launch"}"#),
+        Err(VersionedError::Payload {
+            version: None,
+            message: "document has no version tag `_version`".to_string()
+        })
+    );
+    assert!(matches!(
+        peek_version::<EventVersions>("[1, 2]"),
+        Err(VersionedError::Payload { version: None, .. })
+    ));
+}
+
+#[test]
+fn agrees_with_deserialize_on_numeric_tags() {
+    // Tags are written as strings, and a number is no more a tag to the probe than to the
+    // representation's `Deserialize`.
+    let document = r#"{"_version":1,"name":"launch"}"#;
+    assert!(serde_json::from_str::<EventVersions>(document).is_err());
+    assert!(matches!(
+        peek_version::<EventVersions>(document),
+        Err(VersionedError::Payload { version: None, .. })
+    ));
+    assert_eq!(
+        peek_version_reader::<EventVersions>(document.as_bytes()),
+        Err(VersionedError::Payload {
+            version: None,
+            message: "expected a string version tag".to_string()
+        })
+    );
+}
+
+#[test]
+fn reader_stops_after_the_tag() {
+    let document = r#"{"_version":"2024","attendees":["ada"]}"#;
+    let mut reader = Cursor::new(document);
+    assert_eq!(peek_version_reader::<EventVersions>(&mut reader), Ok(2));
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, r#","attendees":["ada"]}"#);
+}
+
+#[test]
+fn reader_skips_values_before_the_tag() {
+    let document = r#" {
+        "name": "a \"quoted\" {name}",
+        "attendees": [{"_version": "2024"}, "]", null, -1.5e3],
+        "count": 12,
+        "_vers\u0069on" : "1"
+    }"#;
+    assert_eq!(
+        peek_version_reader::<EventVersions>(document.as_bytes()),
+        Ok(1)
+    );
+}
+
+#[test]
+fn reader_reports_missing_and_unknown_tags() {
+    assert_eq!(
+        peek_version_reader::<EventVersions>(&br#"{"_version":"draft"}"#[..]),
+        Err(VersionedError::UnknownTag {
+            tag: "draft".to_string()
+        })
+    );
+    assert_eq!(
+        peek_version_reader::<EventVersions>(&br#"{"name":"launch", "n": 1}"#[..]),
+        Err(VersionedError::Payload {
+            version: None,
+            message: "document has no version tag `_version`".to_string()
+        })
+    );
+    assert_eq!(
+        peek_version_reader::<EventVersions>(&br#"{"name":"launch""#[..]),
+        Err(VersionedError::Payload {
+            version: None,
+            message: "unexpected end of document".to_string()
+        })
+    );
+    assert!(matches!(
+        peek_version_reader::<EventVersions>(
+            format!(r#"{{"_version":"{}"}}"#, "9".repeat(65)).as_bytes()
+        ),
+        Err(VersionedError::Payload { version: None, .. })
+    ));
+}
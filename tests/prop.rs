@@ -0,0 +1,124 @@
+//! Tests for property testing migration chains
+
+#![cfg(feature = "proptest")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_evolve::proptest::prelude::*;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TemperatureV1 {
+    pub fahrenheit: i16,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TemperatureV2 {
+    pub celsius: i32,
+}
+
+impl Arbitrary for TemperatureV1 {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        any::<i16>()
+            .prop_map(|fahrenheit| Self { fahrenheit })
+            .boxed()
+    }
+}
+
+impl Arbitrary for TemperatureV2 {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        any::<i32>().prop_map(|celsius| Self { celsius }).boxed()
+    }
+}
+
+impl From<TemperatureV1> for TemperatureV2 {
+    fn from(v1: TemperatureV1) -> Self {
+        Self {
+            celsius: (i32::from(v1.fahrenheit) - 32) * 5 / 9,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(
+    mode = "infallible",
+    chain(TemperatureV1, TemperatureV2),
+    arbitrary = true
+)]
+pub struct Temperature {
+    pub celsius: i32,
+}
+
+impl From<TemperatureV2> for Temperature {
+    fn from(v2: TemperatureV2) -> Self {
+        Self {
+            celsius: v2.celsius,
+        }
+    }
+}
+
+impl From<&Temperature> for TemperatureV2 {
+    fn from(temperature: &Temperature) -> Self {
+        Self {
+            celsius: temperature.celsius,
+        }
+    }
+}
+
+#[test]
+fn generates_every_version() {
+    let mut runner = serde_evolve::proptest::test_runner::TestRunner::deterministic();
+    let strategy = any::<TemperatureVersions>();
+    let versions: std::collections::BTreeSet<u32> = (0..64)
+        .map(|_| {
+            let rep = strategy.new_tree(&mut runner).unwrap().current();
+            serde_evolve::Representation::version(&rep)
+        })
+        .collect();
+    assert_eq!(versions.into_iter().collect::<Vec<_>>(), vec![1, 2]);
+}
+
+#[test]
+fn accepts_a_sound_chain() {
+    serde_evolve::prop::check_chain::<TemperatureVersions, Temperature>();
+}
+
+// A chain whose last step panics on temperatures below absolute zero.
+#[derive(Clone, Debug, Versioned)]
+#[versioned(
+    mode = "infallible",
+    chain(TemperatureV1, TemperatureV2),
+    rep = "KelvinVersions",
+    arbitrary = true
+)]
+pub struct Kelvin {
+    pub kelvin: u32,
+}
+
+impl From<TemperatureV2> for Kelvin {
+    fn from(v2: TemperatureV2) -> Self {
+        Self {
+            kelvin: u32::try_from(v2.celsius.saturating_add(273)).expect("below absolute zero"),
+        }
+    }
+}
+
+impl From<&Kelvin> for TemperatureV2 {
+    fn from(kelvin: &Kelvin) -> Self {
+        Self {
+            celsius: i32::try_from(kelvin.kelvin).unwrap_or(i32::MAX) - 273,
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "migration chain check failed")]
+fn reports_panicking_migrations() {
+    serde_evolve::prop::check_chain::<KelvinVersions, Kelvin>();
+}
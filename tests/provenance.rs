@@ -0,0 +1,113 @@
+//! Tests for provenance metadata
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::{Provenance, Stamped, Versioned};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountV1 {
+    pub owner: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountV2 {
+    pub owner: String,
+    pub active: bool,
+}
+
+impl From<AccountV1> for AccountV2 {
+    fn from(v1: AccountV1) -> Self {
+        Self {
+            owner: v1.owner,
+            active: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(mode = "infallible", chain(AccountV1, AccountV2))]
+pub struct Account {
+    pub owner: String,
+    pub active: bool,
+}
+
+impl From<AccountV2> for Account {
+    fn from(v2: AccountV2) -> Self {
+        Self {
+            owner: v2.owner,
+            active: v2.active,
+        }
+    }
+}
+
+impl From<&Account> for AccountV2 {
+    fn from(account: &Account) -> Self {
+        Self {
+            owner: account.owner.clone(),
+            active: account.active,
+        }
+    }
+}
+
+#[test]
+fn writes_provenance_alongside_the_version_tag() {
+    let account = Account {
+        owner: "ada".to_string(),
+        active: false,
+    };
+    let stamped = Stamped::wrap(
+        &account,
+        Provenance::new("svc-a", "account").with_created_at(1_700_000_000),
+    );
+
+    let value = serde_json::to_value(&stamped).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "_producer": "svc-a",
+            "_schema": "account",
+            "_created_at": 1_700_000_000,
+            "_version": "2",
+            "owner": "ada",
+            "active": false,
+        })
+    );
+}
+
+#[test]
+fn reads_provenance_and_migrates_the_payload() {
+    let json = r#"{"_version":"1","_producer":"svc-b","_schema":"account","_created_at":42,"owner":"grace"}"#;
+
+    let stamped: Stamped<AccountVersions> = serde_json::from_str(json).unwrap();
+    assert_eq!(stamped.producer(), "svc-b");
+    assert_eq!(stamped.schema(), "account");
+    assert_eq!(stamped.created_at(), 42);
+    assert_eq!(stamped.version(), 1);
+
+    let account = Account::from(stamped.into_inner());
+    assert_eq!(
+        account,
+        Account {
+            owner: "grace".to_string(),
+            active: true,
+        }
+    );
+}
+
+#[test]
+fn rejects_documents_without_provenance() {
+    let json = r#"{"_version":"2","owner":"ada","active":true}"#;
+
+    assert!(serde_json::from_str::<Stamped<AccountVersions>>(json).is_err());
+}
+
+#[test]
+fn records_the_current_time_by_default() {
+    let provenance = Provenance::new("svc-a", "account");
+
+    assert!(provenance.created_at > 1_700_000_000);
+}
@@ -0,0 +1,118 @@
+//! Tests for repairing raw documents before migration
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::raw::RawVersioned;
+use serde_evolve::{Versioned, VersionedError};
+use serde_json::{Value, json};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContactV1 {
+    pub email: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactV2 {
+    pub emails: Vec<String>,
+}
+
+impl From<ContactV1> for ContactV2 {
+    fn from(v1: ContactV1) -> Self {
+        Self {
+            emails: v1.email.into_iter().collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(
+    mode = "infallible",
+    chain(ContactV1, ContactV2),
+    derive(PartialEq, Eq)
+)]
+pub struct Contact {
+    pub emails: Vec<String>,
+}
+
+impl From<ContactV2> for Contact {
+    fn from(v2: ContactV2) -> Self {
+        Self { emails: v2.emails }
+    }
+}
+
+impl From<&Contact> for ContactV2 {
+    fn from(contact: &Contact) -> Self {
+        Self {
+            emails: contact.emails.clone(),
+        }
+    }
+}
+
+#[test]
+fn repairs_a_payload_before_migrating() {
+    // Version 1 records were written with an empty string for a missing email, and a stray
+    // field that the DTO rejects.
+    let mut raw: RawVersioned<Contact> =
+        serde_json::from_value(json!({"_version": "1", "email": "", "legacy": true})).unwrap();
+    assert_eq!(raw.version(), 1);
+    assert_eq!(raw.payload()["legacy"], true);
+    assert!(raw.clone().migrate().is_err());
+
+    if raw.payload()["email"] == "" {
+        raw.payload_mut()["email"] = Value::Null;
+    }
+    let raw = raw.patch(|payload| {
+        payload.as_object_mut().unwrap().remove("legacy");
+    });
+    assert_eq!(
+        raw.to_document().unwrap(),
+        json!({"_version": "1", "email": null})
+    );
+    assert_eq!(raw.migrate().unwrap(), Contact { emails: vec![] });
+}
+
+#[test]
+fn reads_as_a_corrected_version() {
+    let raw: RawVersioned<Contact> =
+        serde_json::from_value(json!({"_version": "1", "emails": ["ada@example.com"]})).unwrap();
+    let rep = raw.with_version(2).into_representation().unwrap();
+    assert_eq!(
+        rep,
+        ContactVersions::V2(ContactV2 {
+            emails: vec!["ada@example.com".to_string()]
+        })
+    );
+}
+
+#[test]
+fn reports_unreadable_documents() {
+    let err = RawVersioned::<Contact>::from_document(json!({"_version": "v9", "email": null}))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        VersionedError::UnknownTag {
+            tag: "v9".to_string()
+        }
+    );
+
+    let err = RawVersioned::<Contact>::from_document(json!({"_version": "9", "email": null}))
+        .unwrap_err();
+    assert!(matches!(err, VersionedError::UnsupportedVersion(_)));
+
+    let err = RawVersioned::<Contact>::from_document(json!({"email": null})).unwrap_err();
+    assert!(matches!(err, VersionedError::Payload { version: None, .. }));
+
+    let err = RawVersioned::<Contact>::new(1, json!({"email": 7}))
+        .migrate()
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        VersionedError::Payload {
+            version: Some(1),
+            ..
+        }
+    ));
+}
@@ -0,0 +1,147 @@
+//! Tests for the runtime registry of versioned types
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_evolve::registry::{Error, Registry};
+use serde_json::json;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserV1 {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserV2 {
+    pub first: String,
+    pub last: String,
+}
+
+impl From<UserV1> for UserV2 {
+    fn from(v1: UserV1) -> Self {
+        let (first, last) = v1.name.split_once(' ').unwrap_or((&v1.name, ""));
+        Self {
+            first: first.to_string(),
+            last: last.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[cfg_attr(feature = "inventory", versioned(register = "user"))]
+#[versioned(mode = "infallible", chain(UserV1, UserV2), any = true)]
+pub struct User {
+    pub first: String,
+    pub last: String,
+}
+
+impl From<UserV2> for User {
+    fn from(v2: UserV2) -> Self {
+        Self {
+            first: v2.first,
+            last: v2.last,
+        }
+    }
+}
+
+impl From<&User> for UserV2 {
+    fn from(user: &User) -> Self {
+        Self {
+            first: user.first.clone(),
+            last: user.last.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TagV1 {
+    pub label: String,
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[cfg_attr(feature = "inventory", versioned(register = "tag"))]
+#[versioned(mode = "infallible", chain(TagV1), any = true)]
+pub struct Tag {
+    pub label: String,
+}
+
+impl From<TagV1> for Tag {
+    fn from(v1: TagV1) -> Self {
+        Self { label: v1.label }
+    }
+}
+
+impl From<&Tag> for TagV1 {
+    fn from(tag: &Tag) -> Self {
+        Self {
+            label: tag.label.clone(),
+        }
+    }
+}
+
+fn registry() -> Registry {
+    let mut registry = Registry::new();
+    registry
+        .register::<UserVersions, User>("user")
+        .register::<TagVersions, Tag>("tag");
+    registry
+}
+
+#[test]
+fn dispatches_by_type_name() {
+    let registry = registry();
+
+    let user = registry
+        .migrate_json("user", json!({"_version": "1", "name": "Ada Lovelace"}))
+        .unwrap();
+    assert_eq!(
+        user,
+        json!({"_version": "2", "first": "Ada", "last": "Lovelace"})
+    );
+
+    let tag = registry
+        .migrate_json("tag", json!({"_version": "1", "label": "urgent"}))
+        .unwrap();
+    assert_eq!(tag, json!({"_version": "1", "label": "urgent"}));
+
+    let mut names: Vec<_> = registry.names().collect();
+    names.sort_unstable();
+    assert_eq!(names, ["tag", "user"]);
+}
+
+#[test]
+fn reports_unknown_types_and_invalid_documents() {
+    let registry = registry();
+
+    let err = registry.migrate_json("order", json!({})).unwrap_err();
+    assert!(matches!(err, Error::UnknownType(ref name) if name == "order"));
+    assert_eq!(
+        err.to_string(),
+        "no versioned type is registered as 'order'"
+    );
+
+    let err = registry
+        .migrate_json("user", json!({"_version": "9"}))
+        .unwrap_err();
+    assert!(matches!(err, Error::Json(_)));
+}
+
+#[cfg(feature = "inventory")]
+#[test]
+fn self_registered_types_are_collected() {
+    let mut names: Vec<_> = Registry::collected().names().collect();
+    names.sort_unstable();
+    assert_eq!(names, ["tag", "user"]);
+
+    let user = serde_evolve::registry::migrate_json(
+        "user",
+        json!({"_version": "1", "name": "Grace Hopper"}),
+    )
+    .unwrap();
+    assert_eq!(
+        user,
+        json!({"_version": "2", "first": "Grace", "last": "Hopper"})
+    );
+}
@@ -0,0 +1,136 @@
+//! Tests for reading reqwest responses
+
+#![cfg(feature = "reqwest")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::reqwest::{Error, json};
+use serde_evolve::{Versioned, VersionedError};
+use std::convert::TryFrom;
+use std::fmt;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderV1 {
+    pub item: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderV2 {
+    pub item: String,
+    pub quantity: u32,
+}
+
+impl From<OrderV1> for OrderV2 {
+    fn from(v1: OrderV1) -> Self {
+        Self {
+            item: v1.item,
+            quantity: 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EmptyOrder;
+
+impl fmt::Display for EmptyOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("orders must have a positive quantity")
+    }
+}
+
+impl std::error::Error for EmptyOrder {}
+
+impl From<std::convert::Infallible> for EmptyOrder {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(error = "EmptyOrder", chain(OrderV1, OrderV2))]
+pub struct Order {
+    pub item: String,
+    pub quantity: u32,
+}
+
+impl TryFrom<OrderV2> for Order {
+    type Error = EmptyOrder;
+
+    fn try_from(v2: OrderV2) -> Result<Self, Self::Error> {
+        if v2.quantity == 0 {
+            return Err(EmptyOrder);
+        }
+        Ok(Self {
+            item: v2.item,
+            quantity: v2.quantity,
+        })
+    }
+}
+
+impl From<&Order> for OrderV2 {
+    fn from(order: &Order) -> Self {
+        Self {
+            item: order.item.clone(),
+            quantity: order.quantity,
+        }
+    }
+}
+
+/// Drive a future that never waits on I/O to completion.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn read(body: &'static str) -> Result<Order, Error> {
+    let response = http::Response::builder()
+        .header("content-type", "application/json")
+        .body(body)
+        .unwrap();
+    block_on(json::<Order>(reqwest::Response::from(response)))
+}
+
+#[test]
+fn reads_any_version() {
+    assert_eq!(
+        read(r#"{"_version":"1","item":"tea"}"#).unwrap(),
+        Order {
+            item: "tea".to_string(),
+            quantity: 1
+        }
+    );
+    assert_eq!(
+        read(r#"{"_version":"2","item":"tea","quantity":3}"#).unwrap(),
+        Order {
+            item: "tea".to_string(),
+            quantity: 3
+        }
+    );
+}
+
+#[test]
+fn reports_invalid_bodies_and_failed_migrations() {
+    let err = read(r#"{"_version":"7"}"#).unwrap_err();
+    assert!(matches!(&err, Error::Http(err) if err.is_decode()));
+    assert!(matches!(
+        VersionedError::from(err),
+        VersionedError::Payload { .. }
+    ));
+
+    let err = read(r#"{"_version":"2","item":"tea","quantity":0}"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "migration failed: orders must have a positive quantity"
+    );
+    assert!(matches!(
+        VersionedError::from(err),
+        VersionedError::Migration { .. }
+    ));
+}
@@ -0,0 +1,102 @@
+//! Tests for JSON Schema generation
+
+#![cfg(feature = "schemars")]
+#![allow(missing_docs)]
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_json::json;
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProductV1 {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProductV2 {
+    pub name: String,
+    pub price: u32,
+}
+
+impl From<ProductV1> for ProductV2 {
+    fn from(v1: ProductV1) -> Self {
+        Self {
+            name: v1.name,
+            price: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(mode = "infallible", chain(ProductV1, ProductV2), schema = true)]
+pub struct Product {
+    pub name: String,
+    pub price: u32,
+}
+
+impl From<ProductV2> for Product {
+    fn from(v2: ProductV2) -> Self {
+        Self {
+            name: v2.name,
+            price: v2.price,
+        }
+    }
+}
+
+impl From<&Product> for ProductV2 {
+    fn from(product: &Product) -> Self {
+        Self {
+            name: product.name.clone(),
+            price: product.price,
+        }
+    }
+}
+
+#[test]
+fn schema_for_returns_each_version_dto() {
+    let v1 = ProductVersions::schema_for(1).expect("version 1 exists");
+    assert_eq!(v1.get("title"), Some(&json!("ProductV1")));
+    assert_eq!(v1.get("required"), Some(&json!(["name"])));
+
+    let v2 = ProductVersions::schema_for(2).expect("version 2 exists");
+    assert_eq!(v2.get("title"), Some(&json!("ProductV2")));
+    assert_eq!(v2.get("required"), Some(&json!(["name", "price"])));
+}
+
+#[test]
+fn schema_for_rejects_unknown_versions() {
+    assert!(ProductVersions::schema_for(0).is_none());
+    assert!(ProductVersions::schema_for(3).is_none());
+}
+
+#[test]
+fn representation_schema_covers_every_tagged_version() {
+    let schema = schemars::schema_for!(ProductVersions);
+    assert_eq!(schema.get("title"), Some(&json!("ProductVersions")));
+
+    let versions = schema.get("oneOf").and_then(|v| v.as_array()).unwrap();
+    assert_eq!(versions.len(), 2);
+    assert_eq!(
+        versions[1]["allOf"][1],
+        json!({
+            "type": "object",
+            "properties": { "_version": { "const": "2" } },
+            "required": ["_version"],
+        })
+    );
+    assert_eq!(
+        versions[1]["allOf"][0],
+        json!({ "$ref": "#/$defs/ProductV2" })
+    );
+    assert!(schema.get("$defs").unwrap().get("ProductV1").is_some());
+}
+
+#[test]
+fn fingerprints_identify_each_version_dto() {
+    let v1 = ProductVersions::fingerprint(1).expect("version 1 exists");
+    let v2 = ProductVersions::fingerprint(2).expect("version 2 exists");
+    assert_ne!(v1, v2);
+    assert_eq!(ProductVersions::fingerprint(1), Some(v1));
+    assert!(ProductVersions::fingerprint(3).is_none());
+}
@@ -82,3 +82,32 @@ fn test_basic() {
     assert_eq!(my_type.field, "test");
     assert_eq!(my_type.new_field, 0);
 }
+
+#[test]
+fn test_into_latest() {
+    let rep = MyTypeVersions::V1(V1 {
+        field: "test".to_string(),
+    });
+
+    let latest = rep.latest();
+    assert_eq!(latest.field, "test");
+    assert_eq!(latest.new_field, 0);
+
+    let latest = rep.into_latest();
+    assert_eq!(latest.field, "test");
+}
+
+#[test]
+fn test_upgrade_once() {
+    let rep = MyTypeVersions::V1(V1 {
+        field: "test".to_string(),
+    });
+    assert_eq!(rep.steps_behind(), 1);
+
+    let rep = rep.upgrade_once();
+    assert_eq!(rep.version(), 2);
+    assert_eq!(rep.steps_behind(), 0);
+
+    let rep = rep.upgrade_once();
+    assert!(rep.is_current());
+}
@@ -0,0 +1,153 @@
+//! Tests for heuristic version detection
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::{Versioned, sniff};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactV1 {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactV2 {
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactV3 {
+    pub display_name: String,
+    pub email: String,
+    #[serde(default)]
+    pub phone: Option<String>,
+}
+
+impl From<ContactV1> for ContactV2 {
+    fn from(v1: ContactV1) -> Self {
+        Self {
+            name: v1.name,
+            email: String::new(),
+        }
+    }
+}
+
+impl From<ContactV2> for ContactV3 {
+    fn from(v2: ContactV2) -> Self {
+        Self {
+            display_name: v2.name,
+            email: v2.email,
+            phone: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(mode = "infallible", chain(ContactV1, ContactV2, ContactV3))]
+pub struct Contact {
+    pub display_name: String,
+    pub email: String,
+    pub phone: Option<String>,
+}
+
+impl From<ContactV3> for Contact {
+    fn from(v3: ContactV3) -> Self {
+        Self {
+            display_name: v3.display_name,
+            email: v3.email,
+            phone: v3.phone,
+        }
+    }
+}
+
+impl From<&Contact> for ContactV3 {
+    fn from(contact: &Contact) -> Self {
+        Self {
+            display_name: contact.display_name.clone(),
+            email: contact.email.clone(),
+            phone: contact.phone.clone(),
+        }
+    }
+}
+
+#[test]
+fn documents_are_classified_by_their_keys() {
+    let v1 = serde_json::json!({"name": "Ada"});
+    let v2 = serde_json::json!({"name": "Ada", "email": "ada@example.com"});
+    let v3 = serde_json::json!({"display_name": "Ada", "email": "ada@example.com"});
+
+    assert_eq!(
+        sniff::classify::<ContactVersions>(&v1).unwrap().version(),
+        1
+    );
+    assert_eq!(
+        sniff::classify::<ContactVersions>(&v2).unwrap().version(),
+        2
+    );
+    assert_eq!(
+        sniff::classify::<ContactVersions>(&v3).unwrap().version(),
+        3
+    );
+}
+
+#[test]
+fn classified_documents_are_migrated() {
+    let contact: Contact = sniff::migrate(&serde_json::json!({"name": "Ada"})).unwrap();
+    assert_eq!(
+        contact,
+        Contact {
+            display_name: "Ada".to_string(),
+            email: String::new(),
+            phone: None,
+        }
+    );
+}
+
+#[test]
+fn unrecognised_documents_are_rejected() {
+    let err = sniff::migrate::<Contact>(&serde_json::json!({"title": "Dr"})).unwrap_err();
+    assert!(matches!(err, sniff::Error::Unrecognised));
+    assert!(sniff::classify::<ContactVersions>(&serde_json::json!([1, 2])).is_none());
+}
+
+#[cfg(feature = "proptest")]
+mod fuzz {
+    use super::*;
+    use serde_evolve::Representation;
+    use serde_evolve::proptest::prelude::*;
+
+    fn any_contact() -> impl Strategy<Value = ContactVersions> {
+        let text = "[a-z]{0,8}";
+        prop_oneof![
+            text.prop_map(|name| ContactVersions::V1(ContactV1 { name })),
+            (text, text).prop_map(|(name, email)| ContactVersions::V2(ContactV2 { name, email })),
+            (text, text, proptest::option::of(text)).prop_map(|(display_name, email, phone)| {
+                ContactVersions::V3(ContactV3 {
+                    display_name,
+                    email,
+                    phone,
+                })
+            }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn untagged_payloads_classify_as_their_version(rep in any_contact()) {
+            let document =
+                serde_json::to_value(serde_evolve::representation::PayloadRef(&rep)).unwrap();
+            let classified = sniff::classify::<ContactVersions>(&document).unwrap();
+            prop_assert_eq!(classified.version(), Representation::version(&rep));
+        }
+
+        #[test]
+        fn arbitrary_documents_never_panic(
+            document in proptest::collection::btree_map("[a-z_]{1,12}", "[a-z]{0,4}", 0..4)
+        ) {
+            let document = serde_json::to_value(document).unwrap();
+            let _ = sniff::classify::<ContactVersions>(&document);
+        }
+    }
+}
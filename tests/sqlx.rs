@@ -0,0 +1,76 @@
+//! Tests for the sqlx `jsonb` integration
+
+#![cfg(feature = "sqlx")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use serde_evolve::sqlx::encode::IsNull;
+use serde_evolve::sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, Postgres};
+use serde_evolve::sqlx::{Encode, Type, TypeInfo};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SettingsV1 {
+    pub dark_mode: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SettingsV2 {
+    pub theme: String,
+}
+
+impl From<SettingsV1> for SettingsV2 {
+    fn from(v1: SettingsV1) -> Self {
+        Self {
+            theme: if v1.dark_mode { "dark" } else { "light" }.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(mode = "infallible", chain(SettingsV1, SettingsV2), sqlx = true)]
+pub struct Settings {
+    pub theme: String,
+}
+
+impl From<SettingsV2> for Settings {
+    fn from(v2: SettingsV2) -> Self {
+        Self { theme: v2.theme }
+    }
+}
+
+impl From<&Settings> for SettingsV2 {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            theme: settings.theme.clone(),
+        }
+    }
+}
+
+#[test]
+fn maps_to_json_columns() {
+    assert_eq!(<Settings as Type<Postgres>>::type_info().name(), "JSONB");
+    assert!(<Settings as Type<Postgres>>::compatible(
+        &PgTypeInfo::with_name("JSON")
+    ));
+    assert!(!<Settings as Type<Postgres>>::compatible(
+        &PgTypeInfo::with_name("TEXT")
+    ));
+}
+
+#[test]
+fn encodes_the_latest_version_as_jsonb() {
+    let settings = Settings {
+        theme: "dark".to_string(),
+    };
+    let mut buf = PgArgumentBuffer::default();
+
+    let is_null = Encode::<Postgres>::encode_by_ref(&settings, &mut buf).unwrap();
+
+    assert!(matches!(is_null, IsNull::No));
+
+    // `jsonb` values are prefixed with the binary format version.
+    assert_eq!(buf[0], 1);
+    let json: serde_json::Value = serde_json::from_slice(&buf[1..]).unwrap();
+    assert_eq!(json, serde_json::json!({"_version": "2", "theme": "dark"}));
+}
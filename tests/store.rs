@@ -0,0 +1,169 @@
+//! Tests for the key-value storage layer
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::store::{Error, EvolveStore, MemoryStore, VersionedStore};
+use serde_evolve::{Versioned, format::Json};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionV1 {
+    pub user: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionV2 {
+    pub user: String,
+    pub ttl: u32,
+}
+
+impl From<SessionV1> for SessionV2 {
+    fn from(v1: SessionV1) -> Self {
+        Self {
+            user: v1.user,
+            ttl: 3600,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Versioned)]
+#[versioned(mode = "infallible", chain(SessionV1, SessionV2))]
+pub struct Session {
+    pub user: String,
+    pub ttl: u32,
+}
+
+impl From<SessionV2> for Session {
+    fn from(v2: SessionV2) -> Self {
+        Self {
+            user: v2.user,
+            ttl: v2.ttl,
+        }
+    }
+}
+
+impl From<&Session> for SessionV2 {
+    fn from(session: &Session) -> Self {
+        Self {
+            user: session.user.clone(),
+            ttl: session.ttl,
+        }
+    }
+}
+
+fn seeded() -> MemoryStore {
+    let store = MemoryStore::default();
+    store
+        .put(b"session:a", br#"{"_version":"1","user":"ada"}"#)
+        .unwrap();
+    store
+        .put(b"session:b", br#"{"_version":"2","user":"bob","ttl":60}"#)
+        .unwrap();
+    store.put(b"other", b"not json").unwrap();
+    store
+}
+
+fn stored(store: &MemoryStore, key: &[u8]) -> serde_json::Value {
+    serde_json::from_slice(&store.get(key).unwrap().unwrap()).unwrap()
+}
+
+#[test]
+fn migrates_on_read_without_touching_the_store() {
+    let sessions = VersionedStore::<_, Session, _>::new(seeded(), Json::default());
+
+    let session = sessions.get(b"session:a").unwrap().unwrap();
+    assert_eq!(session.ttl, 3600);
+    assert!(sessions.get(b"session:z").unwrap().is_none());
+    assert_eq!(stored(sessions.store(), b"session:a")["_version"], "1");
+
+    let scanned = sessions.scan(b"session:").unwrap();
+    let keys: Vec<_> = scanned.iter().map(|(key, _)| key.as_slice()).collect();
+    assert_eq!(keys, [b"session:a".as_slice(), b"session:b".as_slice()]);
+    assert_eq!(scanned[1].1.user, "bob");
+}
+
+#[test]
+fn writes_back_stale_values_when_enabled() {
+    let sessions = VersionedStore::<_, Session, _>::new(seeded(), Json::default()).write_back(true);
+
+    sessions.get(b"session:a").unwrap();
+    assert_eq!(
+        stored(sessions.store(), b"session:a"),
+        serde_json::json!({"_version": "2", "user": "ada", "ttl": 3600})
+    );
+}
+
+#[test]
+fn puts_at_the_current_version_and_migrates_in_bulk() {
+    let sessions = VersionedStore::<_, Session, _>::new(seeded(), Json::default());
+
+    let session = Session {
+        user: "cy".to_string(),
+        ttl: 5,
+    };
+    sessions.put(b"session:c", &session).unwrap();
+    assert_eq!(stored(sessions.store(), b"session:c")["_version"], "2");
+    assert_eq!(sessions.get(b"session:c").unwrap(), Some(session));
+
+    assert_eq!(sessions.migrate_all(b"session:").unwrap(), 1);
+    assert_eq!(stored(sessions.store(), b"session:a")["_version"], "2");
+    assert_eq!(sessions.migrate_all(b"session:").unwrap(), 0);
+}
+
+#[test]
+fn reports_undecodable_values() {
+    let sessions = VersionedStore::<_, Session, _>::new(seeded(), Json::default());
+    assert!(matches!(sessions.get(b"other"), Err(Error::Format(_))));
+}
+
+/// Seed `store` like [`seeded`], and check the migrating view over it.
+#[cfg(any(feature = "sled", feature = "redb"))]
+fn check_backend<S>(store: S)
+where
+    S: EvolveStore,
+    S::Error: std::fmt::Debug,
+{
+    store
+        .put(b"session:b", br#"{"_version":"2","user":"bob","ttl":60}"#)
+        .unwrap();
+    store
+        .put(b"session:a", br#"{"_version":"1","user":"ada"}"#)
+        .unwrap();
+    store.put(b"sessions", b"not json").unwrap();
+
+    let sessions = VersionedStore::<_, Session, _>::new(store, Json::default()).write_back(true);
+    assert!(sessions.get(b"session:z").unwrap().is_none());
+    let scanned = sessions.scan(b"session:").unwrap();
+    let users: Vec<_> = scanned
+        .iter()
+        .map(|(_, session)| session.user.as_str())
+        .collect();
+    assert_eq!(users, ["ada", "bob"]);
+
+    let stored: serde_json::Value =
+        serde_json::from_slice(&sessions.store().get(b"session:a").unwrap().unwrap()).unwrap();
+    assert_eq!(stored["_version"], "2");
+    assert_eq!(sessions.migrate_all(b"session:").unwrap(), 0);
+}
+
+#[cfg(feature = "sled")]
+#[test]
+fn sled_trees_are_stores() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    check_backend(db.open_tree("sessions").unwrap());
+    check_backend(db);
+}
+
+#[cfg(feature = "redb")]
+#[test]
+fn redb_tables_are_stores() {
+    use serde_evolve::store::RedbStore;
+
+    let dir = tempfile::tempdir().unwrap();
+    let db = std::sync::Arc::new(redb::Database::create(dir.path().join("db.redb")).unwrap());
+    let store = RedbStore::new(db.clone(), "sessions");
+    assert!(store.scan(b"").unwrap().is_empty());
+    check_backend(store);
+    check_backend(RedbStore::new(db, "archive"));
+}
@@ -0,0 +1,189 @@
+//! Tests for the fixture corpus harness
+
+#![cfg(feature = "json")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::format::Json;
+use serde_evolve::testing::Corpus;
+use serde_evolve::{Versioned, assert_compat, corpus_dir};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArticleV1 {
+    pub title: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArticleV2 {
+    pub title: String,
+    pub tags: Vec<String>,
+}
+
+impl From<ArticleV1> for ArticleV2 {
+    fn from(v1: ArticleV1) -> Self {
+        Self {
+            title: v1.title,
+            tags: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(mode = "infallible", chain(ArticleV1, ArticleV2))]
+pub struct Article {
+    pub title: String,
+    pub tags: Vec<String>,
+}
+
+impl From<ArticleV2> for Article {
+    fn from(v2: ArticleV2) -> Self {
+        Self {
+            title: v2.title,
+            tags: v2.tags,
+        }
+    }
+}
+
+impl From<&Article> for ArticleV2 {
+    fn from(article: &Article) -> Self {
+        Self {
+            title: article.title.clone(),
+            tags: article.tags.clone(),
+        }
+    }
+}
+
+#[test]
+fn every_fixture_migrates() {
+    let checked = corpus_dir!("tests/fixtures/article").check::<Article, _>(&Json::default());
+    assert_eq!(checked, 2);
+}
+
+#[test]
+fn expected_outputs_are_not_fixtures() {
+    let fixtures = corpus_dir!("tests/fixtures/article").fixtures("json");
+    let names: Vec<_> = fixtures
+        .iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap())
+        .collect();
+    assert_eq!(names, ["v1-minimal.json", "v2-tagged.json"]);
+}
+
+#[test]
+#[should_panic(expected = "1 of 2 fixtures failed")]
+fn reports_fixtures_that_no_longer_decode() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("ok.json"),
+        r#"{"_version":"1","title":"a"}"#,
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("bad.json"), r#"{"_version":"9"}"#).unwrap();
+
+    Corpus::new(dir.path()).check::<Article, _>(&Json::default());
+}
+
+#[test]
+#[should_panic(expected = "migrated value does not match")]
+fn reports_mismatched_expected_outputs() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.json"), r#"{"_version":"1","title":"a"}"#).unwrap();
+    std::fs::write(
+        dir.path().join("a.expected.json"),
+        r#"{"_version":"2","title":"a","tags":["x"]}"#,
+    )
+    .unwrap();
+
+    Corpus::new(dir.path()).check::<Article, _>(&Json::default());
+}
+
+#[test]
+#[should_panic(expected = "has no .json fixtures")]
+fn rejects_empty_corpora() {
+    let dir = tempfile::tempdir().unwrap();
+
+    Corpus::new(dir.path()).check::<Article, _>(&Json::default());
+}
+
+#[test]
+fn renders_the_latest_encoding_as_a_snapshot() {
+    let article = Article {
+        title: "Hi".to_string(),
+        tags: vec!["a".to_string()],
+    };
+    assert_eq!(
+        serde_evolve::testing::latest_snapshot(&article, &Json::default()),
+        r#"{"_version":"2","title":"Hi","tags":["a"]}"#
+    );
+}
+
+#[test]
+fn renders_each_migrated_fixture_as_a_snapshot() {
+    let snapshots = corpus_dir!("tests/fixtures/article").snapshots::<Article, _>(&Json::default());
+    assert_eq!(
+        snapshots,
+        [
+            (
+                "v1-minimal".to_string(),
+                r#"{"_version":"2","title":"Hello","tags":[]}"#.to_string()
+            ),
+            (
+                "v2-tagged".to_string(),
+                r#"{"_version":"2","title":"Tagged","tags":["rust","serde"]}"#.to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn assert_compat_returns_the_migrated_value() {
+    let article = assert_compat!(Article, r#"{"_version":"1","title":"Hello"}"#);
+    assert_eq!(article.title, "Hello");
+
+    assert_compat!(
+        Article,
+        r#"{"_version":"1","title":"Hello"}"#,
+        r#"{"tags": [], "title": "Hello", "_version": "2"}"#,
+    );
+}
+
+#[test]
+#[should_panic(expected = "fixture does not decode")]
+fn assert_compat_rejects_unknown_versions() {
+    assert_compat!(Article, r#"{"_version":"9","title":"Hello"}"#);
+}
+
+#[test]
+fn assert_compat_diffs_unexpected_output() {
+    let panic = std::panic::catch_unwind(|| {
+        assert_compat!(
+            Article,
+            r#"{"_version":"1","title":"Hello"}"#,
+            r#"{"_version":"2","title":"Hi","tags":[]}"#,
+        )
+    })
+    .unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.starts_with("fixture at version 1 migrated to a different document"));
+    assert!(message.contains("\n-   \"title\": \"Hi\"\n+   \"title\": \"Hello\"\n"));
+    assert!(message.contains("\n    \"tags\": [],\n"));
+}
+
+#[cfg(feature = "insta")]
+mod insta_snapshots {
+    use super::*;
+    use serde_evolve::{assert_corpus_snapshots, assert_latest_snapshot};
+
+    #[test]
+    fn records_the_latest_encoding() {
+        assert_latest_snapshot!(Article {
+            title: "Hi".to_string(),
+            tags: vec!["a".to_string()],
+        });
+    }
+
+    #[test]
+    fn records_each_migrated_fixture() {
+        assert_corpus_snapshots!(Article, corpus_dir!("tests/fixtures/article"));
+    }
+}
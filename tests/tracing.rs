@@ -0,0 +1,144 @@
+//! Tests for tracing instrumentation of migration steps
+
+#![cfg(feature = "tracing")]
+#![allow(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_evolve::Versioned;
+use std::fmt::{self, Write as _};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobV1 {
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobV2 {
+    pub name: String,
+    pub retries: u8,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobV3 {
+    pub name: String,
+    pub retries: u8,
+    pub queue: String,
+}
+
+impl From<JobV1> for JobV2 {
+    fn from(v1: JobV1) -> Self {
+        Self {
+            name: v1.name,
+            retries: 3,
+        }
+    }
+}
+
+impl From<JobV2> for JobV3 {
+    fn from(v2: JobV2) -> Self {
+        Self {
+            name: v2.name,
+            retries: v2.retries,
+            queue: "default".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Versioned)]
+#[versioned(mode = "infallible", chain(JobV1, JobV2, JobV3))]
+pub struct Job {
+    pub name: String,
+    pub retries: u8,
+    pub queue: String,
+}
+
+impl From<JobV3> for Job {
+    fn from(v3: JobV3) -> Self {
+        Self {
+            name: v3.name,
+            retries: v3.retries,
+            queue: v3.queue,
+        }
+    }
+}
+
+impl From<&Job> for JobV3 {
+    fn from(job: &Job) -> Self {
+        Self {
+            name: job.name.clone(),
+            retries: job.retries,
+            queue: job.queue.clone(),
+        }
+    }
+}
+
+/// Records the fields of every `migration_step` span, and whether each reported a duration.
+#[derive(Clone, Default)]
+struct Recorder {
+    steps: Arc<Mutex<Vec<String>>>,
+    finished: Arc<Mutex<usize>>,
+}
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        let _ = write!(self.0, "{}={value:?}", field.name());
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut fields = Fields(String::new());
+        span.record(&mut fields);
+        let mut steps = self.steps.lock().unwrap();
+        steps.push(fields.0);
+        Id::from_u64(steps.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        if event.fields().any(|field| field.name() == "elapsed_us") {
+            *self.finished.lock().unwrap() += 1;
+        }
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn each_hop_is_reported_with_its_versions() {
+    let recorder = Recorder::default();
+    let rep = JobVersions::from(JobV1 {
+        name: "nightly".to_string(),
+    });
+
+    let job = tracing::subscriber::with_default(recorder.clone(), || Job::from(rep));
+    assert_eq!(job.queue, "default");
+
+    assert_eq!(
+        *recorder.steps.lock().unwrap(),
+        [
+            "type_name=\"Job\" from=1 to=2",
+            "type_name=\"Job\" from=2 to=3",
+            "type_name=\"Job\" from=3",
+        ]
+    );
+    assert_eq!(*recorder.finished.lock().unwrap(), 3);
+}
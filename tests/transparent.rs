@@ -239,3 +239,43 @@ fn test_transparent_fallible_migration_error() {
     let err = result.unwrap_err();
     assert!(err.is_data());
 }
+
+#[test]
+fn test_fallible_into_latest() {
+    let rep = ProductVersions::V1(ProductV1 {
+        name: "Widget".to_string(),
+        price: 19.99,
+    });
+    let latest = rep.into_latest().unwrap();
+    assert_eq!(latest.price_cents, 1999);
+
+    let rep = ProductVersions::V1(ProductV1 {
+        name: "Invalid".to_string(),
+        price: f64::NAN,
+    });
+    assert!(rep.latest().is_err());
+}
+
+#[test]
+fn test_fallible_upgrade_once() {
+    let rep = ProductVersions::V1(ProductV1 {
+        name: "Widget".to_string(),
+        price: 19.99,
+    });
+    assert_eq!(rep.steps_behind(), 1);
+    let rep = rep.upgrade_once().unwrap();
+    assert!(matches!(
+        rep,
+        ProductVersions::V2(ProductV2 {
+            price_cents: 1999,
+            ..
+        })
+    ));
+    assert!(rep.upgrade_once().unwrap().is_current());
+
+    let rep = ProductVersions::V1(ProductV1 {
+        name: "Invalid".to_string(),
+        price: f64::NAN,
+    });
+    assert!(rep.upgrade_once().is_err());
+}
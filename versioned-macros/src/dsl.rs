@@ -0,0 +1,302 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{Token, braced};
+
+/// The input of `migrate!`: the first version DTO, then each step to the next version.
+struct History {
+    base: syn::ItemStruct,
+    steps: Vec<Step>,
+}
+
+/// `UserV1 -> UserV2 { ... }`
+struct Step {
+    from: syn::Ident,
+    to: syn::Ident,
+    changes: Vec<Change>,
+}
+
+enum Change {
+    /// `rename old => new;`
+    Rename { old: syn::Ident, new: syn::Ident },
+    /// `add name: Type = value;`, the value defaulting to `Default::default()`
+    Add {
+        field: Box<syn::Field>,
+        value: Option<syn::Expr>,
+    },
+    /// `drop name;`
+    Drop { name: syn::Ident },
+}
+
+mod kw {
+    syn::custom_keyword!(rename);
+    syn::custom_keyword!(add);
+    syn::custom_keyword!(drop);
+}
+
+impl Parse for History {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let base = input.parse()?;
+        let mut steps = Vec::new();
+        while !input.is_empty() {
+            steps.push(input.parse()?);
+        }
+        Ok(Self { base, steps })
+    }
+}
+
+impl Parse for Step {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let from = input.parse()?;
+        input.parse::<Token![->]>()?;
+        let to = input.parse()?;
+        let content;
+        braced!(content in input);
+        let mut changes = Vec::new();
+        while !content.is_empty() {
+            changes.push(content.parse()?);
+        }
+        Ok(Self { from, to, changes })
+    }
+}
+
+impl Parse for Change {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let lookahead = input.lookahead1();
+        let change = if lookahead.peek(kw::rename) && attrs.is_empty() {
+            input.parse::<kw::rename>()?;
+            let old = input.parse()?;
+            input.parse::<Token![=>]>()?;
+            Self::Rename {
+                old,
+                new: input.parse()?,
+            }
+        } else if lookahead.peek(kw::add) {
+            input.parse::<kw::add>()?;
+            let name: syn::Ident = input.parse()?;
+            input.parse::<Token![:]>()?;
+            let ty = input.parse()?;
+            let value = if input.parse::<Option<Token![=]>>()?.is_some() {
+                Some(input.parse()?)
+            } else {
+                None
+            };
+            Self::Add {
+                field: Box::new(syn::Field {
+                    attrs,
+                    vis: syn::Visibility::Inherited,
+                    mutability: syn::FieldMutability::None,
+                    ident: Some(name),
+                    colon_token: Some(Token![:](proc_macro2::Span::call_site())),
+                    ty,
+                }),
+                value,
+            }
+        } else if lookahead.peek(kw::drop) && attrs.is_empty() {
+            input.parse::<kw::drop>()?;
+            Self::Drop {
+                name: input.parse()?,
+            }
+        } else {
+            return Err(lookahead.error());
+        };
+        input.parse::<Token![;]>()?;
+        Ok(change)
+    }
+}
+
+/// A field of a version, and where the migration into that version takes its value from.
+struct Slot {
+    field: syn::Field,
+    source: Source,
+}
+
+enum Source {
+    Previous(syn::Ident),
+    Added(Option<syn::Expr>),
+}
+
+/// Expand `migrate!`: the first version DTO, each later version with its changes applied to
+/// the fields of the previous one, and the `From` impl of each step.
+pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let History { base, steps } = syn::parse2(input)?;
+    let syn::Fields::Named(base_fields) = &base.fields else {
+        return Err(syn::Error::new_spanned(
+            &base,
+            "migrate! requires a struct with named fields",
+        ));
+    };
+    if !base.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &base.generics,
+            "migrate! does not support generic versions",
+        ));
+    }
+
+    // Later versions share the attributes of the first, but not its documentation.
+    let attrs: Vec<_> = base
+        .attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("doc"))
+        .collect();
+    let vis = &base.vis;
+    let field_vis = base_fields.named.first().map(|field| field.vis.clone());
+
+    let mut fields: Vec<syn::Field> = base_fields.named.iter().cloned().collect();
+    let mut previous = base.ident.clone();
+    let mut expanded = quote! { #base };
+    for step in steps {
+        if step.from != previous {
+            return Err(syn::Error::new_spanned(
+                &step.from,
+                format!("expected a step from `{previous}`"),
+            ));
+        }
+        let slots = apply(&fields, step.changes, field_vis.as_ref())?;
+
+        let to = &step.to;
+        let values: Vec<_> = slots
+            .iter()
+            .map(|slot| {
+                let name = &slot.field.ident;
+                match &slot.source {
+                    Source::Previous(old) => quote! { #name: prev.#old },
+                    Source::Added(Some(value)) => quote! { #name: #value },
+                    Source::Added(None) => quote! { #name: Default::default() },
+                }
+            })
+            .collect();
+        fields = slots.into_iter().map(|slot| slot.field).collect();
+        expanded.extend(quote! {
+            #(#attrs)*
+            #vis struct #to {
+                #(#fields),*
+            }
+
+            impl From<#previous> for #to {
+                fn from(prev: #previous) -> Self {
+                    Self { #(#values),* }
+                }
+            }
+        });
+        previous = step.to;
+    }
+    Ok(expanded)
+}
+
+/// Apply the changes of a step to the fields of the previous version.
+fn apply(
+    fields: &[syn::Field],
+    changes: Vec<Change>,
+    field_vis: Option<&syn::Visibility>,
+) -> syn::Result<Vec<Slot>> {
+    let mut slots: Vec<_> = fields
+        .iter()
+        .map(|field| Slot {
+            field: field.clone(),
+            source: Source::Previous(field.ident.clone().expect("named fields have an ident")),
+        })
+        .collect();
+    let position = |slots: &[Slot], name: &syn::Ident| {
+        slots
+            .iter()
+            .position(|slot| slot.field.ident.as_ref() == Some(name))
+    };
+    let unknown = |name: &syn::Ident| {
+        syn::Error::new_spanned(name, format!("no field `{name}` in the previous version"))
+    };
+
+    for change in changes {
+        match change {
+            Change::Rename { old, new } => {
+                let idx = position(&slots, &old).ok_or_else(|| unknown(&old))?;
+                slots[idx].field.ident = Some(new);
+            }
+            Change::Drop { name } => {
+                let idx = position(&slots, &name).ok_or_else(|| unknown(&name))?;
+                slots.remove(idx);
+            }
+            Change::Add { mut field, value } => {
+                let name = field.ident.as_ref().expect("added fields have a name");
+                if position(&slots, name).is_some() {
+                    return Err(syn::Error::new_spanned(
+                        name,
+                        format!("field `{name}` already exists"),
+                    ));
+                }
+                if let Some(vis) = field_vis {
+                    field.vis = vis.clone();
+                }
+                slots.push(Slot {
+                    field: *field,
+                    source: Source::Added(value),
+                });
+            }
+        }
+    }
+    Ok(slots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_each_version_and_step() {
+        let tokens = expand(quote! {
+            /// The first version.
+            #[derive(Serialize, Deserialize)]
+            pub struct UserV1 {
+                pub name: String,
+                pub legacy_flag: bool,
+            }
+
+            UserV1 -> UserV2 {
+                rename name => full_name;
+                #[serde(default)]
+                add email: Option<String> = None;
+                drop legacy_flag;
+            }
+
+            UserV2 -> UserV3 {
+                add age: u32;
+            }
+        })
+        .expect("expected expansion success")
+        .to_string();
+
+        assert!(tokens.contains(
+            "# [derive (Serialize , Deserialize)] pub struct UserV2 { pub full_name : String , # [serde (default)] pub email : Option < String > }"
+        ));
+        assert!(tokens.contains(
+            "impl From < UserV1 > for UserV2 { fn from (prev : UserV1) -> Self { Self { full_name : prev . name , email : None } } }"
+        ));
+        assert!(tokens.contains(
+            "Self { full_name : prev . full_name , email : prev . email , age : Default :: default () }"
+        ));
+        assert_eq!(tokens.matches("The first version.").count(), 1);
+    }
+
+    #[test]
+    fn errors_on_unknown_fields() {
+        let err = expand(quote! {
+            pub struct UserV1 { pub name: String }
+            UserV1 -> UserV2 { drop email; }
+        })
+        .expect_err("expected expansion failure");
+
+        assert_eq!(err.to_string(), "no field `email` in the previous version");
+    }
+
+    #[test]
+    fn errors_on_steps_out_of_order() {
+        let err = expand(quote! {
+            pub struct UserV1 { pub name: String }
+            UserV2 -> UserV3 { add age: u32; }
+        })
+        .expect_err("expected expansion failure");
+
+        assert_eq!(err.to_string(), "expected a step from `UserV1`");
+    }
+}
@@ -1,75 +1,688 @@
-use crate::validate::{Mode, ValidatedInput};
-use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use crate::parse::ChainEntry;
+use crate::validate::{Date, DeprecationPolicy, Mode, TagStyle, ValidatedInput};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{ToTokens, format_ident, quote, quote_spanned};
 use std::convert::TryFrom;
+use syn::parse_quote;
+use syn::spanned::Spanned;
 
 pub fn generate(input: &ValidatedInput) -> TokenStream {
-    let rep_enum = generate_rep_enum(&input.rep_ident, &input.versions);
-    let conversions = generate_conversions(
-        &input.mode,
-        &input.domain_ident,
-        &input.rep_ident,
-        &input.versions,
-    );
+    let latest_dto = input
+        .latest_fields
+        .as_ref()
+        .map(|fields| generate_latest_dto(input, fields));
+    let rep_enum = generate_rep_enum(input);
+    let representation = generate_representation_impl(input);
+    let ordering = if input.ord {
+        generate_ordering(input)
+    } else {
+        quote! {}
+    };
+    let assertions = generate_assertions(input);
+    let remote_defs = generate_remote_defs(input);
+    let conversions = if input.versions.iter().any(|entry| entry.is_async) {
+        generate_async_conversion(input)
+    } else {
+        generate_conversions(input)
+    };
+    let latest = if input.versions.iter().any(|entry| entry.is_async) {
+        quote! {}
+    } else {
+        generate_latest(input)
+    };
+    let domain_to_rep = generate_domain_to_rep(input);
+    let downgrades = if input.downgrade {
+        generate_downgrades(input)
+    } else {
+        quote! {}
+    };
+    let schema = if input.schema {
+        generate_schema(input)
+    } else {
+        quote! {}
+    };
+    let arbitrary = if input.arbitrary {
+        generate_arbitrary(input)
+    } else {
+        quote! {}
+    };
+    let registration = input
+        .register
+        .as_ref()
+        .map(|name| generate_registration(input, name));
+    let any = if input.any || input.register.is_some() {
+        generate_any(input)
+    } else {
+        quote! {}
+    };
+    let sqlx = if input.sqlx {
+        generate_sqlx(input)
+    } else {
+        quote! {}
+    };
+    let diesel = if input.diesel {
+        generate_diesel(input)
+    } else {
+        quote! {}
+    };
+    let latest_ref = if let Some(ref_ty) = &input.latest_ref {
+        generate_latest_ref(input, ref_ty)
+    } else if input.self_latest && input.cbor_tag.is_none() {
+        let domain_ident = &input.domain_ident;
+        generate_serialize_latest(input, &quote! { &'__r #domain_ident }, &quote! { domain })
+    } else {
+        quote! {}
+    };
+    let tests = if input.generate_tests {
+        generate_tests(input)
+    } else {
+        quote! {}
+    };
     let transparent_serde = if input.transparent {
-        generate_transparent_serde(&input.mode, &input.domain_ident, &input.rep_ident)
+        generate_transparent_serde(input)
     } else {
         quote! {}
     };
 
     quote! {
+        #latest_dto
         #rep_enum
+        #representation
+        #ordering
+        #assertions
+        #remote_defs
         #conversions
+        #latest
+        #domain_to_rep
+        #downgrades
+        #schema
+        #arbitrary
+        #registration
+        #any
+        #latest_ref
+        #sqlx
+        #diesel
         #transparent_serde
+        #tests
     }
 }
 
-fn generate_rep_enum(rep_name: &syn::Ident, version_types: &[syn::Path]) -> TokenStream {
-    let num_versions = version_types.len();
-    let current_version =
-        u32::try_from(num_versions).expect("too many versions for u32 discriminant");
+/// The latest version DTO, generated from the fields of the domain type, with conversions in
+/// both directions.
+fn generate_latest_dto(input: &ValidatedInput, fields: &syn::Fields) -> TokenStream {
+    let domain_ident = &input.domain_ident;
+    let vis = &input.vis;
+    let latest_idx = input.versions.len() - 1;
+    let dto = &input.versions[latest_idx].ty;
+    let doc = format!(
+        "Version {} of [`{domain_ident}`], generated from its fields.",
+        version_number(input, latest_idx)
+    );
 
-    let variants = version_types.iter().enumerate().map(|(idx, ty)| {
-        let variant_name = format_ident!("V{}", idx + 1);
-        let version_str = (idx + 1).to_string();
-        quote! {
-            #[serde(rename = #version_str)]
-            #variant_name(#ty)
-        }
+    let mut dto_fields = fields.clone();
+    for field in &mut dto_fields {
+        field
+            .attrs
+            .retain(|attr| attr.path().is_ident("serde") || attr.path().is_ident("doc"));
+    }
+    let semi = matches!(fields, syn::Fields::Unnamed(_) | syn::Fields::Unit).then(|| quote! { ; });
+    let members: Vec<_> = fields.members().collect();
+
+    let clone_derive = input.clone.then(|| quote! { Clone, });
+    let debug_derive = input.debug.then(|| quote! { Debug, });
+    // The representation enum's derives require the same traits of every version.
+    let extra_derives = (!input.derives.is_empty()).then(|| {
+        let derives = &input.derives;
+        quote! { #[derive(#(#derives),*)] }
     });
+    let deny_attr = input
+        .deny_unknown_fields
+        .then(|| quote! { #[serde(deny_unknown_fields)] });
 
-    let version_match_arms = (0..num_versions).map(|idx| {
-        let variant_name = format_ident!("V{}", idx + 1);
-        let version_num = u32::try_from(idx + 1).expect("too many versions for u32 discriminant");
+    quote! {
+        #[doc = #doc]
+        #[derive(#clone_derive #debug_derive serde::Serialize, serde::Deserialize)]
+        #extra_derives
+        #deny_attr
+        #vis struct #dto #dto_fields #semi
+
+        impl From<#dto> for #domain_ident {
+            fn from(latest: #dto) -> Self {
+                Self {
+                    #(#members: latest.#members),*
+                }
+            }
+        }
+
+        impl From<&#domain_ident> for #dto {
+            fn from(domain: &#domain_ident) -> Self {
+                Self {
+                    #(#members: Clone::clone(&domain.#members)),*
+                }
+            }
+        }
+
+        impl From<#domain_ident> for #dto {
+            fn from(domain: #domain_ident) -> Self {
+                Self {
+                    #(#members: domain.#members),*
+                }
+            }
+        }
+    }
+}
+
+/// Clone `generics`, adding `predicates` to its where-clause.
+///
+/// Predicates are only added for generic domain types; for concrete types the bounds are
+/// checked where the impls are defined, which gives clearer error messages.
+fn with_predicates(
+    generics: &syn::Generics,
+    predicates: impl IntoIterator<Item = syn::WherePredicate>,
+) -> syn::Generics {
+    let mut generics = generics.clone();
+    if !generics.params.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+    generics
+}
+
+/// Clone `generics`, prepending the `'de` lifetime used by `Deserialize` impls.
+///
+/// `'de` outlives every lifetime of the domain type, so that borrowed version DTOs can
+/// deserialize from the input.
+fn with_de_lifetime(generics: &syn::Generics) -> syn::Generics {
+    let mut generics = generics.clone();
+    let mut de: syn::LifetimeParam = parse_quote!('de);
+    de.bounds
+        .extend(generics.lifetimes().map(|param| param.lifetime.clone()));
+    generics.params.insert(0, de.into());
+    generics
+}
+
+/// Whether a version type borrows through one of the domain type's lifetimes, and so needs
+/// `#[serde(borrow)]`.
+fn borrows(generics: &syn::Generics, ty: &syn::Path) -> bool {
+    fn contains(tokens: TokenStream, lifetime: &syn::Ident) -> bool {
+        let mut after_quote = false;
+        tokens.into_iter().any(|token| {
+            let found = match &token {
+                TokenTree::Ident(ident) => after_quote && ident == lifetime,
+                TokenTree::Group(group) => contains(group.stream(), lifetime),
+                _ => false,
+            };
+            after_quote = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '\'');
+            found
+        })
+    }
+
+    generics
+        .lifetimes()
+        .any(|param| contains(quote! { #ty }, &param.lifetime.ident))
+}
+
+/// How a migration step converts a version into its successor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Conversion {
+    /// `From`: every step of an infallible chain, and steps into `infallible` entries.
+    From,
+    /// `TryFrom`, with an error convertible into the chain's error type.
+    TryFrom,
+    /// `TryFromAsync`, for steps into `async` entries.
+    Async,
+}
+
+/// A single migration step of the chain.
+struct Step {
+    /// The version migrated from, as written (without the `Coded` wrapper of a `codec`).
+    prev: TokenStream,
+    next: TokenStream,
+    conversion: Conversion,
+}
+
+/// Each migration step pairs a version with its successor (past the branches beside it); the
+/// latest version migrates into the domain type itself, unless it is the domain type
+/// (`self_latest`).
+fn migration_steps(input: &ValidatedInput) -> Vec<Step> {
+    let domain_ident = &input.domain_ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let conversion = |entry: Option<&ChainEntry>| match (&input.mode, entry) {
+        (Mode::Infallible, _) => Conversion::From,
+        (Mode::Fallible { .. }, Some(entry)) if entry.is_async => Conversion::Async,
+        (Mode::Fallible { .. }, Some(entry)) if entry.infallible => Conversion::From,
+        (Mode::Fallible { .. }, _) => Conversion::TryFrom,
+    };
+
+    let mut steps: Vec<_> = input
+        .versions
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| match next_entry(input, idx) {
+            Some(next) => {
+                let next = &input.versions[next];
+                let ty = &next.ty;
+                Step {
+                    prev: written_type(entry),
+                    next: quote! { #ty },
+                    conversion: conversion(Some(next)),
+                }
+            }
+            None => Step {
+                prev: written_type(entry),
+                next: quote! { #domain_ident #ty_generics },
+                conversion: conversion(None),
+            },
+        })
+        .collect();
+    if input.self_latest {
+        steps.pop();
+    }
+    steps
+}
+
+/// The where-clause bounds requiring a migration step's conversion to exist.
+fn step_predicates(input: &ValidatedInput, step: &Step) -> Vec<syn::WherePredicate> {
+    let krate = &input.krate;
+    let Step {
+        prev,
+        next,
+        conversion,
+    } = step;
+    let convert = match conversion {
+        Conversion::From => return vec![parse_quote!(#next: From<#prev>)],
+        Conversion::TryFrom => quote! { core::convert::TryFrom<#prev> },
+        Conversion::Async => quote! { #krate::TryFromAsync<#prev> },
+    };
+    let Mode::Fallible { error } = &input.mode else {
+        unreachable!("infallible chains only convert with From");
+    };
+    vec![
+        parse_quote!(#next: #convert),
+        parse_quote!(<#next as #convert>::Error: Into<#error>),
+    ]
+}
+
+/// The version number of the chain entry at `idx`. A `branch` shares the number of the
+/// entry before it.
+fn version_number(input: &ValidatedInput, idx: usize) -> u32 {
+    let steps = input.versions[1..=idx]
+        .iter()
+        .filter(|entry| !entry.branch)
+        .count();
+    let steps = u32::try_from(steps).expect("too many versions for u32 discriminant");
+    input.min_supported + steps
+}
+
+/// The entry that the entry at `idx` migrates into: the next entry that is not a `branch`,
+/// or `None` for the latest version.
+fn next_entry(input: &ValidatedInput, idx: usize) -> Option<usize> {
+    (idx + 1..input.versions.len()).find(|&next| !input.versions[next].branch)
+}
+
+/// The entries a value of the entry at `idx` migrates through, after its own, as pairs of
+/// the entry migrated from and the entry migrated into.
+fn upgrade_path(input: &ValidatedInput, idx: usize) -> Vec<(usize, usize)> {
+    std::iter::successors(Some(idx), |&prev| next_entry(input, prev))
+        .collect::<Vec<_>>()
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .collect()
+}
+
+/// The entry read for a version number: the one that is not a `branch`, as a branch is only
+/// told apart from it by its tag.
+fn entry_of_version(input: &ValidatedInput, version: u32) -> usize {
+    (0..input.versions.len())
+        .find(|&idx| !input.versions[idx].branch && version_number(input, idx) == version)
+        .expect("version numbers are validated against the chain")
+}
+
+/// The version numbers of the chain, oldest first.
+fn version_numbers(input: &ValidatedInput) -> Vec<u32> {
+    (0..input.versions.len())
+        .map(|idx| version_number(input, idx))
+        .collect()
+}
+
+/// The tag written for the chain entry at `idx`: its override, or else its version number.
+fn version_tag(input: &ValidatedInput, idx: usize) -> String {
+    input.versions[idx].tag.as_ref().map_or_else(
+        || version_number(input, idx).to_string(),
+        syn::LitStr::value,
+    )
+}
+
+/// The tags of the chain, oldest first.
+fn version_tags(input: &ValidatedInput) -> Vec<String> {
+    (0..input.versions.len())
+        .map(|idx| version_tag(input, idx))
+        .collect()
+}
+
+/// The representation enum variant of the chain entry at `idx`. A `branch` is named after
+/// its type, since its version number is taken.
+fn variant_ident(input: &ValidatedInput, idx: usize) -> syn::Ident {
+    let entry = &input.versions[idx];
+    match entry.ty.segments.last() {
+        Some(segment) if entry.branch => segment.ident.clone(),
+        _ => format_ident!("V{}", version_number(input, idx)),
+    }
+}
+
+/// The representation enum variants, oldest first.
+fn variant_idents(input: &ValidatedInput) -> Vec<syn::Ident> {
+    (0..input.versions.len())
+        .map(|idx| variant_ident(input, idx))
+        .collect()
+}
+
+/// Wrap a version value for its variant: in a `Box` with `boxed`.
+fn boxed(input: &ValidatedInput, value: TokenStream) -> TokenStream {
+    if input.boxed {
+        quote! { Box::new(#value) }
+    } else {
+        value
+    }
+}
+
+/// A function from a version value to the variant `Self::#variant`, for use with `map`.
+fn variant_ctor(input: &ValidatedInput, variant: &syn::Ident) -> TokenStream {
+    if input.boxed {
+        quote! { |v| Self::#variant(Box::new(v)) }
+    } else {
+        quote! { Self::#variant }
+    }
+}
+
+/// Move the value of the entry at `idx`, bound as `v`, out of its `Box` with `boxed`, and
+/// out of its `Coded` or `Remote` wrapper with a `codec` or `remote` definition.
+fn unbox(input: &ValidatedInput, idx: usize) -> Option<TokenStream> {
+    let unbox = input.boxed.then(|| quote! { let v = *v; });
+    let entry = &input.versions[idx];
+    let uncode = (entry.codec.is_some() || entry.remote.is_some())
+        .then(|| quote! { let v = v.into_inner(); });
+    (unbox.is_some() || uncode.is_some()).then(|| quote! { #unbox #uncode })
+}
+
+/// A match arm expression using the value of the entry at `idx`, bound as `v`, unwrapped.
+fn unboxed_arm(input: &ValidatedInput, idx: usize, expr: TokenStream) -> TokenStream {
+    match unbox(input, idx) {
+        Some(unbox) => quote! {{ #unbox #expr }},
+        None => expr,
+    }
+}
+
+/// The `#[cfg]` attributes gating the chain entry at `idx`. An entry migrates through every
+/// later entry, so it is only compiled when their predicates hold too.
+fn version_cfg(input: &ValidatedInput, idx: usize) -> TokenStream {
+    let predicates = input.versions[idx..]
+        .iter()
+        .filter_map(|entry| entry.cfg.as_ref());
+    quote! { #(#[cfg(#predicates)])* }
+}
+
+/// The `#[cfg]` attributes gating each chain entry, oldest first.
+fn version_cfgs(input: &ValidatedInput) -> Vec<TokenStream> {
+    (0..input.versions.len())
+        .map(|idx| version_cfg(input, idx))
+        .collect()
+}
+
+/// The version types of the chain, oldest first.
+fn version_types(input: &ValidatedInput) -> Vec<&syn::Path> {
+    input.versions.iter().map(|entry| &entry.ty).collect()
+}
+
+/// `RemoteDef` for the serde remote definition of each `remote` entry, forwarding to the
+/// functions `#[serde(remote = "...")]` generates on it.
+fn generate_remote_defs(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let impls = input
+        .versions
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, entry)| {
+            let def = entry.remote.as_ref()?;
+            let ty = written_type(entry);
+            let cfg = version_cfg(input, idx);
+            Some(quote! {
+                #cfg
+                impl #krate::codec::RemoteDef<#ty> for #def {
+                    fn serialize<__S: serde::Serializer>(
+                        value: &#ty,
+                        serializer: __S,
+                    ) -> ::core::result::Result<__S::Ok, __S::Error> {
+                        <#def>::serialize(value, serializer)
+                    }
+
+                    fn deserialize<'de, __D: serde::Deserializer<'de>>(
+                        deserializer: __D,
+                    ) -> ::core::result::Result<#ty, __D::Error> {
+                        <#def>::deserialize(deserializer)
+                    }
+                }
+            })
+        });
+    quote! { #(#impls)* }
+}
+
+/// The type of a chain entry as written, without the `Coded` or `Remote` wrapper of a
+/// `codec` or `remote` definition.
+fn written_type(entry: &ChainEntry) -> TokenStream {
+    if entry.codec.is_some() || entry.remote.is_some() {
+        if let Some(syn::PathArguments::AngleBracketed(args)) =
+            entry.ty.segments.last().map(|segment| &segment.arguments)
+        {
+            if let Some(ty) = args.args.first() {
+                return ty.to_token_stream();
+            }
+        }
+    }
+    entry.ty.to_token_stream()
+}
+
+/// The `VERSIONS` and `SUPPORTED` tables of the representation enum, and the lookups built on
+/// them.
+fn generate_version_support(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let supported = (0..input.versions.len())
+        .filter(|&idx| !input.versions[idx].branch)
+        .map(|idx| {
+            let version_num = version_number(input, idx);
+            let cfg = version_cfg(input, idx);
+            quote! { #cfg #version_num }
+        });
+    let infos = input.versions.iter().enumerate().map(|(idx, entry)| {
+        let version_num = version_number(input, idx);
+        let cfg = version_cfg(input, idx);
+        let type_name: String = written_type(entry).to_string().split_whitespace().collect();
+        let tag = version_tag(input, idx);
+        let deprecated = entry.deprecated;
         quote! {
-            Self::#variant_name(_) => #version_num
+            #cfg
+            #krate::VersionInfo {
+                version: #version_num,
+                type_name: #type_name,
+                tag: #tag,
+                deprecated: #deprecated,
+            }
         }
     });
 
-    let from_impls = version_types.iter().enumerate().map(|(idx, ty)| {
-        let variant_name = format_ident!("V{}", idx + 1);
-        quote! {
-            impl From<#ty> for #rep_name {
-                fn from(v: #ty) -> Self {
-                    Self::#variant_name(v)
+    let dates = input
+        .dates
+        .as_ref()
+        .map(|dates| generate_dates(input, dates));
+
+    quote! {
+        /// Describes each version this build can read, oldest first.
+        pub const VERSIONS: &'static [#krate::VersionInfo] = &[#(#infos),*];
+
+        /// The version numbers this build can read, oldest first.
+        ///
+        /// Versions compiled out with `cfg(...)` are omitted.
+        pub const SUPPORTED: &'static [u32] = &[#(#supported),*];
+
+        /// Whether this build can read the given version.
+        pub const fn supports_version(version: u32) -> bool {
+            let mut idx = 0;
+            while idx < Self::SUPPORTED.len() {
+                if Self::SUPPORTED[idx] == version {
+                    return true;
+                }
+                idx += 1;
+            }
+            false
+        }
+
+        /// Agree on a wire version with a peer that supports `peer_min..=peer_max`.
+        ///
+        /// Returns the newest version both sides support, or `None` if there is none.
+        pub const fn negotiate(peer_min: u32, peer_max: u32) -> Option<u32> {
+            let mut idx = Self::SUPPORTED.len();
+            while idx > 0 {
+                idx -= 1;
+                let version = Self::SUPPORTED[idx];
+                if version >= peer_min && version <= peer_max {
+                    return Some(version);
                 }
             }
+            None
+        }
+
+        #dates
+    }
+}
+
+/// With `dated = true`, the release date of each version, and chronological comparisons.
+fn generate_dates(input: &ValidatedInput, dates: &[Date]) -> TokenStream {
+    let krate = &input.krate;
+    let date_exprs: Vec<_> = dates
+        .iter()
+        .map(|(year, month, day)| quote! { #krate::__private::version_date(#year, #month, #day) })
+        .collect();
+    let variant_arms = date_exprs.iter().enumerate().map(|(idx, date)| {
+        let variant = variant_ident(input, idx);
+        let cfg = version_cfg(input, idx);
+        quote! { #cfg Self::#variant(_) => #date }
+    });
+    let version_arms = date_exprs.iter().enumerate().map(|(idx, date)| {
+        let version_num = version_number(input, idx);
+        let cfg = version_cfg(input, idx);
+        quote! { #cfg #version_num => Some(#date) }
+    });
+
+    quote! {
+        /// The release date of this value's version.
+        pub const fn date(&self) -> #krate::VersionDate {
+            match self {
+                #(#variant_arms),*
+            }
+        }
+
+        /// The release date of the given version, or `None` if this build cannot read it.
+        pub const fn date_of_version(version: u32) -> Option<#krate::VersionDate> {
+            match version {
+                #(#version_arms,)*
+                _ => None,
+            }
+        }
+
+        /// Whether this value's version was released before `date`.
+        pub const fn is_older_than(&self, date: #krate::VersionDate) -> bool {
+            self.date().is_before(date)
+        }
+    }
+}
+
+fn generate_rep_enum(input: &ValidatedInput) -> TokenStream {
+    let rep_name = &input.rep_ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let version_types = version_types(input);
+    let num_versions = version_types.len();
+    let current_version = version_number(input, num_versions - 1);
+    let min_supported = input.min_supported;
+
+    let custom_serialize = input.cbor_tag.is_some();
+    let custom_deserialize = input.on_unknown.is_some()
+        || input.default_version.is_some()
+        || input.min_supported > 1
+        || input.cbor_tag.is_some()
+        || input.deny_unknown_fields
+        || (input.on_deprecated != DeprecationPolicy::Accept
+            && input.versions.iter().any(|entry| entry.deprecated));
+    // Adjacent representations are written as a struct of the tag and then the content,
+    // which serde's derive cannot read back without `deserialize_any`.
+    let adjacent = matches!(input.tag_style, TagStyle::Adjacent { .. });
+    // `#[serde]` attributes are only accepted when at least one serde trait is derived.
+    let derives_serde = !(adjacent || custom_serialize && custom_deserialize);
+
+    let variants = rep_variants(input, derives_serde);
+
+    let version_match_arms = (0..num_versions).map(|idx| {
+        let variant_name = variant_ident(input, idx);
+        let version_num = version_number(input, idx);
+        let cfg = version_cfg(input, idx);
+        quote! {
+            #cfg
+            Self::#variant_name(_) => #version_num
         }
     });
 
-    let latest_variant = format_ident!("V{}", num_versions);
+    let version_support = generate_version_support(input);
+    let from_impls = generate_version_from_impls(input);
+    let latest_variant = variant_ident(input, num_versions - 1);
+
+    let clone_derive = input.clone.then(|| quote! { Clone, });
+    let debug_derive = input.debug.then(|| quote! { Debug, });
+    let extra_derives = (!input.derives.is_empty()).then(|| {
+        let derives = &input.derives;
+        quote! { #[derive(#(#derives),*)] }
+    });
+    let rep_attrs = &input.rep_attrs;
+    let tag = &input.tag;
+    let container_attr = match &input.tag_style {
+        _ if !derives_serde => quote! {},
+        TagStyle::Internal => quote! { #[serde(tag = #tag)] },
+        TagStyle::Adjacent { .. } => {
+            unreachable!("adjacent representations derive no serde traits")
+        }
+        TagStyle::Untagged { .. } => quote! { #[serde(untagged)] },
+    };
+    let deny_attr = (derives_serde && input.deny_unknown_fields)
+        .then(|| quote! { #[serde(deny_unknown_fields)] });
+    let (serialize_derive, serialize_impl) = if custom_serialize {
+        (quote! {}, generate_cbor_serialize(input))
+    } else if adjacent {
+        (quote! {}, generate_adjacent_serialize(input))
+    } else {
+        (quote! { serde::Serialize, }, quote! {})
+    };
+    let (deserialize_derive, deserialize_impl) = if custom_deserialize {
+        (quote! {}, generate_deserialize(input))
+    } else if adjacent {
+        (quote! {}, generate_adjacent_deserialize(input))
+    } else {
+        (quote! { serde::Deserialize, }, quote! {})
+    };
 
     quote! {
-        #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
-        #[serde(tag = "_version")]
-        pub enum #rep_name {
+        #[derive(#clone_derive #debug_derive #serialize_derive #deserialize_derive)]
+        #extra_derives
+        #container_attr
+        #deny_attr
+        #(#rep_attrs)*
+        pub enum #rep_name #impl_generics #where_clause {
             #(#variants),*
         }
 
-        impl #rep_name {
+        impl #impl_generics #rep_name #ty_generics #where_clause {
             /// The current version number.
             pub const CURRENT: u32 = #current_version;
 
+            /// The oldest version number that can still be read.
+            pub const MIN_SUPPORTED: u32 = #min_supported;
+
             /// Get the version number of this instance.
             pub const fn version(&self) -> u32 {
                 match self {
@@ -81,223 +694,2814 @@ fn generate_rep_enum(rep_name: &syn::Ident, version_types: &[syn::Path]) -> Toke
             pub const fn is_current(&self) -> bool {
                 matches!(self, Self::#latest_variant(_))
             }
-        }
 
-        #(#from_impls)*
-    }
-}
+            /// The number of migration steps between this value and the current version.
+            pub const fn steps_behind(&self) -> u32 {
+                Self::CURRENT - self.version()
+            }
 
-fn generate_conversions(
-    mode: &Mode,
-    domain_type: &syn::Ident,
-    rep_name: &syn::Ident,
-    version_types: &[syn::Path],
-) -> TokenStream {
-    let num_versions = version_types.len();
+            #version_support
 
-    let rep_to_domain = match mode {
-        Mode::Infallible => {
-            let variant_conversions = (0..num_versions).map(|idx| {
-                let variant_name = format_ident!("V{}", idx + 1);
-                let chain = build_infallible_chain(domain_type, version_types, idx);
+            /// Sort a slice of representations by version number, oldest first.
+            ///
+            /// The sort is stable, so values sharing a version keep their relative order.
+            pub fn sort_by_version(reps: &mut [Self]) {
+                reps.sort_by_key(Self::version);
+            }
+        }
 
-                quote! {
-                    #rep_name::#variant_name(v) => {
-                        #chain
-                    }
-                }
-            });
+        #from_impls
+
+        #serialize_impl
+        #deserialize_impl
+    }
+}
 
+/// The variants of the representation enum, in declaration order.
+fn rep_variants(input: &ValidatedInput, derives_serde: bool) -> Vec<TokenStream> {
+    let version_types = version_types(input);
+    let mut variants: Vec<_> = version_types
+        .iter()
+        .enumerate()
+        .map(|(idx, ty)| {
+            let variant_name = variant_ident(input, idx);
+            let version_str = version_tag(input, idx);
+            let aliases = &input.versions[idx].aliases;
+            let rename = derives_serde
+                .then(|| quote! { #[serde(rename = #version_str #(, alias = #aliases)*)] });
+            let borrow = (derives_serde && borrows(&input.generics, ty))
+                .then(|| quote! { #[serde(borrow)] });
+            let cfg = version_cfg(input, idx);
+            let payload = if input.boxed {
+                quote! { Box<#ty> }
+            } else {
+                quote! { #ty }
+            };
             quote! {
-                impl From<#rep_name> for #domain_type {
-                    fn from(rep: #rep_name) -> Self {
-                        match rep {
-                            #(#variant_conversions),*
-                        }
-                    }
-                }
+                #cfg
+                #rename
+                #variant_name(#borrow #payload)
             }
-        }
-        Mode::Fallible { error } => {
-            let variant_conversions = (0..num_versions).map(|idx| {
-                let variant_name = format_ident!("V{}", idx + 1);
-                let chain = build_fallible_chain(domain_type, version_types, idx);
+        })
+        .collect();
+    // Untagged enums attempt variants in declaration order.
+    if input.tag_style == (TagStyle::Untagged { newest_first: true }) {
+        variants.reverse();
+    }
+    variants
+}
 
-                quote! {
-                    #rep_name::#variant_name(v) => {
-                        #chain
-                    }
-                }
-            });
+/// `From<Vn>` for the representation enum, wrapping each version DTO in its variant.
+fn generate_version_from_impls(input: &ValidatedInput) -> TokenStream {
+    let rep_name = &input.rep_ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
+    let impls = version_types(input)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, ty)| {
+            let variant_name = variant_ident(input, idx);
+            let value = boxed(input, quote! { v });
+            let cfg = version_cfg(input, idx);
             quote! {
-                impl core::convert::TryFrom<#rep_name> for #domain_type {
-                    type Error = #error;
-
-                    fn try_from(rep: #rep_name) -> Result<Self, Self::Error> {
-                        match rep {
-                            #(#variant_conversions),*
-                        }
+                #cfg
+                impl #impl_generics From<#ty> for #rep_name #ty_generics #where_clause {
+                    fn from(v: #ty) -> Self {
+                        Self::#variant_name(#value)
                     }
                 }
             }
-        }
-    };
+        });
 
-    let latest_version_type = &version_types[num_versions - 1];
-    let latest_variant = format_ident!("V{}", num_versions);
+    quote! { #(#impls)* }
+}
 
-    let domain_to_rep = quote! {
-        impl From<&#domain_type> for #rep_name {
-            fn from(domain: &#domain_type) -> Self {
-                let latest = #latest_version_type::from(domain);
-                Self::#latest_variant(latest)
-            }
-        }
+/// Decode the buffered payload as the version at `idx`, applying the deprecation policy.
+fn read_version(input: &ValidatedInput, idx: usize) -> TokenStream {
+    let krate = &input.krate;
+    let entry = &input.versions[idx];
+    let ty = &entry.ty;
+    let variant_name = variant_ident(input, idx);
+    let version_str = version_tag(input, idx);
+    let type_name = input.domain_ident.to_string();
+    let deserialize = if input.deny_unknown_fields {
+        quote! { deserialize_payload_strict }
+    } else {
+        quote! { deserialize_payload }
+    };
+    let ctor = variant_ctor(input, &variant_name);
+    let read = quote! {
+        #krate::__private::#deserialize::<#ty, __D::Error>(__payload)
+            .map(#ctor)
     };
 
-    quote! {
-        #rep_to_domain
-        #domain_to_rep
+    if !entry.deprecated {
+        return read;
+    }
+    match input.on_deprecated {
+        DeprecationPolicy::Warn => quote! {{
+            #krate::__private::warn_deprecated(#type_name, #version_str);
+            #read
+        }},
+        DeprecationPolicy::Error => quote! {
+            Err(#krate::__private::deprecated_version(#type_name, #version_str))
+        },
+        DeprecationPolicy::Accept => read,
     }
 }
 
-fn generate_transparent_serde(
-    mode: &Mode,
-    domain_type: &syn::Ident,
-    rep_name: &syn::Ident,
-) -> TokenStream {
-    let serialize_impl = quote! {
-        impl serde::Serialize for #domain_type {
-            fn serialize<__S>(
+/// The runtime `Representation` trait, exposing untagged payloads to integrations such as
+/// the envelope codec.
+fn generate_representation_impl(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let rep_name = &input.rep_ident;
+    let version_types = version_types(input);
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let generics = with_predicates(
+        &input.generics,
+        version_types.iter().map(|ty| -> syn::WherePredicate {
+            parse_quote!(#ty: serde::Serialize + serde::de::DeserializeOwned)
+        }),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let variant_names = variant_idents(input);
+    let cfgs = version_cfgs(input);
+    // A payload carries no tag, so a version number reads the entry that is not a `branch`.
+    let payload_entries: Vec<_> = (0..input.versions.len())
+        .filter(|&idx| !input.versions[idx].branch)
+        .collect();
+    let variant_ctors: Vec<_> = payload_entries
+        .iter()
+        .map(|&idx| variant_ctor(input, &variant_names[idx]))
+        .collect();
+    let version_nums: Vec<_> = payload_entries
+        .iter()
+        .map(|&idx| version_number(input, idx))
+        .collect();
+    let payload_cfgs: Vec<_> = payload_entries
+        .iter()
+        .map(|&idx| version_cfg(input, idx))
+        .collect();
+
+    let domain_ident = &input.domain_ident;
+    let tag_field = if matches!(input.tag_style, TagStyle::Untagged { .. }) {
+        quote! { None }
+    } else {
+        let tag = &input.tag;
+        quote! { Some(#tag) }
+    };
+
+    quote! {
+        impl #impl_generics #krate::Versioned for #domain_ident #ty_generics #where_clause {
+            type Representation = #rep_name #ty_generics;
+        }
+
+        impl #impl_generics #krate::Representation for #rep_name #ty_generics #where_clause {
+            const CURRENT: u32 = Self::CURRENT;
+            const MIN_SUPPORTED: u32 = Self::MIN_SUPPORTED;
+            const TAG: Option<&'static str> = #tag_field;
+            const VERSIONS: &'static [#krate::VersionInfo] = Self::VERSIONS;
+
+            fn version(&self) -> u32 {
+                Self::version(self)
+            }
+
+            fn serialize_payload<__S>(
                 &self,
                 __serializer: __S,
             ) -> core::result::Result<__S::Ok, __S::Error>
             where
                 __S: serde::Serializer,
             {
-                #rep_name::from(self).serialize(__serializer)
+                match self {
+                    #(#cfgs Self::#variant_names(v) => serde::Serialize::serialize(v, __serializer)),*
+                }
             }
-        }
-    };
 
-    let deserialize_impl = match mode {
-        Mode::Infallible => {
-            quote! {
-                impl<'de> serde::Deserialize<'de> for #domain_type {
-                    fn deserialize<__D>(
-                        __deserializer: __D,
-                    ) -> core::result::Result<Self, __D::Error>
-                    where
-                        __D: serde::Deserializer<'de>,
-                    {
-                        Ok(#rep_name::deserialize(__deserializer)?.into())
-                    }
+            fn decode_payload<__F>(
+                version: u32,
+                bytes: &[u8],
+                format: &__F,
+            ) -> Option<core::result::Result<Self, __F::Error>>
+            where
+                __F: #krate::Format,
+            {
+                match version {
+                    #(#payload_cfgs #version_nums => Some(
+                        #krate::Format::decode(format, bytes).map(#variant_ctors)
+                    ),)*
+                    _ => None,
+                }
+            }
+
+            fn from_payload(
+                version: u32,
+                payload: #krate::Payload,
+            ) -> Option<core::result::Result<Self, #krate::payload::Error>> {
+                match version {
+                    #(#payload_cfgs #version_nums => Some(payload.deserialize().map(#variant_ctors)),)*
+                    _ => None,
                 }
             }
         }
-        Mode::Fallible { .. } => {
+    }
+}
+
+/// A hand-written `Serialize` that writes the version as a CBOR tag for non-human-readable
+/// serializers, and as the usual internal tag otherwise.
+fn generate_cbor_serialize(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let rep_name = &input.rep_ident;
+    let tag = &input.tag;
+    let base = input.cbor_tag.expect("only called with a CBOR tag");
+    let version_types = version_types(input);
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let generics = with_predicates(
+        &input.generics,
+        version_types
+            .iter()
+            .map(|ty| -> syn::WherePredicate { parse_quote!(#ty: serde::Serialize) }),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let variant_names = variant_idents(input);
+    let version_strs = version_tags(input);
+    let cbor_tags = version_numbers(input)
+        .into_iter()
+        .map(|version| base + u64::from(version));
+    let cfgs = version_cfgs(input);
+
+    quote! {
+        impl #impl_generics serde::Serialize for #rep_name #ty_generics #where_clause {
+            fn serialize<__S>(&self, __serializer: __S) -> core::result::Result<__S::Ok, __S::Error>
+            where
+                __S: serde::Serializer,
+            {
+                if serde::Serializer::is_human_readable(&__serializer) {
+                    match self {
+                        #(#cfgs Self::#variant_names(v) => #krate::__private::serialize_tagged(
+                            __serializer, #tag, #version_strs, v,
+                        )),*
+                    }
+                } else {
+                    match self {
+                        #(#cfgs Self::#variant_names(v) => #krate::__private::cbor::serialize(
+                            __serializer, #cbor_tags, v,
+                        )),*
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The tag and content fields of an adjacent representation.
+fn adjacent_fields(input: &ValidatedInput) -> TokenStream {
+    let tag = &input.tag;
+    let TagStyle::Adjacent { content } = &input.tag_style else {
+        unreachable!("only called for adjacent representations");
+    };
+    quote! { &[#tag, #content] }
+}
+
+/// A hand-written `Serialize` writing the tag as a string field ahead of the content, rather
+/// than as the unit variant serde's derive writes, so that it reads back from formats that
+/// only know the field order.
+fn generate_adjacent_serialize(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let rep_name = &input.rep_ident;
+    let name = rep_name.to_string();
+    let fields = adjacent_fields(input);
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let generics = with_predicates(
+        &input.generics,
+        version_types(input)
+            .iter()
+            .map(|ty| -> syn::WherePredicate { parse_quote!(#ty: serde::Serialize) }),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let variant_names = variant_idents(input);
+    let version_strs = version_tags(input);
+    let cfgs = version_cfgs(input);
+
+    quote! {
+        impl #impl_generics serde::Serialize for #rep_name #ty_generics #where_clause {
+            fn serialize<__S>(&self, __serializer: __S) -> core::result::Result<__S::Ok, __S::Error>
+            where
+                __S: serde::Serializer,
+            {
+                match self {
+                    #(#cfgs Self::#variant_names(v) => #krate::__private::adjacent::serialize(
+                        __serializer, #name, #fields, #version_strs, v,
+                    )),*
+                }
+            }
+        }
+    }
+}
+
+/// A hand-written `Deserialize` reading the tag before the content, from a sequence or a
+/// map, without `deserialize_any` unless a map names the content first.
+fn generate_adjacent_deserialize(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let rep_name = &input.rep_ident;
+    let name = rep_name.to_string();
+    let fields = adjacent_fields(input);
+
+    let arms = (0..input.versions.len()).map(|idx| {
+        let ty = &input.versions[idx].ty;
+        let version_str = version_tag(input, idx);
+        let aliases = &input.versions[idx].aliases;
+        let ctor = variant_ctor(input, &variant_ident(input, idx));
+        let cfg = version_cfg(input, idx);
+        quote! {
+            #cfg
+            #version_str #(| #aliases)* => <#ty as serde::Deserialize<'de>>::deserialize(__content)
+                .map(#ctor)
+        }
+    });
+    let version_strs = version_tags(input);
+    let cfgs = version_cfgs(input);
+
+    let generics = with_predicates(
+        &input.generics,
+        input.versions.iter().map(|entry| -> syn::WherePredicate {
+            let ty = &entry.ty;
+            parse_quote!(#ty: serde::Deserialize<'de>)
+        }),
+    );
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let de_generics = with_de_lifetime(&generics);
+    let (impl_generics, _, _) = de_generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #krate::__private::adjacent::Variants<'de> for #rep_name #ty_generics
+            #where_clause
+        {
+            fn read_content<__D>(
+                __tag: &str,
+                __content: __D,
+            ) -> core::result::Result<Self, __D::Error>
+            where
+                __D: serde::Deserializer<'de>,
+            {
+                match __tag {
+                    #(#arms,)*
+                    __other => Err(serde::de::Error::unknown_variant(
+                        __other,
+                        &[#(#cfgs #version_strs),*],
+                    )),
+                }
+            }
+        }
+
+        impl #impl_generics serde::Deserialize<'de> for #rep_name #ty_generics #where_clause {
+            fn deserialize<__D>(__deserializer: __D) -> core::result::Result<Self, __D::Error>
+            where
+                __D: serde::Deserializer<'de>,
+            {
+                #krate::__private::adjacent::deserialize(
+                    __deserializer,
+                    #name,
+                    #fields,
+                )
+            }
+        }
+    }
+}
+
+/// A hand-written `Deserialize` that buffers the payload, so that documents with a missing
+/// or unrecognised tag can be routed to `default_version` or the user's `on_unknown` handler,
+/// and deprecated versions to the deprecation policy.
+fn generate_deserialize(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let rep_name = &input.rep_ident;
+    let tag = &input.tag;
+    let split = match &input.tag_style {
+        TagStyle::Adjacent { content } => quote! {
+            #krate::__private::split_adjacent(__deserializer, #tag, #content)?
+        },
+        TagStyle::Internal | TagStyle::Untagged { .. } => quote! {
+            #krate::__private::split_tagged(__deserializer, #tag)?
+        },
+    };
+
+    let known_arms = (0..input.versions.len()).map(|idx| {
+        let version_str = version_tag(input, idx);
+        let aliases = &input.versions[idx].aliases;
+        let read = read_version(input, idx);
+        let cfg = version_cfg(input, idx);
+        quote! {
+            #cfg
+            Some(#version_str #(| #aliases)*) => #read
+        }
+    });
+
+    let unknown_arm = unknown_tag_arm(input);
+
+    let generics = with_predicates(
+        &input.generics,
+        input.versions.iter().map(|entry| -> syn::WherePredicate {
+            let ty = &entry.ty;
+            parse_quote!(#ty: serde::Deserialize<'de>)
+        }),
+    );
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let de_generics = with_de_lifetime(&generics);
+    let (impl_generics, _, _) = de_generics.split_for_impl();
+
+    let cbor_dispatch = input.cbor_tag.map(|base| {
+        let arms = (0..input.versions.len()).map(|idx| {
+            let version_num = version_number(input, idx);
+            let read = read_version(input, idx);
+            let cfg = version_cfg(input, idx);
+            quote! { #cfg #version_num => #read }
+        });
+        quote! {
+            if !serde::Deserializer::is_human_readable(&__deserializer) {
+                let (__version, __payload) =
+                    #krate::__private::cbor::split(__deserializer, #base)?;
+                return match __version {
+                    #(#arms,)*
+                    found => Err(serde::de::Error::custom(#krate::UnsupportedVersion {
+                        found,
+                        min: Self::MIN_SUPPORTED,
+                        max: Self::CURRENT,
+                    })),
+                };
+            }
+        }
+    });
+
+    let missing_arm = if let Some(version) = input.default_version {
+        let read = read_version(input, entry_of_version(input, version));
+        quote! {
+            None => #read
+        }
+    } else {
+        quote! {
+            None => Err(serde::de::Error::missing_field(#tag))
+        }
+    };
+
+    quote! {
+        impl #impl_generics serde::Deserialize<'de> for #rep_name #ty_generics #where_clause {
+            fn deserialize<__D>(__deserializer: __D) -> core::result::Result<Self, __D::Error>
+            where
+                __D: serde::Deserializer<'de>,
+            {
+                #cbor_dispatch
+                let (__tag, __payload) = #split;
+                match __tag.as_deref() {
+                    #(#known_arms,)*
+                    #unknown_arm,
+                    #missing_arm,
+                }
+            }
+        }
+    }
+}
+
+/// The match arm handling a tag that names no version of the chain.
+fn unknown_tag_arm(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let version_strs = version_tags(input);
+    let cfgs = version_cfgs(input);
+    let unknown = if let Some(handler) = &input.on_unknown {
+        quote! { #handler(__other, __payload).map_err(serde::de::Error::custom) }
+    } else {
+        quote! {
+            Err(serde::de::Error::unknown_variant(__other, &[#(#cfgs #version_strs),*]))
+        }
+    };
+    // Versions below `min_supported` are recognised, and rejected as unsupported, as are
+    // versions above the current one unless `on_unknown` handles them.
+    if input.min_supported > 1 {
+        let reject = if input.on_unknown.is_some() {
+            quote! { reject_retired }
+        } else {
+            quote! { reject_unsupported }
+        };
+        quote! {
+            Some(__other) => {
+                #krate::__private::#reject(
+                    __other,
+                    Self::MIN_SUPPORTED,
+                    Self::CURRENT,
+                )?;
+                #unknown
+            }
+        }
+    } else {
+        quote! { Some(__other) => #unknown }
+    }
+}
+
+fn generate_ordering(input: &ValidatedInput) -> TokenStream {
+    let rep_name = &input.rep_ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    // Values order by version, then by variant (branch entries share a version), then by
+    // payload, so that the ordering agrees with the `derive(PartialEq, Eq)` that `ord` requires.
+    let generics = with_predicates(
+        &input.generics,
+        std::iter::once(parse_quote!(#rep_name #ty_generics: core::cmp::Eq)).chain(
+            version_types(input)
+                .into_iter()
+                .map(|ty| -> syn::WherePredicate { parse_quote!(#ty: core::cmp::Ord) }),
+        ),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let cfgs = version_cfgs(input);
+    let variants = variant_idents(input);
+    let indices = 0..variants.len();
+
+    quote! {
+        impl #impl_generics core::cmp::PartialOrd for #rep_name #ty_generics #where_clause {
+            fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl #impl_generics core::cmp::Ord for #rep_name #ty_generics #where_clause {
+            #[allow(unreachable_patterns)]
+            fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+                let variant = |rep: &Self| match rep {
+                    #(#cfgs Self::#variants(_) => #indices,)*
+                };
+                self.version()
+                    .cmp(&other.version())
+                    .then_with(|| variant(self).cmp(&variant(other)))
+                    .then_with(|| match (self, other) {
+                        #(#cfgs (Self::#variants(a), Self::#variants(b)) => a.cmp(b),)*
+                        _ => core::cmp::Ordering::Equal,
+                    })
+            }
+        }
+    }
+}
+
+fn generate_conversions(input: &ValidatedInput) -> TokenStream {
+    let domain_ident = &input.domain_ident;
+    let rep_name = &input.rep_ident;
+    let version_types = version_types(input);
+    let num_versions = version_types.len();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let domain_type = quote! { #domain_ident #ty_generics };
+    let rep_type = quote! { #rep_name #ty_generics };
+
+    let generics = with_predicates(
+        &input.generics,
+        migration_steps(input)
+            .iter()
+            .flat_map(|step| step_predicates(input, step)),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let notify = migration_notify(input);
+
+    match &input.mode {
+        Mode::Infallible => {
+            let variant_conversions = (0..num_versions).map(|idx| {
+                let variant_name = variant_ident(input, idx);
+                let chain = if input.self_latest {
+                    build_infallible_steps(input, idx)
+                } else {
+                    build_infallible_chain(input, &domain_type, idx)
+                };
+
+                let cfg = version_cfg(input, idx);
+                let unbox = unbox(input, idx);
+                quote! {
+                    #cfg
+                    #rep_name::#variant_name(v) => {
+                        #unbox
+                        #chain
+                    }
+                }
+            });
+
+            quote! {
+                impl #impl_generics From<#rep_type> for #domain_type #where_clause {
+                    fn from(rep: #rep_type) -> Self {
+                        let __version = rep.version();
+                        let __domain = match rep {
+                            #(#variant_conversions),*
+                        };
+                        #notify
+                        __domain
+                    }
+                }
+            }
+        }
+        Mode::Fallible { error } => {
+            let variant_conversions = (0..num_versions).map(|idx| {
+                let variant_name = variant_ident(input, idx);
+                let chain = build_fallible_chain(input, &domain_type, error, idx);
+
+                let cfg = version_cfg(input, idx);
+                let unbox = unbox(input, idx);
+                quote! {
+                    #cfg
+                    #rep_name::#variant_name(v) => {
+                        #unbox
+                        #chain
+                    }
+                }
+            });
+            let error_type = error_type(input, error);
+
             quote! {
-                impl<'de> serde::Deserialize<'de> for #domain_type {
-                    fn deserialize<__D>(
-                        __deserializer: __D,
-                    ) -> core::result::Result<Self, __D::Error>
-                    where
-                        __D: serde::Deserializer<'de>,
-                    {
-                        #rep_name::deserialize(__deserializer)?
-                            .try_into()
-                            .map_err(serde::de::Error::custom)
+                impl #impl_generics core::convert::TryFrom<#rep_type> for #domain_type #where_clause {
+                    type Error = #error_type;
+
+                    fn try_from(rep: #rep_type) -> Result<Self, Self::Error> {
+                        let __version = rep.version();
+                        let __domain = match rep {
+                            #(#variant_conversions),*
+                        };
+                        #notify
+                        Ok(__domain)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Assert that every conversion in the chain exists, so that a missing impl is reported
+/// against the chain entry it migrates into (or the domain type, for the final step).
+///
+/// Generic domain types are skipped: their conversions are where-clause bounds, which rustc
+/// already reports clearly.
+fn generate_assertions(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    if !input.generics.params.is_empty() {
+        return quote! {};
+    }
+
+    let assertions = migration_steps(input)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, step)| {
+            let Step {
+                prev,
+                next,
+                conversion,
+            } = step;
+            let cfg = version_cfg(input, idx);
+            let assertion = match (conversion, &input.mode) {
+                (Conversion::From, _) => quote! {
+                    #krate::__private::assert::migrates_from::<#prev, #next>();
+                },
+                (Conversion::TryFrom, Mode::Fallible { error }) => quote! {
+                    #krate::__private::assert::try_migrates_from::<#prev, #error, #next>();
+                },
+                (Conversion::Async, Mode::Fallible { error }) => quote! {
+                    #krate::__private::assert::async_migrates_from::<#prev, #error, #next>();
+                },
+                (_, Mode::Infallible) => unreachable!("infallible chains only convert with From"),
+            };
+            quote! { #cfg #assertion }
+        });
+
+    let strict = input
+        .strict_fields
+        .as_ref()
+        .map(|fields| generate_strict(input, fields));
+    quote! {
+        const _: () = {
+            #(#assertions)*
+        };
+        #strict
+    }
+}
+
+/// With `strict = true`, check the type of each field of the latest version DTO against the
+/// domain type's, and build the DTO from the domain type's field names, so that a field
+/// missing from either type fails to compile.
+fn generate_strict(input: &ValidatedInput, fields: &syn::Fields) -> TokenStream {
+    let krate = &input.krate;
+    let latest = &input.versions[input.versions.len() - 1].ty;
+    // Generic arguments are inferred in struct expressions, and cannot be written there
+    // without a turbofish.
+    let mut ctor = latest.clone();
+    if let Some(segment) = ctor.segments.last_mut() {
+        segment.arguments = syn::PathArguments::None;
+    }
+
+    let members: Vec<_> = fields.members().collect();
+    let checks = fields.iter().zip(&members).map(|(field, member)| {
+        let ty = &field.ty;
+        quote_spanned! {ty.span()=>
+            #krate::__private::assert::same_field_type::<#ty, _>(&latest.#member);
+        }
+    });
+
+    quote! {
+        const _: () = {
+            #[allow(dead_code, unreachable_code)]
+            fn strict(latest: &#latest) -> #latest {
+                #(#checks)*
+                #ctor { #(#members: unreachable!()),* }
+            }
+        };
+    }
+}
+
+/// `into_latest` and `latest` on the representation enum, migrating to the latest version
+/// DTO without constructing the domain type, and `upgrade_once`, migrating a single step.
+fn generate_latest(input: &ValidatedInput) -> TokenStream {
+    let rep_name = &input.rep_ident;
+    let version_types = version_types(input);
+    let num_versions = version_types.len();
+    let latest_type = version_types[num_versions - 1];
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let (return_type, variant_conversions): (_, Vec<_>) = match &input.mode {
+        Mode::Infallible => (
+            quote! { #latest_type },
+            (0..num_versions)
+                .map(|idx| unboxed_arm(input, idx, build_infallible_steps(input, idx)))
+                .collect(),
+        ),
+        Mode::Fallible { error } => {
+            let error_type = error_type(input, error);
+            (
+                quote! { core::result::Result<#latest_type, #error_type> },
+                (0..num_versions)
+                    .map(|idx| {
+                        let steps = build_fallible_steps(input, error, idx);
+                        unboxed_arm(input, idx, quote! { Ok(#steps) })
+                    })
+                    .collect(),
+            )
+        }
+    };
+    // The final step, into the domain type, is not taken.
+    let mut steps = migration_steps(input);
+    if !input.self_latest {
+        steps.pop();
+    }
+    let generics = with_predicates(
+        &input.generics,
+        steps.iter().flat_map(|step| step_predicates(input, step)),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let variant_names = variant_idents(input);
+    let cfgs = version_cfgs(input);
+    let (upgrade_type, upgrade_steps) = upgrade_once_arms(input, &variant_names);
+    let errors_doc = matches!(input.mode, Mode::Fallible { .. }).then(|| {
+        quote! {
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if any migration step fails.
+        }
+    });
+
+    let latest = input.clone.then(|| {
+        quote! {
+            /// Migrate a copy of this value to the latest version DTO, without constructing
+            /// the domain type.
+            #errors_doc
+            pub fn latest(&self) -> #return_type
+            where
+                Self: Clone,
+            {
+                self.clone().into_latest()
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics #rep_name #ty_generics #where_clause {
+            /// Migrate this value to the latest version DTO, without constructing the domain
+            /// type.
+            #errors_doc
+            pub fn into_latest(self) -> #return_type {
+                match self {
+                    #(#cfgs Self::#variant_names(v) => #variant_conversions),*
+                }
+            }
+
+            #latest
+
+            /// Migrate this value by a single step, to the next version. The current version
+            /// is returned unchanged.
+            #errors_doc
+            pub fn upgrade_once(self) -> #upgrade_type {
+                match self {
+                    #(#cfgs Self::#variant_names(v) => #upgrade_steps),*
+                }
+            }
+        }
+    }
+}
+
+/// `downgrade_once` and `downgrade_to` on the representation enum, stepping down with the
+/// user's `From<Vn+1> for Vn`.
+fn generate_step_down(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let rep_name = &input.rep_ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let variant_names = variant_idents(input);
+    let cfgs = version_cfgs(input);
+
+    // The oldest version is returned as it is, still boxed with `boxed`.
+    let oldest = &variant_names[0];
+    let mut downgrade_steps = vec![quote! { Self::#oldest(v) }];
+    downgrade_steps.extend(variant_names.windows(2).enumerate().map(|(idx, pair)| {
+        let older = &pair[0];
+        let value = boxed(input, quote! { v.into() });
+        unboxed_arm(input, idx + 1, quote! { Self::#older(#value) })
+    }));
+
+    let generics = with_predicates(&input.generics, step_down_predicates(input));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #rep_name #ty_generics #where_clause {
+            /// Convert this value down by a single step, to the previous version. The oldest
+            /// version is returned unchanged.
+            pub fn downgrade_once(self) -> Self {
+                match self {
+                    #(#cfgs Self::#variant_names(v) => #downgrade_steps),*
+                }
+            }
+
+            /// Convert this value down to the given version, through each version in between.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if `version` is not part of the chain, or is newer than this
+            /// value.
+            pub fn downgrade_to(
+                self,
+                version: u32,
+            ) -> core::result::Result<Self, #krate::UnsupportedVersion> {
+                if !Self::supports_version(version) || version > self.version() {
+                    return Err(#krate::UnsupportedVersion {
+                        found: version,
+                        min: Self::MIN_SUPPORTED,
+                        max: self.version(),
+                    });
+                }
+                let mut rep = self;
+                while rep.version() > version {
+                    rep = rep.downgrade_once();
+                }
+                Ok(rep)
+            }
+        }
+    }
+}
+
+/// `Vn: From<Vn+1>` for each step down the chain.
+fn step_down_predicates(input: &ValidatedInput) -> Vec<syn::WherePredicate> {
+    version_types(input)
+        .windows(2)
+        .map(|pair| {
+            let (older, newer) = (&pair[0], &pair[1]);
+            parse_quote!(#older: From<#newer>)
+        })
+        .collect()
+}
+
+/// `convert_to` on the representation enum, which migrates up or steps down to any version.
+fn generate_conversion_between_versions(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let rep_name = &input.rep_ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    // The upgrade steps up to the latest version, as in `upgrade_once`.
+    let mut steps = migration_steps(input);
+    if !input.self_latest {
+        steps.pop();
+    }
+    let generics = with_predicates(
+        &input.generics,
+        steps
+            .iter()
+            .flat_map(|step| step_predicates(input, step))
+            .chain(step_down_predicates(input)),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let unsupported = quote! {
+        #krate::UnsupportedVersion {
+            found: version,
+            min: Self::MIN_SUPPORTED,
+            max: Self::CURRENT,
+        }
+    };
+    let (return_type, check, upgrade) = match &input.mode {
+        Mode::Infallible => (
+            quote! { core::result::Result<Self, #krate::UnsupportedVersion> },
+            quote! { return Err(#unsupported); },
+            quote! { rep.upgrade_once() },
+        ),
+        Mode::Fallible { error } => {
+            let error_type = error_type(input, error);
+            (
+                quote! { core::result::Result<Self, #krate::ConvertError<#error_type>> },
+                quote! { return Err(#krate::ConvertError::Unsupported(#unsupported)); },
+                if input.step_context {
+                    // Each hop starts from an intermediate version: record the one converted.
+                    quote! {
+                        rep.upgrade_once().map_err(|err| {
+                            #krate::ConvertError::Step(err.with_source_version(__source))
+                        })?
                     }
+                } else {
+                    quote! { rep.upgrade_once().map_err(#krate::ConvertError::Step)? }
+                },
+            )
+        }
+    };
+    let source = input
+        .step_context
+        .then(|| quote! { let __source = self.version(); });
+    let errors_doc = if matches!(input.mode, Mode::Fallible { .. }) {
+        "Returns an error if `version` is not part of the chain, or a migration step towards \
+         it fails."
+    } else {
+        "Returns an error if `version` is not part of the chain."
+    };
+
+    quote! {
+        impl #impl_generics #rep_name #ty_generics #where_clause {
+            /// Convert this value to the given version, migrating up or converting down
+            /// through each version in between, without constructing the domain type.
+            ///
+            /// # Errors
+            ///
+            #[doc = #errors_doc]
+            pub fn convert_to(self, version: u32) -> #return_type {
+                if !Self::supports_version(version) {
+                    #check
+                }
+                #source
+                let mut rep = self;
+                while rep.version() < version {
+                    rep = #upgrade;
                 }
+                while rep.version() > version {
+                    rep = rep.downgrade_once();
+                }
+                Ok(rep)
             }
         }
+    }
+}
+
+/// The return type of `upgrade_once`, and its match arm for each variant.
+fn upgrade_once_arms(
+    input: &ValidatedInput,
+    variant_names: &[syn::Ident],
+) -> (TokenStream, Vec<TokenStream>) {
+    let upgrade_type = match &input.mode {
+        Mode::Infallible => quote! { Self },
+        Mode::Fallible { error } => {
+            let error_type = error_type(input, error);
+            quote! { core::result::Result<Self, #error_type> }
+        }
+    };
+    let upgrade_steps = (0..variant_names.len())
+        .map(|idx| {
+            let step = match (next_entry(input, idx), &input.mode) {
+                // The latest version is returned as it is, still boxed with `boxed`.
+                (None, Mode::Infallible) => {
+                    let latest = &variant_names[idx];
+                    return quote! { Self::#latest(v) };
+                }
+                (None, Mode::Fallible { .. }) => {
+                    let latest = &variant_names[idx];
+                    return quote! { Ok(Self::#latest(v)) };
+                }
+                (Some(next), Mode::Infallible) => {
+                    let value = boxed(input, quote! { v.into() });
+                    let next = &variant_names[next];
+                    quote! { Self::#next(#value) }
+                }
+                (Some(next), Mode::Fallible { .. }) if input.versions[next].infallible => {
+                    let value = boxed(input, quote! { v.into() });
+                    let next = &variant_names[next];
+                    quote! { Ok(Self::#next(#value)) }
+                }
+                (Some(next), Mode::Fallible { error }) => {
+                    let map_err = map_step_error(input, error, idx, idx, Some(next));
+                    let ctor = variant_ctor(input, &variant_names[next]);
+                    quote! {
+                        core::convert::TryInto::try_into(v)
+                            .map(#ctor)
+                            #map_err
+                    }
+                }
+            };
+            unboxed_arm(input, idx, step)
+        })
+        .collect();
+    (upgrade_type, upgrade_steps)
+}
+
+/// Report a completed migration from `__version` to the `on_migrate` and global hooks.
+fn migration_notify(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let rep_name = &input.rep_ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let type_name = input.domain_ident.to_string();
+    let hook = input
+        .on_migrate
+        .as_ref()
+        .map_or_else(|| quote! { None }, |hook| quote! { Some(#hook) });
+
+    quote! {
+        #krate::__private::migrated(
+            #type_name,
+            __version,
+            <#rep_name #ty_generics>::CURRENT,
+            #hook,
+        );
+    }
+}
+
+/// An async `from_rep` constructor on the domain type, for chains with `async` entries. No
+/// `TryFrom` impl is generated for such chains, since it could not await those steps.
+fn generate_async_conversion(input: &ValidatedInput) -> TokenStream {
+    let Mode::Fallible { error } = &input.mode else {
+        unreachable!("async chain entries are validated to require fallible mode");
     };
+    let domain_ident = &input.domain_ident;
+    let rep_name = &input.rep_ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let domain_type = quote! { #domain_ident #ty_generics };
+    let rep_type = quote! { #rep_name #ty_generics };
+
+    let generics = with_predicates(
+        &input.generics,
+        migration_steps(input)
+            .iter()
+            .flat_map(|step| step_predicates(input, step)),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let variant_conversions = (0..input.versions.len()).map(|idx| {
+        let variant_name = variant_ident(input, idx);
+        let chain = build_fallible_chain(input, &domain_type, error, idx);
+        let cfg = version_cfg(input, idx);
+        let unbox = unbox(input, idx);
+
+        quote! {
+            #cfg
+            #rep_name::#variant_name(v) => {
+                #unbox
+                #chain
+            }
+        }
+    });
+    let notify = migration_notify(input);
+    let error_type = error_type(input, error);
+
+    quote! {
+        impl #impl_generics #domain_type #where_clause {
+            /// Migrate a representation to the domain type, awaiting any asynchronous steps.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if any migration step fails.
+            pub async fn from_rep(rep: #rep_type) -> core::result::Result<Self, #error_type> {
+                let __version = rep.version();
+                let __domain = match rep {
+                    #(#variant_conversions),*
+                };
+                #notify
+                Ok(__domain)
+            }
+        }
+    }
+}
+
+/// `From<&Domain>` for the representation enum, always producing the latest version, or
+/// `TryFrom<&Domain>` with `serialize_error`; and `From<Domain>` with `from_owned`.
+fn generate_domain_to_rep(input: &ValidatedInput) -> TokenStream {
+    let domain_ident = &input.domain_ident;
+    let rep_name = &input.rep_ident;
+    let version_types = version_types(input);
+    let num_versions = version_types.len();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let domain_type = quote! { #domain_ident #ty_generics };
+    let rep_type = quote! { #rep_name #ty_generics };
+
+    let latest_version_type = &version_types[num_versions - 1];
+    let latest_variant = variant_ident(input, num_versions - 1);
+    if input.self_latest {
+        let value = boxed(input, quote! { Clone::clone(domain) });
+        return quote! {
+            impl From<&#domain_type> for #rep_type {
+                fn from(domain: &#domain_type) -> Self {
+                    Self::#latest_variant(#value)
+                }
+            }
+        };
+    }
+    let latest = boxed(input, quote! { latest });
+    if let Some(error) = &input.serialize_error {
+        let generics = with_predicates(
+            &input.generics,
+            [
+                parse_quote!(for<'__a> #latest_version_type: TryFrom<&'__a #domain_type>),
+                parse_quote!(
+                    for<'__a> <#latest_version_type as TryFrom<&'__a #domain_type>>::Error:
+                        Into<#error>
+                ),
+            ],
+        );
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        return quote! {
+            impl #impl_generics core::convert::TryFrom<&#domain_type> for #rep_type #where_clause {
+                type Error = #error;
+
+                fn try_from(domain: &#domain_type) -> core::result::Result<Self, Self::Error> {
+                    let latest = <#latest_version_type>::try_from(domain).map_err(Into::into)?;
+                    Ok(Self::#latest_variant(#latest))
+                }
+            }
+        };
+    }
+    let generics = with_predicates(
+        &input.generics,
+        [parse_quote!(for<'__a> #latest_version_type: From<&'__a #domain_type>)],
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let owned = (input.from_owned || input.latest_fields.is_some()).then(|| {
+        let generics = with_predicates(
+            &input.generics,
+            [parse_quote!(#latest_version_type: From<#domain_type>)],
+        );
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        quote! {
+            impl #impl_generics From<#domain_type> for #rep_type #where_clause {
+                fn from(domain: #domain_type) -> Self {
+                    let latest = <#latest_version_type>::from(domain);
+                    Self::#latest_variant(#latest)
+                }
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics From<&#domain_type> for #rep_type #where_clause {
+            fn from(domain: &#domain_type) -> Self {
+                let latest = <#latest_version_type>::from(domain);
+                Self::#latest_variant(#latest)
+            }
+        }
+
+        #owned
+    }
+}
+
+/// `From<&Domain>` for every version but the latest, each built by converting the domain
+/// value into the next version up and stepping down with the user's `From<Vn+1> for Vn`,
+/// plus an `at_version` constructor on the representation enum. Chains without `async`
+/// entries also convert between versions without the domain type.
+fn generate_downgrades(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let domain_ident = &input.domain_ident;
+    let rep_name = &input.rep_ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let domain_type = quote! { #domain_ident #ty_generics };
+
+    let version_types = version_types(input);
+
+    let latest_idx = version_types.len() - 1;
+    // With `self_latest`, the latest version is a copy of the domain value.
+    let from_domain = |idx: usize, ty: &syn::Path| {
+        if input.self_latest && idx == latest_idx {
+            quote! { Clone::clone(domain) }
+        } else {
+            quote! { <#ty>::from(domain) }
+        }
+    };
+    let impls = version_types.windows(2).enumerate().map(|(idx, pair)| {
+        let (older, newer) = (&pair[0], &pair[1]);
+        let cfg = version_cfg(input, idx);
+        let newer_value = from_domain(idx + 1, newer);
+        let generics = with_predicates(
+            &input.generics,
+            [
+                parse_quote!(#older: From<#newer>),
+                parse_quote!(for<'__a> #newer: From<&'__a #domain_type>),
+            ],
+        );
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+        quote! {
+            #cfg
+            impl #impl_generics From<&#domain_type> for #older #where_clause {
+                fn from(domain: &#domain_type) -> Self {
+                    #newer_value.into()
+                }
+            }
+        }
+    });
+
+    let version_arms = version_types.iter().enumerate().map(|(idx, ty)| {
+        let variant_name = variant_ident(input, idx);
+        let version_num = version_number(input, idx);
+        let cfg = version_cfg(input, idx);
+        let value = boxed(input, from_domain(idx, ty));
+        quote! {
+            #cfg
+            #version_num => Ok(Self::#variant_name(#value))
+        }
+    });
+    let generics = with_predicates(
+        &input.generics,
+        version_types
+            .iter()
+            .map(|ty| parse_quote!(for<'__a> #ty: From<&'__a #domain_type>)),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let step_down = generate_step_down(input);
+    let between = if input.versions.iter().any(|entry| entry.is_async) {
+        quote! {}
+    } else {
+        generate_conversion_between_versions(input)
+    };
+
+    quote! {
+        #(#impls)*
+
+        impl #impl_generics #rep_name #ty_generics #where_clause {
+            /// Convert a domain value into the representation of the given version.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if `version` is not part of the chain.
+            pub fn at_version(
+                domain: &#domain_type,
+                version: u32,
+            ) -> core::result::Result<Self, #krate::UnsupportedVersion> {
+                match version {
+                    #(#version_arms,)*
+                    found => Err(#krate::UnsupportedVersion {
+                        found,
+                        min: Self::MIN_SUPPORTED,
+                        max: Self::CURRENT,
+                    }),
+                }
+            }
+        }
+
+        impl #impl_generics #krate::Downgrade for #domain_type #where_clause {
+            fn at_version(
+                &self,
+                version: u32,
+            ) -> core::result::Result<#rep_name #ty_generics, #krate::UnsupportedVersion> {
+                <#rep_name #ty_generics>::at_version(self, version)
+            }
+        }
+
+        #step_down
+        #between
+    }
+}
+
+fn generate_schema(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let rep_name = &input.rep_ident;
+    let rep_name_str = rep_name.to_string();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let version_types = version_types(input);
+    let tag = &input.tag;
+
+    let version_schemas = version_types.iter().enumerate().map(|(idx, ty)| {
+        let version_str = version_tag(input, idx);
+        let cfg = version_cfg(input, idx);
+        let schema = match &input.tag_style {
+            TagStyle::Internal => quote! {
+                #krate::__private::schema::internal::<#ty>(generator, #tag, #version_str)
+            },
+            TagStyle::Adjacent { content } => quote! {
+                #krate::__private::schema::adjacent::<#ty>(
+                    generator, #tag, #content, #version_str,
+                )
+            },
+            TagStyle::Untagged { .. } => quote! {
+                #krate::__private::schema::untagged::<#ty>(generator)
+            },
+        };
+        quote! { #cfg #schema }
+    });
+    let version_arms = version_types.iter().enumerate().map(|(idx, ty)| {
+        let version_num = version_number(input, idx);
+        let cfg = version_cfg(input, idx);
+        quote! {
+            #cfg
+            #version_num => Some(#krate::__private::schema::root::<#ty>())
+        }
+    });
+
+    let generics = with_predicates(
+        &input.generics,
+        version_types
+            .iter()
+            .map(|ty| parse_quote!(#ty: #krate::schemars::JsonSchema)),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #krate::schemars::JsonSchema for #rep_name #ty_generics #where_clause {
+            fn schema_name() -> std::borrow::Cow<'static, str> {
+                std::borrow::Cow::Borrowed(#rep_name_str)
+            }
+
+            fn json_schema(
+                generator: &mut #krate::schemars::SchemaGenerator,
+            ) -> #krate::schemars::Schema {
+                #krate::__private::schema::one_of(&[#(#version_schemas),*])
+            }
+        }
+
+        impl #impl_generics #rep_name #ty_generics #where_clause {
+            /// The JSON Schema of the DTO for a single version, without its version tag.
+            ///
+            /// Returns `None` if `version` is not part of the chain.
+            #[must_use]
+            pub fn schema_for(version: u32) -> Option<#krate::schemars::Schema> {
+                match version {
+                    #(#version_arms,)*
+                    _ => None,
+                }
+            }
+
+            /// A stable hash of the JSON Schema of a single version DTO.
+            ///
+            /// Any change to the DTO's schema changes its fingerprint, so comparing against a
+            /// stored baseline catches edits to versions that have already shipped.
+            ///
+            /// Returns `None` if `version` is not part of the chain.
+            #[must_use]
+            pub fn fingerprint(version: u32) -> Option<u64> {
+                Self::schema_for(version)
+                    .map(|schema| #krate::__private::schema::fingerprint(&schema))
+            }
+
+            /// The Avro record schema of the DTO for a single version, named after the DTO.
+            ///
+            /// Returns `None` if `version` is not part of the chain.
+            #[must_use]
+            pub fn avro_schema_for(
+                version: u32,
+            ) -> Option<Result<#krate::avro::AvroSchema, #krate::avro::Error>> {
+                let schema = Self::schema_for(version)?;
+                let info = <Self as #krate::Representation>::VERSIONS
+                    .iter()
+                    .find(|info| info.version == version)?;
+                let name = #krate::avro::record_name(info.type_name);
+                Some(#krate::avro::from_json_schema(&schema, name, version))
+            }
+        }
+    }
+}
+
+/// proptest's `Arbitrary` for the representation enum, choosing uniformly between versions.
+fn generate_arbitrary(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let rep_name = &input.rep_ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let version_types = version_types(input);
+
+    let strategies = version_types.iter().enumerate().map(|(idx, ty)| {
+        let ctor = variant_ctor(input, &variant_ident(input, idx));
+        let cfg = version_cfg(input, idx);
+        quote! {
+            #cfg
+            #krate::proptest::arbitrary::any::<#ty>()
+                .prop_map(#ctor)
+                .boxed()
+        }
+    });
+
+    let generics = with_predicates(
+        &input.generics,
+        version_types
+            .iter()
+            .map(|ty| parse_quote!(#ty: #krate::proptest::arbitrary::Arbitrary + 'static)),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #krate::proptest::arbitrary::Arbitrary for #rep_name #ty_generics #where_clause {
+            type Parameters = ();
+            type Strategy = #krate::proptest::strategy::BoxedStrategy<Self>;
+
+            fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                use #krate::proptest::strategy::Strategy as _;
+
+                #krate::proptest::strategy::Union::new([#(#strategies),*]).boxed()
+            }
+        }
+    }
+}
+
+/// Self-registration with the runtime registry, through `inventory`.
+fn generate_registration(input: &ValidatedInput, name: &str) -> TokenStream {
+    let krate = &input.krate;
+    let domain_ident = &input.domain_ident;
+    let rep_name = &input.rep_ident;
+
+    quote! {
+        #krate::inventory::submit! {
+            #krate::registry::Registration::new::<#rep_name, #domain_ident>(#name)
+        }
+    }
+}
+
+/// The object-safe `AnyVersioned`, migrating through JSON. Registered types are named by
+/// their registration, so that type-tagged documents can be read back through the registry.
+fn generate_any(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let domain_ident = &input.domain_ident;
+    let rep_name = &input.rep_ident;
+    let type_name = input
+        .register
+        .clone()
+        .unwrap_or_else(|| domain_ident.to_string());
+
+    quote! {
+        impl #krate::any::AnyVersioned for #rep_name {
+            fn type_name(&self) -> &'static str {
+                #type_name
+            }
+
+            fn version(&self) -> u32 {
+                Self::version(self)
+            }
+
+            fn current_version(&self) -> u32 {
+                Self::CURRENT
+            }
+
+            fn to_json(
+                &self,
+            ) -> core::result::Result<#krate::__private::any::Value, #krate::registry::Error> {
+                #krate::__private::any::to_json(self)
+            }
+
+            fn migrate_to_latest_json(
+                &self,
+            ) -> core::result::Result<#krate::__private::any::Value, #krate::registry::Error> {
+                #krate::__private::any::migrate_to_latest_json::<Self, #domain_ident>(self)
+            }
+        }
+    }
+}
+
+/// sqlx impls storing the domain type as Postgres `jsonb`, migrated on decode and encoded
+/// at the latest version.
+fn generate_sqlx(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let domain_ident = &input.domain_ident;
+    let rep_name = &input.rep_ident;
+
+    quote! {
+        impl #krate::sqlx::Type<#krate::sqlx::Postgres> for #domain_ident {
+            fn type_info() -> #krate::sqlx::postgres::PgTypeInfo {
+                #krate::__private::sqlx::type_info()
+            }
+
+            fn compatible(ty: &#krate::sqlx::postgres::PgTypeInfo) -> bool {
+                #krate::__private::sqlx::compatible(ty)
+            }
+        }
+
+        impl<'r> #krate::sqlx::Decode<'r, #krate::sqlx::Postgres> for #domain_ident {
+            fn decode(
+                value: #krate::sqlx::postgres::PgValueRef<'r>,
+            ) -> core::result::Result<Self, #krate::sqlx::error::BoxDynError> {
+                #krate::__private::sqlx::decode::<#rep_name, Self>(value)
+            }
+        }
+
+        impl #krate::sqlx::Encode<'_, #krate::sqlx::Postgres> for #domain_ident {
+            fn encode_by_ref(
+                &self,
+                buf: &mut #krate::sqlx::postgres::PgArgumentBuffer,
+            ) -> core::result::Result<
+                #krate::sqlx::encode::IsNull,
+                #krate::sqlx::error::BoxDynError,
+            > {
+                #krate::__private::sqlx::encode(#rep_name::from(self), buf)
+            }
+        }
+    }
+}
+
+/// diesel `FromSql`/`ToSql` impls for Postgres `jsonb`, on the representation enum and, in
+/// transparent mode, the domain type.
+fn generate_diesel(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let domain_ident = &input.domain_ident;
+    let rep_name = &input.rep_ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let generics = with_predicates(
+        &input.generics,
+        [parse_quote!(Self: serde::Serialize + serde::de::DeserializeOwned)],
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let mut targets = vec![quote! { #rep_name #ty_generics }];
+    if input.transparent {
+        targets.push(quote! { #domain_ident #ty_generics });
+    }
+
+    let impls = targets.iter().map(|target| {
+        quote! {
+            impl #impl_generics #krate::diesel::deserialize::FromSql<
+                #krate::diesel::sql_types::Jsonb,
+                #krate::diesel::pg::Pg,
+            > for #target #where_clause {
+                fn from_sql(
+                    value: #krate::diesel::pg::PgValue<'_>,
+                ) -> #krate::diesel::deserialize::Result<Self> {
+                    #krate::__private::diesel::from_sql(value)
+                }
+            }
+
+            impl #impl_generics #krate::diesel::serialize::ToSql<
+                #krate::diesel::sql_types::Jsonb,
+                #krate::diesel::pg::Pg,
+            > for #target #where_clause {
+                fn to_sql<'__b>(
+                    &'__b self,
+                    out: &mut #krate::diesel::serialize::Output<
+                        '__b,
+                        '_,
+                        #krate::diesel::pg::Pg,
+                    >,
+                ) -> #krate::diesel::serialize::Result {
+                    #krate::__private::diesel::to_sql(self, out)
+                }
+            }
+        }
+    });
+
+    quote! { #(#impls)* }
+}
+
+/// `serialize_latest`, serializing the domain type at the latest version through a borrowing
+/// DTO rather than an owned (cloned) one.
+fn generate_latest_ref(input: &ValidatedInput, ref_ty: &syn::Path) -> TokenStream {
+    let mut borrowed_ty = ref_ty.clone();
+    if let Some(segment) = borrowed_ty.segments.last_mut() {
+        segment.arguments = syn::PathArguments::AngleBracketed(parse_quote!(<'__r>));
+    }
+    generate_serialize_latest(
+        input,
+        &quote! { #borrowed_ty },
+        &quote! { <#ref_ty>::from(domain) },
+    )
+}
+
+/// `serialize_latest`, serializing `payload` (built from `domain`, and of type `payload_ty`,
+/// which may borrow for `'__r`) tagged as the latest version.
+fn generate_serialize_latest(
+    input: &ValidatedInput,
+    payload_ty: &TokenStream,
+    payload: &TokenStream,
+) -> TokenStream {
+    let domain_ident = &input.domain_ident;
+    let rep_name = &input.rep_ident;
+    let latest_version = version_tag(input, input.versions.len() - 1);
+    let tag = &input.tag;
+
+    let serialize = match &input.tag_style {
+        TagStyle::Adjacent { .. } => {
+            let krate = &input.krate;
+            let name = rep_name.to_string();
+            let fields = adjacent_fields(input);
+            quote! {
+                #krate::__private::adjacent::serialize(
+                    __serializer,
+                    #name,
+                    #fields,
+                    #latest_version,
+                    &#payload,
+                )
+            }
+        }
+        TagStyle::Internal | TagStyle::Untagged { .. } => {
+            let container_attr = if input.tag_style == TagStyle::Internal {
+                quote! { #[serde(tag = #tag)] }
+            } else {
+                quote! { #[serde(untagged)] }
+            };
+            quote! {
+                #[derive(serde::Serialize)]
+                #container_attr
+                enum __Latest<'__r> {
+                    #[serde(rename = #latest_version)]
+                    Latest(#payload_ty),
+                }
+
+                serde::Serialize::serialize(&__Latest::Latest(#payload), __serializer)
+            }
+        }
+    };
+
+    quote! {
+        impl #rep_name {
+            /// Serialize a domain value at the latest version, borrowing its fields rather
+            /// than cloning them into a representation.
+            ///
+            /// Usable with `#[serde(serialize_with = "...")]`.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the value cannot be serialized.
+            pub fn serialize_latest<__S>(
+                domain: &#domain_ident,
+                __serializer: __S,
+            ) -> core::result::Result<__S::Ok, __S::Error>
+            where
+                __S: serde::Serializer,
+            {
+                #serialize
+            }
+        }
+    }
+}
+
+/// A `#[cfg(test)]` module exercising the chain.
+///
+/// Sample values come from `Default` where the version types implement it; tests needing a
+/// sample are no-ops otherwise. Migrations are not exercised for async chains, which would
+/// need an executor.
+fn generate_tests(input: &ValidatedInput) -> TokenStream {
+    let krate = &input.krate;
+    let domain_ident = &input.domain_ident;
+    let rep_name = &input.rep_ident;
+    let module = format_ident!("__{}_generated_tests", rep_name);
+    let version_types = version_types(input);
+    let latest = version_types[version_types.len() - 1];
+    let current = version_number(input, version_types.len() - 1);
+    let cfgs = version_cfgs(input);
+
+    let migrate = match &input.mode {
+        Mode::Infallible => quote! { #domain_ident::from(rep) },
+        Mode::Fallible { .. } => quote! {
+            match #domain_ident::try_from(rep) {
+                Ok(domain) => domain,
+                // A default value need not be valid for every migration step.
+                Err(_) => continue,
+            }
+        },
+    };
+    let migration_test = (!input.versions.iter().any(|entry| entry.is_async)).then(|| {
+        quote! {
+            #[test]
+            fn every_version_migrates_to_current() {
+                let samples: Vec<Option<#rep_name>> = vec![
+                    #(#cfgs (&Probe::<#version_types>::new()).sample().map(#rep_name::from)),*
+                ];
+                for rep in samples.into_iter().flatten() {
+                    let domain = #migrate;
+                    assert!(#rep_name::from(&domain).is_current());
+                }
+            }
+        }
+    });
+
+    quote! {
+        #[cfg(test)]
+        #[allow(non_snake_case)]
+        mod #module {
+            use super::*;
+            use #krate::Representation as _;
+            use #krate::__private::testing::{DefaultSample as _, NoSample as _, Probe};
+
+            #[test]
+            fn current_matches_chain_length() {
+                assert_eq!(<#rep_name as #krate::Representation>::CURRENT, #current);
+            }
+
+            #[test]
+            fn latest_version_round_trips() {
+                if let Some(latest) = (&Probe::<#latest>::new()).sample() {
+                    #krate::__private::testing::assert_round_trip(&#rep_name::from(latest));
+                }
+            }
+
+            #migration_test
+        }
+    }
+}
+
+fn generate_transparent_serde(input: &ValidatedInput) -> TokenStream {
+    let domain_ident = &input.domain_ident;
+    let rep_name = &input.rep_ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let domain_type = quote! { #domain_ident #ty_generics };
+    let rep_type = quote! { #rep_name #ty_generics };
+
+    let conversion: Vec<syn::WherePredicate> = if let Some(error) = &input.serialize_error {
+        vec![
+            parse_quote!(for<'__a> #rep_type: TryFrom<&'__a #domain_type, Error = #error>),
+            parse_quote!(#error: core::fmt::Display),
+        ]
+    } else {
+        vec![parse_quote!(for<'__a> #rep_type: From<&'__a #domain_type>)]
+    };
+    let generics = with_predicates(
+        &input.generics,
+        std::iter::once(parse_quote!(#rep_type: serde::Serialize)).chain(conversion),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let serialize_body = if input.latest_ref.is_some() {
+        quote! { <#rep_type>::serialize_latest(self, __serializer) }
+    } else if input.serialize_error.is_some() {
+        quote! {
+            let rep = <#rep_type>::try_from(self).map_err(serde::ser::Error::custom)?;
+            serde::Serialize::serialize(&rep, __serializer)
+        }
+    } else {
+        quote! { serde::Serialize::serialize(&<#rep_type>::from(self), __serializer) }
+    };
+    let serialize_impl = quote! {
+        impl #impl_generics serde::Serialize for #domain_type #where_clause {
+            fn serialize<__S>(
+                &self,
+                __serializer: __S,
+            ) -> core::result::Result<__S::Ok, __S::Error>
+            where
+                __S: serde::Serializer,
+            {
+                #serialize_body
+            }
+        }
+    };
+
+    let mut predicates: Vec<syn::WherePredicate> =
+        vec![parse_quote!(#rep_type: serde::Deserialize<'de>)];
+    let body = match &input.mode {
+        Mode::Infallible => {
+            predicates.push(parse_quote!(#domain_type: From<#rep_type>));
+            quote! {
+                Ok(<#rep_type as serde::Deserialize>::deserialize(__deserializer)?.into())
+            }
+        }
+        Mode::Fallible { .. } => {
+            predicates.push(parse_quote!(#domain_type: core::convert::TryFrom<#rep_type>));
+            predicates.push(parse_quote!(
+                <#domain_type as core::convert::TryFrom<#rep_type>>::Error: core::fmt::Display
+            ));
+            quote! {
+                <#rep_type as serde::Deserialize>::deserialize(__deserializer)?
+                    .try_into()
+                    .map_err(serde::de::Error::custom)
+            }
+        }
+    };
+    let generics = with_de_lifetime(&with_predicates(&input.generics, predicates));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let deserialize_impl = quote! {
+        impl #impl_generics serde::Deserialize<'de> for #domain_type #where_clause {
+            fn deserialize<__D>(
+                __deserializer: __D,
+            ) -> core::result::Result<Self, __D::Error>
+            where
+                __D: serde::Deserializer<'de>,
+            {
+                #body
+            }
+        }
+    };
+
+    quote! {
+        #serialize_impl
+        #deserialize_impl
+    }
+}
+
+/// Start timing the step from the chain entry at `from` to the entry at `to` (or to the
+/// domain type, for `None`), for the `tracing` feature of the runtime crate.
+fn trace_step(input: &ValidatedInput, from: usize, to: Option<usize>) -> TokenStream {
+    let krate = &input.krate;
+    let type_name = input.domain_ident.to_string();
+    let from = version_number(input, from);
+    let to = to.map_or_else(
+        || quote! { None },
+        |to| {
+            let to = version_number(input, to);
+            quote! { Some(#to) }
+        },
+    );
+    quote! { #krate::__private::trace_step(#type_name, #from, #to) }
+}
+
+fn build_infallible_chain(
+    input: &ValidatedInput,
+    domain_type: &TokenStream,
+    start_idx: usize,
+) -> TokenStream {
+    let expr = build_infallible_steps(input, start_idx);
+    let trace = trace_step(input, input.versions.len() - 1, None);
+
+    quote! {{
+        let __prev = #expr;
+        let __step = #trace;
+        let next: #domain_type = __prev.into();
+        next
+    }}
+}
+
+/// Migrate `v`, of the version at `start_idx`, up to the latest version.
+fn build_infallible_steps(input: &ValidatedInput, start_idx: usize) -> TokenStream {
+    let mut expr = quote! { v };
+
+    for (prev, idx) in upgrade_path(input, start_idx) {
+        let ty = &input.versions[idx].ty;
+        let trace = trace_step(input, prev, Some(idx));
+        expr = quote! {{
+            let __prev = #expr;
+            let __step = #trace;
+            let next: #ty = __prev.into();
+            next
+        }};
+    }
+    expr
+}
+
+/// The error type of fallible conversions: the declared error, wrapped in `StepError` when
+/// `step_context` is set.
+fn error_type(input: &ValidatedInput, error: &syn::Path) -> TokenStream {
+    let krate = &input.krate;
+    if input.step_context {
+        quote! { #krate::StepError<#error> }
+    } else {
+        quote! { #error }
+    }
+}
+
+/// Convert the error of the step from the chain entry at `from` to the entry at `to` (or to
+/// the domain type, for `None`) into the conversion's error type, while migrating a value of
+/// the entry at `source`.
+fn map_step_error(
+    input: &ValidatedInput,
+    error: &syn::Path,
+    source: usize,
+    from: usize,
+    to: Option<usize>,
+) -> TokenStream {
+    let krate = &input.krate;
+    if !input.step_context {
+        return quote! { .map_err(Into::<#error>::into) };
+    }
+    let type_name = input.domain_ident.to_string();
+    // A step error defaults to having been read at the version the step starts from.
+    let source = (source != from).then(|| {
+        let source = version_number(input, source);
+        quote! { .with_source_version(#source) }
+    });
+    let from = version_number(input, from);
+    let to = to.map_or_else(
+        || quote! { None },
+        |to| {
+            let to = version_number(input, to);
+            quote! { Some(#to) }
+        },
+    );
+    quote! {
+        .map_err(|err| #krate::StepError::new(#type_name, #from, #to, Into::<#error>::into(err))#source)
+    }
+}
+
+/// Each step converts its own error into the declared error type, so individual migrations
+/// may fail with any error that implements `Into<Error>`. Steps into `async` entries are
+/// awaited, so the chain must then be expanded inside an async fn.
+fn build_fallible_chain(
+    input: &ValidatedInput,
+    domain_type: &TokenStream,
+    error: &syn::Path,
+    start_idx: usize,
+) -> TokenStream {
+    let expr = build_fallible_steps(input, error, start_idx);
+    if input.self_latest {
+        return expr;
+    }
+    let map_err = map_step_error(input, error, start_idx, input.versions.len() - 1, None);
+    let trace = trace_step(input, input.versions.len() - 1, None);
+
+    quote! {{
+        let __prev = #expr;
+        let __step = #trace;
+        let next: #domain_type = core::convert::TryInto::try_into(__prev)#map_err?;
+        next
+    }}
+}
+
+/// Migrate `v`, of the version at `start_idx`, up to the latest version, returning early on
+/// the first failing step.
+fn build_fallible_steps(
+    input: &ValidatedInput,
+    error: &syn::Path,
+    start_idx: usize,
+) -> TokenStream {
+    let krate = &input.krate;
+    let mut expr = quote! { v };
+
+    for (prev, idx) in upgrade_path(input, start_idx) {
+        let entry = &input.versions[idx];
+        let ty = &entry.ty;
+        let trace = trace_step(input, prev, Some(idx));
+        if entry.infallible {
+            expr = quote! {{
+                let __prev = #expr;
+                let __step = #trace;
+                let next: #ty = __prev.into();
+                next
+            }};
+            continue;
+        }
+        let convert = if entry.is_async {
+            quote! { <#ty as #krate::TryFromAsync<_>>::try_from_async(__prev).await }
+        } else {
+            quote! { core::convert::TryInto::try_into(__prev) }
+        };
+        let map_err = map_step_error(input, error, start_idx, prev, Some(idx));
+        expr = quote! {{
+            let __prev = #expr;
+            let __step = #trace;
+            let next: #ty = #convert #map_err?;
+            next
+        }};
+    }
+    expr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_str;
+
+    fn validated_input(mode: Mode) -> ValidatedInput {
+        ValidatedInput {
+            domain_ident: parse_str::<syn::Ident>("Example").unwrap(),
+            vis: parse_quote!(pub),
+            generics: syn::Generics::default(),
+            rep_ident: parse_str::<syn::Ident>("ExampleVersions").unwrap(),
+            mode,
+            step_context: false,
+            transparent: false,
+            ord: false,
+            downgrade: false,
+            schema: false,
+            arbitrary: false,
+            generate_tests: false,
+            register: None,
+            any: false,
+            sqlx: false,
+            diesel: false,
+            clone: true,
+            debug: true,
+            derives: Vec::new(),
+            rep_attrs: Vec::new(),
+            on_unknown: None,
+            on_migrate: None,
+            latest_ref: None,
+            serialize_error: None,
+            from_owned: false,
+            boxed: false,
+            self_latest: false,
+            latest_fields: None,
+            strict_fields: None,
+            tag: "_version".to_string(),
+            tag_style: TagStyle::Internal,
+            cbor_tag: None,
+            default_version: None,
+            min_supported: 1,
+            on_deprecated: DeprecationPolicy::Warn,
+            deny_unknown_fields: false,
+            dates: None,
+            krate: parse_quote!(::serde_evolve),
+            versions: vec![parse_quote!(Version1), parse_quote!(Version2)],
+        }
+    }
+
+    #[test]
+    fn generates_infallible_conversions() {
+        let input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("impl From < ExampleVersions > for Example"));
+        assert!(tokens.contains("impl From < & Example > for ExampleVersions"));
+    }
+
+    #[test]
+    fn generates_fallible_conversions() {
+        let input = validated_input(Mode::Fallible {
+            error: parse_quote!(ExampleError),
+        });
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("impl core :: convert :: TryFrom < ExampleVersions > for Example"));
+        assert!(tokens.contains("type Error = ExampleError"));
+        assert!(tokens.contains(
+            "let next : Version2 = core :: convert :: TryInto :: try_into (__prev) . map_err (Into :: < ExampleError > :: into) ?"
+        ));
+    }
+
+    #[test]
+    fn traces_each_migration_step() {
+        let mut input = validated_input(Mode::Infallible);
+        input.min_supported = 3;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "let __prev = v ; let __step = :: serde_evolve :: __private :: trace_step (\"Example\" , 3u32 , Some (4u32)) ; let next : Version2 = __prev . into () ;"
+        ));
+        assert!(tokens.contains(
+            "let __step = :: serde_evolve :: __private :: trace_step (\"Example\" , 4u32 , None) ; let next : Example = __prev . into () ;"
+        ));
+    }
+
+    #[test]
+    fn wraps_step_errors_with_context() {
+        let mut input = validated_input(Mode::Fallible {
+            error: parse_quote!(ExampleError),
+        });
+        input.step_context = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("type Error = :: serde_evolve :: StepError < ExampleError >"));
+        assert!(tokens.contains(
+            "let next : Version2 = core :: convert :: TryInto :: try_into (__prev) . map_err (| err | :: serde_evolve :: StepError :: new (\"Example\" , 1u32 , Some (2u32) , Into :: < ExampleError > :: into (err))) ?"
+        ));
+        assert!(tokens.contains(
+            ":: serde_evolve :: StepError :: new (\"Example\" , 2u32 , None , Into :: < ExampleError > :: into (err))"
+        ));
+    }
+
+    #[test]
+    fn wraps_step_errors_with_source_version() {
+        let mut input = validated_input(Mode::Fallible {
+            error: parse_quote!(ExampleError),
+        });
+        input.step_context = true;
+        let tokens = generate(&input).to_string();
+        // Version 1 read, failing in its final conversion into the domain type.
+        assert!(tokens.contains(
+            ":: serde_evolve :: StepError :: new (\"Example\" , 2u32 , None , Into :: < ExampleError > :: into (err)) . with_source_version (1u32)"
+        ));
+        assert!(tokens.contains(
+            ":: serde_evolve :: StepError :: new (\"Example\" , 2u32 , None , Into :: < ExampleError > :: into (err)))"
+        ));
+    }
+
+    #[test]
+    fn asserts_chain_conversions_exist() {
+        let tokens = generate(&validated_input(Mode::Infallible)).to_string();
+        assert!(tokens.contains(
+            ":: serde_evolve :: __private :: assert :: migrates_from :: < Version1 , Version2 > () ;"
+        ));
+        assert!(tokens.contains(
+            ":: serde_evolve :: __private :: assert :: migrates_from :: < Version2 , Example > () ;"
+        ));
+
+        let mut input = validated_input(Mode::Fallible {
+            error: parse_quote!(ExampleError),
+        });
+        input.versions[1].is_async = true;
+        let tokens = generate(&input).to_string();
+        assert!(
+            tokens.contains("async_migrates_from :: < Version1 , ExampleError , Version2 > () ;")
+        );
+        assert!(tokens.contains("try_migrates_from :: < Version2 , ExampleError , Example > () ;"));
+
+        input.generics = parse_quote!(<T>);
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains(":: assert ::"));
+    }
+
+    #[test]
+    fn infallible_entries_convert_with_from() {
+        let mut input = validated_input(Mode::Fallible {
+            error: parse_quote!(ExampleError),
+        });
+        input.versions.push(parse_quote!(Version3));
+        input.versions[1].infallible = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("let next : Version2 = __prev . into () ;"));
+        assert!(tokens.contains(
+            "let __prev = { let __prev = v ; let __step = :: serde_evolve :: __private :: trace_step (\"Example\" , 1u32 , Some (2u32)) ; let next : Version2 = __prev . into () ; next } ;"
+        ));
+        assert!(tokens.contains("Self :: V1 (v) => Ok (Self :: V2 (v . into ()))"));
+        assert!(tokens.contains("migrates_from :: < Version1 , Version2 > () ;"));
+
+        input.generics = parse_quote!(<T>);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("where Version2 : From < Version1 > , Version3 : core :: convert :: TryFrom < Version2 >"));
+    }
 
-    quote! {
-        #serialize_impl
-        #deserialize_impl
+    #[test]
+    fn min_supported_offsets_version_numbers() {
+        let mut input = validated_input(Mode::Infallible);
+        input.min_supported = 3;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("V3 (Version1)"));
+        assert!(tokens.contains("V4 (Version2)"));
+        assert!(tokens.contains("pub const CURRENT : u32 = 4u32"));
+        assert!(tokens.contains("pub const MIN_SUPPORTED : u32 = 3u32"));
+        assert!(tokens.contains("Some (\"3\") =>"));
+        assert!(tokens.contains(
+            ":: serde_evolve :: __private :: reject_unsupported (__other , Self :: MIN_SUPPORTED , Self :: CURRENT ,) ?"
+        ));
+
+        input.on_unknown = Some(parse_quote!(fallback));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            ":: serde_evolve :: __private :: reject_retired (__other , Self :: MIN_SUPPORTED , Self :: CURRENT ,) ?"
+        ));
     }
-}
 
-fn build_infallible_chain(
-    domain_type: &syn::Ident,
-    version_types: &[syn::Path],
-    start_idx: usize,
-) -> TokenStream {
-    let mut expr = quote! { v };
+    #[test]
+    fn includes_representation_metadata() {
+        let input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("pub enum ExampleVersions"));
+        assert!(tokens.contains("pub const CURRENT : u32 = 2"));
+        assert!(tokens.contains(
+            "impl :: serde_evolve :: Versioned for Example { type Representation = ExampleVersions ; }"
+        ));
+        assert!(tokens.contains("pub fn sort_by_version"));
+        assert!(tokens.contains(
+            "pub const fn negotiate (peer_min : u32 , peer_max : u32) -> Option < u32 >"
+        ));
+        assert!(tokens.contains("pub const SUPPORTED : & 'static [u32] = & [1u32 , 2u32] ;"));
+        assert!(tokens.contains("pub const fn supports_version (version : u32) -> bool"));
+        assert!(tokens.contains(
+            ":: serde_evolve :: VersionInfo { version : 1u32 , type_name : \"Version1\" , tag : \"1\" , deprecated : false , }"
+        ));
+    }
 
-    for ty in version_types.iter().skip(start_idx + 1) {
-        expr = quote! {{
-            let next: #ty = #expr.into();
-            next
-        }};
+    #[test]
+    fn uses_configured_tag_field() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("# [serde (tag = \"_version\")]"));
+
+        input.tag = "schema_version".to_string();
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("# [serde (tag = \"schema_version\")]"));
     }
 
-    quote! {{
-        let next: #domain_type = #expr.into();
-        next
-    }}
-}
+    #[test]
+    fn untagged_style_orders_variants() {
+        let mut input = validated_input(Mode::Infallible);
+        input.tag_style = TagStyle::Untagged { newest_first: true };
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("# [serde (untagged)]"));
+        let v1 = tokens.find("V1 (Version1)").unwrap();
+        let v2 = tokens.find("V2 (Version2)").unwrap();
+        assert!(v2 < v1);
 
-fn build_fallible_chain(
-    domain_type: &syn::Ident,
-    version_types: &[syn::Path],
-    start_idx: usize,
-) -> TokenStream {
-    let mut expr = quote! { v };
+        input.tag_style = TagStyle::Untagged {
+            newest_first: false,
+        };
+        let tokens = generate(&input).to_string();
+        let v1 = tokens.find("V1 (Version1)").unwrap();
+        let v2 = tokens.find("V2 (Version2)").unwrap();
+        assert!(v1 < v2);
+    }
 
-    for ty in version_types.iter().skip(start_idx + 1) {
-        expr = quote! {{
-            let next: #ty = #expr.try_into()?;
-            next
-        }};
+    #[test]
+    fn adjacent_style_names_tag_and_content() {
+        let mut input = validated_input(Mode::Infallible);
+        input.tag_style = TagStyle::Adjacent {
+            content: "data".to_string(),
+        };
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("# [serde (tag"));
+        assert!(tokens.contains(
+            "adjacent :: serialize (__serializer , \"ExampleVersions\" , & [\"_version\" , \"data\"] , \"1\" , v ,)"
+        ));
+        assert!(tokens.contains(
+            "\"1\" => < Version1 as serde :: Deserialize < 'de >> :: deserialize (__content) . map (Self :: V1)"
+        ));
+
+        input.on_unknown = Some(parse_quote!(recover));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("split_adjacent (__deserializer , \"_version\" , \"data\")"));
     }
 
-    quote! {{
-        let next: #domain_type = #expr.try_into()?;
-        Ok(next)
-    }}
-}
+    #[test]
+    fn default_version_handles_missing_tag() {
+        let mut input = validated_input(Mode::Infallible);
+        input.default_version = Some(1);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("impl < 'de > serde :: Deserialize < 'de > for ExampleVersions"));
+        assert!(tokens.contains(
+            "None => :: serde_evolve :: __private :: deserialize_payload :: < Version1 , __D :: Error > (__payload) . map (Self :: V1)"
+        ));
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use syn::{parse_quote, parse_str};
+    #[test]
+    fn unknown_handler_replaces_derived_deserialize() {
+        let mut input = validated_input(Mode::Infallible);
+        input.on_unknown = Some(parse_quote!(recover));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("# [derive (Clone , Debug , serde :: Serialize ,)]"));
+        assert!(tokens.contains("impl < 'de > serde :: Deserialize < 'de > for ExampleVersions"));
+        assert!(tokens.contains("Some (__other) => recover (__other , __payload)"));
+        assert!(
+            tokens.contains("None => Err (serde :: de :: Error :: missing_field (\"_version\"))")
+        );
+    }
 
-    fn validated_input(mode: Mode) -> ValidatedInput {
-        ValidatedInput {
-            domain_ident: parse_str::<syn::Ident>("Example").unwrap(),
-            rep_ident: parse_str::<syn::Ident>("ExampleVersions").unwrap(),
-            mode,
-            transparent: false,
-            versions: vec![parse_quote!(Version1), parse_quote!(Version2)],
-        }
+    #[test]
+    fn clone_and_debug_derives_can_be_disabled() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("# [derive (Clone , Debug , serde :: Serialize"));
+
+        input.clone = false;
+        input.debug = false;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("# [derive (serde :: Serialize , serde :: Deserialize ,)]"));
     }
 
     #[test]
-    fn generates_infallible_conversions() {
+    fn forwards_derives_and_attributes() {
+        let mut input = validated_input(Mode::Infallible);
+        input.derives = vec![parse_quote!(PartialEq), parse_quote!(Hash)];
+        input.rep_attrs = vec![parse_quote!(#[serde(deny_unknown_fields)])];
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "# [derive (PartialEq , Hash)] # [serde (tag = \"_version\")] # [serde (deny_unknown_fields)] pub enum ExampleVersions"
+        ));
+    }
+
+    #[test]
+    fn generates_ordering_only_when_requested() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("impl core :: cmp :: Ord for ExampleVersions"));
+
+        input.ord = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("impl core :: cmp :: Ord for ExampleVersions"));
+        assert!(tokens.contains("impl core :: cmp :: PartialOrd for ExampleVersions"));
+        assert!(!tokens.contains("impl core :: cmp :: PartialEq for ExampleVersions"));
+        assert!(tokens.contains("(Self :: V1 (a) , Self :: V1 (b)) => a . cmp (b) ,"));
+    }
+
+    #[test]
+    fn propagates_generics_and_bounds() {
+        let mut input = validated_input(Mode::Infallible);
+        input.generics = parse_quote!(<T: Clone>);
+        input.versions = vec![parse_quote!(Version1<T>), parse_quote!(Version2<T>)];
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("pub enum ExampleVersions < T : Clone >"));
+        assert!(
+            tokens.contains("impl < T : Clone > From < Version1 < T > > for ExampleVersions < T >")
+        );
+        assert!(tokens.contains(
+            "impl < T : Clone > From < ExampleVersions < T > > for Example < T > where Version2 < T > : From < Version1 < T > > , Example < T > : From < Version2 < T > >"
+        ));
+    }
+
+    #[test]
+    fn concrete_domain_types_have_no_extra_bounds() {
         let input = validated_input(Mode::Infallible);
         let tokens = generate(&input).to_string();
-        assert!(tokens.contains("impl From < ExampleVersions > for Example"));
-        assert!(tokens.contains("impl From < & Example > for ExampleVersions"));
+        assert!(!tokens.contains("where Version2"));
     }
 
     #[test]
-    fn generates_fallible_conversions() {
-        let input = validated_input(Mode::Fallible {
+    fn generates_downgrades_only_when_requested() {
+        let mut input = validated_input(Mode::Infallible);
+        input.versions.push(parse_quote!(Version3));
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("for Version1"));
+
+        input.downgrade = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("impl From < & Example > for Version1"));
+        assert!(tokens.contains("< Version2 > :: from (domain) . into ()"));
+        assert!(tokens.contains("impl From < & Example > for Version2"));
+        assert!(!tokens.contains("impl From < & Example > for Version3"));
+        assert!(tokens.contains("pub fn at_version"));
+        assert!(tokens.contains("3u32 => Ok (Self :: V3 (< Version3 > :: from (domain)))"));
+        assert!(tokens.contains("Self :: V3 (v) => Self :: V2 (v . into ())"));
+        assert!(tokens.contains("impl :: serde_evolve :: Downgrade for Example"));
+        assert!(tokens.contains(
+            "pub fn convert_to (self , version : u32) -> core :: result :: Result < Self , :: serde_evolve :: UnsupportedVersion >"
+        ));
+    }
+
+    #[test]
+    fn applies_deprecation_policy() {
+        let mut input = validated_input(Mode::Infallible);
+        input.versions[0].deprecated = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "Some (\"1\") => { :: serde_evolve :: __private :: warn_deprecated (\"Example\" , \"1\") ;"
+        ));
+
+        input.on_deprecated = DeprecationPolicy::Error;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "Some (\"1\") => Err (:: serde_evolve :: __private :: deprecated_version (\"Example\" , \"1\"))"
+        ));
+
+        input.on_deprecated = DeprecationPolicy::Accept;
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("impl < 'de > serde :: Deserialize < 'de > for ExampleVersions"));
+    }
+
+    #[test]
+    fn converts_from_the_domain_type_fallibly() {
+        let mut input = validated_input(Mode::Infallible);
+        input.transparent = true;
+        input.serialize_error = Some(parse_quote!(RedactionError));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "impl core :: convert :: TryFrom < & Example > for ExampleVersions { type Error = RedactionError ;"
+        ));
+        assert!(!tokens.contains("impl From < & Example > for ExampleVersions"));
+        assert!(tokens.contains(
+            "< ExampleVersions > :: try_from (self) . map_err (serde :: ser :: Error :: custom) ?"
+        ));
+    }
+
+    #[test]
+    fn converts_owned_domain_values() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("impl From < Example > for ExampleVersions"));
+
+        input.from_owned = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "impl From < Example > for ExampleVersions { fn from (domain : Example) -> Self { let latest = < Version2 > :: from (domain) ;"
+        ));
+    }
+
+    #[test]
+    fn resolves_runtime_items_through_the_crate_path() {
+        let mut input = validated_input(Mode::Infallible);
+        input.krate = parse_quote!(facade::evolve);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("impl facade :: evolve :: Representation for ExampleVersions"));
+        assert!(!tokens.contains(":: serde_evolve"));
+    }
+
+    #[test]
+    fn boxes_variants() {
+        let mut input = validated_input(Mode::Infallible);
+        input.boxed = true;
+        input.on_unknown = Some(parse_quote!(recover));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("V1 (Box < Version1 >)"));
+        assert!(tokens.contains("fn from (v : Version1) -> Self { Self :: V1 (Box :: new (v)) }"));
+        assert!(tokens.contains(". map (| v | Self :: V1 (Box :: new (v)))"));
+        assert!(tokens.contains("ExampleVersions :: V1 (v) => { let v = * v ;"));
+        assert!(
+            tokens.contains(
+                "Self :: V1 (v) => { let v = * v ; Self :: V2 (Box :: new (v . into ())) }"
+            )
+        );
+        assert!(tokens.contains("Self :: V2 (v) => Self :: V2 (v)"));
+    }
+
+    #[test]
+    fn unwraps_coded_entries_before_migrating() {
+        let mut input = validated_input(Mode::Infallible);
+        input.versions[0].codec = Some(parse_quote!(Protobuf));
+        input.versions[0].ty = parse_quote!(::serde_evolve::codec::Coded<Version1, Protobuf>);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("V1 (:: serde_evolve :: codec :: Coded < Version1 , Protobuf >)"));
+        assert!(tokens.contains("ExampleVersions :: V1 (v) => { let v = v . into_inner () ;"));
+        assert!(tokens.contains(
+            "Self :: V1 (v) => { let v = v . into_inner () ; Self :: V2 (v . into ()) }"
+        ));
+        assert!(tokens.contains("type_name : \"Version1\""));
+    }
+
+    #[test]
+    fn implements_remote_definitions() {
+        let mut input = validated_input(Mode::Infallible);
+        input.versions[0].remote = Some(parse_quote!(ConfigDef));
+        input.versions[0].ty =
+            parse_quote!(::serde_evolve::codec::Remote<vendor::Config, ConfigDef>);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "impl :: serde_evolve :: codec :: RemoteDef < vendor :: Config > for ConfigDef"
+        ));
+        assert!(tokens.contains("< ConfigDef > :: deserialize (deserializer)"));
+        assert!(tokens.contains("ExampleVersions :: V1 (v) => { let v = v . into_inner () ;"));
+    }
+
+    #[test]
+    fn denies_unknown_fields() {
+        let mut input = validated_input(Mode::Infallible);
+        input.deny_unknown_fields = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("# [serde (deny_unknown_fields)] pub enum ExampleVersions"));
+        assert!(tokens.contains(
+            "Some (\"1\") => :: serde_evolve :: __private :: deserialize_payload_strict :: < Version1 , __D :: Error > (__payload)"
+        ));
+    }
+
+    #[test]
+    fn accepts_version_aliases() {
+        let mut input = validated_input(Mode::Infallible);
+        input.versions[0].aliases = vec![parse_quote!("v1"), parse_quote!("1.0")];
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("# [serde (rename = \"1\" , alias = \"v1\" , alias = \"1.0\")]"));
+
+        input.on_unknown = Some(parse_quote!(recover));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("Some (\"1\" | \"v1\" | \"1.0\") =>"));
+    }
+
+    #[test]
+    fn generates_the_latest_dto_from_the_domain_fields() {
+        let mut input = validated_input(Mode::Infallible);
+        input
+            .versions
+            .push(ChainEntry::new(parse_quote!(ExampleV3)));
+        input.latest_fields = Some(syn::Fields::Named(parse_quote!({
+            /// The name.
+            #[serde(rename = "n")]
+            #[validate]
+            pub name: String,
+            count: u32,
+        })));
+        let tokens = generate(&input).to_string();
+        assert!(
+            tokens.contains("# [doc = \"Version 3 of [`Example`], generated from its fields.\"]")
+        );
+        assert!(tokens.contains(
+            "pub struct ExampleV3 { # [doc = r\" The name.\"] # [serde (rename = \"n\")] pub name : String , count : u32 , }"
+        ));
+        assert!(tokens.contains("Self { name : latest . name , count : latest . count }"));
+        assert!(tokens.contains("count : Clone :: clone (& domain . count)"));
+
+        input.latest_fields = Some(syn::Fields::Unnamed(parse_quote!((pub String, u32))));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("pub struct ExampleV3 (pub String , u32) ;"));
+        assert!(tokens.contains("Self { 0 : latest . 0 , 1 : latest . 1 }"));
+
+        input.derives = vec![parse_quote!(PartialEq), parse_quote!(Eq)];
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "# [derive (Clone , Debug , serde :: Serialize , serde :: Deserialize)] # [derive (PartialEq , Eq)] pub struct ExampleV3"
+        ));
+    }
+
+    #[test]
+    fn branches_merge_into_the_next_version() {
+        let mut input = validated_input(Mode::Infallible);
+        let mut branch = ChainEntry::new(parse_quote!(MobileV1));
+        branch.branch = true;
+        branch.tag = Some(parse_quote!("1-mobile"));
+        input.versions.insert(1, branch);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("# [serde (rename = \"1-mobile\")] MobileV1 (MobileV1)"));
+        assert!(tokens.contains("Self :: MobileV1 (_) => 1u32"));
+        assert!(tokens.contains("pub const SUPPORTED : & 'static [u32] = & [1u32 , 2u32]"));
+        assert!(
+            tokens
+                .contains("ExampleVersions :: MobileV1 (v) => { { let __prev = { let __prev = v ;")
+        );
+        assert!(tokens.contains("let next : Version2 = __prev . into () ;"));
+        assert!(tokens.contains("Self :: MobileV1 (v) => Self :: V2 (v . into ())"));
+        assert!(!tokens.contains("let next : MobileV1"));
+    }
+
+    #[test]
+    fn dates_each_version() {
+        let mut input = validated_input(Mode::Infallible);
+        input.dates = Some(vec![(2024, 3, 1), (2025, 1, 15)]);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "Self :: V2 (_) => :: serde_evolve :: __private :: version_date (2025u16 , 1u8 , 15u8)"
+        ));
+        assert!(tokens.contains(
+            "1u32 => Some (:: serde_evolve :: __private :: version_date (2024u16 , 3u8 , 1u8))"
+        ));
+        assert!(tokens.contains(
+            "pub const fn is_older_than (& self , date : :: serde_evolve :: VersionDate) -> bool"
+        ));
+    }
+
+    #[test]
+    fn checks_the_latest_version_against_the_domain_fields() {
+        let mut input = validated_input(Mode::Infallible);
+        input.strict_fields = Some(syn::Fields::Named(parse_quote!({
+            pub name: String,
+            count: u32,
+        })));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            ":: serde_evolve :: __private :: assert :: same_field_type :: < u32 , _ > (& latest . count) ;"
+        ));
+        assert!(tokens.contains("Version2 { name : unreachable ! () , count : unreachable ! () }"));
+    }
+
+    #[test]
+    fn uses_the_domain_type_as_the_latest_version() {
+        let mut input = validated_input(Mode::Fallible {
+            error: parse_quote!(MyError),
+        });
+        input.self_latest = true;
+        input.versions.push(ChainEntry::new(parse_quote!(Example)));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("V3 (Example)"));
+        assert!(tokens.contains("Self :: V3 (Clone :: clone (domain))"));
+        assert!(tokens.contains("ExampleVersions :: V3 (v) => { v }"));
+        assert!(tokens.contains("Latest (& '__r Example)"));
+        assert!(!tokens.contains("migrates_from :: < Example , Example >"));
+    }
+
+    #[test]
+    fn overrides_version_tags() {
+        let mut input = validated_input(Mode::Infallible);
+        input.versions[0].tag = Some(parse_quote!("initial"));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("# [serde (rename = \"initial\")] V1 (Version1)"));
+        assert!(tokens.contains("# [serde (rename = \"2\")] V2 (Version2)"));
+        assert!(tokens.contains("Self :: V1 (_) => 1u32"));
+
+        input.on_unknown = Some(parse_quote!(recover));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("Some (\"initial\") =>"));
+    }
+
+    #[test]
+    fn gates_cfg_entries() {
+        let mut input = validated_input(Mode::Infallible);
+        input.versions.push(parse_quote!(Version3));
+        input.versions[0].cfg = Some(parse_quote!(feature = "legacy"));
+        input.versions[1].cfg = Some(parse_quote!(unix));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "# [cfg (feature = \"legacy\")] # [cfg (unix)] # [serde (rename = \"1\")] V1 (Version1)"
+        ));
+        assert!(tokens.contains("# [cfg (unix)] # [serde (rename = \"2\")] V2 (Version2)"));
+        assert!(tokens.contains("# [serde (rename = \"3\")] V3 (Version3)"));
+        assert!(tokens.contains(
+            "# [cfg (feature = \"legacy\")] # [cfg (unix)] impl From < Version1 > for ExampleVersions"
+        ));
+        assert!(tokens.contains("# [cfg (unix)] ExampleVersions :: V2 (v) =>"));
+        assert!(tokens.contains(
+            "& [# [cfg (feature = \"legacy\")] # [cfg (unix)] 1u32 , # [cfg (unix)] 2u32 , 3u32]"
+        ));
+    }
+
+    #[test]
+    fn reports_migrations_to_hooks() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            ":: serde_evolve :: __private :: migrated (\"Example\" , __version , < ExampleVersions > :: CURRENT , None ,)"
+        ));
+
+        input.on_migrate = Some(parse_quote!(telemetry::record));
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("Some (telemetry :: record) ,)"));
+    }
+
+    #[test]
+    fn async_entries_generate_async_constructor() {
+        let mut input = validated_input(Mode::Fallible {
             error: parse_quote!(ExampleError),
         });
+        input.versions[1].is_async = true;
         let tokens = generate(&input).to_string();
-        assert!(tokens.contains("impl core :: convert :: TryFrom < ExampleVersions > for Example"));
-        assert!(tokens.contains("type Error = ExampleError"));
+        assert!(!tokens.contains("TryFrom < ExampleVersions > for Example"));
+        assert!(tokens.contains(
+            "pub async fn from_rep (rep : ExampleVersions) -> core :: result :: Result < Self , ExampleError >"
+        ));
+        assert!(tokens.contains(
+            "let next : Version2 = < Version2 as :: serde_evolve :: TryFromAsync < _ >> :: try_from_async (__prev) . await . map_err (Into :: < ExampleError > :: into) ?"
+        ));
     }
 
     #[test]
-    fn includes_representation_metadata() {
+    fn implements_runtime_representation_trait() {
         let input = validated_input(Mode::Infallible);
         let tokens = generate(&input).to_string();
-        assert!(tokens.contains("pub enum ExampleVersions"));
-        assert!(tokens.contains("pub const CURRENT : u32 = 2"));
+        assert!(tokens.contains("impl :: serde_evolve :: Representation for ExampleVersions"));
+        assert!(
+            tokens.contains("Self :: V2 (v) => serde :: Serialize :: serialize (v , __serializer)")
+        );
+        assert!(tokens.contains(
+            "2u32 => Some (:: serde_evolve :: Format :: decode (format , bytes) . map (Self :: V2)) ,"
+        ));
+        assert!(tokens.contains("const TAG : Option < & 'static str > = Some (\"_version\") ;"));
+
+        let mut input = validated_input(Mode::Infallible);
+        input.tag_style = TagStyle::Untagged {
+            newest_first: false,
+        };
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("const TAG : Option < & 'static str > = None ;"));
+    }
+
+    #[test]
+    fn cbor_tag_replaces_serde_derives() {
+        let mut input = validated_input(Mode::Infallible);
+        input.cbor_tag = Some(1000);
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("# [derive (Clone , Debug ,)]"));
+        assert!(!tokens.contains("# [serde"));
+        assert!(tokens.contains(
+            ":: serde_evolve :: __private :: cbor :: serialize (__serializer , 1002u64 , v ,)"
+        ));
+        assert!(tokens.contains(
+            ":: serde_evolve :: __private :: cbor :: split (__deserializer , 1000u64) ?"
+        ));
+    }
+
+    #[test]
+    fn generates_schema_only_when_requested() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("JsonSchema"));
+
+        input.schema = true;
+        let tokens = generate(&input).to_string();
+        assert!(
+            tokens.contains("impl :: serde_evolve :: schemars :: JsonSchema for ExampleVersions")
+        );
+        assert!(tokens.contains(
+            ":: serde_evolve :: __private :: schema :: internal :: < Version2 > (generator , \"_version\" , \"2\")"
+        ));
+        assert!(tokens.contains(
+            "2u32 => Some (:: serde_evolve :: __private :: schema :: root :: < Version2 > ())"
+        ));
+        assert!(tokens.contains("pub fn fingerprint (version : u32) -> Option < u64 >"));
+        assert!(
+            tokens.contains(
+                ":: serde_evolve :: avro :: from_json_schema (& schema , name , version)"
+            )
+        );
+    }
+
+    #[test]
+    fn generates_test_module_only_when_requested() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("__ExampleVersions_generated_tests"));
+
+        input.generate_tests = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "# [cfg (test)] # [allow (non_snake_case)] mod __ExampleVersions_generated_tests"
+        ));
+        assert!(tokens.contains("fn every_version_migrates_to_current"));
+
+        input.mode = Mode::Fallible {
+            error: parse_quote!(ExampleError),
+        };
+        input.versions[1].is_async = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("fn latest_version_round_trips"));
+        assert!(!tokens.contains("fn every_version_migrates_to_current"));
+    }
+
+    #[test]
+    fn generates_arbitrary_only_when_requested() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("Arbitrary"));
+
+        input.arbitrary = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "impl :: serde_evolve :: proptest :: arbitrary :: Arbitrary for ExampleVersions"
+        ));
+        assert!(tokens.contains(
+            ":: serde_evolve :: proptest :: arbitrary :: any :: < Version2 > () . prop_map (Self :: V2) . boxed ()"
+        ));
+    }
+
+    #[test]
+    fn generates_registration_only_when_requested() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("inventory"));
+
+        input.register = Some("example".to_string());
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            ":: serde_evolve :: registry :: Registration :: new :: < ExampleVersions , Example > (\"example\")"
+        ));
+        assert!(tokens.contains("impl :: serde_evolve :: any :: AnyVersioned for ExampleVersions"));
+        assert!(tokens.contains("fn type_name (& self) -> & 'static str { \"example\" }"));
+    }
+
+    #[test]
+    fn generates_any_versioned_only_when_requested() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("AnyVersioned"));
+
+        input.any = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("impl :: serde_evolve :: any :: AnyVersioned for ExampleVersions"));
+        assert!(tokens.contains(
+            ":: serde_evolve :: __private :: any :: migrate_to_latest_json :: < Self , Example > (self)"
+        ));
+    }
+
+    #[test]
+    fn generates_sqlx_impls_only_when_requested() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("sqlx"));
+
+        input.sqlx = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(
+            "impl < 'r > :: serde_evolve :: sqlx :: Decode < 'r , :: serde_evolve :: sqlx :: Postgres > for Example"
+        ));
+        assert!(tokens.contains(
+            ":: serde_evolve :: __private :: sqlx :: decode :: < ExampleVersions , Self > (value)"
+        ));
+    }
+
+    #[test]
+    fn generates_diesel_impls_for_the_domain_only_when_transparent() {
+        let mut input = validated_input(Mode::Infallible);
+        let tokens = generate(&input).to_string();
+        assert!(!tokens.contains("diesel"));
+
+        input.diesel = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(":: pg :: Pg , > for ExampleVersions {"));
+        assert!(!tokens.contains(":: pg :: Pg , > for Example {"));
+
+        input.transparent = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains(":: pg :: Pg , > for ExampleVersions {"));
+        assert!(tokens.contains(":: pg :: Pg , > for Example {"));
+    }
+
+    #[test]
+    fn borrows_version_types_carrying_domain_lifetimes() {
+        let mut input = validated_input(Mode::Infallible);
+        input.generics = parse_quote!(<'a>);
+        input.versions = vec![parse_quote!(Version1), parse_quote!(Version2<'a>)];
+        input.transparent = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("V1 (Version1)"));
+        assert!(tokens.contains("V2 (# [serde (borrow)] Version2 < 'a >)"));
+        assert!(
+            tokens
+                .contains("impl < 'de : 'a , 'a > serde :: Deserialize < 'de > for Example < 'a >")
+        );
+    }
+
+    #[test]
+    fn serializes_through_latest_ref() {
+        let mut input = validated_input(Mode::Infallible);
+        input.latest_ref = Some(parse_quote!(Version2Ref));
+        input.transparent = true;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("Latest (Version2Ref < '__r >)"));
+        assert!(tokens.contains("< ExampleVersions > :: serialize_latest (self , __serializer)"));
+    }
+
+    #[test]
+    fn generates_latest_accessors() {
+        let tokens = generate(&validated_input(Mode::Infallible)).to_string();
+        assert!(tokens.contains("pub fn into_latest (self) -> Version2"));
+        assert!(tokens.contains("pub fn latest (& self) -> Version2 where Self : Clone"));
+
+        let mut input = validated_input(Mode::Infallible);
+        input.clone = false;
+        let tokens = generate(&input).to_string();
+        assert!(tokens.contains("pub fn into_latest"));
+        assert!(!tokens.contains("pub fn latest"));
+    }
+
+    #[test]
+    fn generates_single_step_upgrades() {
+        let tokens = generate(&validated_input(Mode::Fallible {
+            error: parse_quote!(ExampleError),
+        }))
+        .to_string();
+        assert!(tokens.contains(
+            "Self :: V1 (v) => core :: convert :: TryInto :: try_into (v) . map (Self :: V2)"
+        ));
+        assert!(tokens.contains("Self :: V2 (v) => Ok (Self :: V2 (v))"));
+        assert!(tokens.contains("pub const fn steps_behind (& self) -> u32"));
     }
 }
@@ -0,0 +1,133 @@
+use darling::util::Override;
+use darling::{FromDeriveInput, FromField};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::DeriveInput;
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(evolve), supports(struct_named))]
+struct EvolveReceiver {
+    ident: syn::Ident,
+
+    generics: syn::Generics,
+
+    data: darling::ast::Data<(), EvolveField>,
+
+    /// The previous version, converted from
+    from: syn::Path,
+}
+
+#[derive(Debug, FromField)]
+#[darling(attributes(evolve))]
+struct EvolveField {
+    ident: Option<syn::Ident>,
+
+    /// The name of the field in the previous version, if it was renamed
+    #[darling(default)]
+    rename_from: Option<syn::Ident>,
+
+    /// A field added in this version: `Default::default()`, or the given expression
+    #[darling(default)]
+    default: Option<Override<syn::Expr>>,
+}
+
+/// Expand `#[derive(Evolve)]`: `From<prev>` for a version DTO, moving each field over from
+/// the previous version, by its `rename_from` name if it was renamed, and initialising added
+/// fields marked `default`.
+pub fn expand(input: &DeriveInput) -> darling::Result<TokenStream> {
+    let receiver = EvolveReceiver::from_derive_input(input)?;
+    let fields = receiver
+        .data
+        .take_struct()
+        .expect("`supports(struct_named)` only accepts structs");
+
+    let mut errors = darling::Error::accumulator();
+    let values: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let name = field.ident.as_ref().expect("named fields have an ident");
+            match (&field.rename_from, &field.default) {
+                (Some(_), Some(_)) => {
+                    errors.push(
+                        darling::Error::custom(
+                            "a field cannot be both renamed and added; use either 'rename_from' or 'default'",
+                        )
+                        .with_span(name),
+                    );
+                    quote! {}
+                }
+                (Some(old), None) => quote! { #name: prev.#old },
+                (None, Some(Override::Inherit)) => quote! { #name: Default::default() },
+                (None, Some(Override::Explicit(expr))) => quote! { #name: #expr },
+                (None, None) => quote! { #name: prev.#name },
+            }
+        })
+        .collect();
+    errors.finish()?;
+
+    let ident = &receiver.ident;
+    let from = &receiver.from;
+    let (impl_generics, ty_generics, where_clause) = receiver.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics From<#from> for #ident #ty_generics #where_clause {
+            fn from(prev: #from) -> Self {
+                Self { #(#values),* }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn moves_renames_and_initialises_fields() {
+        let tokens = expand(&parse_quote! {
+            #[evolve(from = ProductV1)]
+            pub struct ProductV2 {
+                #[evolve(rename_from = "title")]
+                pub name: String,
+                pub price: u32,
+                #[evolve(default)]
+                pub tags: Vec<String>,
+                #[evolve(default = "Currency::Eur")]
+                pub currency: Currency,
+            }
+        })
+        .expect("expected expansion success")
+        .to_string();
+
+        assert!(tokens.contains("impl From < ProductV1 > for ProductV2"));
+        assert!(tokens.contains(
+            "Self { name : prev . title , price : prev . price , tags : Default :: default () , currency : Currency :: Eur }"
+        ));
+    }
+
+    #[test]
+    fn errors_on_renamed_and_added_fields() {
+        let err = expand(&parse_quote! {
+            #[evolve(from = ProductV1)]
+            pub struct ProductV2 {
+                #[evolve(rename_from = "title", default)]
+                pub name: String,
+            }
+        })
+        .expect_err("expected expansion failure");
+
+        assert!(err.to_string().contains("cannot be both renamed and added"));
+    }
+
+    #[test]
+    fn errors_without_a_previous_version() {
+        let err = expand(&parse_quote! {
+            pub struct ProductV2 {
+                pub name: String,
+            }
+        })
+        .expect_err("expected expansion failure");
+
+        assert!(err.to_string().contains("Missing field `from`"));
+    }
+}
@@ -1,21 +1,32 @@
 //! Procedural macros for the `serde-evolve` crate.
 //!
 //! This crate provides the `Versioned` derive macro for generating versioned type
-//! conversions and serialization/deserialization implementations.
+//! conversions and serialization/deserialization implementations, the `Evolve` derive for
+//! synthesizing simple migration steps, the `migrate!` macro for declaring a chain of version
+//! DTOs as a series of field changes, and the `versioned_module` attribute macro for
+//! discovering the version chain of a module.
 
 #![allow(clippy::option_if_let_else)] // `darling` expands field defaults into if-let/else; suppress noisy lint.
+#![allow(clippy::struct_excessive_bools)] // Attribute options are naturally a bag of independent flags.
+#![allow(clippy::needless_continue)] // `darling` emits a trailing `continue` when collecting attribute fields.
 
+mod dsl;
 mod emit;
+mod evolve;
+mod module;
 mod parse;
 mod validate;
 
 use proc_macro::TokenStream;
-use syn::{DeriveInput, parse_macro_input};
+use syn::{DeriveInput, ItemMod, parse_macro_input};
 
 /// Derive macro for versioned data structures.
 ///
 /// See the `serde-evolve` crate documentation for usage examples.
-#[proc_macro_derive(Versioned, attributes(versioned))]
+///
+/// `serde` field attributes are accepted without a serde derive on the domain type, to be
+/// copied onto the DTO generated by `generate_latest`.
+#[proc_macro_derive(Versioned, attributes(versioned, serde))]
 pub fn derive_versioned(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
@@ -27,3 +38,51 @@ pub fn derive_versioned(input: TokenStream) -> TokenStream {
         Err(err) => err.write_errors().into(),
     }
 }
+
+/// Derive macro for the migration step into a version DTO from the previous version.
+///
+/// Fields are moved over from the previous version by name. `#[evolve(rename_from = "old")]`
+/// moves a renamed field, and `#[evolve(default)]` or `#[evolve(default = "expr")]`
+/// initialises a field added in this version.
+///
+/// See the `serde-evolve` crate documentation for usage examples.
+#[proc_macro_derive(Evolve, attributes(evolve))]
+pub fn derive_evolve(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match evolve::expand(&input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.write_errors().into(),
+    }
+}
+
+/// Declare a chain of version DTOs as the first version followed by the changes in each step.
+///
+/// Each step `Prev -> Next { ... }` defines `Next` from the fields of `Prev`, changed by
+/// `rename old => new;`, `drop name;` and `add name: Type [= expr];`, along with
+/// `From<Prev> for Next`. Added fields without an expression start as `Default::default()`.
+///
+/// See the `serde-evolve` crate documentation for usage examples.
+#[proc_macro]
+pub fn migrate(input: TokenStream) -> TokenStream {
+    match dsl::expand(input.into()) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Attribute macro for an inline module holding the version types of a domain type.
+///
+/// Every type named like `V<n>` (or `UserV<n>`) in the module is added, in order, to the
+/// chain of the module's `#[derive(Versioned)]` type.
+///
+/// See the `serde-evolve` crate documentation for usage examples.
+#[proc_macro_attribute]
+pub fn versioned_module(args: TokenStream, input: TokenStream) -> TokenStream {
+    let module = parse_macro_input!(input as ItemMod);
+
+    match module::expand(args.into(), module) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
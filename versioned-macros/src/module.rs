@@ -0,0 +1,438 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::punctuated::Punctuated;
+use syn::{Token, parse_quote};
+
+/// A version type found in the module, and its number.
+struct Discovered<'a> {
+    ident: &'a syn::Ident,
+    number: u32,
+}
+
+/// Expand `#[versioned_module]`: discover the `V<n>` types of an inline module, and wire them
+/// into the chain of the module's `#[derive(Versioned)]` type.
+///
+/// With `#[versioned_module(auto_migrate)]`, the step into each version that only adds fields
+/// to the previous one is also generated, unless the module already implements it.
+pub fn expand(args: TokenStream, mut module: syn::ItemMod) -> syn::Result<TokenStream> {
+    let auto_migrate = parse_args(args)?;
+    let Some((_, items)) = &mut module.content else {
+        return Err(syn::Error::new_spanned(
+            &module,
+            "versioned_module requires an inline module",
+        ));
+    };
+
+    let domains: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item_attrs(item).is_some_and(|attrs| derives(attrs, "Versioned")))
+        .map(|(idx, _)| idx)
+        .collect();
+    let &[domain_idx] = domains.as_slice() else {
+        return Err(syn::Error::new_spanned(
+            &module.ident,
+            "versioned_module requires exactly one #[derive(Versioned)] type in the module",
+        ));
+    };
+
+    let mut versions: Vec<_> = items
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| *idx != domain_idx)
+        .filter_map(|(_, item)| discover(item))
+        .collect();
+    versions.sort_by_key(|version| version.number);
+    let Some(first) = versions.first().map(|version| version.number) else {
+        return Err(syn::Error::new_spanned(
+            &module.ident,
+            "versioned_module found no version types (named like `V1`, `UserV2`, ...)",
+        ));
+    };
+    for (expected, version) in (first..).zip(&versions) {
+        if version.number < expected {
+            return Err(syn::Error::new_spanned(
+                version.ident,
+                format!("version {} is defined more than once", version.number),
+            ));
+        }
+        if version.number > expected {
+            return Err(syn::Error::new_spanned(
+                version.ident,
+                format!("expected version {expected} before `{}`", version.ident),
+            ));
+        }
+    }
+
+    let migrations = if auto_migrate {
+        versions
+            .windows(2)
+            .filter_map(|pair| additive_migration(items, pair[0].ident, pair[1].ident))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let chain: Vec<_> = versions.iter().map(|version| version.ident).collect();
+    let chain = quote! { chain(#(#chain),*) };
+    let versioned = item_attrs_mut(&mut items[domain_idx])
+        .expect("the domain is a struct or enum")
+        .iter_mut()
+        .find(|attr| attr.path().is_ident("versioned"));
+    let Some(versioned) = versioned else {
+        return Err(syn::Error::new_spanned(
+            &module.ident,
+            "the #[derive(Versioned)] type requires a #[versioned(...)] attribute",
+        ));
+    };
+    let mut options = match &versioned.meta {
+        syn::Meta::List(list) => {
+            list.parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated)?
+        }
+        _ => Punctuated::new(),
+    };
+    if let Some(option) = options
+        .iter()
+        .find(|option| option.path().is_ident("chain"))
+    {
+        return Err(syn::Error::new_spanned(
+            option,
+            "'chain' is discovered by versioned_module, and must not be given",
+        ));
+    }
+    options.push(syn::Meta::List(parse_quote!(#chain)));
+    // Retired versions are simply absent from the module.
+    if first > 1
+        && !options
+            .iter()
+            .any(|option| option.path().is_ident("min_supported"))
+    {
+        let first = proc_macro2::Literal::u32_unsuffixed(first);
+        options.push(parse_quote!(min_supported = #first));
+    }
+    versioned.meta = parse_quote!(versioned(#options));
+    items.extend(migrations);
+
+    Ok(module.into_token_stream())
+}
+
+/// Whether `auto_migrate` was given, the only argument.
+fn parse_args(args: TokenStream) -> syn::Result<bool> {
+    if args.is_empty() {
+        return Ok(false);
+    }
+    let message = "versioned_module only accepts 'auto_migrate'";
+    let Ok(arg) = syn::parse2::<syn::Ident>(args.clone()) else {
+        return Err(syn::Error::new_spanned(args, message));
+    };
+    if arg != "auto_migrate" {
+        return Err(syn::Error::new_spanned(arg, message));
+    }
+    Ok(true)
+}
+
+/// `From<prev> for next`, when both are structs with named fields, `next` has every field of
+/// `prev` with the same type, and the module does not convert between them already. Added
+/// fields take their `Default` value.
+fn additive_migration(
+    items: &[syn::Item],
+    prev: &syn::Ident,
+    next: &syn::Ident,
+) -> Option<syn::Item> {
+    let named_fields = |ident: &syn::Ident| {
+        items.iter().find_map(|item| match item {
+            syn::Item::Struct(item) if item.ident == *ident && item.generics.params.is_empty() => {
+                match &item.fields {
+                    syn::Fields::Named(fields) => Some(&fields.named),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+    };
+    let prev_fields = named_fields(prev)?;
+    let next_fields = named_fields(next)?;
+    let type_of = |name: &Option<syn::Ident>| {
+        prev_fields
+            .iter()
+            .find(|field| field.ident == *name)
+            .map(|field| field.ty.to_token_stream().to_string())
+    };
+    let is_superset = prev_fields.iter().all(|field| {
+        next_fields.iter().any(|next_field| {
+            next_field.ident == field.ident
+                && type_of(&next_field.ident) == Some(next_field.ty.to_token_stream().to_string())
+        })
+    });
+    if !is_superset || converts(items, prev, next) {
+        return None;
+    }
+
+    let values = next_fields.iter().map(|field| {
+        let name = &field.ident;
+        if type_of(name).is_some() {
+            quote! { #name: prev.#name }
+        } else {
+            quote! { #name: Default::default() }
+        }
+    });
+    Some(parse_quote! {
+        impl From<#prev> for #next {
+            fn from(prev: #prev) -> Self {
+                Self { #(#values),* }
+            }
+        }
+    })
+}
+
+/// Whether the module implements `From<prev>` or `TryFrom<prev>` for `next`, by hand or
+/// with `#[derive(Evolve)]`.
+fn converts(items: &[syn::Item], prev: &syn::Ident, next: &syn::Ident) -> bool {
+    let derives_evolve = items.iter().any(|item| {
+        matches!(item, syn::Item::Struct(item) if item.ident == *next && derives(&item.attrs, "Evolve"))
+    });
+    let last_ident = |ty: &syn::Type| match ty {
+        syn::Type::Path(ty) => ty.path.segments.last().map(|segment| segment.ident.clone()),
+        _ => None,
+    };
+    derives_evolve
+        || items.iter().any(|item| {
+            let syn::Item::Impl(item) = item else {
+                return false;
+            };
+            let Some((_, trait_path, _)) = &item.trait_ else {
+                return false;
+            };
+            let Some(segment) = trait_path.segments.last() else {
+                return false;
+            };
+            let from = match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                    Some(syn::GenericArgument::Type(ty)) => last_ident(ty),
+                    _ => None,
+                },
+                _ => None,
+            };
+            (segment.ident == "From" || segment.ident == "TryFrom")
+                && from.as_ref() == Some(prev)
+                && last_ident(&item.self_ty).as_ref() == Some(next)
+        })
+}
+
+const fn item_attrs(item: &syn::Item) -> Option<&Vec<syn::Attribute>> {
+    match item {
+        syn::Item::Struct(item) => Some(&item.attrs),
+        syn::Item::Enum(item) => Some(&item.attrs),
+        _ => None,
+    }
+}
+
+const fn item_attrs_mut(item: &mut syn::Item) -> Option<&mut Vec<syn::Attribute>> {
+    match item {
+        syn::Item::Struct(item) => Some(&mut item.attrs),
+        syn::Item::Enum(item) => Some(&mut item.attrs),
+        _ => None,
+    }
+}
+
+/// Whether the attributes include a `#[derive(..., <name>, ...)]`.
+fn derives(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .any(|path| {
+            path.segments
+                .last()
+                .is_some_and(|segment| segment.ident == name)
+        })
+}
+
+/// A struct, enum or type alias whose name ends in `V<n>`, such as `V3` or `UserV3`.
+fn discover(item: &syn::Item) -> Option<Discovered<'_>> {
+    let ident = match item {
+        syn::Item::Struct(item) => &item.ident,
+        syn::Item::Enum(item) => &item.ident,
+        syn::Item::Type(item) => &item.ident,
+        _ => return None,
+    };
+    let name = ident.to_string();
+    let (_, digits) = name.rsplit_once('V')?;
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let number = digits.parse().ok()?;
+    Some(Discovered { ident, number })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_module(module: syn::ItemMod) -> syn::Result<String> {
+        expand(TokenStream::new(), module).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn wires_discovered_versions_into_the_chain() {
+        let tokens = expand_module(parse_quote! {
+            mod user {
+                pub struct UserV2;
+                pub struct UserV1;
+                pub type UserV3 = super::Legacy;
+                pub struct Helper;
+
+                #[derive(Versioned)]
+                #[versioned(mode = "infallible")]
+                pub struct User;
+            }
+        })
+        .expect("expected expansion success");
+
+        assert!(
+            tokens.contains(
+                "# [versioned (mode = \"infallible\" , chain (UserV1 , UserV2 , UserV3))]"
+            )
+        );
+    }
+
+    #[test]
+    fn infers_min_supported_from_the_oldest_version() {
+        let tokens = expand_module(parse_quote! {
+            mod user {
+                pub struct V3;
+                pub struct V4;
+
+                #[derive(serde_evolve::Versioned)]
+                #[versioned(mode = "infallible")]
+                pub struct User;
+            }
+        })
+        .expect("expected expansion success");
+
+        assert!(tokens.contains("chain (V3 , V4) , min_supported = 3"));
+    }
+
+    #[test]
+    fn errors_on_gaps_in_the_versions() {
+        let err = expand_module(parse_quote! {
+            mod user {
+                pub struct V1;
+                pub struct V3;
+
+                #[derive(Versioned)]
+                #[versioned(mode = "infallible")]
+                pub struct User;
+            }
+        })
+        .expect_err("expected expansion failure");
+
+        assert_eq!(err.to_string(), "expected version 2 before `V3`");
+    }
+
+    #[test]
+    fn errors_on_duplicate_versions() {
+        let err = expand_module(parse_quote! {
+            mod user {
+                pub struct V1;
+                pub struct UserV1;
+
+                #[derive(Versioned)]
+                #[versioned(mode = "infallible")]
+                pub struct User;
+            }
+        })
+        .expect_err("expected expansion failure");
+
+        assert_eq!(err.to_string(), "version 1 is defined more than once");
+    }
+
+    #[test]
+    fn errors_on_explicit_chain() {
+        let err = expand_module(parse_quote! {
+            mod user {
+                pub struct V1;
+
+                #[derive(Versioned)]
+                #[versioned(mode = "infallible", chain(V1))]
+                pub struct User;
+            }
+        })
+        .expect_err("expected expansion failure");
+
+        assert_eq!(
+            err.to_string(),
+            "'chain' is discovered by versioned_module, and must not be given"
+        );
+    }
+
+    #[test]
+    fn generates_additive_migrations_on_request() {
+        let module: syn::ItemMod = parse_quote! {
+            mod user {
+                pub struct V1 { pub name: String }
+                pub struct V2 { pub name: String, pub email: Option<String> }
+                pub struct V3 { pub name: String, pub email: Option<String>, pub age: u32 }
+                pub struct V4 { pub full_name: String }
+                #[derive(Evolve)]
+                #[evolve(from = V4)]
+                pub struct V5 { pub full_name: String, pub nickname: Option<String> }
+
+                impl From<V2> for V3 {
+                    fn from(v2: V2) -> Self {
+                        Self { name: v2.name, email: v2.email, age: 18 }
+                    }
+                }
+
+                #[derive(Versioned)]
+                #[versioned(mode = "infallible")]
+                pub struct User;
+            }
+        };
+        let tokens = expand_module(module.clone()).expect("expected expansion success");
+        assert!(!tokens.contains("impl From < V1 > for V2"));
+
+        let tokens = expand(quote!(auto_migrate), module)
+            .expect("expected expansion success")
+            .to_string();
+        assert!(tokens.contains(
+            "impl From < V1 > for V2 { fn from (prev : V1) -> Self { Self { name : prev . name , email : Default :: default () } } }"
+        ));
+        // Written by hand.
+        assert_eq!(tokens.matches("impl From < V2 > for V3").count(), 1);
+        // Not a superset.
+        assert!(!tokens.contains("impl From < V3 > for V4"));
+        // Derived.
+        assert!(!tokens.contains("impl From < V4 > for V5"));
+    }
+
+    #[test]
+    fn errors_on_unknown_arguments() {
+        let module: syn::ItemMod = parse_quote! {
+            mod user {}
+        };
+        let err = expand(quote!(auto), module).expect_err("expected expansion failure");
+        assert_eq!(
+            err.to_string(),
+            "versioned_module only accepts 'auto_migrate'"
+        );
+    }
+
+    #[test]
+    fn errors_without_a_domain_type() {
+        let err = expand_module(parse_quote! {
+            mod user {
+                pub struct V1;
+            }
+        })
+        .expect_err("expected expansion failure");
+
+        assert_eq!(
+            err.to_string(),
+            "versioned_module requires exactly one #[derive(Versioned)] type in the module"
+        );
+    }
+}
@@ -1,25 +1,219 @@
+use darling::util::PathList;
 use darling::{FromDeriveInput, FromMeta};
-use syn::DeriveInput;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{DeriveInput, Token};
 
 #[derive(Debug)]
 pub struct ParsedInput {
     pub ident: syn::Ident,
+    pub vis: syn::Visibility,
+    pub generics: syn::Generics,
+    /// The fields of a struct domain type (`None` for enums).
+    pub fields: Option<syn::Fields>,
     pub representation: Option<syn::Ident>,
     pub mode: Option<String>,
     pub error: Option<syn::Path>,
+    pub step_context: bool,
     pub transparent: bool,
-    pub versions: Vec<syn::Path>,
+    pub ord: bool,
+    pub downgrade: bool,
+    pub schema: bool,
+    pub arbitrary: bool,
+    pub generate_tests: bool,
+    pub register: Option<String>,
+    pub any: bool,
+    pub sqlx: bool,
+    pub diesel: bool,
+    pub clone: bool,
+    pub debug: bool,
+    pub derives: Vec<syn::Path>,
+    pub rep_attrs: Vec<syn::Attribute>,
+    pub on_unknown: Option<syn::Path>,
+    pub on_migrate: Option<syn::Path>,
+    pub latest_ref: Option<syn::Path>,
+    pub serialize_error: Option<syn::Path>,
+    pub from_owned: bool,
+    pub boxed: bool,
+    pub self_latest: bool,
+    pub generate_latest: bool,
+    pub strict: bool,
+    pub tag: Option<String>,
+    pub tag_style: Option<String>,
+    pub untagged_order: Option<String>,
+    pub content: Option<String>,
+    pub cbor_tag: Option<u64>,
+    pub default_version: Option<u32>,
+    pub min_supported: Option<u32>,
+    pub on_deprecated: Option<String>,
+    pub deny_unknown_fields: bool,
+    pub dated: bool,
+    pub latest_date: Option<syn::LitStr>,
+    pub krate: Option<syn::Path>,
+    pub versions: Vec<ChainEntry>,
+}
+
+/// A version type in the chain, followed by optional modifiers (`V1 deprecated`,
+/// `V2 infallible`, `V3 alias("v3", "3.0")`, `V0 cfg(feature = "legacy")`,
+/// `V4 as "2023-06"`, `V0 codec(Protobuf)`, `vendor::Config remote(ConfigDef)`,
+/// `V2Mobile as "2-mobile" branch`).
+#[derive(Debug, Clone)]
+pub struct ChainEntry {
+    pub ty: syn::Path,
+    pub deprecated: bool,
+    /// The migration into this version is asynchronous (`TryFromAsync`).
+    pub is_async: bool,
+    /// The migration into this version cannot fail (`From`), within a fallible chain.
+    pub infallible: bool,
+    /// Additional tag spellings accepted for this version when deserializing.
+    pub aliases: Vec<syn::LitStr>,
+    /// The tag written for this version, in place of its version number.
+    pub tag: Option<syn::LitStr>,
+    /// The version, and all code referring to it, is only compiled when this predicate holds.
+    pub cfg: Option<syn::Meta>,
+    /// The version does not implement serde, and is serialized through this `Codec`.
+    pub codec: Option<syn::Path>,
+    /// The version is a foreign type serialized through this serde remote definition.
+    pub remote: Option<syn::Path>,
+    /// The version is a fork beside the previous entry: it shares its version number, and
+    /// migrates into the next entry that is not a branch, while nothing migrates into it.
+    pub branch: bool,
+}
+
+impl ChainEntry {
+    /// An entry for `ty`, without modifiers.
+    pub const fn new(ty: syn::Path) -> Self {
+        Self {
+            ty,
+            deprecated: false,
+            is_async: false,
+            infallible: false,
+            aliases: Vec::new(),
+            tag: None,
+            cfg: None,
+            codec: None,
+            remote: None,
+            branch: false,
+        }
+    }
+}
+
+impl Parse for ChainEntry {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut entry = Self::new(input.parse()?);
+
+        while !input.is_empty() && !input.peek(Token![,]) {
+            if input.parse::<Option<Token![async]>>()?.is_some() {
+                entry.is_async = true;
+                continue;
+            }
+            if input.parse::<Option<Token![as]>>()?.is_some() {
+                entry.tag = Some(input.parse()?);
+                continue;
+            }
+            let modifier: syn::Ident = input.parse()?;
+            if modifier == "deprecated" {
+                entry.deprecated = true;
+            } else if modifier == "infallible" {
+                entry.infallible = true;
+            } else if modifier == "branch" {
+                entry.branch = true;
+            } else if modifier == "alias" {
+                let content;
+                syn::parenthesized!(content in input);
+                let aliases =
+                    Punctuated::<syn::LitStr, Token![,]>::parse_separated_nonempty(&content)?;
+                if !content.is_empty() {
+                    return Err(content.error("expected a string literal"));
+                }
+                entry.aliases.extend(aliases);
+            } else if modifier == "cfg" {
+                let content;
+                syn::parenthesized!(content in input);
+                entry.cfg = Some(content.parse()?);
+                if !content.is_empty() {
+                    return Err(content.error("expected a single cfg predicate"));
+                }
+            } else if modifier == "codec" {
+                let content;
+                syn::parenthesized!(content in input);
+                entry.codec = Some(content.parse()?);
+                if !content.is_empty() {
+                    return Err(content.error("expected a single codec type"));
+                }
+            } else if modifier == "remote" {
+                let content;
+                syn::parenthesized!(content in input);
+                entry.remote = Some(content.parse()?);
+                if !content.is_empty() {
+                    return Err(content.error("expected a single remote definition type"));
+                }
+            } else {
+                return Err(syn::Error::new_spanned(
+                    &modifier,
+                    format!(
+                        "unknown chain modifier '{modifier}', expected 'deprecated', 'async', 'infallible', 'branch', 'alias', 'cfg', 'codec' or 'remote'"
+                    ),
+                ));
+            }
+        }
+
+        Ok(entry)
+    }
 }
 
 pub fn parse_input(input: &DeriveInput) -> darling::Result<ParsedInput> {
     let receiver = VersionedReceiver::from_derive_input(input)?;
 
+    let fields = match &input.data {
+        syn::Data::Struct(data) => Some(data.fields.clone()),
+        syn::Data::Enum(_) | syn::Data::Union(_) => None,
+    };
+
     Ok(ParsedInput {
         ident: receiver.ident,
+        vis: input.vis.clone(),
+        generics: receiver.generics,
+        fields,
         representation: receiver.rep,
         mode: receiver.mode,
         error: receiver.error,
+        step_context: receiver.step_context.unwrap_or(false),
         transparent: receiver.transparent.unwrap_or(false),
+        ord: receiver.ord.unwrap_or(false),
+        downgrade: receiver.downgrade.unwrap_or(false),
+        schema: receiver.schema.unwrap_or(false),
+        arbitrary: receiver.arbitrary.unwrap_or(false),
+        generate_tests: receiver.generate_tests.unwrap_or(false),
+        register: receiver.register,
+        any: receiver.any.unwrap_or(false),
+        sqlx: receiver.sqlx.unwrap_or(false),
+        diesel: receiver.diesel.unwrap_or(false),
+        clone: receiver.clone.unwrap_or(true),
+        debug: receiver.debug.unwrap_or(true),
+        derives: receiver.derive.to_vec(),
+        rep_attrs: receiver.rep_attrs.0,
+        on_unknown: receiver.on_unknown,
+        on_migrate: receiver.on_migrate,
+        latest_ref: receiver.latest_ref,
+        serialize_error: receiver.serialize_error,
+        from_owned: receiver.from_owned.unwrap_or(false),
+        boxed: receiver.boxed.unwrap_or(false),
+        self_latest: receiver.self_latest.unwrap_or(false),
+        generate_latest: receiver.generate_latest.unwrap_or(false),
+        strict: receiver.strict.unwrap_or(false),
+        tag: receiver.tag,
+        tag_style: receiver.tag_style,
+        untagged_order: receiver.untagged_order,
+        content: receiver.content,
+        cbor_tag: receiver.cbor_tag,
+        default_version: receiver.default_version,
+        min_supported: receiver.min_supported,
+        on_deprecated: receiver.on_deprecated,
+        deny_unknown_fields: receiver.deny_unknown_fields.unwrap_or(false),
+        dated: receiver.dated.unwrap_or(false),
+        latest_date: receiver.latest_date,
+        krate: receiver.krate,
         versions: receiver.chain.0,
     })
 }
@@ -28,10 +222,12 @@ pub fn parse_input(input: &DeriveInput) -> darling::Result<ParsedInput> {
 // Clippy's option-if-let-else lint; suppress it locally so callers do not need to.
 #[allow(clippy::option_if_let_else)]
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(versioned), supports(struct_any))]
+#[darling(attributes(versioned), supports(struct_any, enum_any))]
 struct VersionedReceiver {
     pub(crate) ident: syn::Ident,
 
+    pub(crate) generics: syn::Generics,
+
     /// Name of the generated representation enum (defaults to {Type}Versions)
     #[darling(default)]
     pub(crate) rep: Option<syn::Ident>,
@@ -44,33 +240,201 @@ struct VersionedReceiver {
     #[darling(default)]
     pub(crate) error: Option<syn::Path>,
 
+    /// Wrap step errors in `StepError`, naming the version read and the versions the failing
+    /// step converts between
+    #[darling(default)]
+    pub(crate) step_context: Option<bool>,
+
     /// Enable transparent serde support (serialize/deserialize domain type directly)
     #[darling(default)]
     pub(crate) transparent: Option<bool>,
 
+    /// Implement `PartialOrd`/`Ord` on the representation enum, keyed by version number
+    #[darling(default)]
+    pub(crate) ord: Option<bool>,
+
+    /// Generate conversions from the domain type to every earlier version
+    #[darling(default)]
+    pub(crate) downgrade: Option<bool>,
+
+    /// Implement `JsonSchema` for the representation (requires the `schemars` feature)
+    #[darling(default)]
+    pub(crate) schema: Option<bool>,
+
+    /// Implement proptest's `Arbitrary` for the representation (requires the `proptest`
+    /// feature)
+    #[darling(default)]
+    pub(crate) arbitrary: Option<bool>,
+
+    /// Generate a test module checking round trips and the migration chain
+    #[darling(default)]
+    pub(crate) generate_tests: Option<bool>,
+
+    /// Name under which the type registers itself for type-erased migration (requires the
+    /// `inventory` feature)
+    #[darling(default)]
+    pub(crate) register: Option<String>,
+
+    /// Implement the object-safe `AnyVersioned` for the representation (requires the `json`
+    /// feature)
+    #[darling(default)]
+    pub(crate) any: Option<bool>,
+
+    /// Implement sqlx's `Type`, `Encode` and `Decode` for the domain type as Postgres `jsonb`
+    /// (requires the `sqlx` feature)
+    #[darling(default)]
+    pub(crate) sqlx: Option<bool>,
+
+    /// Implement diesel's `FromSql`/`ToSql` for Postgres `jsonb` on the representation and,
+    /// when transparent, the domain type (requires the `diesel` feature)
+    #[darling(default)]
+    pub(crate) diesel: Option<bool>,
+
+    /// Derive `Clone` on the representation enum (defaults to true)
+    #[darling(default)]
+    pub(crate) clone: Option<bool>,
+
+    /// Derive `Debug` on the representation enum (defaults to true)
+    #[darling(default)]
+    pub(crate) debug: Option<bool>,
+
+    /// Additional derives for the representation enum
+    #[darling(default)]
+    pub(crate) derive: PathList,
+
+    /// Attributes forwarded to the representation enum
+    #[darling(default)]
+    pub(crate) rep_attrs: RepAttrs,
+
+    /// Handler invoked with the tag and payload of documents carrying an unrecognised version
+    #[darling(default)]
+    pub(crate) on_unknown: Option<syn::Path>,
+
+    /// Hook invoked after a value is migrated from an old version
+    #[darling(default)]
+    pub(crate) on_migrate: Option<syn::Path>,
+
+    /// Borrowing DTO of the latest version (`VLatestRef<'a>`), used to serialize without
+    /// cloning
+    #[darling(default)]
+    pub(crate) latest_ref: Option<syn::Path>,
+
+    /// Error type of a fallible `TryFrom<&Domain>` conversion into the latest version, used
+    /// in place of `From<&Domain>`
+    #[darling(default)]
+    pub(crate) serialize_error: Option<syn::Path>,
+
+    /// Generate `From<Domain>` for the representation enum, through the user's
+    /// `From<Domain>` for the latest version
+    #[darling(default)]
+    pub(crate) from_owned: Option<bool>,
+
+    /// Hold each version in a `Box`, so the representation enum stays pointer-sized
+    #[darling(default)]
+    pub(crate) boxed: Option<bool>,
+
+    /// Use the domain type itself as the latest version, after the chain's entries
+    #[darling(default)]
+    pub(crate) self_latest: Option<bool>,
+
+    /// Generate the latest version DTO (`{Type}V{n}`) from the domain type's fields, after
+    /// the chain's entries
+    #[darling(default)]
+    pub(crate) generate_latest: Option<bool>,
+
+    /// Check at compile time that the latest version DTO has the fields of the domain type,
+    /// with the same names and types
+    #[darling(default)]
+    pub(crate) strict: Option<bool>,
+
+    /// Name of the version tag field (defaults to "_version")
+    #[darling(default)]
+    pub(crate) tag: Option<String>,
+
+    /// Representation style: "internal", "adjacent" or "untagged" (defaults to "internal")
+    #[darling(default)]
+    pub(crate) tag_style: Option<String>,
+
+    /// Order in which untagged versions are attempted: `newest_first` or `oldest_first`
+    #[darling(default)]
+    pub(crate) untagged_order: Option<String>,
+
+    /// Name of the payload field for adjacently tagged representations (defaults to "data")
+    #[darling(default)]
+    pub(crate) content: Option<String>,
+
+    /// Base CBOR tag number; version `n` is written as tag `cbor_tag + n` by non-human-readable
+    /// serializers
+    #[darling(default)]
+    pub(crate) cbor_tag: Option<u64>,
+
+    /// Version assumed for documents that lack a version tag
+    #[darling(default)]
+    pub(crate) default_version: Option<u32>,
+
+    /// Version number of the first chain entry; older versions are recognised but rejected
+    /// as unsupported (defaults to 1)
+    #[darling(default)]
+    pub(crate) min_supported: Option<u32>,
+
+    /// Policy applied when reading a deprecated version: "warn", "error" or "accept"
+    #[darling(default)]
+    pub(crate) on_deprecated: Option<String>,
+
+    /// Reject documents with keys that the version type does not declare
+    #[darling(default)]
+    pub(crate) deny_unknown_fields: Option<bool>,
+
+    /// Identify versions by the release dates in their tags (`V1 as "2024-03-01"`), in
+    /// chronological order
+    #[darling(default)]
+    pub(crate) dated: Option<bool>,
+
+    /// The date tag of the implicit latest entry of a dated chain, added by `self_latest` or
+    /// `generate_latest`
+    #[darling(default)]
+    pub(crate) latest_date: Option<syn::LitStr>,
+
+    /// Path to the `serde_evolve` crate, for use through a re-export (defaults to
+    /// `::serde_evolve`)
+    #[darling(default, rename = "crate")]
+    pub(crate) krate: Option<syn::Path>,
+
     /// Chain of version types
     pub(crate) chain: ChainList,
 }
 
+#[derive(Debug, Clone, Default)]
+struct RepAttrs(Vec<syn::Attribute>);
+
+// Forwarded attributes are written in full (`rep_attrs(#[serde(deny_unknown_fields)])`), so
+// they are parsed as outer attributes rather than as nested meta items.
+impl FromMeta for RepAttrs {
+    fn from_meta(item: &syn::Meta) -> darling::Result<Self> {
+        let syn::Meta::List(list) = item else {
+            return Err(darling::Error::unsupported_format("non-list").with_span(item));
+        };
+
+        list.parse_args_with(syn::Attribute::parse_outer)
+            .map(Self)
+            .map_err(darling::Error::from)
+    }
+}
+
 #[derive(Debug, Clone)]
-struct ChainList(Vec<syn::Path>);
+struct ChainList(Vec<ChainEntry>);
 
+// Chain entries are parsed from the raw tokens rather than as nested meta items, since meta
+// paths cannot carry generic arguments (`chain(V1<T>, V2<T>)`) or modifiers.
 impl FromMeta for ChainList {
-    fn from_list(items: &[darling::ast::NestedMeta]) -> darling::Result<Self> {
-        items
-            .iter()
-            .map(|item| match item {
-                darling::ast::NestedMeta::Meta(meta) => {
-                    if let syn::Meta::Path(path) = meta {
-                        Ok(path.clone())
-                    } else {
-                        Err(darling::Error::unexpected_type("path"))
-                    }
-                }
-                darling::ast::NestedMeta::Lit(_) => Err(darling::Error::unexpected_type("path")),
-            })
-            .collect::<darling::Result<Vec<_>>>()
-            .map(ChainList)
+    fn from_meta(item: &syn::Meta) -> darling::Result<Self> {
+        let syn::Meta::List(list) = item else {
+            return Err(darling::Error::unsupported_format("non-list").with_span(item));
+        };
+
+        list.parse_args_with(Punctuated::<ChainEntry, Token![,]>::parse_terminated)
+            .map(|entries| Self(entries.into_iter().collect()))
+            .map_err(darling::Error::from)
     }
 }
 
@@ -89,7 +453,30 @@ mod tests {
                 rep = "CustomRep",
                 mode = "fallible",
                 error = "MyError",
-                transparent = true
+                step_context = true,
+                transparent = true,
+                ord = true,
+                downgrade = true,
+                schema = true,
+                arbitrary = true,
+                generate_tests = true,
+                register = "example",
+                sqlx = true,
+                diesel = true,
+                clone = false,
+                derive(PartialEq, std::hash::Hash),
+                rep_attrs(#[serde(deny_unknown_fields)] #[allow(dead_code)]),
+                on_unknown = "handlers::recover",
+                on_migrate = telemetry::record,
+                latest_ref = Version2Ref,
+                tag = "schema_version",
+                tag_style = "untagged",
+                untagged_order = "oldest_first",
+                content = "body",
+                cbor_tag = 1000,
+                default_version = 1,
+                min_supported = 1,
+                on_deprecated = "error"
             )]
             struct Example;
         };
@@ -102,7 +489,258 @@ mod tests {
             parsed.error.unwrap().to_token_stream().to_string(),
             "MyError"
         );
+        assert!(parsed.step_context);
         assert!(parsed.transparent);
+        assert!(parsed.ord);
+        assert!(parsed.downgrade);
+        assert!(parsed.schema);
+        assert!(parsed.arbitrary);
+        assert!(parsed.generate_tests);
+        assert_eq!(parsed.register.as_deref(), Some("example"));
+        assert!(parsed.sqlx);
+        assert!(parsed.diesel);
+        assert!(!parsed.clone);
+        assert!(parsed.debug);
+        assert_eq!(
+            parsed
+                .derives
+                .iter()
+                .map(|path| path.to_token_stream().to_string())
+                .collect::<Vec<_>>(),
+            ["PartialEq", "std :: hash :: Hash"]
+        );
+        assert_eq!(parsed.rep_attrs.len(), 2);
+        assert_eq!(
+            parsed.on_unknown.unwrap().to_token_stream().to_string(),
+            "handlers :: recover"
+        );
+        assert_eq!(
+            parsed.on_migrate.unwrap().to_token_stream().to_string(),
+            "telemetry :: record"
+        );
+        assert_eq!(
+            parsed.latest_ref.unwrap().to_token_stream().to_string(),
+            "Version2Ref"
+        );
+        assert_eq!(parsed.tag.as_deref(), Some("schema_version"));
+        assert_eq!(parsed.tag_style.as_deref(), Some("untagged"));
+        assert_eq!(parsed.untagged_order.as_deref(), Some("oldest_first"));
+        assert_eq!(parsed.content.as_deref(), Some("body"));
+        assert_eq!(parsed.cbor_tag, Some(1000));
+        assert_eq!(parsed.default_version, Some(1));
+        assert_eq!(parsed.min_supported, Some(1));
+        assert_eq!(parsed.on_deprecated.as_deref(), Some("error"));
+        assert_eq!(parsed.versions.len(), 2);
+    }
+
+    #[test]
+    fn parses_conversion_and_strictness_options() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(
+                chain(Version1, Version2),
+                serialize_error = RedactionError,
+                from_owned = true,
+                boxed,
+                any = true,
+                deny_unknown_fields = true,
+                crate = "facade::serde_evolve"
+            )]
+            struct Example;
+        };
+
+        let parsed = parse_input(&input).expect("expected parse success");
+        assert_eq!(
+            parsed
+                .serialize_error
+                .unwrap()
+                .to_token_stream()
+                .to_string(),
+            "RedactionError"
+        );
+        assert!(parsed.from_owned);
+        assert!(parsed.boxed);
+        assert!(parsed.any);
+        assert!(parsed.deny_unknown_fields);
+        assert_eq!(
+            parsed.krate.unwrap().to_token_stream().to_string(),
+            "facade :: serde_evolve"
+        );
+    }
+
+    #[test]
+    fn accepts_enum_domain_types() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(mode = "infallible", chain(CommandV1, CommandV2))]
+            enum Command {
+                Start,
+                Stop { force: bool },
+            }
+        };
+
+        let parsed = parse_input(&input).expect("expected parse success");
+        assert_eq!(parsed.ident, format_ident!("Command"));
         assert_eq!(parsed.versions.len(), 2);
     }
+
+    #[test]
+    fn rejects_union_domain_types() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(mode = "infallible", chain(RawV1))]
+            union Raw {
+                int: u32,
+                float: f32,
+            }
+        };
+
+        assert!(parse_input(&input).is_err());
+    }
+
+    #[test]
+    fn parses_generic_domain_and_chain_types() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(mode = "infallible", chain(WrapperV1<T>, versions::WrapperV2<T>))]
+            struct Wrapper<T: Clone> where T: Default {
+                inner: T,
+            }
+        };
+
+        let parsed = parse_input(&input).expect("expected parse success");
+        assert_eq!(parsed.generics.params.len(), 1);
+        assert!(parsed.generics.where_clause.is_some());
+        assert_eq!(
+            parsed.versions[1].ty.to_token_stream().to_string(),
+            "versions :: WrapperV2 < T >"
+        );
+    }
+
+    #[test]
+    fn parses_chain_modifiers() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(mode = "infallible", chain(V1 deprecated, V2 async, V3 infallible))]
+            struct Example;
+        };
+
+        let parsed = parse_input(&input).expect("expected parse success");
+        assert!(parsed.versions[0].deprecated);
+        assert!(!parsed.versions[0].is_async);
+        assert!(!parsed.versions[1].deprecated);
+        assert!(parsed.versions[1].is_async);
+        assert!(parsed.versions[2].infallible);
+    }
+
+    #[test]
+    fn parses_chain_aliases() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(mode = "infallible", chain(V1 alias("v1", "1.0") deprecated, V2))]
+            struct Example;
+        };
+
+        let parsed = parse_input(&input).expect("expected parse success");
+        let aliases: Vec<_> = parsed.versions[0]
+            .aliases
+            .iter()
+            .map(syn::LitStr::value)
+            .collect();
+        assert_eq!(aliases, ["v1", "1.0"]);
+        assert!(parsed.versions[0].deprecated);
+        assert!(parsed.versions[1].aliases.is_empty());
+    }
+
+    #[test]
+    fn parses_chain_tag_overrides() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(mode = "infallible", chain(V1 as "initial", V2 as "2023-06" deprecated, V3))]
+            struct Example;
+        };
+
+        let parsed = parse_input(&input).expect("expected parse success");
+        let tags: Vec<_> = parsed
+            .versions
+            .iter()
+            .map(|entry| entry.tag.as_ref().map(syn::LitStr::value))
+            .collect();
+        assert_eq!(
+            tags,
+            [
+                Some("initial".to_string()),
+                Some("2023-06".to_string()),
+                None
+            ]
+        );
+        assert!(parsed.versions[1].deprecated);
+    }
+
+    #[test]
+    fn parses_chain_cfgs() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(mode = "infallible", chain(V1 cfg(feature = "legacy") deprecated, V2))]
+            struct Example;
+        };
+
+        let parsed = parse_input(&input).expect("expected parse success");
+        let cfg = parsed.versions[0].cfg.as_ref().expect("expected a cfg");
+        assert_eq!(cfg.to_token_stream().to_string(), "feature = \"legacy\"");
+        assert!(parsed.versions[0].deprecated);
+        assert!(parsed.versions[1].cfg.is_none());
+    }
+
+    #[test]
+    fn parses_chain_codecs() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(mode = "infallible", chain(V1 codec(codecs::Protobuf) deprecated, V2))]
+            struct Example;
+        };
+
+        let parsed = parse_input(&input).expect("expected parse success");
+        let codec = parsed.versions[0].codec.as_ref().expect("expected a codec");
+        assert_eq!(codec.to_token_stream().to_string(), "codecs :: Protobuf");
+        assert!(parsed.versions[0].deprecated);
+        assert!(parsed.versions[1].codec.is_none());
+    }
+
+    #[test]
+    fn rejects_empty_alias_lists() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(mode = "infallible", chain(V1 alias(), V2))]
+            struct Example;
+        };
+
+        assert!(parse_input(&input).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_chain_modifiers() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(mode = "infallible", chain(V1 obsolete, V2))]
+            struct Example;
+        };
+
+        let err = parse_input(&input).expect_err("expected parse failure");
+        assert!(
+            err.to_string()
+                .contains("unknown chain modifier 'obsolete'")
+        );
+    }
+
+    #[test]
+    fn rejects_non_path_chain_entries() {
+        let input: DeriveInput = parse_quote! {
+            #[derive(Versioned)]
+            #[versioned(mode = "infallible", chain("V1"))]
+            struct Example;
+        };
+
+        assert!(parse_input(&input).is_err());
+    }
 }
@@ -1,13 +1,80 @@
-use crate::parse::ParsedInput;
-use quote::format_ident;
+use crate::parse::{ChainEntry, ParsedInput};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{ToTokens, format_ident};
 
 #[derive(Debug, Clone)]
 pub struct ValidatedInput {
     pub domain_ident: syn::Ident,
+    pub vis: syn::Visibility,
+    pub generics: syn::Generics,
     pub rep_ident: syn::Ident,
     pub mode: Mode,
+    pub step_context: bool,
     pub transparent: bool,
-    pub versions: Vec<syn::Path>,
+    pub ord: bool,
+    pub downgrade: bool,
+    pub schema: bool,
+    pub arbitrary: bool,
+    pub generate_tests: bool,
+    pub register: Option<String>,
+    pub any: bool,
+    pub sqlx: bool,
+    pub diesel: bool,
+    pub clone: bool,
+    pub debug: bool,
+    pub derives: Vec<syn::Path>,
+    pub rep_attrs: Vec<syn::Attribute>,
+    pub on_unknown: Option<syn::Path>,
+    pub on_migrate: Option<syn::Path>,
+    pub latest_ref: Option<syn::Path>,
+    /// The domain type converts into the latest version with `TryFrom`, failing with this error.
+    pub serialize_error: Option<syn::Path>,
+    /// `From<Domain>` is generated for the representation enum.
+    pub from_owned: bool,
+    /// Variants hold their version in a `Box`.
+    pub boxed: bool,
+    /// The domain type is the last chain entry, appended after the declared versions.
+    pub self_latest: bool,
+    /// The domain type's fields, when the latest version DTO is generated from them.
+    pub latest_fields: Option<syn::Fields>,
+    /// Fields of the domain type, which the latest version DTO must match (`strict = true`).
+    pub strict_fields: Option<syn::Fields>,
+    pub tag: String,
+    pub tag_style: TagStyle,
+    pub cbor_tag: Option<u64>,
+    pub default_version: Option<u32>,
+    pub min_supported: u32,
+    pub on_deprecated: DeprecationPolicy,
+    /// Documents with keys their version type does not declare are rejected.
+    pub deny_unknown_fields: bool,
+    /// The release date of each version (`dated = true`).
+    pub dates: Option<Vec<Date>>,
+    /// Path to the runtime crate in generated code.
+    pub krate: syn::Path,
+    pub versions: Vec<ChainEntry>,
+}
+
+/// A version date (`dated = true`): year, month and day.
+pub type Date = (u16, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeprecationPolicy {
+    /// Accept the document and emit a warning through `log`/`tracing`, if enabled.
+    Warn,
+    /// Reject the document.
+    Error,
+    /// Accept the document silently.
+    Accept,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagStyle {
+    /// The version tag is a field inside the payload object.
+    Internal,
+    /// The version tag and the payload are sibling fields of an outer object.
+    Adjacent { content: String },
+    /// No tag; versions are attempted in turn until one deserializes.
+    Untagged { newest_first: bool },
 }
 
 #[derive(Debug, Clone)]
@@ -16,97 +83,1904 @@ pub enum Mode {
     Fallible { error: syn::Path },
 }
 
-pub fn validate(parsed: ParsedInput) -> Result<ValidatedInput, syn::Error> {
+pub fn validate(mut parsed: ParsedInput) -> Result<ValidatedInput, syn::Error> {
+    let validated_mode = validate_mode(&parsed)?;
+    let tag_style = validate_tag_style(&parsed)?;
+    let on_deprecated = validate_deprecation(&parsed, &tag_style)?;
+    validate_entries(&parsed)?;
+    validate_extras(&parsed)?;
+    let strict_fields = validate_conversions(&parsed)?;
+    validate_derives(&parsed)?;
+    let dates = validate_dates(&parsed)?;
+    let latest_fields = append_latest_entry(&mut parsed)?;
+
     let ParsedInput {
         ident,
+        vis,
+        generics,
         representation,
-        mode,
-        error,
+        step_context,
         transparent,
-        versions,
+        ord,
+        downgrade,
+        schema,
+        arbitrary,
+        generate_tests,
+        register,
+        any,
+        sqlx,
+        diesel,
+        clone,
+        debug,
+        derives,
+        rep_attrs,
+        on_unknown,
+        on_migrate,
+        latest_ref,
+        serialize_error,
+        from_owned,
+        boxed,
+        self_latest,
+        tag,
+        cbor_tag,
+        default_version,
+        min_supported,
+        deny_unknown_fields,
+        krate,
+        mut versions,
+        ..
     } = parsed;
+    let krate = krate.unwrap_or_else(|| syn::parse_quote!(::serde_evolve));
+    wrap_coded_entries(&mut versions, &krate);
+
+    let min_supported = validate_numbering(
+        &ident,
+        &tag_style,
+        versions.iter().filter(|entry| !entry.branch).count(),
+        min_supported,
+        default_version,
+    )?;
+    validate_tags(&versions, &tag_style, min_supported)?;
+
+    let tag = validate_tag_name(&ident, tag)?;
+    let rep_ident = representation.unwrap_or_else(|| format_ident!("{}Versions", ident));
+
+    Ok(ValidatedInput {
+        domain_ident: ident,
+        vis,
+        generics,
+        rep_ident,
+        mode: validated_mode,
+        step_context,
+        transparent,
+        ord,
+        downgrade,
+        schema,
+        arbitrary,
+        generate_tests,
+        register,
+        any,
+        sqlx,
+        diesel,
+        clone,
+        debug,
+        derives,
+        rep_attrs,
+        on_unknown,
+        on_migrate,
+        latest_ref,
+        serialize_error,
+        from_owned,
+        boxed,
+        self_latest,
+        latest_fields,
+        strict_fields,
+        tag,
+        tag_style,
+        cbor_tag,
+        default_version,
+        min_supported,
+        on_deprecated,
+        deny_unknown_fields,
+        dates,
+        krate,
+        versions,
+    })
+}
+
+/// The name of the version tag field, defaulting to `_version`.
+fn validate_tag_name(ident: &syn::Ident, tag: Option<String>) -> Result<String, syn::Error> {
+    let tag = tag.unwrap_or_else(|| "_version".to_string());
+    if tag.is_empty() {
+        return Err(syn::Error::new_spanned(ident, "tag must not be empty"));
+    }
+    Ok(tag)
+}
+
+/// Append the implicit latest entry of `self_latest` (the domain type) or `generate_latest`
+/// (a `{Type}V{n}` DTO) to the chain, returning the fields the generated DTO is built from.
+///
+/// Without either option, the chain must be non-empty.
+fn append_latest_entry(parsed: &mut ParsedInput) -> Result<Option<syn::Fields>, syn::Error> {
+    let ident = &parsed.ident;
+    let latest_date = parsed.latest_date.take();
+    if parsed.self_latest {
+        let mut entry = ChainEntry::new(ident.clone().into());
+        entry.tag.clone_from(&latest_date);
+        parsed.versions.push(entry);
+    }
+    if !parsed.generate_latest {
+        if parsed.versions.is_empty() {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "chain must contain at least one version type",
+            ));
+        }
+        return Ok(None);
+    }
 
-    if versions.is_empty() {
+    let number = u32::try_from(parsed.versions.len())
+        .ok()
+        .and_then(|len| parsed.min_supported.unwrap_or(1).checked_add(len))
+        .ok_or_else(|| {
+            syn::Error::new_spanned(ident, "too many versions for u32 version numbers")
+        })?;
+    let mut entry = ChainEntry::new(format_ident!("{ident}V{number}").into());
+    entry.tag = latest_date;
+    parsed.versions.push(entry);
+    Ok(parsed.fields.take())
+}
+
+/// Validate `min_supported` and `default_version` against the version numbers of the chain,
+/// returning the number of its first entry.
+fn validate_numbering(
+    ident: &syn::Ident,
+    tag_style: &TagStyle,
+    num_versions: usize,
+    min_supported: Option<u32>,
+    default_version: Option<u32>,
+) -> Result<u32, syn::Error> {
+    let min_supported = min_supported.unwrap_or(1);
+    if min_supported == 0 {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "min_supported must be at least 1",
+        ));
+    }
+    let current = u32::try_from(num_versions)
+        .ok()
+        .and_then(|len| min_supported.checked_add(len - 1))
+        .ok_or_else(|| {
+            syn::Error::new_spanned(ident, "too many versions for u32 version numbers")
+        })?;
+    if min_supported > 1 && matches!(tag_style, TagStyle::Untagged { .. }) {
         return Err(syn::Error::new_spanned(
-            &ident,
-            "chain must contain at least one version type",
+            ident,
+            "'min_supported' requires a tagged representation",
         ));
     }
 
-    let rep_ident = representation.unwrap_or_else(|| format_ident!("{}Versions", ident));
+    if let Some(version) = default_version {
+        if !(min_supported..=current).contains(&version) {
+            return Err(syn::Error::new_spanned(
+                ident,
+                format!(
+                    "default_version {version} is out of range, expected {min_supported}..={current}"
+                ),
+            ));
+        }
+        if let TagStyle::Untagged { .. } = tag_style {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "'default_version' requires a tagged representation",
+            ));
+        }
+    }
 
-    let validated_mode = match mode.as_deref().unwrap_or("fallible") {
-        "infallible" => Mode::Infallible,
-        "fallible" => match error {
-            Some(error) => Mode::Fallible { error },
-            None => {
-                return Err(syn::Error::new_spanned(
-                    &ident,
+    Ok(min_supported)
+}
+
+fn validate_mode(parsed: &ParsedInput) -> Result<Mode, syn::Error> {
+    let ident = &parsed.ident;
+    match parsed.mode.as_deref().unwrap_or("fallible") {
+        "infallible" if parsed.step_context => Err(syn::Error::new_spanned(
+            ident,
+            "'step_context' requires fallible mode",
+        )),
+        "infallible" => Ok(Mode::Infallible),
+        "fallible" => parsed.error.clone().map_or_else(
+            || {
+                Err(syn::Error::new_spanned(
+                    ident,
                     "fallible mode requires 'error' attribute",
-                ));
+                ))
+            },
+            |error| Ok(Mode::Fallible { error }),
+        ),
+        other => Err(syn::Error::new_spanned(
+            ident,
+            format!("invalid mode '{other}', expected 'infallible' or 'fallible'"),
+        )),
+    }
+}
+
+/// Options generating optional items: `arbitrary`, `generate_tests`, `latest_ref`, `register`,
+/// `any` and `sqlx`.
+fn validate_extras(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    if parsed.arbitrary && !parsed.debug {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "'arbitrary' requires the representation to implement Debug",
+        ));
+    }
+
+    if parsed.generate_tests && !parsed.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "'generate_tests' is not supported for generic domain types",
+        ));
+    }
+
+    if let Some(name) = &parsed.register {
+        if name.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "registered name must not be empty",
+            ));
+        }
+        if !parsed.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'register' is not supported for generic domain types",
+            ));
+        }
+        if parsed.versions.iter().any(|entry| entry.is_async) {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'register' cannot be used with async chain entries",
+            ));
+        }
+    }
+
+    if parsed.any {
+        if !parsed.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'any' is not supported for generic domain types",
+            ));
+        }
+        if parsed.versions.iter().any(|entry| entry.is_async) {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'any' cannot be used with async chain entries",
+            ));
+        }
+    }
+
+    if let Some(latest_ref) = &parsed.latest_ref {
+        if !latest_ref
+            .segments
+            .last()
+            .is_some_and(|segment| segment.arguments.is_none())
+        {
+            return Err(syn::Error::new_spanned(
+                latest_ref,
+                "'latest_ref' must name the type without arguments; its lifetime is supplied",
+            ));
+        }
+        if !parsed.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'latest_ref' is not supported for generic domain types",
+            ));
+        }
+        if parsed.cbor_tag.is_some() {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'latest_ref' cannot be combined with 'cbor_tag'",
+            ));
+        }
+    }
+
+    if parsed.sqlx {
+        if !parsed.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'sqlx' is not supported for generic domain types",
+            ));
+        }
+        if parsed.versions.iter().any(|entry| entry.is_async) {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'sqlx' cannot be used with async chain entries",
+            ));
+        }
+    }
+
+    validate_codecs(parsed)
+}
+
+/// Entries with a `codec` or `remote` definition are wrapped in `Coded` or `Remote`, which
+/// only take part in migrations out of the entry: it cannot be the latest version, or be built
+/// from newer versions or strategies.
+fn validate_codecs(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    if let Some(both) = parsed
+        .versions
+        .iter()
+        .find(|entry| entry.codec.is_some() && entry.remote.is_some())
+    {
+        return Err(syn::Error::new_spanned(
+            &both.ty,
+            "an entry cannot have both a 'codec' and a 'remote' definition",
+        ));
+    }
+    let Some((idx, entry)) = parsed
+        .versions
+        .iter()
+        .enumerate()
+        .find(|(_, entry)| entry.codec.is_some() || entry.remote.is_some())
+    else {
+        return Ok(());
+    };
+    let modifier = if entry.codec.is_some() {
+        "codec"
+    } else {
+        "remote"
+    };
+
+    let is_latest =
+        idx + 1 == parsed.versions.len() && !parsed.self_latest && !parsed.generate_latest;
+    let message = if is_latest {
+        format!("a '{modifier}' entry cannot be the latest version")
+    } else if !parsed.generics.params.is_empty() {
+        format!("'{modifier}' entries are not supported for generic domain types")
+    } else if let Some(conflict) = [
+        (parsed.downgrade, "downgrade"),
+        (parsed.schema, "schema"),
+        (parsed.arbitrary, "arbitrary"),
+        (parsed.generate_tests, "generate_tests"),
+    ]
+    .into_iter()
+    .find_map(|(set, option)| set.then_some(option))
+    {
+        format!("'{modifier}' entries cannot be combined with '{conflict}'")
+    } else {
+        return Ok(());
+    };
+    Err(syn::Error::new_spanned(&entry.ty, message))
+}
+
+/// Wrap the type of each entry with a `codec` in `Coded`, and of each entry with a `remote`
+/// definition in `Remote`, so that it serializes through it.
+fn wrap_coded_entries(versions: &mut [ChainEntry], krate: &syn::Path) {
+    for entry in versions {
+        let ty = &entry.ty;
+        if let Some(codec) = &entry.codec {
+            entry.ty = syn::parse_quote!(#krate::codec::Coded<#ty, #codec>);
+        } else if let Some(def) = &entry.remote {
+            entry.ty = syn::parse_quote!(#krate::codec::Remote<#ty, #def>);
+        }
+    }
+}
+
+/// Options changing how the domain type converts to and from the latest version, returning
+/// the fields the latest version must match with `strict`.
+fn validate_conversions(parsed: &ParsedInput) -> Result<Option<syn::Fields>, syn::Error> {
+    validate_self_latest(parsed)?;
+    validate_generate_latest(parsed)?;
+    validate_serialize_error(parsed)?;
+    validate_from_owned(parsed)?;
+    validate_strict(parsed)
+}
+
+/// The domain type serializes its own payload as the latest version, so it cannot also be
+/// serialized transparently, or through a separate borrowing DTO.
+fn validate_self_latest(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    if !parsed.self_latest {
+        return Ok(());
+    }
+
+    let conflict = if parsed.transparent {
+        "transparent"
+    } else if parsed.latest_ref.is_some() {
+        "latest_ref"
+    } else if !parsed.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &parsed.generics,
+            "'self_latest' is not supported for generic domain types",
+        ));
+    } else {
+        return Ok(());
+    };
+    Err(syn::Error::new_spanned(
+        &parsed.ident,
+        format!("'self_latest' cannot be combined with '{conflict}'"),
+    ))
+}
+
+/// The latest version DTO is generated from the fields of a struct domain type.
+fn validate_generate_latest(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    if !parsed.generate_latest {
+        return Ok(());
+    }
+
+    if parsed.fields.is_none() {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "'generate_latest' requires a struct domain type",
+        ));
+    }
+    if !parsed.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &parsed.generics,
+            "'generate_latest' is not supported for generic domain types",
+        ));
+    }
+    if parsed.self_latest {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "'generate_latest' cannot be combined with 'self_latest'",
+        ));
+    }
+    // serde cannot write an internal tag into the sequence a tuple struct serializes as.
+    if matches!(parsed.fields, Some(syn::Fields::Unnamed(_)))
+        && parsed.tag_style.as_deref().unwrap_or("internal") == "internal"
+    {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "'generate_latest' on a tuple struct requires tag_style = \"adjacent\" or \"untagged\"",
+        ));
+    }
+
+    Ok(())
+}
+
+/// `strict` compares the fields of a non-generic struct domain type with those of a separate
+/// latest DTO.
+fn validate_strict(parsed: &ParsedInput) -> Result<Option<syn::Fields>, syn::Error> {
+    if !parsed.strict {
+        return Ok(None);
+    }
+
+    let message = if parsed.fields.is_none() {
+        "'strict' requires a struct domain type"
+    } else if !parsed.generics.params.is_empty() {
+        "'strict' is not supported for generic domain types"
+    } else if parsed.self_latest {
+        "'strict' cannot be combined with 'self_latest'"
+    } else if parsed.generate_latest {
+        "'strict' cannot be combined with 'generate_latest'"
+    } else {
+        return Ok(parsed.fields.clone());
+    };
+    Err(syn::Error::new_spanned(&parsed.ident, message))
+}
+
+/// A fallible conversion from the domain type replaces `From<&Domain>`, which the options
+/// that build representations from the domain type rely on.
+fn validate_serialize_error(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    if parsed.serialize_error.is_none() {
+        return Ok(());
+    }
+
+    let conflict = if parsed.self_latest {
+        "self_latest"
+    } else if parsed.generate_latest {
+        "generate_latest"
+    } else if parsed.latest_ref.is_some() {
+        "latest_ref"
+    } else if parsed.downgrade {
+        "downgrade"
+    } else if parsed.arbitrary {
+        "arbitrary"
+    } else if parsed.generate_tests {
+        "generate_tests"
+    } else if parsed.sqlx {
+        "sqlx"
+    } else if parsed.diesel {
+        "diesel"
+    } else if parsed.any {
+        "any"
+    } else if parsed.register.is_some() {
+        "register"
+    } else {
+        return Ok(());
+    };
+    Err(syn::Error::new_spanned(
+        &parsed.ident,
+        format!("'serialize_error' cannot be combined with '{conflict}'"),
+    ))
+}
+
+/// `self_latest` and `generate_latest` always convert owned domain values, and a fallible
+/// conversion must not be bypassed by an infallible one.
+fn validate_from_owned(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    if !parsed.from_owned {
+        return Ok(());
+    }
+
+    let message = if parsed.self_latest {
+        "'from_owned' is implied by 'self_latest'"
+    } else if parsed.generate_latest {
+        "'from_owned' is implied by 'generate_latest'"
+    } else if parsed.serialize_error.is_some() {
+        "'from_owned' cannot be combined with 'serialize_error'"
+    } else {
+        return Ok(());
+    };
+    Err(syn::Error::new_spanned(&parsed.ident, message))
+}
+
+/// Extra derives must not repeat the derives the macro already controls.
+fn validate_derives(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    for path in &parsed.derives {
+        let Some(name) = path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+        else {
+            continue;
+        };
+        let reason = match name.as_str() {
+            "Clone" | "Debug" => "is derived by default; use the 'clone' and 'debug' options",
+            "Serialize" | "Deserialize" => "is derived or implemented by the macro",
+            "PartialOrd" | "Ord" if parsed.ord => {
+                "conflicts with the version ordering generated by 'ord'"
             }
-        },
-        other => {
+            _ => continue,
+        };
+        return Err(syn::Error::new_spanned(path, format!("'{name}' {reason}")));
+    }
+
+    // The ordering falls back to payloads, and must agree with structural equality.
+    let derives = |name: &str| {
+        parsed.derives.iter().any(|path| {
+            path.segments
+                .last()
+                .is_some_and(|segment| segment.ident == name)
+        })
+    };
+    if parsed.ord && !(derives("PartialEq") && derives("Eq")) {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "'ord' requires derive(PartialEq, Eq) on the representation",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Modifiers of individual chain entries.
+fn validate_entries(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    validate_async(parsed)?;
+    validate_infallible_entries(parsed)?;
+    validate_cfgs(parsed)?;
+    validate_branches(parsed)?;
+    validate_borrowed(parsed)
+}
+
+/// Options that buffer the document before choosing a version deserialize from an owned
+/// copy, which version types cannot borrow from.
+fn validate_borrowed(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    let option = if parsed.on_unknown.is_some() {
+        "on_unknown"
+    } else if parsed.default_version.is_some() {
+        "default_version"
+    } else if parsed.cbor_tag.is_some() {
+        "cbor_tag"
+    } else if parsed.min_supported.is_some_and(|version| version > 1) {
+        "min_supported"
+    } else if parsed.deny_unknown_fields {
+        "deny_unknown_fields"
+    } else if parsed.versions.iter().any(|entry| entry.deprecated)
+        && parsed.on_deprecated.as_deref() != Some("accept")
+    {
+        "deprecated"
+    } else {
+        return Ok(());
+    };
+
+    for entry in &parsed.versions {
+        if let Some(lifetime) = borrowed_lifetime(&parsed.generics, entry.ty.to_token_stream()) {
             return Err(syn::Error::new_spanned(
-                &ident,
-                format!("invalid mode '{other}', expected 'infallible' or 'fallible'"),
+                lifetime,
+                format!(
+                    "version types borrowing from the input cannot be combined with '{option}', \
+                     which deserializes from a buffered copy"
+                ),
             ));
         }
+    }
+    Ok(())
+}
+
+/// The first lifetime of the domain type appearing in `tokens`.
+fn borrowed_lifetime(generics: &syn::Generics, tokens: TokenStream) -> Option<TokenStream> {
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match &token {
+            TokenTree::Group(group) => {
+                if let Some(lifetime) = borrowed_lifetime(generics, group.stream()) {
+                    return Some(lifetime);
+                }
+            }
+            TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                if let Some(TokenTree::Ident(ident)) = tokens.peek() {
+                    if generics
+                        .lifetimes()
+                        .any(|param| param.lifetime.ident == *ident)
+                    {
+                        return Some(
+                            [token.clone(), TokenTree::Ident(ident.clone())]
+                                .into_iter()
+                                .collect(),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A `branch` entry forks from the entry before it and merges into a later one, which the
+/// tag alone tells apart from its siblings. Options that find an entry by its version
+/// number, or migrate down the chain, cannot choose between the siblings.
+fn validate_branches(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    let Some((idx, entry)) = parsed
+        .versions
+        .iter()
+        .enumerate()
+        .find(|(_, entry)| entry.branch)
+    else {
+        return Ok(());
     };
 
-    Ok(ValidatedInput {
-        domain_ident: ident,
-        rep_ident,
-        mode: validated_mode,
-        transparent,
-        versions,
-    })
+    let merges = parsed.self_latest
+        || parsed.generate_latest
+        || parsed.versions.last().is_some_and(|latest| !latest.branch);
+    let message = if idx == 0 {
+        "the first chain entry cannot be a 'branch'".to_string()
+    } else if !merges {
+        "the latest chain entry cannot be a 'branch'".to_string()
+    } else if let Some(untagged) = parsed
+        .versions
+        .iter()
+        .find(|entry| entry.branch && entry.tag.is_none())
+    {
+        return Err(syn::Error::new_spanned(
+            &untagged.ty,
+            "a 'branch' entry needs its own tag, as in `V2Mobile as \"2-mobile\" branch`",
+        ));
+    } else if let Some(into) = parsed
+        .versions
+        .iter()
+        .find(|entry| entry.branch && (entry.infallible || entry.is_async))
+    {
+        return Err(syn::Error::new_spanned(
+            &into.ty,
+            "a 'branch' entry cannot be infallible or async, as nothing migrates into it",
+        ));
+    } else if let Some(conflict) = [
+        (parsed.downgrade, "downgrade"),
+        (parsed.schema, "schema"),
+        (parsed.cbor_tag.is_some(), "cbor_tag"),
+        (
+            parsed
+                .versions
+                .iter()
+                .any(|entry| entry.branch && entry.codec.is_some()),
+            "codec",
+        ),
+        (
+            parsed
+                .versions
+                .iter()
+                .any(|entry| entry.branch && entry.remote.is_some()),
+            "remote",
+        ),
+    ]
+    .into_iter()
+    .find_map(|(set, option)| set.then_some(option))
+    {
+        format!("'branch' entries cannot be combined with '{conflict}'")
+    } else if let Some(clash) = parsed.versions.iter().find(|entry| {
+        entry.branch
+            && entry.ty.segments.last().is_some_and(|segment| {
+                let name = segment.ident.to_string();
+                name.strip_prefix('V')
+                    .is_some_and(|number| number.parse::<u32>().is_ok())
+            })
+    }) {
+        return Err(syn::Error::new_spanned(
+            &clash.ty,
+            "a 'branch' entry's variant is named after its type, which must not be named like a version variant (`V<n>`)",
+        ));
+    } else {
+        return Ok(());
+    };
+    Err(syn::Error::new_spanned(&entry.ty, message))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use syn::{parse_quote, parse_str};
+/// With `dated = true`, every entry is tagged with a date, `YYYY-MM-DD` or `YYYY-MM`, and the
+/// dates increase along the chain. The implicit latest entry of `self_latest` and
+/// `generate_latest`, not yet appended, is dated by `latest_date`.
+fn validate_dates(parsed: &ParsedInput) -> Result<Option<Vec<Date>>, syn::Error> {
+    let implicit_latest = parsed.self_latest || parsed.generate_latest;
+    if !parsed.dated {
+        return match &parsed.latest_date {
+            Some(latest_date) => Err(syn::Error::new_spanned(
+                latest_date,
+                "'latest_date' requires 'dated = true'",
+            )),
+            None => Ok(None),
+        };
+    }
+    let latest_date = match (&parsed.latest_date, implicit_latest) {
+        (Some(latest_date), false) => {
+            return Err(syn::Error::new_spanned(
+                latest_date,
+                "'latest_date' requires 'self_latest' or 'generate_latest'; date the latest \
+                 chain entry with its tag",
+            ));
+        }
+        (None, true) => {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'dated' with an implicit latest version requires 'latest_date', as in \
+                 `latest_date = \"2025-01-15\"`",
+            ));
+        }
+        (latest_date, _) => latest_date.as_ref(),
+    };
 
-    fn base_parsed_input() -> ParsedInput {
-        ParsedInput {
-            ident: parse_str::<syn::Ident>("Example").unwrap(),
-            representation: None,
-            mode: None,
-            error: Some(parse_quote!(ExampleError)),
-            transparent: false,
-            versions: vec![parse_quote!(Version1), parse_quote!(Version2)],
+    let mut dates: Vec<Date> = Vec::with_capacity(parsed.versions.len() + 1);
+    let written = parsed
+        .versions
+        .iter()
+        .map(|entry| entry.tag.as_ref().ok_or(entry));
+    for tag in written.chain(latest_date.map(Ok)) {
+        let tag = tag.map_err(|entry| {
+            syn::Error::new_spanned(
+                &entry.ty,
+                "'dated' requires a date tag on every chain entry, as in `V1 as \"2024-03-01\"`",
+            )
+        })?;
+        let date = parse_date(&tag.value()).ok_or_else(|| {
+            syn::Error::new_spanned(tag, "expected a date tag, `YYYY-MM-DD` or `YYYY-MM`")
+        })?;
+        if dates.last().is_some_and(|&previous| previous >= date) {
+            return Err(syn::Error::new_spanned(
+                tag,
+                "version dates must increase along the chain",
+            ));
         }
+        dates.push(date);
     }
+    Ok(Some(dates))
+}
 
-    #[test]
-    fn infers_defaults_and_representation_name() {
-        let parsed = base_parsed_input();
-        let validated = validate(parsed).expect("validation should succeed");
+/// Parse `YYYY-MM-DD`, or `YYYY-MM` as the first of the month.
+fn parse_date(tag: &str) -> Option<Date> {
+    let mut parts = tag.split('-');
+    let year = parts.next().filter(|part| part.len() == 4)?;
+    let month = parts.next().filter(|part| part.len() == 2)?;
+    let day = parts
+        .next()
+        .map_or(Some("01"), |part| (part.len() == 2).then_some(part))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if !(all_digits(year) && all_digits(month) && all_digits(day)) {
+        return None;
+    }
 
-        assert_eq!(validated.domain_ident.to_string(), "Example");
-        assert_eq!(validated.rep_ident.to_string(), "ExampleVersions");
-        assert!(matches!(validated.mode, Mode::Fallible { .. }));
-        assert!(!validated.transparent);
-        assert_eq!(validated.versions.len(), 2);
+    let (year, month, day): Date = (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days_in_month)
+        .contains(&day)
+        .then_some((year, month, day))
+}
+
+fn validate_async(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    if !parsed.versions.iter().any(|entry| entry.is_async) {
+        return Ok(());
     }
 
-    #[test]
-    fn errors_when_missing_error_in_fallible_mode() {
-        let mut parsed = base_parsed_input();
-        parsed.error = None;
-        let err = validate(parsed).expect_err("validation should fail");
-        assert_eq!(err.to_string(), "fallible mode requires 'error' attribute");
+    if parsed.versions[0].is_async {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "the first chain entry cannot be async, as nothing migrates into it",
+        ));
+    }
+    if parsed.mode.as_deref() == Some("infallible") {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "async chain entries require fallible mode",
+        ));
+    }
+    if parsed.transparent {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "async chain entries cannot be used with 'transparent', as deserialization is synchronous",
+        ));
     }
 
-    #[test]
-    fn errors_on_empty_version_chain() {
-        let mut parsed = base_parsed_input();
-        parsed.versions.clear();
-        let err = validate(parsed).expect_err("validation should fail");
-        assert_eq!(
-            err.to_string(),
-            "chain must contain at least one version type"
+    Ok(())
+}
+
+/// Only the oldest versions may be compiled out, so that every remaining version still
+/// migrates through the versions after it.
+fn validate_cfgs(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    if !parsed.versions.iter().any(|entry| entry.cfg.is_some()) {
+        return Ok(());
+    }
+
+    if !parsed.self_latest
+        && parsed
+            .versions
+            .last()
+            .is_some_and(|entry| entry.cfg.is_some())
+    {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "the latest chain entry cannot be cfg-gated",
+        ));
+    }
+    if parsed
+        .versions
+        .windows(2)
+        .any(|pair| pair[0].cfg.is_none() && pair[1].cfg.is_some())
+    {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "cfg-gated chain entries must precede every ungated entry",
+        ));
+    }
+    if !parsed.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &parsed.generics,
+            "cfg-gated chain entries cannot be used with generic domain types",
+        ));
+    }
+    let default_entry = parsed.default_version.and_then(|version| {
+        let idx = version.checked_sub(parsed.min_supported.unwrap_or(1))?;
+        let mut main_line = parsed.versions.iter().filter(|entry| !entry.branch);
+        main_line.nth(usize::try_from(idx).ok()?)
+    });
+    if default_entry.is_some_and(|entry| entry.cfg.is_some()) {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "'default_version' cannot refer to a cfg-gated chain entry",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Tag overrides and aliases must be unambiguous: no two versions may share a tag spelling.
+fn validate_tags(
+    versions: &[ChainEntry],
+    tag_style: &TagStyle,
+    min_supported: u32,
+) -> Result<(), syn::Error> {
+    // Branches share the number of the entry before them, and always have their own tag.
+    let mut seen: Vec<String> = (min_supported..)
+        .zip(versions.iter().filter(|entry| !entry.branch))
+        .filter(|(_, entry)| entry.tag.is_none())
+        .map(|(version, _)| version.to_string())
+        .collect();
+    let tags = versions.iter().filter_map(|entry| entry.tag.as_ref());
+    let aliases = versions.iter().flat_map(|entry| &entry.aliases);
+    for (tag, kind) in tags
+        .map(|tag| (tag, "version tag"))
+        .chain(aliases.map(|alias| (alias, "alias")))
+    {
+        if matches!(tag_style, TagStyle::Untagged { .. }) {
+            return Err(syn::Error::new_spanned(
+                tag,
+                "version tags and aliases require a tagged representation",
+            ));
+        }
+        let value = tag.value();
+        if value.is_empty() {
+            return Err(syn::Error::new_spanned(
+                tag,
+                format!("{kind} must not be empty"),
+            ));
+        }
+        if seen.contains(&value) {
+            return Err(syn::Error::new_spanned(
+                tag,
+                format!("version tag \"{value}\" is used more than once"),
+            ));
+        }
+        seen.push(value);
+    }
+
+    Ok(())
+}
+
+fn validate_infallible_entries(parsed: &ParsedInput) -> Result<(), syn::Error> {
+    if !parsed.versions.iter().any(|entry| entry.infallible) {
+        return Ok(());
+    }
+
+    if parsed.versions[0].infallible {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "the first chain entry cannot be infallible, as nothing migrates into it",
+        ));
+    }
+    if parsed.mode.as_deref() == Some("infallible") {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "infallible chain entries require fallible mode, as every step is already infallible",
+        ));
+    }
+    if parsed
+        .versions
+        .iter()
+        .any(|entry| entry.infallible && entry.is_async)
+    {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "a chain entry cannot be both async and infallible",
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_deprecation(
+    parsed: &ParsedInput,
+    tag_style: &TagStyle,
+) -> Result<DeprecationPolicy, syn::Error> {
+    let policy = match parsed.on_deprecated.as_deref().unwrap_or("warn") {
+        "warn" => DeprecationPolicy::Warn,
+        "error" => DeprecationPolicy::Error,
+        "accept" => DeprecationPolicy::Accept,
+        other => {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                format!("invalid on_deprecated '{other}', expected 'warn', 'error' or 'accept'"),
+            ));
+        }
+    };
+
+    if !parsed.versions.iter().any(|entry| entry.deprecated) {
+        if parsed.on_deprecated.is_some() {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'on_deprecated' requires at least one deprecated chain entry",
+            ));
+        }
+        return Ok(policy);
+    }
+
+    if parsed.versions.last().is_some_and(|entry| entry.deprecated) {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "the latest chain entry cannot be deprecated",
+        ));
+    }
+    if let TagStyle::Untagged { .. } = tag_style {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "deprecated chain entries require a tagged representation",
+        ));
+    }
+
+    Ok(policy)
+}
+
+fn validate_tag_style(parsed: &ParsedInput) -> Result<TagStyle, syn::Error> {
+    let tag_style = match parsed.tag_style.as_deref().unwrap_or("internal") {
+        "internal" => TagStyle::Internal,
+        "adjacent" => {
+            let content = parsed.content.clone().unwrap_or_else(|| "data".to_string());
+            if content.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &parsed.ident,
+                    "content must not be empty",
+                ));
+            }
+            if parsed.tag.as_deref() == Some(content.as_str()) {
+                return Err(syn::Error::new_spanned(
+                    &parsed.ident,
+                    "tag and content fields must have different names",
+                ));
+            }
+            TagStyle::Adjacent { content }
+        }
+        "untagged" => {
+            let newest_first = match parsed.untagged_order.as_deref().unwrap_or("newest_first") {
+                "newest_first" => true,
+                "oldest_first" => false,
+                other => {
+                    return Err(syn::Error::new_spanned(
+                        &parsed.ident,
+                        format!(
+                            "invalid untagged_order '{other}', expected 'newest_first' or 'oldest_first'"
+                        ),
+                    ));
+                }
+            };
+            TagStyle::Untagged { newest_first }
+        }
+        other => {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                format!(
+                    "invalid tag_style '{other}', expected 'internal', 'adjacent' or 'untagged'"
+                ),
+            ));
+        }
+    };
+
+    if let TagStyle::Untagged { .. } = tag_style {
+        if parsed.tag.is_some() {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'tag' cannot be used with tag_style = \"untagged\"",
+            ));
+        }
+        if parsed.on_unknown.is_some() {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'on_unknown' requires a tagged representation",
+            ));
+        }
+        if parsed.deny_unknown_fields {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'deny_unknown_fields' requires a tagged representation",
+            ));
+        }
+    } else if parsed.untagged_order.is_some() {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "'untagged_order' requires tag_style = \"untagged\"",
+        ));
+    }
+
+    if parsed.content.is_some() && !matches!(tag_style, TagStyle::Adjacent { .. }) {
+        return Err(syn::Error::new_spanned(
+            &parsed.ident,
+            "'content' requires tag_style = \"adjacent\"",
+        ));
+    }
+
+    if let Some(base) = parsed.cbor_tag {
+        if tag_style != TagStyle::Internal {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "'cbor_tag' requires tag_style = \"internal\"",
+            ));
+        }
+        let newest = u64::from(parsed.min_supported.unwrap_or(1)) + parsed.versions.len() as u64;
+        if base.checked_add(newest).is_none() {
+            return Err(syn::Error::new_spanned(
+                &parsed.ident,
+                "cbor_tag is too large for the number of versions",
+            ));
+        }
+    }
+
+    Ok(tag_style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::ToTokens;
+    use syn::{parse_quote, parse_str};
+
+    fn base_parsed_input() -> ParsedInput {
+        ParsedInput {
+            ident: parse_str::<syn::Ident>("Example").unwrap(),
+            vis: parse_quote!(pub),
+            generics: syn::Generics::default(),
+            fields: Some(syn::Fields::Unit),
+            representation: None,
+            mode: None,
+            error: Some(parse_quote!(ExampleError)),
+            step_context: false,
+            transparent: false,
+            ord: false,
+            downgrade: false,
+            schema: false,
+            arbitrary: false,
+            generate_tests: false,
+            register: None,
+            any: false,
+            sqlx: false,
+            diesel: false,
+            clone: true,
+            debug: true,
+            derives: Vec::new(),
+            rep_attrs: Vec::new(),
+            on_unknown: None,
+            on_migrate: None,
+            latest_ref: None,
+            serialize_error: None,
+            from_owned: false,
+            boxed: false,
+            self_latest: false,
+            generate_latest: false,
+            strict: false,
+            dated: false,
+            latest_date: None,
+            tag: None,
+            tag_style: None,
+            untagged_order: None,
+            content: None,
+            cbor_tag: None,
+            default_version: None,
+            min_supported: None,
+            on_deprecated: None,
+            deny_unknown_fields: false,
+            krate: None,
+            versions: vec![parse_quote!(Version1), parse_quote!(Version2)],
+        }
+    }
+
+    #[test]
+    fn infers_defaults_and_representation_name() {
+        let parsed = base_parsed_input();
+        let validated = validate(parsed).expect("validation should succeed");
+
+        assert_eq!(validated.domain_ident.to_string(), "Example");
+        assert_eq!(validated.rep_ident.to_string(), "ExampleVersions");
+        assert!(matches!(validated.mode, Mode::Fallible { .. }));
+        assert!(!validated.transparent);
+        assert_eq!(validated.tag, "_version");
+        assert_eq!(validated.tag_style, TagStyle::Internal);
+        assert_eq!(validated.versions.len(), 2);
+    }
+
+    #[test]
+    fn errors_when_missing_error_in_fallible_mode() {
+        let mut parsed = base_parsed_input();
+        parsed.error = None;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(err.to_string(), "fallible mode requires 'error' attribute");
+    }
+
+    #[test]
+    fn errors_on_step_context_in_infallible_mode() {
+        let mut parsed = base_parsed_input();
+        parsed.mode = Some("infallible".to_string());
+        parsed.step_context = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(err.to_string(), "'step_context' requires fallible mode");
+    }
+
+    #[test]
+    fn errors_on_empty_version_chain() {
+        let mut parsed = base_parsed_input();
+        parsed.versions.clear();
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "chain must contain at least one version type"
+        );
+    }
+
+    #[test]
+    fn errors_on_empty_tag() {
+        let mut parsed = base_parsed_input();
+        parsed.tag = Some(String::new());
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(err.to_string(), "tag must not be empty");
+    }
+
+    #[test]
+    fn untagged_defaults_to_newest_first() {
+        let mut parsed = base_parsed_input();
+        parsed.tag_style = Some("untagged".to_string());
+        let validated = validate(parsed).expect("validation should succeed");
+        assert_eq!(
+            validated.tag_style,
+            TagStyle::Untagged { newest_first: true }
+        );
+    }
+
+    #[test]
+    fn errors_on_untagged_with_deny_unknown_fields() {
+        let mut parsed = base_parsed_input();
+        parsed.tag_style = Some("untagged".to_string());
+        parsed.deny_unknown_fields = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'deny_unknown_fields' requires a tagged representation"
+        );
+    }
+
+    #[test]
+    fn errors_on_untagged_with_unknown_handler() {
+        let mut parsed = base_parsed_input();
+        parsed.tag_style = Some("untagged".to_string());
+        parsed.on_unknown = Some(parse_quote!(recover));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'on_unknown' requires a tagged representation"
+        );
+    }
+
+    #[test]
+    fn errors_on_untagged_order_without_untagged_style() {
+        let mut parsed = base_parsed_input();
+        parsed.untagged_order = Some("oldest_first".to_string());
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'untagged_order' requires tag_style = \"untagged\""
+        );
+    }
+
+    #[test]
+    fn adjacent_defaults_content_field() {
+        let mut parsed = base_parsed_input();
+        parsed.tag_style = Some("adjacent".to_string());
+        let validated = validate(parsed).expect("validation should succeed");
+        assert_eq!(
+            validated.tag_style,
+            TagStyle::Adjacent {
+                content: "data".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn errors_when_tag_and_content_collide() {
+        let mut parsed = base_parsed_input();
+        parsed.tag_style = Some("adjacent".to_string());
+        parsed.tag = Some("v".to_string());
+        parsed.content = Some("v".to_string());
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "tag and content fields must have different names"
+        );
+    }
+
+    #[test]
+    fn errors_on_out_of_range_default_version() {
+        let mut parsed = base_parsed_input();
+        parsed.default_version = Some(3);
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "default_version 3 is out of range, expected 1..=2"
+        );
+    }
+
+    #[test]
+    fn min_supported_offsets_default_version_range() {
+        let mut parsed = base_parsed_input();
+        parsed.min_supported = Some(3);
+        parsed.default_version = Some(2);
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "default_version 2 is out of range, expected 3..=4"
+        );
+    }
+
+    #[test]
+    fn errors_on_min_supported_with_untagged_style() {
+        let mut parsed = base_parsed_input();
+        parsed.min_supported = Some(3);
+        parsed.tag_style = Some("untagged".to_string());
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'min_supported' requires a tagged representation"
+        );
+    }
+
+    #[test]
+    fn deprecation_policy_defaults_to_warn() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[0].deprecated = true;
+        let validated = validate(parsed).expect("validation should succeed");
+        assert_eq!(validated.on_deprecated, DeprecationPolicy::Warn);
+    }
+
+    #[test]
+    fn errors_when_latest_entry_is_deprecated() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[1].deprecated = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "the latest chain entry cannot be deprecated"
+        );
+    }
+
+    #[test]
+    fn errors_on_deprecation_policy_without_deprecated_entries() {
+        let mut parsed = base_parsed_input();
+        parsed.on_deprecated = Some("error".to_string());
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'on_deprecated' requires at least one deprecated chain entry"
+        );
+    }
+
+    #[test]
+    fn errors_on_async_entry_in_infallible_mode() {
+        let mut parsed = base_parsed_input();
+        parsed.mode = Some("infallible".to_string());
+        parsed.versions[1].is_async = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(err.to_string(), "async chain entries require fallible mode");
+    }
+
+    #[test]
+    fn errors_on_async_first_entry() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[0].is_async = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "the first chain entry cannot be async, as nothing migrates into it"
+        );
+    }
+
+    #[test]
+    fn errors_on_infallible_entry_in_infallible_mode() {
+        let mut parsed = base_parsed_input();
+        parsed.mode = Some("infallible".to_string());
+        parsed.versions[1].infallible = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "infallible chain entries require fallible mode, as every step is already infallible"
+        );
+    }
+
+    #[test]
+    fn errors_on_async_infallible_entry() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[1].infallible = true;
+        parsed.versions[1].is_async = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "a chain entry cannot be both async and infallible"
+        );
+    }
+
+    #[test]
+    fn errors_on_cfg_gated_latest_entry() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[1].cfg = Some(parse_quote!(feature = "legacy"));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "the latest chain entry cannot be cfg-gated"
+        );
+    }
+
+    #[test]
+    fn errors_on_cfg_gated_entry_after_ungated_entry() {
+        let mut parsed = base_parsed_input();
+        parsed.versions.push(parse_quote!(Version3));
+        parsed.versions[1].cfg = Some(parse_quote!(feature = "legacy"));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "cfg-gated chain entries must precede every ungated entry"
+        );
+    }
+
+    #[test]
+    fn errors_on_default_version_of_cfg_gated_entry() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[0].cfg = Some(parse_quote!(feature = "legacy"));
+        parsed.default_version = Some(1);
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'default_version' cannot refer to a cfg-gated chain entry"
+        );
+    }
+
+    #[test]
+    fn errors_on_alias_colliding_with_version_tag() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[0].aliases = vec![parse_quote!("v1"), parse_quote!("2")];
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(err.to_string(), "version tag \"2\" is used more than once");
+    }
+
+    #[test]
+    fn errors_on_duplicate_aliases() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[0].aliases = vec![parse_quote!("v")];
+        parsed.versions[1].aliases = vec![parse_quote!("v")];
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(err.to_string(), "version tag \"v\" is used more than once");
+    }
+
+    #[test]
+    fn errors_on_aliases_with_untagged_style() {
+        let mut parsed = base_parsed_input();
+        parsed.tag_style = Some("untagged".to_string());
+        parsed.versions[0].aliases = vec![parse_quote!("v1")];
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "version tags and aliases require a tagged representation"
+        );
+    }
+
+    #[test]
+    fn errors_on_tag_override_colliding_with_version_tag() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[0].tag = Some(parse_quote!("2"));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(err.to_string(), "version tag \"2\" is used more than once");
+    }
+
+    #[test]
+    fn allows_tag_override_reusing_its_own_number() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[0].tag = Some(parse_quote!("initial"));
+        parsed.versions[1].tag = Some(parse_quote!("1"));
+        assert!(validate(parsed).is_ok());
+    }
+
+    #[test]
+    fn errors_on_arbitrary_without_debug() {
+        let mut parsed = base_parsed_input();
+        parsed.arbitrary = true;
+        parsed.debug = false;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'arbitrary' requires the representation to implement Debug"
+        );
+    }
+
+    #[test]
+    fn errors_on_buffering_borrowed_versions() {
+        let mut parsed = base_parsed_input();
+        parsed.generics = parse_quote!(<'a>);
+        parsed.versions[1] = ChainEntry::new(parse_quote!(Version2<'a>));
+        parsed.deny_unknown_fields = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "version types borrowing from the input cannot be combined with \
+             'deny_unknown_fields', which deserializes from a buffered copy"
+        );
+
+        let mut parsed = base_parsed_input();
+        parsed.generics = parse_quote!(<'a>);
+        parsed.versions[1] = ChainEntry::new(parse_quote!(Version2<'a>));
+        parsed.min_supported = Some(1);
+        assert!(validate(parsed).is_ok());
+
+        let mut parsed = base_parsed_input();
+        parsed.generics = parse_quote!(<'a>);
+        parsed.min_supported = Some(2);
+        assert!(validate(parsed).is_ok());
+    }
+
+    #[test]
+    fn errors_on_repeated_derives() {
+        let mut parsed = base_parsed_input();
+        parsed.derives = vec![parse_quote!(PartialEq), parse_quote!(serde::Serialize)];
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'Serialize' is derived or implemented by the macro"
+        );
+
+        let mut parsed = base_parsed_input();
+        parsed.derives = vec![parse_quote!(PartialEq), parse_quote!(Eq), parse_quote!(Ord)];
+        parsed.ord = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'Ord' conflicts with the version ordering generated by 'ord'"
+        );
+    }
+
+    #[test]
+    fn errors_on_ordering_without_equality() {
+        let mut parsed = base_parsed_input();
+        parsed.derives = vec![parse_quote!(PartialEq)];
+        parsed.ord = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'ord' requires derive(PartialEq, Eq) on the representation"
+        );
+
+        let mut parsed = base_parsed_input();
+        parsed.derives = vec![parse_quote!(PartialEq), parse_quote!(Eq)];
+        parsed.ord = true;
+        assert!(validate(parsed).is_ok());
+    }
+
+    #[test]
+    fn errors_on_registering_generic_domain() {
+        let mut parsed = base_parsed_input();
+        parsed.generics = parse_quote!(<T>);
+        parsed.register = Some("example".to_string());
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'register' is not supported for generic domain types"
+        );
+    }
+
+    #[test]
+    fn wraps_coded_entries() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[0].codec = Some(parse_quote!(Protobuf));
+        let validated = validate(parsed).expect("validation should succeed");
+        assert_eq!(
+            validated.versions[0].ty.to_token_stream().to_string(),
+            ":: serde_evolve :: codec :: Coded < Version1 , Protobuf >"
+        );
+        assert_eq!(
+            validated.versions[1].ty.to_token_stream().to_string(),
+            "Version2"
+        );
+    }
+
+    #[test]
+    fn errors_on_misplaced_codecs() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[1].codec = Some(parse_quote!(Protobuf));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "a 'codec' entry cannot be the latest version"
+        );
+
+        let mut parsed = base_parsed_input();
+        parsed.versions[0].codec = Some(parse_quote!(Protobuf));
+        parsed.downgrade = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'codec' entries cannot be combined with 'downgrade'"
+        );
+    }
+
+    #[test]
+    fn wraps_remote_entries() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[0].remote = Some(parse_quote!(ConfigDef));
+        let validated = validate(parsed).expect("validation should succeed");
+        assert_eq!(
+            validated.versions[0].ty.to_token_stream().to_string(),
+            ":: serde_evolve :: codec :: Remote < Version1 , ConfigDef >"
+        );
+
+        let mut parsed = base_parsed_input();
+        parsed.versions[1].remote = Some(parse_quote!(ConfigDef));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "a 'remote' entry cannot be the latest version"
+        );
+
+        let mut parsed = base_parsed_input();
+        parsed.versions[0].codec = Some(parse_quote!(Protobuf));
+        parsed.versions[0].remote = Some(parse_quote!(ConfigDef));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "an entry cannot have both a 'codec' and a 'remote' definition"
+        );
+    }
+
+    #[test]
+    fn errors_on_type_erasing_generic_domain() {
+        let mut parsed = base_parsed_input();
+        parsed.generics = parse_quote!(<T>);
+        parsed.any = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'any' is not supported for generic domain types"
+        );
+    }
+
+    #[test]
+    fn dated_chains_parse_their_tags() {
+        let mut parsed = base_parsed_input();
+        parsed.dated = true;
+        parsed.versions[0].tag = Some(parse_quote!("2024-02"));
+        parsed.versions[1].tag = Some(parse_quote!("2024-02-29"));
+        let validated = validate(parsed).expect("validation should succeed");
+        assert_eq!(validated.dates, Some(vec![(2024, 2, 1), (2024, 2, 29)]));
+    }
+
+    #[test]
+    fn errors_on_dated_chains_out_of_order() {
+        let mut parsed = base_parsed_input();
+        parsed.dated = true;
+        parsed.versions[0].tag = Some(parse_quote!("2024-03-01"));
+        parsed.versions[1].tag = Some(parse_quote!("2024-03"));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "version dates must increase along the chain"
+        );
+    }
+
+    #[test]
+    fn errors_on_invalid_version_dates() {
+        let mut parsed = base_parsed_input();
+        parsed.dated = true;
+        parsed.versions[0].tag = Some(parse_quote!("2023-02-29"));
+        parsed.versions[1].tag = Some(parse_quote!("2024-01-01"));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "expected a date tag, `YYYY-MM-DD` or `YYYY-MM`"
+        );
+
+        let mut parsed = base_parsed_input();
+        parsed.dated = true;
+        parsed.versions[0].tag = Some(parse_quote!("2024-01-01"));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert!(err.to_string().starts_with("'dated' requires a date tag"));
+    }
+
+    #[test]
+    fn dates_implicit_latest_entries_with_latest_date() {
+        let mut parsed = base_parsed_input();
+        parsed.dated = true;
+        parsed.self_latest = true;
+        parsed.versions[0].tag = Some(parse_quote!("2024-02"));
+        parsed.versions[1].tag = Some(parse_quote!("2024-06"));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert!(
+            err.to_string()
+                .starts_with("'dated' with an implicit latest version requires 'latest_date'")
+        );
+
+        let mut parsed = base_parsed_input();
+        parsed.dated = true;
+        parsed.self_latest = true;
+        parsed.versions[0].tag = Some(parse_quote!("2024-02"));
+        parsed.versions[1].tag = Some(parse_quote!("2024-06"));
+        parsed.latest_date = Some(parse_quote!("2025-01-15"));
+        let validated = validate(parsed).expect("validation should succeed");
+        assert_eq!(
+            validated.dates,
+            Some(vec![(2024, 2, 1), (2024, 6, 1), (2025, 1, 15)])
+        );
+        assert_eq!(
+            validated.versions[2].tag.as_ref().map(syn::LitStr::value),
+            Some("2025-01-15".to_string())
+        );
+
+        let mut parsed = base_parsed_input();
+        parsed.latest_date = Some(parse_quote!("2025-01-15"));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(err.to_string(), "'latest_date' requires 'dated = true'");
+    }
+
+    #[test]
+    fn errors_on_branches_without_tags() {
+        let mut parsed = base_parsed_input();
+        parsed.versions.push(parse_quote!(Version3));
+        parsed.versions[1].branch = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert!(
+            err.to_string()
+                .starts_with("a 'branch' entry needs its own tag")
+        );
+    }
+
+    #[test]
+    fn errors_on_infallible_branches() {
+        let mut parsed = base_parsed_input();
+        parsed.versions.push(parse_quote!(Version3));
+        parsed.versions[1].branch = true;
+        parsed.versions[1].infallible = true;
+        parsed.versions[1].tag = Some(parse_quote!("1-mobile"));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "a 'branch' entry cannot be infallible or async, as nothing migrates into it"
+        );
+    }
+
+    #[test]
+    fn errors_on_latest_branch() {
+        let mut parsed = base_parsed_input();
+        parsed.versions[1].branch = true;
+        parsed.versions[1].tag = Some(parse_quote!("1-mobile"));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "the latest chain entry cannot be a 'branch'"
+        );
+    }
+
+    #[test]
+    fn branches_share_the_number_of_their_sibling() {
+        let mut parsed = base_parsed_input();
+        parsed.versions.push(parse_quote!(Version3));
+        parsed.versions[1].branch = true;
+        parsed.versions[1].tag = Some(parse_quote!("1-mobile"));
+        parsed.default_version = Some(2);
+        validate(parsed).expect("validation should succeed");
+    }
+
+    #[test]
+    fn errors_on_strict_enum_domain() {
+        let mut parsed = base_parsed_input();
+        parsed.fields = None;
+        parsed.strict = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(err.to_string(), "'strict' requires a struct domain type");
+    }
+
+    #[test]
+    fn errors_on_sqlx_with_async_entries() {
+        let mut parsed = base_parsed_input();
+        parsed.sqlx = true;
+        parsed.versions[1].is_async = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'sqlx' cannot be used with async chain entries"
+        );
+    }
+
+    #[test]
+    fn self_latest_appends_the_domain_type() {
+        let mut parsed = base_parsed_input();
+        parsed.self_latest = true;
+        parsed.versions[1].cfg = Some(parse_quote!(feature = "legacy"));
+        parsed.versions[0].cfg = Some(parse_quote!(feature = "legacy"));
+        let validated = validate(parsed).expect("validation should succeed");
+        assert_eq!(validated.versions.len(), 3);
+        assert!(validated.versions[2].ty.is_ident("Example"));
+    }
+
+    #[test]
+    fn generate_latest_appends_a_numbered_dto() {
+        let mut parsed = base_parsed_input();
+        parsed.generate_latest = true;
+        parsed.min_supported = Some(4);
+        let validated = validate(parsed).expect("validation should succeed");
+        assert_eq!(validated.versions.len(), 3);
+        assert!(validated.versions[2].ty.is_ident("ExampleV6"));
+        assert!(validated.latest_fields.is_some());
+    }
+
+    #[test]
+    fn errors_on_generate_latest_for_enums() {
+        let mut parsed = base_parsed_input();
+        parsed.generate_latest = true;
+        parsed.fields = None;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'generate_latest' requires a struct domain type"
+        );
+    }
+
+    #[test]
+    fn errors_on_generate_latest_for_internally_tagged_tuple_structs() {
+        let tuple_struct = || {
+            let mut parsed = base_parsed_input();
+            parsed.generate_latest = true;
+            parsed.fields = Some(syn::Fields::Unnamed(parse_quote!((u32, u32))));
+            parsed
+        };
+        let err = validate(tuple_struct()).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'generate_latest' on a tuple struct requires tag_style = \"adjacent\" or \"untagged\""
+        );
+
+        let mut parsed = tuple_struct();
+        parsed.tag_style = Some("adjacent".to_string());
+        assert!(validate(parsed).is_ok());
+    }
+
+    #[test]
+    fn errors_on_self_latest_with_transparent() {
+        let mut parsed = base_parsed_input();
+        parsed.self_latest = true;
+        parsed.transparent = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'self_latest' cannot be combined with 'transparent'"
+        );
+    }
+
+    #[test]
+    fn errors_on_serialize_error_with_downgrade() {
+        let mut parsed = base_parsed_input();
+        parsed.serialize_error = Some(parse_quote!(RedactionError));
+        parsed.downgrade = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'serialize_error' cannot be combined with 'downgrade'"
+        );
+    }
+
+    #[test]
+    fn errors_on_redundant_from_owned() {
+        let mut parsed = base_parsed_input();
+        parsed.from_owned = true;
+        parsed.self_latest = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(err.to_string(), "'from_owned' is implied by 'self_latest'");
+    }
+
+    #[test]
+    fn errors_on_latest_ref_with_arguments() {
+        let mut parsed = base_parsed_input();
+        parsed.latest_ref = Some(parse_quote!(Version2Ref<'a>));
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'latest_ref' must name the type without arguments; its lifetime is supplied"
+        );
+    }
+
+    #[test]
+    fn errors_on_generated_tests_for_generic_domain() {
+        let mut parsed = base_parsed_input();
+        parsed.generics = parse_quote!(<T>);
+        parsed.generate_tests = true;
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'generate_tests' is not supported for generic domain types"
+        );
+    }
+
+    #[test]
+    fn errors_on_cbor_tag_with_untagged_style() {
+        let mut parsed = base_parsed_input();
+        parsed.cbor_tag = Some(1000);
+        parsed.tag_style = Some("untagged".to_string());
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "'cbor_tag' requires tag_style = \"internal\""
+        );
+    }
+
+    #[test]
+    fn errors_on_overflowing_cbor_tag() {
+        let mut parsed = base_parsed_input();
+        parsed.cbor_tag = Some(u64::MAX);
+        let err = validate(parsed).expect_err("validation should fail");
+        assert_eq!(
+            err.to_string(),
+            "cbor_tag is too large for the number of versions"
         );
     }
 }